@@ -0,0 +1,65 @@
+//! [Anti-Alias Benchmark]
+//!
+//! Compares the cost of sampling only the pixels along edges again
+//! against sampling every pixel on a 3x3 grid, on the initial view and
+//! a view near the boundary. Run with `cargo bench --bench antialias`.
+//! Both costs are over that of the single-sample frame alone. Views
+//! thick with filaments are mostly edges, and save much less.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use mandelbrot_piston::antialias::{AntiAliasing, Supersamples};
+use mandelbrot_piston::fractal::{Escaping, Formula};
+use mandelbrot_piston::settings::Settings;
+use mandelbrot_piston::viewport::Viewport;
+use num::complex::Complex as cmp;
+
+const ITERATIONS: u32 = 1200;
+const RUNS: usize = 9;
+
+/// Median wall-clock time of RUNS invocations, after one warm-up.
+fn median(mut f: impl FnMut()) -> Duration {
+    f();
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+
+    times.sort();
+    times[RUNS / 2]
+}
+
+fn compare(name: &str, viewport: Viewport) {
+    let width = viewport.width_px();
+    let mut vals = vec![0; width * viewport.height_px()];
+    let mut fine = vec![0; vals.len() * 9];
+    let settings = AntiAliasing { enabled: true, ..AntiAliasing::default() };
+
+    let primary = median(|| {
+        Formula::Mandelbrot.compute_parallel(black_box(&mut vals), width, |a, b| viewport.pixel_to_complex(a as f64, b as f64), ITERATIONS);
+    });
+    let adaptive = median(|| {
+        black_box(Supersamples::of(Escaping::default(), &viewport, &vals, ITERATIONS, &settings));
+    });
+    let full = median(|| {
+        Formula::Mandelbrot.compute_parallel(black_box(&mut fine), width * 3, |a, b| {
+            viewport.pixel_to_complex(a as f64 / 3.0 - 1.0 / 3.0, b as f64 / 3.0 - 1.0 / 3.0)
+        }, ITERATIONS);
+    });
+    let share = Supersamples::of(Escaping::default(), &viewport, &vals, ITERATIONS, &settings).share(vals.len());
+
+    println!("{name}");
+    println!("  primary pass   {:>10.3?}", primary);
+    println!("  adaptive       {:>10.3?}  ({:.1}% of pixels)", adaptive, share * 100.0);
+    println!("  full 3x3       {:>10.3?}", full);
+    println!("  adaptive/full  {:>10.3}", adaptive.as_secs_f64() / full.as_secs_f64());
+}
+
+fn main() {
+    compare("initial view", Settings::default().validate().unwrap());
+    compare("near the boundary", Viewport::new(cmp::new(-0.7436, 0.1318), 0.01, 400, 200));
+}
@@ -0,0 +1,89 @@
+//! [Kernel Benchmark]
+//!
+//! Compares the trait-based Mandelbrot kernel against the original
+//! hand-written escape-time loop on the initial view. Run with
+//! `cargo bench --bench kernel`; the hand-written and f64 timings
+//! should agree to within run-to-run noise. The f32 instantiation is
+//! listed for comparison.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use mandelbrot_piston::fractal::Formula;
+use num::complex::Complex as cmp;
+use rayon::prelude::*;
+
+const WIDTH: usize = 400;
+const HEIGHT: usize = 200;
+const ITERATIONS: u32 = 1200;
+const RUNS: usize = 15;
+
+const RE_MIN: f64 = 0.3602404434376143 - 2.0;
+const IM_MIN: f64 = -0.6413130610648032 - 1.0;
+const SCALE: f64 = 100.0;
+
+/// The loop as it was written before the Fractal trait existed.
+fn hand_written(vals: &mut [u32]) {
+    let bound = cmp::new(2.0, 0.0);
+
+    vals.par_chunks_mut(WIDTH)
+        .enumerate()
+        .for_each(|(im, row)| {
+            for (a, val) in row.iter_mut().enumerate() {
+                let c = cmp::new(a as f64 / SCALE + RE_MIN, im as f64 / SCALE + IM_MIN);
+                let mut z = cmp::new(0.0, 0.0);
+                let mut count = 0;
+
+                while count < ITERATIONS {
+                    z = z * z + c;
+                    count += 1;
+
+                    if cmp::norm_sqr(&z) >= cmp::norm_sqr(&bound) {
+                        break;
+                    }
+                }
+
+                *val = count;
+            }
+        });
+}
+
+fn through_trait(vals: &mut [u32]) {
+    Formula::Mandelbrot.compute_parallel(vals, WIDTH, |a, b| {
+        cmp::new(a as f64 / SCALE + RE_MIN, b as f64 / SCALE + IM_MIN)
+    }, ITERATIONS);
+}
+
+fn through_trait_f32(vals: &mut [u32]) {
+    Formula::Mandelbrot.compute_parallel(vals, WIDTH, |a, b| {
+        cmp::new((a as f64 / SCALE + RE_MIN) as f32, (b as f64 / SCALE + IM_MIN) as f32)
+    }, ITERATIONS);
+}
+
+/// Median wall-clock time of RUNS invocations, after one warm-up.
+fn median(f: fn(&mut [u32])) -> Duration {
+    let mut vals = vec![0; WIDTH * HEIGHT];
+    f(&mut vals);
+
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f(black_box(&mut vals));
+            start.elapsed()
+        })
+        .collect();
+
+    times.sort();
+    times[RUNS / 2]
+}
+
+fn main() {
+    let reference = median(hand_written);
+    let generic = median(through_trait);
+    let single = median(through_trait_f32);
+
+    println!("hand-written   {:>10.3?}", reference);
+    println!("through trait  {:>10.3?}", generic);
+    println!("ratio          {:>10.3}", generic.as_secs_f64() / reference.as_secs_f64());
+    println!("f32            {:>10.3?}", single);
+}
@@ -0,0 +1,55 @@
+//! [Mandelbrot for Python]
+//!
+//! A pyo3 module exposing the escape-time kernel, so that iteration
+//! grids can be computed from notebooks without any window. Built with
+//! maturin; see python/README.md.
+
+use mandelbrot_piston::fractal::Formula;
+use mandelbrot_piston::viewport::Viewport;
+use num::complex::Complex as cmp;
+use numpy::{PyArray1, PyArray2, PyArrayMethods};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// [Compute]
+///
+/// Computes the iteration counts of a px_w by px_h view of the
+/// Mandelbrot set, centred on (center_re, center_im) and `width` units
+/// wide, as a (px_h, px_w) array of uint32. Row 0 is the top of the view
+/// (the most negative imaginary part, as on screen), and points inside
+/// the set report exactly max_iter.
+///
+/// The GIL is released while the rayon kernel runs.
+#[pyfunction]
+fn compute<'py>(
+    py: Python<'py>,
+    center_re: f64,
+    center_im: f64,
+    width: f64,
+    px_w: usize,
+    px_h: usize,
+    max_iter: u32,
+) -> PyResult<Bound<'py, PyArray2<u32>>> {
+    if !(center_re.is_finite() && center_im.is_finite() && width.is_finite() && width > 0.0) {
+        return Err(PyValueError::new_err("the centre must be finite and the width positive"));
+    }
+    if px_w == 0 || px_h == 0 || max_iter == 0 {
+        return Err(PyValueError::new_err("px_w, px_h and max_iter must all be non-zero"));
+    }
+
+    let viewport = Viewport::new(cmp::new(center_re, center_im), width, px_w, px_h);
+    let vals = py.allow_threads(|| {
+        let mut vals = vec![0; px_w * px_h];
+        Formula::Mandelbrot.compute_parallel(&mut vals, px_w, |a, b| {
+            viewport.pixel_to_complex(a as f64, b as f64)
+        }, max_iter);
+        vals
+    });
+
+    PyArray1::from_vec(py, vals).reshape([px_h, px_w])
+}
+
+#[pymodule]
+fn mandelbrot(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(compute, module)?)
+}
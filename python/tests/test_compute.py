@@ -0,0 +1,30 @@
+"""Checks the kernel's output for a small, well-understood view."""
+
+import numpy as np
+import pytest
+
+import mandelbrot
+
+
+def test_known_view():
+    # 60x40 pixels, 3 units wide, centred on -0.5: the pixel grid
+    # samples -2.0..0.95 along the real axis and -1.0..0.95 along the
+    # imaginary axis, in steps of 0.05.
+    grid = mandelbrot.compute(-0.5, 0.0, 3.0, 60, 40, 100)
+
+    assert grid.shape == (40, 60)
+    assert grid.dtype == np.uint32
+
+    # The centre, -0.5, is in the main cardioid and never escapes.
+    assert grid[20, 30] == 100
+    # The top-left pixel samples -2 - 1i, which escapes on the first step.
+    assert grid[0, 0] == 1
+    # The set is symmetric about the real axis, which is row 20.
+    assert (grid[1:] == grid[:0:-1]).all()
+
+
+def test_rejects_empty_views():
+    with pytest.raises(ValueError):
+        mandelbrot.compute(0.0, 0.0, 0.0, 10, 10, 10)
+    with pytest.raises(ValueError):
+        mandelbrot.compute(0.0, 0.0, 1.0, 0, 10, 10)
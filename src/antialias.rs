@@ -0,0 +1,225 @@
+//! [Anti-Alias]
+//!
+//! Extra samples only where the frame needs them. The interior and the
+//! smooth gradients of the exterior look the same however finely they
+//! are sampled; it is where the counts jump from one pixel to the next,
+//! along the boundary, that a single sample per pixel shows jagged
+//! edges. After the primary pass, pixels whose counts differ from a
+//! 4-neighbour's by more than a threshold are evaluated again on a grid
+//! of sub-samples centred on the primary one, and coloured with the
+//! average of their colours. Toggled with X; Shift+X highlights the
+//! pixels that got extra samples, for tuning the threshold.
+//!
+//! Both the search for such pixels and their sub-samples run in
+//! parallel, except on wasm32.
+
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+use crate::colour::{shaded, Colorizer, IterResult, Tone};
+use crate::fractal::Escaping;
+use crate::overlay::{Bitmap, Overlay, Shape};
+use crate::viewport::Viewport;
+
+/// The sizes of sub-sample grid allowed, from 2x2 to 3x3.
+pub const GRIDS: std::ops::RangeInclusive<usize> = 2..=3;
+
+/// The colour refined pixels are highlighted in.
+const HIGHLIGHT: [u8; 4] = [255, 0, 255, 160];
+
+/// [Anti-Aliasing]
+///
+/// Fields:
+/// [enabled] Whether pixels along edges are sampled again;
+/// [threshold] How far apart neighbouring counts have to be for a pixel to be;
+/// [grid] The sub-samples taken along each side of such a pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AntiAliasing {
+    pub enabled: bool,
+    pub threshold: u32,
+    pub grid: usize,
+}
+
+impl Default for AntiAliasing {
+    fn default() -> AntiAliasing {
+        AntiAliasing { enabled: false, threshold: 8, grid: 3 }
+    }
+}
+
+/// [Edges]
+/// The indices of the pixels, in order, whose count differs from that
+/// of one of their 4-neighbours by more than `threshold`.
+pub fn edges(vals: &[u32], width: usize, height: usize, threshold: u32) -> Vec<usize> {
+    let row = |b: usize| -> Vec<usize> {
+        let at = |a: usize, b: usize| vals[b * width + a];
+        (0..width)
+            .filter(|&a| {
+                let here = at(a, b);
+                let differs = |there: u32| there.abs_diff(here) > threshold;
+                (a > 0 && differs(at(a - 1, b)))
+                    || (a + 1 < width && differs(at(a + 1, b)))
+                    || (b > 0 && differs(at(a, b - 1)))
+                    || (b + 1 < height && differs(at(a, b + 1)))
+            })
+            .map(|a| b * width + a)
+            .collect()
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let rows = (0..height).into_par_iter();
+    #[cfg(target_arch = "wasm32")]
+    let rows = 0..height;
+
+    rows.map(row).collect::<Vec<_>>().concat()
+}
+
+/// [Supersamples]
+/// The extra samples taken for one frame.
+///
+/// Fields:
+/// [grid] The sub-samples along each side of a pixel;
+/// [pixels] The indices of the pixels sampled again, in order;
+/// [counts] Their sub-samples' counts, grid x grid per pixel, row by row.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Supersamples {
+    grid: usize,
+    pixels: Vec<usize>,
+    counts: Vec<u32>,
+}
+
+impl Supersamples {
+    /// [Of]
+    /// The sub-samples for a frame of counts computed for the viewport,
+    /// or none at all while anti-aliasing is disabled. The grid is
+    /// centred on the primary sample, so an odd grid takes it again.
+    pub fn of(formula: Escaping, viewport: &Viewport, vals: &[u32], limit: u32, settings: &AntiAliasing) -> Supersamples {
+        if !settings.enabled {
+            return Supersamples::default();
+        }
+
+        let (width, grid) = (viewport.width_px(), settings.grid);
+        let pixels = edges(vals, width, viewport.height_px(), settings.threshold);
+        let offset = |i: usize| (i as f64 + 0.5) / grid as f64 - 0.5;
+
+        let mut counts = vec![0; pixels.len() * grid * grid];
+        formula.compute_points(&mut counts, |i| {
+            let (pixel, sub) = (pixels[i / (grid * grid)], i % (grid * grid));
+            let (a, b) = (pixel % width, pixel / width);
+            viewport.sample(a as f64 + offset(sub % grid), b as f64 + offset(sub / grid))
+        }, limit);
+
+        Supersamples { grid, pixels, counts }
+    }
+
+    pub fn pixels(&self) -> &[usize] {
+        &self.pixels
+    }
+
+    /// The share of the frame's `total` pixels that were sampled again.
+    pub fn share(&self, total: usize) -> f64 {
+        self.pixels.len() as f64 / total.max(1) as f64
+    }
+
+    /// [Blend]
+    /// Recolours the refined pixels of a coloured frame with the average
+    /// of their sub-samples' colours, each saturated as it would be on
+    /// its own, and averaged in linear light before being encoded, and
+    /// shaded as the pixel is, if there are shades.
+    pub fn blend<C: Colorizer>(&self, colorizer: &C, rgba: &mut [u8], limit: u32, tone: Tone, shades: Option<&[f32]>) {
+        let per_pixel = self.grid * self.grid;
+
+        for (&pixel, samples) in self.pixels.iter().zip(self.counts.chunks(per_pixel)) {
+            let mut sum = [0.0; 4];
+            for &count in samples {
+                for (total, channel) in sum.iter_mut().zip(tone.saturate(shaded(colorizer.color(IterResult { count, limit }), shades, pixel))) {
+                    *total += channel;
+                }
+            }
+
+            let average = tone.encode(sum.map(|total| total / per_pixel as f32));
+            rgba[pixel * 4..pixel * 4 + 4].copy_from_slice(&average);
+        }
+    }
+}
+
+/// [Sample Map]
+/// The debug view of which pixels got extra samples, toggled with Shift+X.
+///
+/// Fields:
+/// [visible] Whether the refined pixels are highlighted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SampleMap {
+    pub visible: bool,
+}
+
+impl SampleMap {
+    /// [Draw]
+    /// Covers the frame with a bitmap tinting the refined pixels, for an
+    /// overlay with `scale` frame pixels to each of its own.
+    pub fn draw(&self, overlay: &mut Overlay, supersamples: &Supersamples, viewport: &Viewport, scale: f64) {
+        if !self.visible {
+            return;
+        }
+
+        let (width, height) = (viewport.width_px(), viewport.height_px());
+        let mut rgba = vec![0; width * height * 4];
+        for &pixel in supersamples.pixels() {
+            rgba[pixel * 4..pixel * 4 + 4].copy_from_slice(&HIGHLIGHT);
+        }
+
+        let bitmap = Arc::new(Bitmap { width, height, rgba });
+        overlay.push(Shape::Image { bitmap, rect: [0.0, 0.0, width as f64 / scale, height as f64 / scale] });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::complex::Complex as cmp;
+
+    use super::*;
+    use crate::colour::{Gradient, LegacyColorizer};
+    use crate::fractal::Formula;
+
+    #[test]
+    fn only_jumps_are_edges() {
+        // A gentle ramp, with one column where the counts jump.
+        let vals: Vec<u32> = (0..6 * 3).map(|i| (i % 6) as u32 + if i % 6 >= 4 { 50 } else { 0 }).collect();
+
+        assert_eq!(edges(&vals, 6, 3, 8), vec![3, 4, 9, 10, 15, 16]);
+        assert_eq!(edges(&vals, 6, 3, 100), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn flat_frames_cost_nothing_extra() {
+        let viewport = Viewport::new(cmp::new(-0.2, 0.1), 0.01, 40, 20);
+        let settings = AntiAliasing { enabled: true, ..AntiAliasing::default() };
+
+        assert_eq!(Supersamples::of(Escaping::default(), &viewport, &[100; 800], 100, &settings).pixels(), &[] as &[usize]);
+    }
+
+    #[test]
+    fn boundary_pixels_blend_between_inside_and_out() {
+        let viewport = Viewport::new(cmp::new(-0.75, 0.0), 3.0, 120, 80);
+        let limit = 200;
+        let mut vals = vec![0; 120 * 80];
+        Formula::Mandelbrot.compute_parallel(&mut vals, 120, |a, b| viewport.pixel_to_complex(a as f64, b as f64), limit);
+
+        let settings = AntiAliasing { enabled: true, ..AntiAliasing::default() };
+        let samples = Supersamples::of(Escaping::default(), &viewport, &vals, limit, &settings);
+        assert!(samples.share(vals.len()) > 0.0 && samples.share(vals.len()) < 0.5, "{}", samples.share(vals.len()));
+
+        let colorizer = LegacyColorizer { scalar: 0.05, gradient: Gradient::default() };
+        let plain = crate::colour::colourise(&colorizer, &vals, limit, Tone::Srgb);
+        let mut blended = plain.clone();
+        samples.blend(&colorizer, &mut blended, limit, Tone::Srgb, None);
+
+        // Pixels away from the edges are untouched, and some on them change.
+        let unrefined = (0..vals.len()).filter(|i| !samples.pixels().contains(i));
+        assert!(unrefined.into_iter().all(|i| plain[i * 4..i * 4 + 4] == blended[i * 4..i * 4 + 4]));
+        assert_ne!(plain, blended);
+
+        assert_eq!(Supersamples::of(Escaping::default(), &viewport, &vals, limit, &AntiAliasing::default()), Supersamples::default());
+    }
+}
@@ -0,0 +1,1987 @@
+//! [App]
+//!
+//! The zoom itself: the simulation state, what happens on each update,
+//! how keys are handled, and how frames are coloured. Drawing to the
+//! screen is left to a `Backend`, so none of this depends on Piston.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use num::complex::Complex as cmp;
+#[cfg(not(target_arch = "wasm32"))]
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::antialias::{AntiAliasing, SampleMap, Supersamples};
+use crate::area::AreaEstimate;
+use crate::autolimit::AutoLimit;
+use crate::autopilot::AutoPilot;
+use crate::backend::{Backend, Event, Key, Pacing, UPDATES_PER_SECOND};
+use crate::bindings::{Action, Bindings};
+use crate::bookmark::{self, Bookmark};
+use crate::clock::{clock_time, RunClock};
+use crate::colour::{colourise_shaded, Gradient, LegacyColorizer, ScalarFade, Tone};
+use crate::crossfade::Crossfade;
+use crate::demo::Demo;
+use crate::crosshair::Crosshair;
+use crate::diff::DiffView;
+use crate::error::{report, AppError};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::explore::{search, BUDGET};
+use crate::export::save_png;
+use crate::fit::Fit;
+use crate::follow::Follower;
+use crate::fractal::{Escaping, Formula, ESCAPE_RADIUS};
+use crate::goto::{Entry, Prompt};
+use crate::graph::{FrameGraph, FrameHistory};
+use crate::grid::Grid;
+use crate::histogram::{Histogram, HistogramPanel};
+use crate::hud::{millis, window_title, FrameTimes, Hud, HudStats};
+use crate::inspect::{Inspector, PixelInfo};
+use crate::julia::JuliaPreview;
+use crate::legend::{draw_legend, Legend};
+use crate::heightmap::HeightMap;
+use crate::lighting::{self, Light};
+use crate::lyapunov::Lyapunov;
+use crate::minimap::Minimap;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::nebula::{self, Nebula};
+use crate::orbit::Orbit;
+use crate::overlay::Overlay;
+use crate::palette::Palette;
+use crate::panel::{Change, Panel, Params};
+use crate::pathlog::{LogEntry, PathLog};
+use crate::pause::Pause;
+use crate::perturb::Perturbed;
+use crate::progress::{Phase, Progress};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::screensaver::{self, Screensaver};
+#[cfg(unix)]
+use crate::signals::{Request, Signals};
+use crate::settings::{ConfigError, Settings, GRAPH_SCALE};
+use crate::split::{Side, Split};
+use crate::stats::FrameStats;
+use crate::tilecache::TileCache;
+use crate::tour::Tour;
+use crate::transition::Transition;
+use crate::viewport::{Projection, Viewport};
+use crate::watchdog::{Recovery, Watchdog};
+use crate::worker::{Frame, Job, Poll, Work};
+use crate::zoomer::Zoomer;
+
+/// The most zoom steps fast-forwarding takes a frame.
+const MAX_SPEED: u32 = 8;
+
+/// The most renders a second while saving power.
+const POWER_SAVING_FPS: u64 = 10;
+
+/// [App]
+/// The App struct defines the application and associated data. All
+/// fields within this structure are statically accessible from within
+/// the application's associated methods.
+///
+/// Fields:
+/// [vals] Row-major iteration counts determining whether a point is in the set or not;
+/// [histogram] The distribution of the counts in vals;
+/// [area] The area of the set in vals, measured as frames arrive while the HUD shows it;
+/// [whole_set] The area of the whole set, with the formula and limit it was rendered at;
+/// [supersamples] The extra samples taken along the edges in vals;
+/// [antialiasing] Whether and how edges are sampled again;
+/// [rgba] The coloured frame, rebuilt from vals whenever a frame is presented;
+/// [overlay] What is drawn over the frame, rebuilt alongside it;
+/// [viewport] The current mapping between pixels and the complex plane;
+/// [fit] Whether the frame is reshaped to the window when it is resized;
+/// [watchdog] The check on the view after each step, with the last good view;
+/// [initial] The view the zoom started from;
+/// [zoomer] The zoom animation;
+/// [tour] The tour of the bookmarks under way, if one is;
+/// [transition] The travel to a new view under way, if one is;
+/// [transition_frames] How many frames travelling to a new view takes, 0 jumping there;
+/// [bookmarks] The file bookmarks are kept in;
+/// [bindings] Which key does what;
+/// [autopilot] What steers the zoom target toward detail, when enabled;
+/// [follower] What walks the zoom target along the boundary, when enabled;
+/// [fade] The animated scalar of the colouring;
+/// [gradient] The tint of the colouring's ramp;
+/// [tone] How the colouring becomes the bytes shown and saved;
+/// [light] Whether and from where the counts are shaded as a height field;
+/// [lyapunov] Whether frames are coloured by their Lyapunov exponents, and on what scales;
+/// [exponents] The Lyapunov exponents of the frame's pixels, while they are kept;
+/// [heights] Their smooth counts, which the light shades from, while they are kept;
+/// [start] The zoom animation and the fade as they began, for restarts;
+/// [limit] The iteration limit (starts at 1200);
+/// [auto_limit] Whether and how far the limit is raised when frames look under-resolved;
+/// [formula] The escape-time formula being rendered;
+/// [radius] The radius its orbits escape at;
+/// [frames] How many frames have been computed, which only ever increases;
+/// [speed] How many zoom steps each frame takes, only the last being computed (1, 2, 4 or 8);
+/// [ups] How many updates a second the backend is asked for, from 1 to 120;
+/// [power_saving] Whether renders are few, skipped when nothing has changed, and wait on input while paused;
+/// [stale] Whether the counts or the colouring may have changed since the frame was last presented;
+/// [job] The frame being computed off the event loop, if one is;
+/// [held] The view of a frame that arrived while paused, and how long it took, its step waiting on the zoom resuming;
+/// [crossfade] The blend from the frame shown before the latest arrived;
+/// [skip] Whether the counts far from the boundary are interpolated rather than iterated;
+/// [perturb] Whether the counts are iterated against reference orbits, their glitches put right;
+/// [series] Whether perturbed counts skip iterations by series approximation;
+/// [validate] Whether the series is checked against a sample of pixels each frame;
+/// [perturbed] What perturbing the last frame took, if it was perturbed;
+/// [cursor_first] Whether frames are computed outward from the cursor's row, when it is over the frame;
+/// [clock] When the zoom started, and how long it has been paused;
+/// [compute_times] How long the escape-time pass took for recent frames;
+/// [history] The same for the last few seconds' worth, for the chart;
+/// [hud] The heads-up display;
+/// [minimap] The thumbnail of the whole set;
+/// [crosshair] The marker on the zoom target;
+/// [grid] Gridlines at round coordinates;
+/// [histogram_panel] The panel showing the histogram;
+/// [graph] The chart of recent compute times;
+/// [sample_map] The highlight on the pixels that got extra samples;
+/// [legend] The strip showing which colour each count gets;
+/// [diff] The comparison of each frame against a brute-force render, when enabled;
+/// [julia] The preview of the Julia set under the cursor;
+/// [inspector] The readout of the pixel under the cursor;
+/// [panel] The side panel of sliders, which takes the clicks on it;
+/// [split] The split view, and the configuration of its right side;
+/// [split_frame] The right side's counts, when they are computed apart from the left's;
+/// [goto] The prompt for a view to go to, while it is open;
+/// [height_map] The 3D view of the counts, and the camera it is seen from;
+/// [nebula] The Nebulabrot and the density built up for it (not on wasm32);
+/// [cursor] Where the pointer last was over the frame, in frame pixels;
+/// [hidpi] The frame's pixels per logical pixel: the window's physical pixels per logical pixel, times the render scale;
+/// [render_scale] The frame's pixels per physical pixel of the window;
+/// [rng] The generator behind the search for new targets (not on wasm32);
+/// [path_log] The log each computed frame's view is appended to, if there is one;
+/// [tile_cache] The counts of ground already covered, if frames are assembled from them,
+///              shared with the frame under way;
+/// [screensaver] The screensaver's state, when running as one (not on wasm32);
+/// [demo] The demo's clock and frame times, when running one;
+/// [signals] The flags Unix signals set, once their handlers are installed (Unix only);
+/// [pause] Game state: why the zoom is paused, if it is;
+/// [degenerate] The shares of interior and of fast-escaping pixels above which a frame is empty;
+/// [degenerate_overruled] Whether the user resumed from an empty frame, which holds off
+///                        pausing for empty frames until one is not.
+pub struct App {
+    vals: Vec<u32>,
+    histogram: Histogram,
+    area: AreaEstimate,
+    whole_set: Option<(Formula, u32, AreaEstimate)>,
+    supersamples: Supersamples,
+    antialiasing: AntiAliasing,
+    rgba: Vec<u8>,
+    overlay: Overlay,
+    viewport: Viewport,
+    fit: Fit,
+    watchdog: Watchdog,
+    initial: Viewport,
+    zoomer: Zoomer,
+    tour: Option<Tour>,
+    transition: Option<Transition>,
+    transition_frames: u64,
+    bookmarks: PathBuf,
+    bindings: Bindings,
+    autopilot: AutoPilot,
+    follower: Follower,
+    fade: ScalarFade,
+    gradient: Gradient,
+    tone: Tone,
+    light: Light,
+    lyapunov: Lyapunov,
+    exponents: Vec<f32>,
+    heights: Vec<f32>,
+    start: (Zoomer, ScalarFade),
+    limit: u32,
+    auto_limit: AutoLimit,
+    formula: Formula,
+    radius: f64,
+    frames: u64,
+    speed: u32,
+    ups: u64,
+    power_saving: bool,
+    stale: bool,
+    job: Option<Job>,
+    held: Option<(Viewport, Option<Duration>)>,
+    crossfade: Crossfade,
+    skip: bool,
+    perturb: bool,
+    series: bool,
+    validate: bool,
+    perturbed: Option<Perturbed>,
+    cursor_first: bool,
+    clock: RunClock,
+    compute_times: FrameTimes,
+    history: FrameHistory,
+    hud: Hud,
+    minimap: Minimap,
+    crosshair: Crosshair,
+    grid: Grid,
+    histogram_panel: HistogramPanel,
+    graph: FrameGraph,
+    sample_map: SampleMap,
+    legend: Legend,
+    diff: DiffView,
+    julia: JuliaPreview,
+    inspector: Inspector,
+    panel: Panel,
+    split: Split,
+    split_frame: Option<Frame>,
+    goto: Option<Prompt>,
+    height_map: HeightMap,
+    #[cfg(not(target_arch = "wasm32"))]
+    nebula: Nebula,
+    cursor: Option<[f64; 2]>,
+    hidpi: f64,
+    render_scale: f64,
+    #[cfg(not(target_arch = "wasm32"))]
+    rng: StdRng,
+    path_log: Option<PathLog>,
+    tile_cache: Option<Arc<Mutex<TileCache>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    screensaver: Option<Screensaver>,
+    demo: Option<Demo>,
+    #[cfg(unix)]
+    signals: Option<Signals>,
+    pause: Option<Pause>,
+    degenerate: (f64, f64),
+    degenerate_overruled: bool,
+}
+
+/// [App]
+/// Application related methods.
+impl App {
+
+    /// [New]
+    /// Validates the settings and builds the application state they
+    /// describe.
+    pub fn new(settings: &Settings) -> Result<App, ConfigError> {
+        let viewport = settings.validate()?;
+        let zoomer = Zoomer::new(settings.zoom, viewport.centre()).with_limits(settings.width_limits);
+        let fade = ScalarFade { scalar: settings.scalar, step_factor: settings.step_factor };
+
+        Ok(App {
+            vals: vec![0; viewport.width_px() * viewport.height_px()],
+            histogram: Histogram::new(settings.iterations),
+            area: AreaEstimate::default(),
+            whole_set: None,
+            supersamples: Supersamples::default(),
+            antialiasing: settings.antialias,
+            rgba: Vec::new(),
+            overlay: Overlay::new(),
+            viewport,
+            fit: settings.fit,
+            watchdog: Watchdog::new(viewport),
+            initial: viewport,
+            zoomer,
+            tour: None,
+            transition: None,
+            transition_frames: settings.transition_frames,
+            bookmarks: settings.bookmarks.clone(),
+            bindings: settings.bindings.clone(),
+            autopilot: AutoPilot::default(),
+            follower: Follower::default(),
+            fade,
+            gradient: settings.gradient,
+            tone: settings.tone,
+            light: settings.light,
+            lyapunov: settings.lyapunov,
+            exponents: Vec::new(),
+            heights: Vec::new(),
+            start: (zoomer, fade),
+            limit: settings.iterations,
+            auto_limit: settings.auto_limit,
+            formula: settings.formula,
+            radius: ESCAPE_RADIUS,
+            frames: 0,
+            speed: 1,
+            ups: UPDATES_PER_SECOND,
+            power_saving: false,
+            stale: true,
+            job: None,
+            held: None,
+            crossfade: Crossfade::default(),
+            skip: false,
+            perturb: false,
+            series: true,
+            validate: false,
+            perturbed: None,
+            cursor_first: true,
+            clock: RunClock::new(Local::now()),
+            compute_times: FrameTimes::default(),
+            history: FrameHistory::default(),
+            hud: Hud::default(),
+            minimap: Minimap::new(settings.formula, Projection::Plane),
+            crosshair: Crosshair::default(),
+            grid: Grid::default(),
+            histogram_panel: HistogramPanel::default(),
+            graph: FrameGraph::default(),
+            sample_map: SampleMap::default(),
+            legend: Legend::default(),
+            diff: DiffView::default(),
+            julia: JuliaPreview::default(),
+            inspector: Inspector::default(),
+            panel: Panel::default(),
+            split: Split::default(),
+            split_frame: None,
+            goto: None,
+            height_map: HeightMap::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            nebula: Nebula::default(),
+            cursor: None,
+            hidpi: 1.0,
+            render_scale: 1.0,
+            #[cfg(not(target_arch = "wasm32"))]
+            rng: settings.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+            path_log: None,
+            tile_cache: settings.tile_cache.clone().map(|cache| Arc::new(Mutex::new(TileCache::new(cache)))),
+            #[cfg(not(target_arch = "wasm32"))]
+            screensaver: None,
+            demo: None,
+            #[cfg(unix)]
+            signals: None,
+            pause: None,
+            degenerate: (settings.degenerate_interior, settings.degenerate_fast_escape),
+            degenerate_overruled: false,
+        })
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    pub fn vals(&self) -> &[u32] {
+        &self.vals
+    }
+
+    pub fn histogram(&self) -> &Histogram {
+        &self.histogram
+    }
+
+    pub fn paused(&self) -> bool {
+        self.pause.is_some()
+    }
+
+    pub fn pause(&self) -> Option<Pause> {
+        self.pause
+    }
+
+    pub fn compute_times(&self) -> &FrameTimes {
+        &self.compute_times
+    }
+
+    /// [Set Centre]
+    /// Moves the zoom to a new point, keeping the current width, and
+    /// carries on converging on it.
+    pub fn set_centre(&mut self, centre: cmp<f64>) {
+        self.viewport.set_centre(centre);
+        self.zoomer.set_target(centre);
+    }
+
+    /// [Restart]
+    /// Starts the zoom again from the initial view, diving into `target`
+    /// with the colour scalar starting from `scalar`, once the view has
+    /// travelled back there.
+    pub fn restart(&mut self, target: cmp<f64>, scalar: f32) {
+        let (zoomer, fade) = self.start;
+
+        self.travel(Bookmark::of(&self.initial()));
+        self.zoomer = zoomer;
+        self.zoomer.set_target(target);
+        self.fade = ScalarFade { scalar, ..fade };
+    }
+
+    /// [Start Screensaver]
+    /// Runs as a screensaver from the next update on, logging each
+    /// segment to `log`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_screensaver(&mut self, log: PathBuf) {
+        self.screensaver = Some(Screensaver::new(log));
+    }
+
+    /// [Start Demo]
+    /// Runs as a demo from the next update on, for `duration`.
+    pub fn start_demo(&mut self, duration: Duration) {
+        self.demo = Some(Demo::new(duration));
+    }
+
+    /// [End Demo]
+    /// Prints the demo's summary, if running one, returning whether it
+    /// has run its length.
+    fn end_demo(&mut self, now: Option<Instant>) -> bool {
+        let magnified = self.initial().width() / self.viewport.width();
+        let Some(demo) = self.demo.as_mut() else { return false };
+        if now.is_some_and(|now| !demo.due(now)) {
+            return false;
+        }
+        println!("{}", demo.summary(magnified));
+        self.demo = None;
+        true
+    }
+
+    /// [Listen For Signals]
+    /// Acts on the requests Unix signals make from the next event on.
+    #[cfg(unix)]
+    pub fn listen_for_signals(&mut self, signals: Signals) {
+        self.signals = Some(signals);
+    }
+
+    /// [Handle Signals]
+    /// Acts on the requests signals have made since the last event,
+    /// returning whether the run should end.
+    #[cfg(unix)]
+    fn handle_signals(&mut self) -> bool {
+        let Some(signals) = &self.signals else { return false };
+
+        for request in signals.take() {
+            match request {
+                Request::Pause => self.toggle_pause(),
+                Request::Screenshot => if let Some(path) = report(self.screenshot(false)) { println!("saved {}", path.display()) },
+                Request::Terminate => {
+                    if let Some(frame) = self.job.take().and_then(Job::wait).filter(|frame| frame.work == self.work()) {
+                        self.install(frame);
+                    }
+                    println!("terminated");
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// [Log Path]
+    /// Starts appending each computed frame's view to the log.
+    pub fn log_path(&mut self, log: PathLog) {
+        self.path_log = Some(log);
+    }
+
+    /// [Record Frame]
+    /// Appends the frame just computed to the path log, if there is one.
+    /// A log that fails to write is reported and closed, so that the zoom
+    /// carries on without it.
+    fn record_frame(&mut self, compute: Option<Duration>) {
+        let Some(log) = &mut self.path_log else { return };
+
+        let entry = LogEntry {
+            time: Local::now(),
+            frame: self.frames + 1,
+            centre: self.viewport.centre(),
+            width: self.viewport.width(),
+            limit: self.limit,
+            compute,
+            steps: self.speed,
+        };
+        let written = log.record(&entry);
+        self.log_written(written);
+    }
+
+    /// [Flush Log]
+    /// Writes out the path log's buffer if it is due, whether or not
+    /// frames are arriving.
+    fn flush_log(&mut self) {
+        let Some(log) = &mut self.path_log else { return };
+        let written = log.tick();
+        self.log_written(written);
+    }
+
+    /// Reports a write to the path log that failed, and closes the log.
+    fn log_written(&mut self, written: Result<(), AppError>) {
+        if report(written).is_none() {
+            if let Some(log) = self.path_log.take() {
+                println!("stopped logging to {}", log.path().display());
+            }
+        }
+    }
+
+    /// [Screensave]
+    /// Cuts to a new random dive when the screensaver's segment is up.
+    /// Should the search come up empty, it is tried again next update.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn screensave(&mut self) {
+        let (now, initial) = (Instant::now(), self.initial());
+        let Some(saver) = self.screensaver.as_mut().filter(|saver| saver.due(now, &self.viewport)) else { return };
+        let Some(find) = search(self.formula, &initial, self.limit, &mut self.rng, BUDGET) else { return };
+
+        let colouring = screensaver::colouring(&mut self.rng);
+        report(saver.begin(now, find.point, colouring));
+        self.gradient = colouring.gradient;
+        self.restart(find.point, colouring.scalar);
+    }
+
+    /// [Frame]
+    /// Colours the current iteration counts, checking the value in vals
+    /// at each pixel and colouring it with the colorizer. While frames
+    /// are being compared, the heatmap of differences is shown instead,
+    /// and in the 3D view the surface is, coloured as the frame would be.
+    pub fn frame(&mut self) -> &[u8] {
+        self.rgba = self.diff.heatmap(self.vals.len()).unwrap_or_else(|| self.coloured());
+        if self.height_map.visible {
+            self.rgba = self.height_map.render(&self.vals, self.viewport.width_px(), self.limit, &self.rgba);
+        }
+        &self.rgba
+    }
+
+    /// [Coloured]
+    /// The frame as coloured on screen, with the pixels along edges
+    /// blended from their extra samples, and shaded if it is lit. Split,
+    /// the right side is coloured as it is set up, from the left's counts
+    /// if they do for it, or from its own once they are in. Coloured by
+    /// their Lyapunov exponents, once a frame has them, pixels are not
+    /// blended, the extra samples having no exponents. Lit, pixels are
+    /// shaded once a frame has their smooth counts.
+    fn coloured(&self) -> Vec<u8> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.nebula.enabled {
+            return self.nebula.image();
+        }
+        let width = self.viewport.width_px();
+        let colour = |colorizer: LegacyColorizer, tone: Tone, vals: &[u32], exponents: &[f32], heights: &[f32], supersamples: &Supersamples| {
+            let shades = self.light.shades(heights, width, self.limit).filter(|shades| shades.len() == vals.len());
+            if self.lyapunov.enabled && exponents.len() == vals.len() {
+                return self.lyapunov.colourise(vals, exponents, self.limit, tone, shades.as_deref());
+            }
+            let mut rgba = colourise_shaded(&colorizer, vals, self.limit, tone, shades.as_deref());
+            supersamples.blend(&colorizer, &mut rgba, self.limit, tone, shades.as_deref());
+            rgba
+        };
+        let mut rgba = colour(self.colorizer(), self.tone, &self.vals, &self.exponents, &self.heights, &self.supersamples);
+
+        let Some(right) = self.split.right() else { return rgba };
+        let (vals, exponents, heights, supersamples) = match &self.split_frame {
+            _ if right.shares_counts(&self.left_side()) => (&self.vals, &self.exponents, &self.heights, &self.supersamples),
+            Some(frame) if frame.vals.len() == self.vals.len() => (&frame.vals, &frame.exponents, &frame.heights, &frame.supersamples),
+            _ => return rgba,
+        };
+        let other = colour(LegacyColorizer { scalar: right.scalar, gradient: self.gradient }, right.tone, vals, exponents, heights, supersamples);
+        self.split.compose(&mut rgba, &other, width);
+
+        rgba
+    }
+
+    /// The left side of a split, which is the app as it is set up.
+    fn left_side(&self) -> Side {
+        Side { formula: self.formula, perturb: self.perturb, scalar: self.fade.scalar, tone: self.tone }
+    }
+
+    /// The colour mapping, which follows the animated scalar.
+    fn colorizer(&self) -> LegacyColorizer {
+        LegacyColorizer { scalar: self.fade.scalar, gradient: self.gradient }
+    }
+
+    fn stats(&self) -> HudStats<'_> {
+        let now = Local::now();
+        HudStats {
+            viewport: &self.viewport,
+            initial_width: self.initial().width(),
+            limit: self.limit,
+            frames: self.frames,
+            speed: self.speed,
+            progress: self.phase(),
+            compute: &self.compute_times,
+            total: self.clock.total(now),
+            running: self.clock.running(now),
+            area: self.area,
+        }
+    }
+
+    /// How far the frame under way has got, if it is slow enough to say.
+    fn phase(&self) -> Option<Phase> {
+        self.job.as_ref().and_then(|job| job.phase(Instant::now()))
+    }
+
+    /// [Title]
+    /// The window title: magnification, limit, compute time and whether
+    /// the zoom is paused.
+    pub fn title(&self) -> String {
+        window_title(&self.stats(), self.pause)
+    }
+
+    /// [Refresh Overlay]
+    /// Rebuilds what is drawn over the frame.
+    /// Everything is laid out in logical pixels, on a view of the frame
+    /// as the window measures it, so that it comes out the same size on
+    /// any display, and is scaled up to frame pixels at the end.
+    fn refresh_overlay(&mut self) {
+        let mut overlay = Overlay::new();
+        let logical = self.logical_viewport();
+        let frame = [logical.width_px() as f64, logical.height_px() as f64];
+
+        // The grid goes underneath everything else.
+        self.grid.draw(&mut overlay, &logical);
+        self.sample_map.draw(&mut overlay, &self.supersamples, &self.viewport, self.hidpi);
+        let stats = self.stats();
+        self.hud.draw(&mut overlay, &stats);
+        self.legend.draw(&mut overlay, &self.colorizer(), self.tone, self.limit, frame, self.hud.bounds(&stats));
+        let panel_top = self.panel_top(&stats);
+
+        let magnification = self.initial().width() / self.viewport.width();
+        self.minimap.draw(&mut overlay, &logical, magnification);
+        self.crosshair.draw(&mut overlay, &logical, self.zoomer.target());
+        self.histogram_panel.draw(&mut overlay, &self.histogram, frame[0]);
+        let below = self.histogram_panel.bounds(frame[0]).map_or(4.0, |[_, y, _, h]| y + h + 4.0);
+        self.graph.draw(&mut overlay, &self.history, frame[0], below);
+
+        // The preview follows the cursor, so it is brought up to date here
+        // rather than on update, and keeps up while the zoom is paused. It
+        // follows the tone too, with the cursor away.
+        let parameter = self.cursor.map(|[x, y]| self.viewport.sample(x, y)).or(self.julia.parameter());
+        if let Some(c) = parameter {
+            self.julia.set_parameter(c, self.tone);
+        }
+        self.julia.draw(&mut overlay, frame[0], frame[1]);
+
+        self.panel.draw(&mut overlay, &self.params(), panel_top);
+        self.split.draw(&mut overlay, &self.left_side(), frame);
+
+        if let Some(phase) = self.phase() {
+            phase.draw(&mut overlay, frame);
+        }
+        if let Some(prompt) = &self.goto {
+            prompt.draw(&mut overlay, frame);
+        }
+        if let Some(pause) = self.pause {
+            pause.draw(&mut overlay, frame[0], frame[1]);
+        }
+
+        // The readout goes on top, as it follows the cursor over everything.
+        // Only looked up while shown, as finding the period takes iterating.
+        if let Some(at) = self.cursor.filter(|_| self.inspector.visible) {
+            if let Some(info) = PixelInfo::at(self.formula, &self.viewport, &self.vals, self.limit, at) {
+                self.inspector.draw(&mut overlay, &logical, at.map(|v| v / self.hidpi), &info);
+            }
+        }
+
+        overlay.scale(self.hidpi);
+        self.overlay = overlay;
+    }
+
+    /// Where the panel starts, under the HUD if it is shown.
+    fn panel_top(&self, stats: &HudStats) -> f64 {
+        self.hud.bounds(stats).map_or(4.0, |[_, y, _, h]| y + h + 4.0)
+    }
+
+    /// [Params]
+    /// The values the panel shows, the zoom step as the share of the
+    /// view's width it trims off.
+    fn params(&self) -> Params {
+        Params {
+            limit: self.limit,
+            radius: self.radius,
+            zoom_step: 2.0 * self.zoomer.zoom() / self.viewport.width(),
+            scalar: self.fade.scalar,
+            speed: self.speed,
+            render_scale: self.render_scale,
+            tone: self.tone,
+            formula: self.formula,
+            gradient: self.gradient,
+            lyapunov: self.lyapunov.enabled,
+            light: self.light.enabled,
+        }
+    }
+
+    /// [Apply]
+    /// Makes a change asked for on the panel, printing it as the keys do.
+    fn apply(&mut self, change: Change) {
+        match change {
+            Change::Limit(limit) => {
+                self.limit = limit.max(1);
+                println!("limit={}", self.limit);
+            }
+            Change::Radius(radius) => {
+                self.radius = radius;
+                println!("escape_radius={radius}");
+            }
+            Change::ZoomStep(step) => {
+                self.zoomer.set_zoom(step * self.viewport.width() / 2.0);
+                println!("zoom={}", self.zoomer.zoom());
+            }
+            Change::Scalar(scalar) => {
+                self.fade.scalar = scalar;
+                println!("scalar={scalar}");
+            }
+            Change::Speed(speed) => self.set_speed(speed.clamp(1, MAX_SPEED)),
+            Change::RenderScale(render_scale) => self.set_render_scale(render_scale),
+            Change::Tone(tone) => {
+                self.tone = tone;
+                println!("tone={}", self.tone.name());
+            }
+            Change::Formula(formula) => self.set_formula(formula),
+            Change::Gradient(gradient) => {
+                self.gradient = gradient;
+                println!("gradient={}", gradient.name());
+            }
+            Change::Lyapunov(on) => self.set_lyapunov(on),
+            Change::Light(on) => self.set_light(on),
+        }
+    }
+
+    /// Turns the Lyapunov colouring on or off.
+    fn set_lyapunov(&mut self, on: bool) {
+        self.lyapunov.enabled = on;
+        println!("lyapunov={}", if on { self.lyapunov.to_arg() } else { "off".to_string() });
+    }
+
+    /// Turns the light on or off.
+    fn set_light(&mut self, on: bool) {
+        self.light.enabled = on;
+        println!("light={}", if on { self.light.to_arg() } else { "off".to_string() });
+    }
+
+    /// [Set Formula]
+    /// Switches to another formula, in the minimap as well.
+    fn set_formula(&mut self, formula: Formula) {
+        self.formula = formula;
+        self.minimap.set(formula, self.viewport.projection());
+        println!("formula={}", formula.name());
+    }
+
+    /// [Initial]
+    /// The view the zoom starts from, in the projection the view is in:
+    /// inverted, the overview of the inverted figure, across as many
+    /// pixels.
+    fn initial(&self) -> Viewport {
+        let projection = self.viewport.projection();
+        if projection == Projection::Plane {
+            return self.initial;
+        }
+        let (centre, width) = projection.overview(self.formula);
+        let mut initial = Viewport::new(centre, width, self.initial.width_px(), self.initial.height_px());
+        initial.set_projection(projection);
+        initial
+    }
+
+    /// [Toggle Projection]
+    /// Switches between the plane and the inverted projection. A view
+    /// well inside the initial one is carried across, the map being its
+    /// own inverse and stretching widths by 1/|w|^2 about the centre, so
+    /// that the zoom dives on into the same place; a wider one, or one
+    /// centred on the origin, starts from the other's overview. The view
+    /// jumps there, a transition between the two planes meaning nothing.
+    fn toggle_projection(&mut self) {
+        let (centre, width) = (self.viewport.centre(), self.viewport.width());
+        let carried = width * 10.0 < self.initial().width() && centre.norm_sqr() > 0.0;
+
+        let projection = self.viewport.projection().other();
+        self.viewport.set_projection(projection);
+        let to = if carried {
+            Bookmark { centre: projection.apply(centre), width: width / centre.norm_sqr() }
+        } else {
+            Bookmark::of(&self.initial())
+        };
+
+        if self.tour.is_some() {
+            self.end_tour();
+        }
+        self.transition = None;
+        self.viewport.set_centre(to.centre);
+        self.viewport.set_width(to.width);
+        let (start, _) = self.start;
+        self.zoomer.set_target(to.centre);
+        self.zoomer.set_zoom(start.zoom() * to.width / self.initial().width());
+        self.follower.hold();
+        self.minimap.set(self.formula, projection);
+        println!("projection={}", projection.name());
+    }
+
+    /// [Logical Viewport]
+    /// The view as the window measures it, in logical pixels.
+    fn logical_viewport(&self) -> Viewport {
+        let [width, height] = [self.viewport.width_px(), self.viewport.height_px()]
+            .map(|side| ((side as f64 / self.hidpi).round() as usize).max(1));
+        self.viewport.rescaled(width, height)
+    }
+
+    /// [Work]
+    /// What the next frame is computed from.
+    fn work(&self) -> Work {
+        Work {
+            formula: self.formula,
+            radius: self.radius,
+            viewport: self.viewport,
+            limit: self.limit,
+            antialiasing: self.antialiasing,
+            skip: self.skip,
+            perturb: self.perturb,
+            series: self.series,
+            validate: self.validate,
+            lyapunov: self.lyapunov.enabled,
+            light: self.light.enabled,
+        }
+    }
+
+    /// The formula with the radius its orbits escape at.
+    fn escaping(&self) -> Escaping {
+        self.formula.escaping(self.radius)
+    }
+
+    /// [Other Work]
+    /// What the right side of a split is computed from, when the left's
+    /// counts do not do for it.
+    fn other_work(&self) -> Option<Work> {
+        let right = self.split.right().filter(|right| !right.shares_counts(&self.left_side()))?;
+        Some(Work { formula: right.formula, perturb: right.perturb, ..self.work() })
+    }
+
+    /// [Update Parallel]
+    ///
+    /// The update method services the application logic (as opposed
+    /// to rendering).
+    ///
+    /// In this case, the method is going through every point in the
+    /// current domain, and determining whether or not it is a member
+    /// of the set by iterating over the selected formula.
+    ///
+    /// The is the parallelized version of the function, using rayon,
+    /// computing the frame before it returns, as the benchmark needs.
+    /// Only the escape-time pass itself, with the extra samples along
+    /// edges, is timed, and not the render it is compared against. With
+    /// a tile cache, the frame is assembled from it where it can be.
+    pub fn update_parallel(&mut self) {
+        // Only update if the game is unpaused:
+        if self.pause.is_none() {
+            let work = self.work();
+            let ((histogram, supersamples, perturbed), elapsed) = time(|| work.compute(&mut self.vals, &mut self.exponents, &mut self.heights, self.tile_cache.as_deref(), &Progress::default(), None));
+
+            self.histogram = histogram;
+            self.supersamples = supersamples;
+            self.perturbed = perturbed;
+            self.split_frame = self.other_work().map(|work| {
+                let (mut vals, mut exponents, mut heights) = (vec![0; self.vals.len()], Vec::new(), Vec::new());
+                let (histogram, supersamples, perturbed) = work.compute(&mut vals, &mut exponents, &mut heights, self.tile_cache.as_deref(), &Progress::default(), None);
+                Frame { work, vals, histogram, supersamples, perturbed, exponents, heights, other: None, elapsed: Duration::ZERO }
+            });
+            self.finish(elapsed);
+        }
+    }
+
+    /// [Update Background]
+    ///
+    /// The update the window runs on: the frame is computed on a worker
+    /// thread, so that renders carry on while it is under way. Each
+    /// update takes the frame if it has arrived, then starts the next
+    /// unless the zoom is paused. A frame whose view, formula or limit
+    /// has changed under it (by a resize, a restart or a key, say) is
+    /// cancelled, or dropped if it has arrived, and the new one started
+    /// straight away; one arriving after a pause is kept, as a frame
+    /// under way is always finished, though the view only steps on from
+    /// it once the zoom resumes. Split, the right side's frame is
+    /// computed with the left's, when it needs its own counts. Showing
+    /// the Nebulabrot, an update samples into its density instead.
+    pub fn update_background(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.nebula.enabled {
+            if self.pause.is_none() {
+                self.nebula.sample(&self.viewport, &mut self.rng, nebula::BUDGET);
+                self.stale = true;
+            }
+            return;
+        }
+
+        if let Some(job) = &self.job {
+            let current = job.work == self.work() && job.other == self.other_work();
+            match job.poll() {
+                Poll::Running if !current => self.job = None,
+                Poll::Running => return,
+                Poll::Done(frame) => {
+                    self.job = None;
+                    if current {
+                        self.install(*frame);
+                    }
+                }
+                Poll::Lost => {
+                    eprintln!("error: the frame under way was lost");
+                    self.job = None;
+                }
+            }
+        }
+
+        if self.pause.is_none() {
+            self.release_held();
+            let first = self.cursor.filter(|_| self.cursor_first).map(|[_, y]| y as usize);
+            self.job = Some(Job::start(self.work(), self.other_work(), self.tile_cache.clone(), first));
+        }
+    }
+
+    /// [Install]
+    /// Takes a frame computed off the event loop as the current one.
+    fn install(&mut self, frame: Frame) {
+        self.vals = frame.vals;
+        self.histogram = frame.histogram;
+        self.supersamples = frame.supersamples;
+        self.perturbed = frame.perturbed;
+        self.exponents = frame.exponents;
+        self.heights = frame.heights;
+        self.split_frame = frame.other.map(|other| *other);
+        self.finish(Some(frame.elapsed));
+    }
+
+    /// [Finish]
+    /// Everything that follows a frame's counts being computed: the
+    /// comparison against a brute-force render, the records of how long
+    /// it took, the fade into it, and the step to the next view. A frame
+    /// that raises the limit stays where it is, to be computed again
+    /// with the new one, and one that arrives while paused waits for the
+    /// zoom to resume before taking its step.
+    fn finish(&mut self, elapsed: Option<Duration>) {
+        self.diff.compare(self.escaping(), &self.viewport, &self.vals, self.limit);
+        self.measure();
+
+        if let Some(elapsed) = elapsed {
+            self.compute_times.record(elapsed);
+            if let Some(demo) = &mut self.demo {
+                demo.record(elapsed);
+            }
+            self.history.record(elapsed);
+            self.crossfade.arrive(Instant::now(), &self.rgba);
+        }
+        self.stale = true;
+        if self.raise_limit() {
+            return;
+        }
+        // A frame finished after the zoom was paused is shown for the
+        // view it was computed for, which must stay the view in place.
+        if self.pause.is_some() {
+            self.held = Some((self.viewport, elapsed));
+            return;
+        }
+        self.held = None;
+        self.record_frame(elapsed);
+        self.advance();
+    }
+
+    /// [Release Held]
+    /// Takes the step a frame that arrived while paused was held back
+    /// from, now the zoom has resumed, unless the view has been moved
+    /// from it since, when the frame is stale anyway.
+    fn release_held(&mut self) {
+        let Some((viewport, elapsed)) = self.held.take() else { return };
+        if viewport == self.viewport {
+            self.record_frame(elapsed);
+            self.advance();
+        }
+    }
+
+    /// [Raise Limit]
+    /// Raises the limit if the frame looks under-resolved at it, saying
+    /// why, so that a jump in frame times is explained.
+    fn raise_limit(&mut self) -> bool {
+        let Some((limit, share)) = self.auto_limit.raise(&self.vals, self.viewport.width_px(), self.limit) else { return false };
+
+        println!("limit={limit} (raised from {}: {:.1}% of the pixels at it border slow escapes)", self.limit, share * 100.0);
+        self.limit = limit;
+        true
+    }
+
+    /// [Update Sequential]
+    ///
+    /// The sequential counterpart of update_parallel, kept for
+    /// comparing against the parallel speedup. The extra samples along
+    /// edges are still taken in parallel.
+    pub fn update_sequential(&mut self) {
+        if self.pause.is_none() {
+            let viewport = self.viewport;
+            let (histogram, elapsed) = time(|| {
+                let histogram = self.escaping().compute_sequential(&mut self.vals, viewport.width_px(), |a, b| {
+                    viewport.sample(a as f64, b as f64)
+                }, self.limit);
+                self.supersamples = Supersamples::of(self.escaping(), &viewport, &self.vals, self.limit, &self.antialiasing);
+                histogram
+            });
+
+            self.histogram = histogram;
+            self.finish(elapsed);
+        }
+    }
+
+    /// [Resize]
+    /// Follows the window to a new size, in logical pixels. Letterboxing
+    /// is left to the backend, but extending the view reshapes the frame,
+    /// and the initial view with it, to the render scale's frame pixels
+    /// per physical window pixel, at the same size on the plane. The new frame is
+    /// computed straight away, so that nothing stale or blank is shown
+    /// while resizing, even when paused.
+    pub fn resize(&mut self, window: [f64; 2]) {
+        let [width, height] = window.map(|side| (side * self.hidpi).round().max(0.0) as usize);
+        if self.fit != Fit::Extend || width == 0 || height == 0 || [width, height] == [self.viewport.width_px(), self.viewport.height_px()] {
+            return;
+        }
+
+        self.viewport.resize(width, height);
+        self.initial.resize(width, height);
+        self.recompute();
+    }
+
+    /// [Rescale]
+    /// Follows the window to a display with a different scale factor.
+    /// The frame keeps its view, and its size in logical pixels, but is
+    /// spread over as many pixels as the display has physical ones there,
+    /// so that it stays sharp, and is computed again straight away as on
+    /// a resize.
+    pub fn rescale(&mut self, scale: f64) {
+        if scale.is_finite() && scale > 0.0 {
+            self.reframe(scale * self.render_scale);
+        }
+    }
+
+    /// [Set Render Scale]
+    /// Computes frames at another share of the window's physical pixels,
+    /// fewer for speed or more to smooth the edges, the backend filling
+    /// the window with them either way.
+    fn set_render_scale(&mut self, render_scale: f64) {
+        let display = self.hidpi / self.render_scale;
+        self.render_scale = render_scale;
+        self.reframe(display * render_scale);
+        println!("render_scale={render_scale}");
+    }
+
+    /// [Reframe]
+    /// Spreads the frame over `hidpi` pixels per logical pixel, keeping
+    /// its view, and computes it again.
+    fn reframe(&mut self, hidpi: f64) {
+        if hidpi == self.hidpi {
+            return;
+        }
+
+        let [width, height] = [self.viewport.width_px(), self.viewport.height_px()]
+            .map(|side| ((side as f64 / self.hidpi * hidpi).round() as usize).max(1));
+        self.hidpi = hidpi;
+        self.viewport = self.viewport.rescaled(width, height);
+        self.initial = self.initial.rescaled(width, height);
+        self.recompute();
+    }
+
+    /// [Recompute]
+    /// Computes the frame again from scratch after its size has changed.
+    fn recompute(&mut self) {
+        let (width, height) = (self.viewport.width_px(), self.viewport.height_px());
+        self.vals = vec![0; width * height];
+        self.cursor = None;
+        self.split_frame = None;
+
+        let viewport = self.viewport;
+        let map = |a: usize, b: usize| viewport.sample(a as f64, b as f64);
+        self.heights.clear();
+        self.histogram = if self.light.enabled {
+            self.escaping().compute_heights(&mut self.vals, &mut self.heights, width, map, self.limit)
+        } else {
+            self.escaping().compute_parallel(&mut self.vals, width, map, self.limit)
+        };
+        self.supersamples = Supersamples::of(self.escaping(), &viewport, &self.vals, self.limit, &self.antialiasing);
+        self.diff.compare(self.escaping(), &viewport, &self.vals, self.limit);
+        self.measure();
+    }
+
+    /// [Measure]
+    /// Estimates the area of the set in the frame just computed, for the
+    /// HUD. The histogram has counted the interior already, but finding
+    /// its edge takes a pass over vals, so with the HUD hidden it waits
+    /// until the HUD is shown.
+    fn measure(&mut self) {
+        if self.hud.visible {
+            self.area = AreaEstimate::of(&self.histogram, &self.vals, &self.viewport);
+        }
+    }
+
+    /// [Advance]
+    /// Steps both animations: the zoom, and the colour scalar fading with
+    /// it. The auto-pilot steers from the counts just computed. A tour
+    /// moves the view in place of the zoom while it lasts. Fast-forwarded,
+    /// each takes several steps at once, the target being steered only
+    /// from the frame in view.
+    fn advance(&mut self) {
+        self.frames += 1;
+        let speed = self.speed;
+
+        if let Some(transition) = &mut self.transition {
+            for _ in 0..speed {
+                transition.advance(&mut self.viewport);
+            }
+            if transition.finished() {
+                self.transition = None;
+            }
+        } else if let Some(tour) = &mut self.tour {
+            for _ in 0..speed {
+                if let Some(stop) = tour.advance(&mut self.viewport) {
+                    println!("reached {}", stop.to_line());
+                }
+                if tour.over() {
+                    break;
+                }
+            }
+            if tour.over() {
+                self.end_tour();
+            }
+        } else {
+            self.check_degenerate();
+
+            let target = self.autopilot.steer(self.frames, &self.vals, &self.viewport, self.zoomer.target());
+            let target = self.follower.steer(&self.vals, &self.viewport, target);
+            self.zoomer.set_target(target);
+            for _ in 0..speed {
+                self.zoomer.advance(&mut self.viewport);
+            }
+        }
+
+        for _ in 0..speed {
+            self.fade.advance();
+        }
+        self.watch();
+    }
+
+    /// [Watch]
+    /// Checks the view the step left, and if it has gone wrong, says
+    /// what was wrong with it and how it was put right, and pauses. Put
+    /// back to the last good view, the zoom starts again from there at
+    /// the rate it began with, whatever was moving the view being
+    /// stopped, as it may be what took it wrong.
+    fn watch(&mut self) {
+        let Some(recovery) = self.watchdog.check(&mut self.viewport) else { return };
+        eprintln!("error: {}", recovery.describe());
+
+        if let Recovery::Restored { .. } = recovery {
+            self.transition = None;
+            self.end_tour();
+        }
+        self.pause = Some(Pause::Recovered);
+        self.clock.pause(Local::now());
+    }
+
+    /// [Set UPS]
+    /// Changes how many updates a second are asked for, within 1 to 120.
+    /// At 1 a deep zoom becomes a slideshow; renders keep their pace
+    /// either way, so the window stays responsive.
+    fn set_ups(&mut self, ups: u64) {
+        self.ups = ups.clamp(1, UPDATES_PER_SECOND);
+        println!("ups={}", self.ups);
+    }
+
+    /// [Set Power Saving]
+    /// Starts or stops saving power: rendering at most 10 times a second,
+    /// skipping renders that would show the same as the last, and while
+    /// paused, waiting on input rather than updating at all (so a signal
+    /// is only acted on once there is some).
+    pub fn set_power_saving(&mut self, on: bool) {
+        self.power_saving = on;
+        announce("power_saving", on);
+    }
+
+    /// [Pacing]
+    /// How often the backend should report updates and renders.
+    pub fn pacing(&self) -> Pacing {
+        Pacing {
+            ups: self.ups,
+            max_fps: if self.power_saving { POWER_SAVING_FPS } else { Pacing::default().max_fps },
+            lazy: self.power_saving && self.pause.is_some(),
+        }
+    }
+
+    /// [Render]
+    /// Brings the frame and its overlay up to date for presenting at
+    /// `now`, blended from the last frame while a crossfade is under way,
+    /// or, while saving power, says there is nothing to present when
+    /// neither has changed since the last was.
+    fn render(&mut self, now: Instant) -> bool {
+        let shown = std::mem::take(&mut self.overlay);
+        self.refresh_overlay();
+        let fading = self.crossfade.progress(now).is_some();
+        if self.power_saving && !self.stale && !fading && self.overlay == shown {
+            return false;
+        }
+
+        self.frame();
+        self.crossfade.blend(&mut self.rgba, now);
+        self.stale = false;
+        true
+    }
+
+    /// [Set Speed]
+    /// Changes how many zoom steps each frame takes.
+    fn set_speed(&mut self, speed: u32) {
+        self.speed = speed;
+        println!("speed={speed}x");
+    }
+
+    /// [Bookmark]
+    /// Adds the current view to the bookmarks file.
+    fn bookmark(&self) {
+        let bookmark = Bookmark::of(&self.viewport);
+        if report(bookmark::append(&self.bookmarks, &bookmark)).is_some() {
+            println!("bookmarked {}", bookmark.to_line());
+        }
+    }
+
+    /// [Toggle Tour]
+    /// Starts a tour of the bookmarks from the current view, or stops
+    /// the one under way.
+    fn toggle_tour(&mut self, looping: bool) {
+        if self.tour.is_some() {
+            println!("tour stopped");
+            self.end_tour();
+            return;
+        }
+
+        let Some(stops) = report(bookmark::load(&self.bookmarks)) else { return };
+        self.tour = Tour::new(stops, looping);
+        match &self.tour {
+            Some(_) => println!("touring {}{}", self.bookmarks.display(), if looping { " on a loop" } else { "" }),
+            None => println!("no bookmarks in {} (B adds one)", self.bookmarks.display()),
+        }
+    }
+
+    /// [End Tour]
+    /// Hands the view back to the zoom, diving into wherever the tour
+    /// left off at the rate the zoom started with.
+    fn end_tour(&mut self) {
+        self.tour = None;
+
+        let (start, _) = self.start;
+        self.zoomer = start;
+        self.zoomer.set_zoom(start.zoom() * self.viewport.width() / self.initial().width());
+        self.zoomer.set_target(self.viewport.centre());
+    }
+
+    /// [Travel]
+    /// Moves the view to another, over the transition's frames from the
+    /// next update on, or at once if transitions take none. A tour under
+    /// way is ended, as the view is going elsewhere.
+    fn travel(&mut self, to: Bookmark) {
+        if self.tour.is_some() {
+            self.end_tour();
+        }
+        if self.transition_frames == 0 {
+            self.viewport.set_centre(to.centre);
+            self.viewport.set_width(to.width);
+            return;
+        }
+        self.transition = Some(Transition::new(Bookmark::of(&self.viewport), to, self.transition_frames));
+    }
+
+    /// [Prompt Key]
+    /// Passes a key to the goto prompt, travelling to the view on it once
+    /// entered, the zoom going on to dive there at the rate it would have.
+    fn prompt_key(&mut self, key: Key) {
+        let Some(prompt) = &mut self.goto else { return };
+        match prompt.key(key) {
+            Entry::Open => {}
+            Entry::Cancelled => self.goto = None,
+            Entry::Go(to) => {
+                self.goto = None;
+                let (start, _) = self.start;
+                self.zoomer.set_target(to.centre);
+                self.zoomer.set_zoom(start.zoom() * to.width / self.initial().width());
+                self.travel(to);
+                println!("going to {}", to.to_line());
+            }
+        }
+    }
+
+    /// Types a character into the goto prompt, if it is open.
+    fn type_char(&mut self, c: char) {
+        if let Some(prompt) = &mut self.goto {
+            prompt.insert(&c.to_string());
+        }
+    }
+
+    /// [Paste]
+    /// Pastes the clipboard's text into the goto prompt, if it is open,
+    /// or says under it that there is none to paste.
+    fn paste(&mut self) {
+        let Some(prompt) = &mut self.goto else { return };
+        #[cfg(not(target_arch = "wasm32"))]
+        let text = crate::goto::clipboard();
+        #[cfg(target_arch = "wasm32")]
+        let text: Option<String> = None;
+        match text {
+            Some(text) => prompt.insert(&text),
+            None => prompt.fail("no text on the clipboard to paste".to_string()),
+        }
+    }
+
+    /// [Stop Transition]
+    /// Stops the travel to a new view where it is, the zoom diving on
+    /// from there, returning whether there was one.
+    fn stop_transition(&mut self) -> bool {
+        let Some(transition) = self.transition.take() else { return false };
+        self.zoomer.set_target(self.viewport.centre());
+        self.zoomer.set_zoom(self.zoomer.zoom() * self.viewport.width() / transition.to().width);
+        println!("transition stopped {} frames short of re={} im={}", transition.remaining(), transition.to().centre.re, transition.to().centre.im);
+        true
+    }
+
+    /// [Check Degenerate]
+    ///
+    /// Deals with a frame that has nothing left to zoom into. The
+    /// screensaver cuts to its next dive, the auto-pilot finds a new
+    /// target from the initial view, and otherwise the zoom pauses until
+    /// the user resumes it. Resuming says the frame was worth zooming
+    /// into after all, so empty frames are let through until the zoom
+    /// reaches one that is not. Zooming out far enough to leave the set a
+    /// dot is empty too, but deliberate, so it is let through as well.
+    fn check_degenerate(&mut self) {
+        if self.zoomer.outward() {
+            return;
+        }
+
+        let (interior, fast_escape) = self.degenerate;
+        if !FrameStats::of(&self.vals, self.limit).degenerate(interior, fast_escape) {
+            self.degenerate_overruled = false;
+            return;
+        }
+        if self.degenerate_overruled {
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(saver) = &mut self.screensaver {
+                saver.cut();
+                return;
+            }
+            if self.autopilot.enabled {
+                println!("empty frame, retargeting");
+                self.explore(true);
+                return;
+            }
+        }
+
+        println!("empty frame, pausing (Space to carry on)");
+        self.pause = Some(Pause::Degenerate);
+        self.clock.pause(Local::now());
+    }
+
+    /// [Key]
+    ///
+    /// Services user interaction. Such input is necessary for pausing
+    /// the zoom, inspecting it, and saving what it shows.
+    pub fn key(&mut self, key: Key) {
+        // Key Functions Added! Which key does what is looked up in the
+        // bindings; the defaults, and what each action does, are listed
+        // in bindings.rs, and --print-bindings lists those in effect.
+        // Clicking, rather than a key, retargets the zoom.
+        let Some(action) = self.bindings.action(key) else { return };
+        match action {
+            Action::Pause => self.toggle_pause(),
+            Action::Print => self.print(),
+            Action::PrintJson => self.print_json(),
+            Action::FormulaNext => self.set_formula(self.formula.next()),
+            Action::Screenshot => if let Some(path) = report(self.screenshot(false)) { println!("saved {}", path.display()) },
+            Action::ExportPalette => self.export_palette(),
+            Action::Goto => self.goto = Some(Prompt::default()),
+            #[cfg(not(target_arch = "wasm32"))]
+            Action::Nebula => self.toggle_nebula(),
+            #[cfg(target_arch = "wasm32")]
+            Action::Nebula => {}
+            Action::ScreenshotOverlays => if let Some(path) = report(self.screenshot(true)) { println!("saved {}", path.display()) },
+            Action::Hud => {
+                self.hud.visible = !self.hud.visible;
+                self.measure();
+            }
+            Action::Minimap => self.minimap.visible = !self.minimap.visible,
+            Action::Crosshair => self.crosshair.visible = !self.crosshair.visible,
+            Action::Grid => self.grid.visible = !self.grid.visible,
+            Action::Histogram => self.histogram_panel.visible = !self.histogram_panel.visible,
+            Action::Graph => self.graph.visible = !self.graph.visible,
+            Action::Julia => self.julia.visible = !self.julia.visible,
+            Action::Inspector => self.inspector.visible = !self.inspector.visible,
+            Action::Legend => self.legend.visible = !self.legend.visible,
+            Action::LegendExport => {
+                self.legend.exported = !self.legend.exported;
+                announce("legend in screenshots", self.legend.exported);
+            }
+            Action::Tone => {
+                self.tone = self.tone.other();
+                println!("tone={}", self.tone.name());
+            }
+            Action::Diff => {
+                self.diff.toggle();
+                announce("diff", self.diff.enabled);
+            }
+            Action::Antialias => self.toggle_antialiasing(),
+            Action::SampleMap => self.sample_map.visible = !self.sample_map.visible,
+            Action::Bookmark => self.bookmark(),
+            Action::Tour => self.toggle_tour(false),
+            Action::TourLoop => self.toggle_tour(true),
+            Action::ZoomOut => {
+                self.zoomer.set_outward(!self.zoomer.outward());
+                println!("zooming {}", if self.zoomer.outward() { "out" } else { "in" });
+            }
+            Action::Autopilot => {
+                self.autopilot.toggle();
+                self.follower.enabled &= !self.autopilot.enabled;
+                announce("autopilot", self.autopilot.enabled);
+            }
+            Action::Follow => {
+                self.follower.enabled = !self.follower.enabled;
+                if self.follower.enabled && self.autopilot.enabled {
+                    self.autopilot.toggle();
+                }
+                announce("follow", self.follower.enabled);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Action::Explore => self.explore(false),
+            #[cfg(not(target_arch = "wasm32"))]
+            Action::ExploreInitial => self.explore(true),
+            #[cfg(target_arch = "wasm32")]
+            Action::Explore | Action::ExploreInitial => {}
+            Action::FastForward => self.set_speed((self.speed * 2).min(MAX_SPEED)),
+            Action::RealTime => self.set_speed(1),
+            Action::Slower => self.set_ups(self.ups / 2),
+            Action::Faster => self.set_ups(self.ups * 2),
+            Action::PowerSaving => self.set_power_saving(!self.power_saving),
+            Action::Skip => {
+                self.skip = !self.skip;
+                announce("skip_far", self.skip);
+            }
+            Action::Light => self.set_light(!self.light.enabled),
+            Action::LightLeft => {
+                self.light.turn(-lighting::STEP);
+                println!("light={}", self.light.to_arg());
+            }
+            Action::LightRight => {
+                self.light.turn(lighting::STEP);
+                println!("light={}", self.light.to_arg());
+            }
+            Action::LightUp => {
+                self.light.raise(lighting::STEP);
+                println!("light={}", self.light.to_arg());
+            }
+            Action::LightDown => {
+                self.light.raise(-lighting::STEP);
+                println!("light={}", self.light.to_arg());
+            }
+            Action::Invert => self.toggle_projection(),
+            Action::Lyapunov => self.set_lyapunov(!self.lyapunov.enabled),
+            Action::HeightMap => {
+                self.height_map.visible = !self.height_map.visible;
+                self.height_map.release();
+                self.stale = true;
+                announce("height_map", self.height_map.visible);
+            }
+            Action::Split => self.toggle_split(),
+            Action::SwapSides => self.swap_sides(),
+            Action::Perturb => {
+                self.perturb = !self.perturb;
+                announce("perturb", self.perturb);
+            }
+            Action::Series => {
+                self.series = !self.series;
+                announce("series", self.series);
+            }
+            Action::ValidateSeries => {
+                self.validate = !self.validate;
+                announce("validate_series", self.validate);
+            }
+            Action::CursorFirst => {
+                self.cursor_first = !self.cursor_first;
+                announce("cursor_first", self.cursor_first);
+            }
+            Action::AutoLimit => {
+                self.auto_limit.enabled = !self.auto_limit.enabled;
+                println!("auto_limit={}", if self.auto_limit.enabled { format!("on, up to {}", self.auto_limit.ceiling) } else { "off".to_string() });
+            }
+            Action::Panel => self.panel.visible = !self.panel.visible,
+            Action::Crossfade => {
+                self.crossfade.enabled = !self.crossfade.enabled;
+                announce("crossfade", self.crossfade.enabled);
+            }
+        }
+    }
+
+    /// [Explore]
+    /// Searches the current view, or the initial one, for a point near
+    /// the boundary and retargets the zoom on it, printing it for
+    /// bookmarking. A search of the initial view restarts the zoom from
+    /// there, as the point will usually be out of the current view.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn explore(&mut self, from_initial: bool) {
+        let view = if from_initial { self.initial() } else { self.viewport };
+
+        match search(self.formula, &view, self.limit, &mut self.rng, BUDGET) {
+            Some(find) => {
+                if from_initial {
+                    self.restart(find.point, self.start.1.scalar);
+                } else {
+                    self.zoomer.set_target(find.point);
+                }
+                println!("target re={} im={} count={}", find.point.re, find.point.im, find.count);
+            }
+            None => println!("no target found within {} ms", BUDGET.as_millis()),
+        }
+    }
+
+    /// [Click]
+    /// Retargets the zoom on the point under the cursor, holding off the
+    /// boundary walk for a moment, and recentres the view on it, travelling
+    /// there rather than jumping. While paused it prints the data of the
+    /// pixel there instead, so that the frame it is read from stays the
+    /// one on screen. A click on the panel is left to the panel, one on
+    /// the divider of a split grabs it, and in the 3D view one starts
+    /// orbiting the camera. The Nebulabrot does not zoom, so takes none.
+    pub fn click(&mut self) {
+        let Some([x, y]) = self.cursor else { return };
+
+        let (at, top) = ([x / self.hidpi, y / self.hidpi], self.panel_top(&self.stats()));
+        if self.panel.contains(at, top) {
+            if let Some(change) = self.panel.click(at, &self.params(), top) {
+                self.apply(change);
+            }
+            return;
+        }
+        if self.split.press(at, self.logical_viewport().width_px() as f64) {
+            return;
+        }
+        if self.height_map.visible {
+            self.height_map.press(at);
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.nebula.enabled {
+            return;
+        }
+
+        if self.pause.is_none() {
+            let target = self.viewport.pixel_to_complex(x, y);
+            self.zoomer.set_target(target);
+            self.follower.hold();
+            self.travel(Bookmark { centre: target, width: self.viewport.width() });
+            return;
+        }
+
+        let Some(info) = PixelInfo::at(self.formula, &self.viewport, &self.vals, self.limit, [x, y]) else { return };
+        println!("{}", info.lines().join(", "));
+    }
+
+    /// [Move Cursor]
+    /// Follows the pointer to a point over the frame, in frame pixels,
+    /// dragging a slider of the panel or the divider of a split along if
+    /// it is held, or orbiting the camera of the 3D view.
+    pub fn move_cursor(&mut self, at: [f64; 2]) {
+        self.cursor = Some(at);
+        if let Some(change) = self.panel.drag(at[0] / self.hidpi, &self.params()) {
+            self.apply(change);
+            self.stale = true;
+        }
+        let dragged = self.split.drag(at[0] / self.hidpi, self.logical_viewport().width_px() as f64);
+        let orbited = self.height_map.drag([at[0] / self.hidpi, at[1] / self.hidpi]);
+        if dragged || orbited {
+            self.stale = true;
+        }
+    }
+
+    /// Lets go of whatever the last click grabbed.
+    pub fn release(&mut self) {
+        self.panel.release();
+        self.split.release();
+        self.height_map.release();
+    }
+
+    /// [Toggle Nebula]
+    /// Shows the Nebulabrot in place of the zoom, or the zoom again,
+    /// saying which. The frame under way is dropped either way, as it
+    /// would only be shown in the zoom.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn toggle_nebula(&mut self) {
+        self.nebula.toggle();
+        self.job = None;
+        self.stale = true;
+        announce("nebula", self.nebula.enabled);
+    }
+
+    /// [Toggle Split]
+    /// Splits the frame or collapses it back to the left side, saying which.
+    fn toggle_split(&mut self) {
+        self.split.toggle(self.left_side());
+        if !self.split.enabled {
+            self.split_frame = None;
+        }
+        announce("split", self.split.enabled);
+    }
+
+    /// [Swap Sides]
+    /// Swaps the sides of a split over, configurations and counts, so
+    /// that what was on the right can be changed. The left's counts are
+    /// kept for the right, when they are its own.
+    fn swap_sides(&mut self) {
+        let Some(right) = self.split.right() else { return };
+        let left = self.left_side();
+
+        self.set_formula(right.formula);
+        (self.perturb, self.fade.scalar, self.tone) = (right.perturb, right.scalar, right.tone);
+        self.split.other = Some(left);
+
+        // Sides with counts of their own still have them after the swap.
+        let other = self.other_work();
+        if let (Some(frame), Some(work)) = (&mut self.split_frame, other) {
+            std::mem::swap(&mut self.vals, &mut frame.vals);
+            std::mem::swap(&mut self.histogram, &mut frame.histogram);
+            std::mem::swap(&mut self.supersamples, &mut frame.supersamples);
+            std::mem::swap(&mut self.perturbed, &mut frame.perturbed);
+            std::mem::swap(&mut self.exponents, &mut frame.exponents);
+            std::mem::swap(&mut self.heights, &mut frame.heights);
+            frame.work = work;
+            self.measure();
+        }
+        println!("swapped sides: left {}, right {}", right.formula.name(), left.formula.name());
+    }
+
+    /// [Export Orbit]
+    /// Writes the orbit of the point under the cursor to CSV, the point
+    /// being the one its pixel's count was computed for, and prints
+    /// where it went and whether the orbit escaped. The panel takes
+    /// Ctrl+clicks on it as it does clicks.
+    pub fn export_orbit(&mut self) {
+        let Some(at) = self.cursor else { return };
+        if self.panel.contains(at.map(|v| v / self.hidpi), self.panel_top(&self.stats())) {
+            return self.click();
+        }
+        let Some(info) = PixelInfo::at(self.formula, &self.viewport, &self.vals, self.limit, at) else { return };
+
+        let orbit = Orbit::of(self.escaping(), info.point, self.limit);
+        let path = orbit.path();
+        if report(orbit.save(&path)).is_some() {
+            match orbit.escaped_at() {
+                Some(n) => println!("saved {} (escaped at n={n})", path.display()),
+                None => println!("saved {} (did not escape within {})", path.display(), self.limit),
+            }
+        }
+    }
+
+    /// [Export Palette]
+    /// Writes the colours as they are applied to the frame now to a .map
+    /// file in the working directory, named for the time as screenshots
+    /// are, with the strip of them beside it.
+    fn export_palette(&self) {
+        let path = PathBuf::from(format!("palette-{}-frame{:06}.map", Local::now().format("%Y%m%d-%H%M%S"), self.frames));
+        if let Some(strip) = report(Palette::of(&self.colorizer(), self.tone, self.limit).save(&path)) {
+            println!("saved {} and {}", path.display(), strip.display());
+        }
+    }
+
+    /// [Toggle Anti-Aliasing]
+    /// Starts or stops sampling edges again. Starting takes effect from
+    /// the next computed frame, as the view has already moved on from
+    /// the one on screen.
+    fn toggle_antialiasing(&mut self) {
+        self.antialiasing.enabled = !self.antialiasing.enabled;
+        if !self.antialiasing.enabled {
+            self.supersamples = Supersamples::default();
+        }
+        announce("antialias", self.antialiasing.enabled);
+    }
+
+    /// [Toggle Pause]
+    /// Pauses the zoom, or resumes it however it came to be paused.
+    fn toggle_pause(&mut self) {
+        if let Some(pause) = self.pause.take() {
+            self.degenerate_overruled = pause == Pause::Degenerate;
+            self.clock.resume(Local::now());
+            println!("playing");
+        } else {
+            self.pause = Some(Pause::User);
+            self.clock.pause(Local::now());
+            println!("paused");
+        }
+    }
+
+    /// [Debug Fields]
+    /// Everything P prints, as names and values. The frame statistics
+    /// take a pass over vals, so they are only gathered here, as is the
+    /// area in view while the HUD is hidden. The area of the whole set
+    /// takes a render of its own, so it is kept for the formula and limit
+    /// until either changes.
+    fn debug_fields(&mut self) -> Vec<(&'static str, String)> {
+        let whole = match self.whole_set {
+            Some((formula, limit, whole)) if (formula, limit) == (self.formula, self.limit) => whole,
+            _ => {
+                let whole = AreaEstimate::whole_set(self.formula, self.limit);
+                self.whole_set = Some((self.formula, self.limit, whole));
+                whole
+            }
+        };
+        let centre = self.viewport.centre();
+        let (last, average) = (self.compute_times.last().unwrap_or_default(), self.compute_times.average().unwrap_or_default());
+        let stats = FrameStats::of(&self.vals, self.limit);
+        let hud = self.stats();
+        let area = AreaEstimate::of(&self.histogram, &self.vals, &self.viewport);
+        // The frame under way holds the cache until it is done with it.
+        let cache = self.tile_cache.as_ref().map(|cache| cache.lock().unwrap_or_else(PoisonError::into_inner));
+
+        vec![
+            ("centre_re", centre.re.to_string()),
+            ("centre_im", centre.im.to_string()),
+            ("width", self.viewport.width().to_string()),
+            ("height", self.viewport.height().to_string()),
+            ("rotation", self.viewport.rotation().to_string()),
+            ("projection", self.viewport.projection().name().to_string()),
+            ("scale", self.viewport.scale().to_string()),
+            ("zoom", self.zoomer.zoom().to_string()),
+            ("scalar", self.fade.scalar.to_string()),
+            ("gradient", self.gradient.name().to_string()),
+            ("tone", self.tone.name().to_string()),
+            ("light", if self.light.enabled { self.light.to_arg() } else { "off".to_string() }),
+            ("lyapunov", if self.lyapunov.enabled { self.lyapunov.to_arg() } else { "off".to_string() }),
+            ("step_factor", self.fade.step_factor.to_string()),
+            ("speed", self.speed.to_string()),
+            ("ups_target", self.ups.to_string()),
+            ("ups", format!("{:.1}", self.hud.ups.rate())),
+            ("power_saving", self.power_saving.to_string()),
+            ("crossfade", self.crossfade.enabled.to_string()),
+            ("skip_far", self.skip.to_string()),
+            ("perturb", self.perturb.to_string()),
+            ("split", self.split.right().map_or("off".to_string(), |right| format!("{} {} {}", right.formula.name(), right.tone.name(), right.scalar))),
+            ("split_divider", format!("{:.3}", self.split.divider)),
+            ("transition_frames", self.transition_frames.to_string()),
+            ("transition", self.transition.map_or("off".to_string(), |transition| format!("{} frames to go", transition.remaining()))),
+            #[cfg(not(target_arch = "wasm32"))]
+            ("nebula", if self.nebula.enabled { format!("{} samples", self.nebula.samples()) } else { "off".to_string() }),
+            ("height_map", if self.height_map.visible { format!("yaw {:.2} pitch {:.2}", self.height_map.camera.yaw, self.height_map.camera.pitch) } else { "off".to_string() }),
+            ("series", self.series.to_string()),
+            ("validate_series", self.validate.to_string()),
+            ("cursor_first", self.cursor_first.to_string()),
+            ("limit", self.limit.to_string()),
+            ("escape_radius", self.radius.to_string()),
+            ("render_scale", self.render_scale.to_string()),
+            ("auto_limit", if self.auto_limit.enabled { self.auto_limit.ceiling.to_string() } else { "off".to_string() }),
+            ("GRAPH_SCALE", GRAPH_SCALE.to_string()),
+            ("compute_ms", format!("{:.3}", millis(last))),
+            ("compute_avg_ms", format!("{:.3}", millis(average))),
+            ("min_count", stats.min.to_string()),
+            ("mean_count", format!("{:.3}", stats.mean)),
+            ("max_count", stats.max.to_string()),
+            ("interior_fraction", format!("{:.6}", stats.interior_fraction)),
+            ("fast_escape_fraction", format!("{:.6}", stats.fast_escape_fraction)),
+            ("total_iterations", stats.total_iterations.to_string()),
+            ("glitched", self.perturbed.map_or("-".to_string(), |perturbed| perturbed.first.to_string())),
+            ("glitch_passes", self.perturbed.map_or("-".to_string(), |perturbed| perturbed.passes.to_string())),
+            ("glitch_references", self.perturbed.map_or("-".to_string(), |perturbed| perturbed.references.to_string())),
+            ("glitch_remaining", self.perturbed.map_or("-".to_string(), |perturbed| perturbed.remaining.to_string())),
+            ("series_skipped", self.perturbed.map_or("-".to_string(), |perturbed| perturbed.skipped.to_string())),
+            ("series_checked", self.perturbed.filter(|_| self.validate).map_or("-".to_string(), |perturbed| perturbed.checked.to_string())),
+            ("series_mismatched", self.perturbed.filter(|_| self.validate).map_or("-".to_string(), |perturbed| perturbed.mismatched.to_string())),
+            ("antialiased_fraction", format!("{:.6}", self.supersamples.share(self.vals.len()))),
+            ("tile_cache_hit_rate", cache.as_ref().map_or("off".to_string(), |cache| cache.hit_rate().map_or("-".to_string(), |rate| format!("{rate:.6}")))),
+            ("tile_cache_tiles", cache.as_ref().map_or("off".to_string(), |cache| cache.len().to_string())),
+            ("started", self.clock.started().to_rfc3339()),
+            ("elapsed_total", clock_time(hud.total)),
+            ("elapsed_running", clock_time(hud.running)),
+            ("frame", self.frames.to_string()),
+            ("average_ups", format!("{:.3}", hud.average_ups())),
+            ("area", format!("{:e}", area.area)),
+            ("area_uncertainty", format!("{:e}", area.uncertainty)),
+            ("set_area", format!("{:.6}", whole.area)),
+            ("set_area_uncertainty", format!("{:.6}", whole.uncertainty)),
+        ]
+    }
+
+    /// [Print]
+    ///
+    /// This is a simple function that gets called when the 'P' key
+    /// is pressed that prints all the details of the current frame
+    /// of simulation to the terminal for debug.
+    fn print(&mut self) {
+        println!(">===---");
+        for (name, value) in self.debug_fields() {
+            println!("{name}={value}");
+        }
+        println!(">===---");
+    }
+
+    /// [Print JSON]
+    /// The same details on Shift+P, as a single line of JSON for scripts.
+    /// Numbers that are not finite are written as null, and everything
+    /// that is not a number as a string.
+    fn print_json(&mut self) {
+        let fields: Vec<String> = self.debug_fields().into_iter()
+            .map(|(name, value)| match value.parse::<f64>() {
+                Ok(number) if number.is_finite() => format!("\"{name}\":{value}"),
+                Ok(_) => format!("\"{name}\":null"),
+                Err(_) => format!("\"{name}\":\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
+            })
+            .collect();
+
+        println!("{{{}}}", fields.join(","));
+    }
+
+    /// [Screenshot]
+    ///
+    /// Saves the current frame, as coloured on screen, to a PNG in the
+    /// working directory. Overlays such as the HUD are left out, but for
+    /// the colour legend when it has been asked for. With `overlays`,
+    /// the frame is saved exactly as shown instead, everything over it
+    /// included, drawn into it the way the window draws it. The name
+    /// carries the time and the frame number, so that the screenshots of
+    /// a long run sort in order. Failures are returned for the caller to report,
+    /// so a full disk never ends the run.
+    fn screenshot(&mut self, overlays: bool) -> Result<PathBuf, AppError> {
+        let (width, height) = (self.viewport.width_px(), self.viewport.height_px());
+        let mut rgba = if overlays {
+            self.refresh_overlay();
+            let mut shown = self.frame().to_vec();
+            self.overlay.composite(&mut shown, width, height);
+            shown
+        } else {
+            self.coloured()
+        };
+        if self.legend.exported && !overlays {
+            let logical = self.logical_viewport();
+            let mut legend = Overlay::new();
+            draw_legend(&mut legend, &self.colorizer(), self.tone, self.limit, [logical.width_px() as f64, logical.height_px() as f64], None);
+            legend.scale(self.hidpi);
+            legend.composite(&mut rgba, width, height);
+        }
+
+        let stamp = Local::now().format("%Y%m%d-%H%M%S");
+        let path = PathBuf::from(format!("mandelbrot-{stamp}-frame{:06}{}.png", self.frames, if overlays { "-overlays" } else { "" }));
+
+        save_png(&path, width, height, &rgba)?;
+        Ok(path)
+    }
+}
+
+/// [Time]
+/// Runs `f`, returning its result and how long it took. std has no
+/// clock on wasm32, so there nothing is measured.
+fn time<R>(f: impl FnOnce() -> R) -> (R, Option<Duration>) {
+    if cfg!(target_arch = "wasm32") {
+        return (f(), None);
+    }
+
+    let start = Instant::now();
+    let result = f();
+    (result, Some(start.elapsed()))
+}
+
+/// [Announce]
+/// Prints that the named setting is now on or off.
+fn announce(name: &str, on: bool) {
+    println!("{name}={}", if on { "on" } else { "off" });
+}
+
+/// [Run]
+///
+/// The main loop, which actually runs all the app functions repeatedly
+/// until the backend reports that its window has closed, or, as a
+/// screensaver, until the user comes back, or, as a demo, until its
+/// time is up, its summary printed however it ends. The window
+/// title is refreshed twice a second, which keeps it readable and spares
+/// the window manager. On Unix, signals are acted on between events.
+/// A change to the pacing is passed on to the backend once the event
+/// is handled. Anything but an update, a render or the pointer moving
+/// may change the frame, and an update only by bringing a new one in.
+pub fn run<B: Backend>(app: &mut App, backend: &mut B) {
+    let mut titled: Option<Instant> = None;
+    let mut paced = Pacing::default();
+
+    while let Some(event) = backend.next_event() {
+        #[cfg(unix)]
+        if app.handle_signals() {
+            break;
+        }
+
+        match event {
+            Event::Render => {
+                let now = Instant::now();
+                if titled.is_none_or(|at| now - at >= Duration::from_millis(500)) {
+                    backend.set_title(&app.title());
+                    titled = Some(now);
+                }
+
+                if app.render(now) {
+                    app.hud.fps.tick(now);
+                    let (width, height) = (app.viewport.width_px(), app.viewport.height_px());
+                    backend.present(&app.rgba, width, height, &app.overlay);
+                }
+            }
+            Event::Update => {
+                app.hud.ups.tick(Instant::now());
+                if app.end_demo(Some(Instant::now())) {
+                    return;
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                app.screensave();
+                app.update_background();
+                app.flush_log();
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            _ if app.screensaver.as_mut().is_some_and(|saver| saver.wakes(&event)) => return,
+            _ if app.demo.as_ref().is_some_and(|demo| demo.ignores(&event)) => {}
+            Event::Press(key) if app.goto.is_some() => app.prompt_key(key),
+            Event::Press(Key::Escape) => if !app.stop_transition() { break },
+            Event::Press(key) => app.key(key),
+            Event::Text(c) => app.type_char(c),
+            Event::Paste => app.paste(),
+            Event::Cursor(at) => app.move_cursor(at),
+            Event::Release => app.release(),
+            Event::Click => app.click(),
+            Event::CtrlClick => app.export_orbit(),
+            Event::Resize(window) => app.resize(window),
+            Event::Scale(scale) => app.rescale(scale),
+        }
+
+        app.stale |= !matches!(event, Event::Render | Event::Update | Event::Cursor(_));
+        if app.pacing() != paced {
+            paced = app.pacing();
+            backend.set_pacing(paced);
+        }
+    }
+    app.end_demo(None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_frame_arriving_while_paused_keeps_its_view() {
+        let mut app = App::new(&Settings::default()).unwrap();
+        app.update_background();
+        assert!(app.job.is_some());
+
+        app.toggle_pause();
+        let viewport = app.viewport;
+        while app.job.is_some() {
+            std::thread::sleep(Duration::from_millis(1));
+            app.update_background();
+        }
+        assert_eq!((app.viewport, app.frames), (viewport, 0));
+        assert!(app.vals.iter().any(|&count| count > 0), "the frame was installed");
+
+        // The step it was held back from is taken on resuming.
+        app.toggle_pause();
+        app.update_background();
+        assert_eq!(app.frames, 1);
+        assert_ne!(app.viewport, viewport);
+    }
+
+    #[test]
+    fn a_view_gone_wrong_is_put_back_and_what_moved_it_stopped() {
+        let mut app = App::new(&Settings::default()).unwrap();
+        let good = app.viewport;
+        let wrong = Bookmark { centre: cmp::new(f64::NAN, 0.0), width: good.width() };
+
+        app.transition = Some(Transition::new(Bookmark::of(&good), wrong, 10));
+        app.advance();
+        assert_eq!((app.viewport, app.pause), (good, Some(Pause::Recovered)));
+        assert!(app.transition.is_none());
+
+        app.toggle_pause();
+        app.tour = Tour::new(vec![wrong], true);
+        assert!(app.tour.is_some());
+        app.advance();
+        assert_eq!((app.viewport, app.pause), (good, Some(Pause::Recovered)));
+        assert!(app.tour.is_none());
+    }
+
+    #[test]
+    fn the_area_in_view_is_measured_while_the_hud_shows() {
+        // Well inside the main cardioid, so every pixel is interior.
+        let settings = Settings { dimensions: Some((80, 40)), iterations: 200, ..Settings::default() };
+        let mut app = App::new(&settings.framed(cmp::new(-0.25, 0.0), 0.5, 2.0)).unwrap();
+        app.recompute();
+        assert_eq!(app.area, AreaEstimate::default(), "not measured with the HUD hidden");
+
+        app.key(app.bindings.key(Action::Hud));
+        assert!((app.area.area - 0.5 * 0.25).abs() < 1e-12, "{:?}", app.area);
+        assert_eq!(app.area.uncertainty, 0.0);
+    }
+}
@@ -0,0 +1,120 @@
+//! [Area]
+//!
+//! Estimates of the area of the set, from the pixels found to be
+//! interior. Each interior pixel stands for one pixel's worth of the
+//! plane, and the pixels on the edge of the interior are the ones that
+//! may as well have gone either way, so they give the uncertainty. For
+//! comparison, the best estimates of the Mandelbrot set's area are
+//! around 1.50659.
+//!
+//! The area in view is only for the HUD, and is only measured while the
+//! HUD is shown (and when P prints it); the worker does not gather it.
+
+use crate::fractal::Formula;
+use crate::histogram::Histogram;
+use crate::viewport::Viewport;
+
+/// The size of the grid the whole set is estimated on.
+const WHOLE_WIDTH: usize = 2000;
+const WHOLE_HEIGHT: usize = 1000;
+
+/// [Area Estimate]
+///
+/// Fields:
+/// [area] The area of the interior pixels on the plane;
+/// [uncertainty] The area of the interior pixels bordering the exterior.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AreaEstimate {
+    pub area: f64,
+    pub uncertainty: f64,
+}
+
+impl AreaEstimate {
+    /// [Of]
+    /// The estimate for a frame of counts computed for the viewport, with
+    /// the histogram gathered as they were. The histogram has already
+    /// counted the interior pixels, so only those on its edge are looked
+    /// for. Pixels are square, so a pixel's area is its size squared.
+    pub fn of(histogram: &Histogram, vals: &[u32], viewport: &Viewport) -> AreaEstimate {
+        let (width, height) = (viewport.width_px(), viewport.height_px());
+        let limit = histogram.limit();
+        let interior = |a: usize, b: usize| vals[b * width + a] >= limit;
+
+        let mut boundary = 0u64;
+        for b in 0..height {
+            for a in 0..width {
+                if !interior(a, b) {
+                    continue;
+                }
+
+                let exterior_neighbour = (a > 0 && !interior(a - 1, b))
+                    || (a + 1 < width && !interior(a + 1, b))
+                    || (b > 0 && !interior(a, b - 1))
+                    || (b + 1 < height && !interior(a, b + 1));
+                boundary += exterior_neighbour as u64;
+            }
+        }
+
+        let pixel_area = viewport.pixel_size() * viewport.pixel_size();
+        AreaEstimate { area: histogram.interior() as f64 * pixel_area, uncertainty: boundary as f64 * pixel_area }
+    }
+
+    /// [Whole Set]
+    /// The estimate for the whole of the formula's set, computed afresh
+    /// over its overview at a fine grid. This takes a moment, so it is
+    /// only done on demand.
+    pub fn whole_set(formula: Formula, limit: u32) -> AreaEstimate {
+        let (centre, width) = formula.overview();
+        let view = Viewport::new(centre, width, WHOLE_WIDTH, WHOLE_HEIGHT);
+
+        let mut vals = vec![0; WHOLE_WIDTH * WHOLE_HEIGHT];
+        let histogram = formula.compute_parallel(&mut vals, WHOLE_WIDTH, |a, b| view.pixel_to_complex(a as f64, b as f64), limit);
+
+        AreaEstimate::of(&histogram, &vals, &view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::complex::Complex as cmp;
+
+    use super::*;
+
+    /// The histogram the kernels would have gathered for the counts.
+    fn histogram(vals: &[u32], limit: u32) -> Histogram {
+        let mut histogram = Histogram::new(limit);
+        vals.iter().for_each(|&count| histogram.add(count));
+        histogram
+    }
+
+    #[test]
+    fn interior_frames_cover_the_view() {
+        let viewport = Viewport::new(cmp::new(-0.2, 0.1), 0.01, 40, 20);
+        let estimate = AreaEstimate::of(&histogram(&[100; 800], 100), &[100; 800], &viewport);
+
+        assert!((estimate.area - viewport.width() * viewport.height()).abs() < 1e-18);
+        assert_eq!(estimate.uncertainty, 0.0);
+    }
+
+    #[test]
+    fn the_edge_of_the_interior_is_uncertain() {
+        // A 2x2 interior block in a 4x4 frame.
+        let viewport = Viewport::new(cmp::new(0.0, 0.0), 4.0, 4, 4);
+        let mut vals = [0; 16];
+        for i in [5, 6, 9, 10] {
+            vals[i] = 100;
+        }
+
+        let estimate = AreaEstimate::of(&histogram(&vals, 100), &vals, &viewport);
+
+        assert_eq!((estimate.area, estimate.uncertainty), (4.0, 4.0));
+    }
+
+    #[test]
+    fn whole_mandelbrot_set() {
+        let estimate = AreaEstimate::whole_set(Formula::Mandelbrot, 500);
+
+        assert!((estimate.area - 1.5066).abs() < estimate.uncertainty, "{estimate:?}");
+        assert!(estimate.uncertainty < 0.1, "{estimate:?}");
+    }
+}
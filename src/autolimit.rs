@@ -0,0 +1,118 @@
+//! [Auto Limit]
+//!
+//! Raising the iteration limit as the zoom outgrows it. Deep enough,
+//! points near the boundary take more iterations to escape than the
+//! limit allows, and come out black as if they were interior. The sign
+//! of it is pixels at the limit bordering pixels that only just escaped
+//! within it: a truly interior region is ringed by counts of every size,
+//! while an under-resolved one sits among slow escapes on all sides.
+//! When the share of pixels at the limit with a slow escape among their
+//! 8-neighbours passes a threshold, the limit is raised by a factor, up
+//! to a ceiling, and the frame computed again with it. The limit is
+//! never lowered again by this, only by hand. Enabled with --auto-limit
+//! or N.
+
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+/// The share of the limit above which an escaping count is a slow escape.
+const SLOW: f64 = 0.5;
+
+/// [Auto Limit]
+///
+/// Fields:
+/// [enabled] Whether the limit is raised when frames look under-resolved;
+/// [ceiling] The highest it is raised to;
+/// [factor] How much it is raised by each time;
+/// [threshold] The share of pixels at the limit bordering slow escapes that raises it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutoLimit {
+    pub enabled: bool,
+    pub ceiling: u32,
+    pub factor: f64,
+    pub threshold: f64,
+}
+
+impl Default for AutoLimit {
+    fn default() -> AutoLimit {
+        AutoLimit { enabled: false, ceiling: 100_000, factor: 2.0, threshold: 0.2 }
+    }
+}
+
+impl AutoLimit {
+    /// [Raise]
+    /// The limit to compute the frame again at, if it is enabled, the
+    /// frame looks under-resolved at `limit`, and the ceiling is not yet
+    /// reached, along with the share that prompted it.
+    pub fn raise(&self, vals: &[u32], width: usize, limit: u32) -> Option<(u32, f64)> {
+        if !self.enabled || limit >= self.ceiling {
+            return None;
+        }
+
+        let share = under_resolved(vals, width, limit);
+        let raised = ((limit as f64 * self.factor).ceil() as u32).max(limit + 1).min(self.ceiling);
+        (share > self.threshold).then_some((raised, share))
+    }
+}
+
+/// [Under-Resolved]
+/// The share of the pixels at the limit with a slow escape among their
+/// 8-neighbours, or 0 if there are none at the limit.
+pub fn under_resolved(vals: &[u32], width: usize, limit: u32) -> f64 {
+    if width == 0 {
+        return 0.0;
+    }
+    let height = vals.len() / width;
+    let slow = (limit as f64 * SLOW).ceil() as u32;
+
+    let row = |b: usize| -> (u64, u64) {
+        let at = |a: usize, b: usize| vals[b * width + a];
+        let mut counts = (0, 0);
+        for a in (0..width).filter(|&a| at(a, b) >= limit) {
+            counts.0 += 1;
+            let near = (b.saturating_sub(1)..(b + 2).min(height))
+                .flat_map(|y| (a.saturating_sub(1)..(a + 2).min(width)).map(move |x| (x, y)))
+                .any(|(x, y)| (slow..limit).contains(&at(x, y)));
+            counts.1 += near as u64;
+        }
+        counts
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let (interior, bordering) = (0..height).into_par_iter().map(row).reduce(|| (0, 0), |x, y| (x.0 + y.0, x.1 + y.1));
+    #[cfg(target_arch = "wasm32")]
+    let (interior, bordering) = (0..height).map(row).fold((0, 0), |x, y| (x.0 + y.0, x.1 + y.1));
+
+    if interior == 0 { 0.0 } else { bordering as f64 / interior as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_interior_pixels_among_slow_escapes() {
+        // The left pixel at the limit borders a slow escape, the right
+        // one only fast ones.
+        let vals = [
+            60, 2, 3, 2,
+            100, 5, 4, 100,
+            8, 7, 3, 1,
+        ];
+
+        assert_eq!(under_resolved(&vals, 4, 100), 0.5);
+        assert_eq!(under_resolved(&[1, 2, 3], 3, 100), 0.0);
+    }
+
+    #[test]
+    fn raises_by_the_factor_up_to_the_ceiling() {
+        let vals = [90, 100, 100, 90];
+        let auto = AutoLimit { enabled: true, ceiling: 150, ..AutoLimit::default() };
+
+        assert_eq!(auto.raise(&vals, 2, 100), Some((150, 1.0)));
+        assert_eq!(auto.raise(&vals, 2, 150), None);
+        assert_eq!(AutoLimit { ceiling: 1000, ..auto }.raise(&vals, 2, 100), Some((200, 1.0)));
+        assert_eq!(AutoLimit::default().raise(&vals, 2, 100), None);
+        assert_eq!(auto.raise(&[1, 100, 100, 2], 2, 100), None);
+    }
+}
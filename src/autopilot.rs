@@ -0,0 +1,172 @@
+//! [Auto-pilot]
+//!
+//! Steering the zoom toward detail, toggled with A. A fixed target
+//! eventually closes in on a region of flat interior or flat exterior;
+//! the auto-pilot instead looks at the counts around the target every
+//! few frames, picks the neighbouring patch where they vary the most
+//! (which is where the boundary's filaments are), and drifts the target
+//! toward it. Aims and drift are kept in the complex plane and measured
+//! against the view's width, so steering behaves the same at any depth.
+
+use num::complex::Complex as cmp;
+
+use crate::viewport::Viewport;
+
+/// How many frames pass between choosing where to steer.
+const EVERY: u64 = 10;
+
+/// The furthest the target moves in one frame, as a share of the
+/// view's width.
+const DRIFT: f64 = 0.004;
+
+/// The side of each patch, as a share of the frame's shorter side. The
+/// target's patch and its eight neighbours are compared.
+const PATCH: f64 = 1.0 / 6.0;
+
+/// [Auto-pilot]
+///
+/// Fields:
+/// [enabled] Whether the target is being steered;
+/// [aim] Where the target is drifting toward, once one has been chosen.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AutoPilot {
+    pub enabled: bool,
+    aim: Option<cmp<f64>>,
+}
+
+impl AutoPilot {
+    /// [Toggle]
+    /// Turns steering on or off. Turning it off forgets the aim, so the
+    /// zoom carries on into wherever the target has got to.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.aim = None;
+    }
+
+    /// [Steer]
+    /// The target for the next frame: `target` moved at most DRIFT of the
+    /// view's width toward the aim, choosing a new aim from the counts in
+    /// vals every EVERY frames. Disabled, the target is left alone.
+    pub fn steer(&mut self, frame: u64, vals: &[u32], viewport: &Viewport, target: cmp<f64>) -> cmp<f64> {
+        if !self.enabled {
+            return target;
+        }
+
+        if frame.is_multiple_of(EVERY) || self.aim.is_none() {
+            let aim = busiest_neighbour(vals, viewport, viewport.complex_to_pixel(target));
+            self.aim = aim.map(|[x, y]| viewport.pixel_to_complex(x, y)).or(self.aim);
+        }
+
+        let Some(aim) = self.aim else { return target };
+        let offset = aim - target;
+        let step = DRIFT * viewport.width();
+        if offset.norm() <= step {
+            aim
+        } else {
+            target + offset * (step / offset.norm())
+        }
+    }
+}
+
+/// [Busiest Neighbour]
+/// The centre, in frame pixels, of the patch around `at` (its own or one
+/// of its eight neighbours) whose counts have the highest variance.
+/// Patches mostly off the frame are passed over, and None is returned
+/// when `at` is off the frame itself.
+pub fn busiest_neighbour(vals: &[u32], viewport: &Viewport, at: [f64; 2]) -> Option<[f64; 2]> {
+    let (width, height) = (viewport.width_px(), viewport.height_px());
+    let [x, y] = at;
+    if !(x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64) {
+        return None;
+    }
+
+    let side = (width.min(height) as f64 * PATCH).max(2.0);
+    let mut busiest: Option<([f64; 2], f64)> = None;
+
+    for dy in [-1.0, 0.0, 1.0] {
+        for dx in [-1.0, 0.0, 1.0] {
+            let centre = [x + dx * side, y + dy * side];
+            let columns = clip(centre[0] - side / 2.0, side, width);
+            let rows = clip(centre[1] - side / 2.0, side, height);
+            if (columns.len() * rows.len()) as f64 * 2.0 < side * side {
+                continue;
+            }
+
+            let variance = variance(rows.flat_map(|b| vals[b * width..][columns.clone()].iter().copied()));
+            if busiest.is_none_or(|(_, most)| variance > most) {
+                busiest = Some((centre, variance));
+            }
+        }
+    }
+
+    busiest.map(|(centre, _)| centre)
+}
+
+/// The pixels from `start` to `start + side` that lie within 0..len.
+fn clip(start: f64, side: f64, len: usize) -> std::ops::Range<usize> {
+    let from = start.max(0.0) as usize;
+    let to = ((start + side).max(0.0) as usize).min(len);
+    from.min(to)..to
+}
+
+fn variance(counts: impl Iterator<Item = u32>) -> f64 {
+    let (mut n, mut sum, mut sum_sqr) = (0.0, 0.0, 0.0);
+    for count in counts {
+        let count = count as f64;
+        n += 1.0;
+        sum += count;
+        sum_sqr += count * count;
+    }
+
+    if n == 0.0 { 0.0 } else { sum_sqr / n - (sum / n) * (sum / n) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewport() -> Viewport {
+        Viewport::new(cmp::new(0.0, 0.0), 4.0, 120, 60)
+    }
+
+    /// Flat counts, with noise only in the square [x, x + 10) x [y, y + 10).
+    fn noisy_at(x: usize, y: usize) -> Vec<u32> {
+        let mut vals = vec![50; 120 * 60];
+        for b in y..y + 10 {
+            for a in x..x + 10 {
+                vals[b * 120 + a] = ((a * 7 + b * 13) % 40) as u32;
+            }
+        }
+        vals
+    }
+
+    #[test]
+    fn finds_the_noisy_neighbour() {
+        // Patches are 10 pixels across, so the one right of (60, 30) spans 65..75.
+        let [x, y] = busiest_neighbour(&noisy_at(65, 25), &viewport(), [60.0, 30.0]).unwrap();
+
+        assert_eq!([x, y], [70.0, 30.0]);
+        assert_eq!(busiest_neighbour(&noisy_at(65, 25), &viewport(), [-1.0, 30.0]), None);
+    }
+
+    #[test]
+    fn drift_is_limited_per_frame() {
+        let viewport = viewport();
+        let target = viewport.pixel_to_complex(60.0, 30.0);
+        let mut pilot = AutoPilot::default();
+        pilot.toggle();
+
+        let next = pilot.steer(0, &noisy_at(65, 25), &viewport, target);
+
+        assert!(((next - target).norm() - DRIFT * viewport.width()).abs() < 1e-12);
+        assert!(next.re > target.re);
+    }
+
+    #[test]
+    fn disabled_keeps_the_target() {
+        let viewport = viewport();
+        let target = viewport.pixel_to_complex(60.0, 30.0);
+
+        assert_eq!(AutoPilot::default().steer(0, &noisy_at(65, 25), &viewport, target), target);
+    }
+}
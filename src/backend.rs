@@ -0,0 +1,102 @@
+//! [Backend]
+//!
+//! The boundary between the application and whatever puts pixels on
+//! the screen. A backend turns its windowing events into the small
+//! backend-agnostic `Event` enum, and presents finished RGBA frames
+//! along with an overlay to draw over them.
+//! Nothing on this side of the boundary knows which backend is in use.
+
+use crate::overlay::Overlay;
+
+/// How many updates a second backends ask for, Piston's default, and
+/// the most they can be asked for at runtime.
+pub const UPDATES_PER_SECOND: u64 = 120;
+
+/// The most renders a second backends ask for, Piston's default.
+pub const FRAMES_PER_SECOND: u64 = 60;
+
+/// [Pacing]
+/// How often a backend reports updates and renders.
+///
+/// Fields:
+/// [ups] Updates a second;
+/// [max_fps] The most renders a second;
+/// [lazy] Whether to wait for input instead, with no updates and a
+///        render after each input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pacing {
+    pub ups: u64,
+    pub max_fps: u64,
+    pub lazy: bool,
+}
+
+impl Default for Pacing {
+    /// The pacing backends start with.
+    fn default() -> Pacing {
+        Pacing { ups: UPDATES_PER_SECOND, max_fps: FRAMES_PER_SECOND, lazy: false }
+    }
+}
+
+/// [Key]
+/// The keys the application responds to. Letters and digits are
+/// reported as characters, with letters in uppercase while Shift is held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Space,
+    Escape,
+    Tab,
+    Return,
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    F(u8),
+}
+
+/// [Event]
+///
+/// Variants:
+/// [Update] Time to advance the simulation by one step;
+/// [Render] Time to present a frame;
+/// [Press] A key was pressed;
+/// [Text] A character was typed, as well as its key pressed, for entering text;
+/// [Paste] Ctrl+V was pressed, in place of a press of V;
+/// [Cursor] The pointer moved to a point over the frame, in frame pixels;
+/// [Click] The primary mouse button was pressed;
+/// [CtrlClick] The same, with either Ctrl key held;
+/// [Release] The primary mouse button was released;
+/// [Resize] The window changed size, to this many logical window pixels across and down;
+/// [Scale] The window's scale factor, in physical pixels per logical
+///         pixel, as first known or whenever it changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Update,
+    Render,
+    Press(Key),
+    Text(char),
+    Paste,
+    Cursor([f64; 2]),
+    Click,
+    CtrlClick,
+    Release,
+    Resize([f64; 2]),
+    Scale(f64),
+}
+
+/// [Backend]
+///
+/// Methods:
+/// [next_event] Waits for the next event, or None once the window closes;
+/// [present] Shows a frame of packed RGBA bytes, `width` pixels per row,
+///           with the overlay drawn over it, letterboxed to the window
+///           with every pixel outside the frame cleared;
+/// [set_title] Changes the window's title;
+/// [set_pacing] Changes how often updates and renders are reported.
+pub trait Backend {
+    fn next_event(&mut self) -> Option<Event>;
+    fn present(&mut self, rgba: &[u8], width: usize, height: usize, overlay: &Overlay);
+    fn set_title(&mut self, title: &str);
+    fn set_pacing(&mut self, pacing: Pacing);
+}
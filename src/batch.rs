@@ -0,0 +1,231 @@
+//! [Batch]
+//!
+//! `render --list FILE`: still images of a list of views, rendered one
+//! after another without a window. Each row of the list is a CSV line
+//!
+//! ```text
+//! name,re,im,width[,iterations[,scalar]]
+//! ```
+//!
+//! giving the name of the PNG to write in the output directory, the
+//! centre of the view and its width on the complex plane, and
+//! optionally an iteration limit and a colour scalar (which sets how
+//! bright the one palette there is comes out) in place of those of the
+//! whole batch.
+//! Blank lines, lines starting with '#' and a header row are skipped.
+//! A row that is not a view is reported and skipped, and so is one
+//! whose image fails to write, so one bad row never costs the rest.
+//! Rows go one at a time, each rendered by the whole rayon pool.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use num::complex::Complex as cmp;
+
+use crate::colour::{colourise_shaded, LegacyColorizer};
+use crate::error::{report, AppError};
+use crate::export::save_png;
+use crate::settings::Settings;
+use crate::viewport::Viewport;
+
+/// [Target]
+///
+/// Fields:
+/// [name] The name of its image, without the extension;
+/// [centre] The centre of the view;
+/// [width] The width of the view on the complex plane;
+/// [iterations] Its iteration limit, if not the batch's;
+/// [scalar] Its colour scalar, if not the batch's.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Target {
+    pub name: String,
+    pub centre: cmp<f64>,
+    pub width: f64,
+    pub iterations: Option<u32>,
+    pub scalar: Option<f32>,
+}
+
+impl Target {
+    /// [Parse]
+    /// Reads a target from a row of the list, or says what is wrong
+    /// with it.
+    pub fn parse(row: &str) -> Result<Target, String> {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        if !(4..=6).contains(&fields.len()) {
+            return Err(format!("expected name,re,im,width[,iterations[,scalar]], got {} fields", fields.len()));
+        }
+
+        let name = fields[0];
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(format!("'{name}' cannot be the name of a file in the output directory"));
+        }
+
+        let number = |i: usize, what: &str| {
+            fields[i].parse::<f64>().ok().filter(|n| n.is_finite()).ok_or_else(|| format!("{what} must be a finite number, got '{}'", fields[i]))
+        };
+        let (re, im, width) = (number(1, "re")?, number(2, "im")?, number(3, "width")?);
+        if width <= 0.0 {
+            return Err(format!("width must be positive, got {width}"));
+        }
+
+        let optional = |i: usize| fields.get(i).filter(|field| !field.is_empty());
+        let iterations = optional(4)
+            .map(|field| field.parse::<u32>().ok().filter(|n| *n > 0).ok_or_else(|| format!("iterations must be a positive whole number, got '{field}'")))
+            .transpose()?;
+        let scalar = optional(5)
+            .map(|field| field.parse::<f32>().ok().filter(|n| n.is_finite()).ok_or_else(|| format!("scalar must be a finite number, got '{field}'")))
+            .transpose()?;
+
+        Ok(Target { name: name.to_string(), centre: cmp::new(re, im), width, iterations, scalar })
+    }
+}
+
+/// [Parse List]
+/// The targets in the text of a list, and a message for each row that
+/// is not one, naming its line.
+pub fn parse_list(text: &str) -> (Vec<Target>, Vec<String>) {
+    let (mut targets, mut skipped) = (Vec::new(), Vec::new());
+
+    for (i, row) in text.lines().enumerate() {
+        let row = row.trim();
+        if row.is_empty() || row.starts_with('#') || (i == 0 && row.starts_with("name,")) {
+            continue;
+        }
+
+        match Target::parse(row) {
+            Ok(target) => targets.push(target),
+            Err(reason) => skipped.push(format!("line {}: {reason}", i + 1)),
+        }
+    }
+
+    (targets, skipped)
+}
+
+/// [Render]
+/// The target's view, `size` pixels across and down, coloured as the
+/// settings colour the zoom but for what the target overrides.
+pub fn render(target: &Target, size: [usize; 2], settings: &Settings) -> Vec<u8> {
+    let [width_px, height_px] = size;
+    let viewport = Viewport::new(target.centre, target.width, width_px, height_px);
+    let limit = target.iterations.unwrap_or(settings.iterations);
+
+    let (mut vals, mut heights) = (vec![0; width_px * height_px], Vec::new());
+    let map = |a: usize, b: usize| viewport.pixel_to_complex(a as f64, b as f64);
+    if settings.light.enabled {
+        settings.formula.compute_heights(&mut vals, &mut heights, width_px, map, limit);
+    } else {
+        settings.formula.compute_parallel(&mut vals, width_px, map, limit);
+    }
+
+    let colorizer = LegacyColorizer { scalar: target.scalar.unwrap_or(settings.scalar), gradient: settings.gradient };
+    let shades = settings.light.shades(&heights, width_px, limit);
+    colourise_shaded(&colorizer, &vals, limit, settings.tone, shades.as_deref())
+}
+
+/// [Summary]
+///
+/// Fields:
+/// [rendered] How many images were written;
+/// [skipped] How many rows were not views;
+/// [failed] How many images could not be written;
+/// [elapsed] How long the whole batch took.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub rendered: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub elapsed: Duration,
+}
+
+impl Summary {
+    /// The summary as printed once the batch is done.
+    pub fn line(&self) -> String {
+        format!(
+            "rendered {} of {} in {:.1} s ({} skipped, {} failed)",
+            self.rendered,
+            self.rendered + self.skipped + self.failed,
+            self.elapsed.as_secs_f64(),
+            self.skipped,
+            self.failed,
+        )
+    }
+}
+
+/// [Run]
+/// Renders every view in the list at `list` into `out`, creating it if
+/// need be, and printing a line as each is written. Only failing to
+/// read the list or to create the directory ends the batch.
+pub fn run(list: &Path, out: &Path, size: [usize; 2], settings: &Settings) -> Result<Summary, AppError> {
+    let start = Instant::now();
+    let text = fs::read_to_string(list).map_err(|e| AppError::Batch { path: list.to_path_buf(), reason: e.to_string() })?;
+    fs::create_dir_all(out).map_err(|e| AppError::Export { path: out.to_path_buf(), reason: e.to_string() })?;
+
+    let (targets, skipped) = parse_list(&text);
+    for reason in &skipped {
+        eprintln!("skipping {reason}");
+    }
+
+    let mut summary = Summary { rendered: 0, skipped: skipped.len(), failed: 0, elapsed: Duration::ZERO };
+    for (i, target) in targets.iter().enumerate() {
+        let rendering = Instant::now();
+        let path = out.join(format!("{}.png", target.name));
+        let rgba = render(target, size, settings);
+
+        match report(save_png(&path, size[0], size[1], &rgba)) {
+            Some(()) => {
+                summary.rendered += 1;
+                println!("[{}/{}] {} ({:.2} s)", i + 1, targets.len(), path.display(), rendering.elapsed().as_secs_f64());
+            }
+            None => summary.failed += 1,
+        }
+    }
+
+    summary.elapsed = start.elapsed();
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_give_views_and_overrides() {
+        assert_eq!(
+            Target::parse("seahorse, -0.745, 0.1, 0.01").unwrap(),
+            Target { name: "seahorse".to_string(), centre: cmp::new(-0.745, 0.1), width: 0.01, iterations: None, scalar: None },
+        );
+
+        let target = Target::parse("deep,-0.75,0,1e-6,20000,0.5").unwrap();
+        assert_eq!((target.iterations, target.scalar), (Some(20000), Some(0.5)));
+        assert_eq!(Target::parse("dim,-0.75,0,3,,0.1").unwrap().iterations, None);
+    }
+
+    #[test]
+    fn bad_rows_are_skipped_with_their_line() {
+        let text = "name,re,im,width\n# favourites\nwhole,-0.75,0,3\n\nflat,0,0,0\n../escape,0,0,1\nshort,0,0\nwhole2,-0.75,0,3,many\n";
+        let (targets, skipped) = parse_list(text);
+
+        assert_eq!(targets.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["whole"]);
+        assert_eq!(skipped.len(), 4);
+        assert!(skipped[0].starts_with("line 5: width must be positive"), "{}", skipped[0]);
+        assert!(skipped[1].starts_with("line 6: "), "{}", skipped[1]);
+        assert!(skipped[3].contains("iterations must be"), "{}", skipped[3]);
+    }
+
+    #[test]
+    fn renders_the_list_into_the_directory() {
+        let dir = std::env::temp_dir().join(format!("mandelbrot-batch-{}", std::process::id()));
+        let list = dir.join("targets.csv");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&list, "whole,-0.75,0,3\nnot a row\nedge,-0.745,0.1,0.01,300\n").unwrap();
+
+        let summary = run(&list, &dir.join("out"), [40, 20], &Settings::default()).unwrap();
+        let written = ["whole", "edge"].map(|name| image::open(dir.join("out").join(format!("{name}.png"))).map(|image| (image.width(), image.height())));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!((summary.rendered, summary.skipped, summary.failed), (2, 1, 0));
+        assert!(written.iter().all(|size| matches!(size, Ok((40, 20)))));
+        assert!(matches!(run(&list, &dir, [40, 20], &Settings::default()), Err(AppError::Batch { .. })));
+    }
+}
@@ -0,0 +1,133 @@
+//! [Bench]
+//!
+//! The canned zoom run by `bench --canned`, for catching performance
+//! regressions in the kernels by diffing its CSV between commits. It is
+//! the original zoom at its original size, run through the application
+//! as a window would drive it, but for a fixed number of frames with
+//! nothing on a clock or at random deciding where it goes: the target,
+//! the limit and the seed are fixed, so frame N of one run is the same
+//! view as frame N of any other, and only the times differ.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::app::App;
+use crate::error::AppError;
+use crate::hud::millis;
+use crate::settings::{ConfigError, Settings};
+use crate::stats::FrameStats;
+
+/// How many frames the canned zoom runs for.
+pub const FRAMES: u64 = 200;
+
+/// The seed for the search for targets, although nothing in the canned
+/// zoom searches, so that no run is seeded differently from another.
+pub const SEED: u64 = 0;
+
+/// [Frame Record]
+///
+/// Fields:
+/// [frame] Which frame of the zoom this was, from 0;
+/// [compute] How long the escape-time pass took;
+/// [total_iterations] The iterations executed over the frame;
+/// [pixels_per_sec] The frame's pixels over the time taken.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameRecord {
+    pub frame: u64,
+    pub compute: Duration,
+    pub total_iterations: u64,
+    pub pixels_per_sec: f64,
+}
+
+/// The settings of the canned zoom: the original ones, seeded.
+pub fn settings() -> Settings {
+    Settings { seed: Some(SEED), ..Settings::default() }
+}
+
+/// [Run]
+/// Runs the first `frames` frames of the canned zoom, recording each.
+pub fn run(frames: u64) -> Result<Vec<FrameRecord>, ConfigError> {
+    let settings = settings();
+    let mut app = App::new(&settings)?;
+    let pixels = app.vals().len() as f64;
+
+    let records = (0..frames)
+        .map(|frame| {
+            app.update_parallel();
+            let compute = app.compute_times().last().unwrap_or_default();
+
+            FrameRecord {
+                frame,
+                compute,
+                total_iterations: FrameStats::of(app.vals(), settings.iterations).total_iterations,
+                pixels_per_sec: pixels / compute.as_secs_f64(),
+            }
+        })
+        .collect();
+
+    Ok(records)
+}
+
+/// [Header]
+/// The comment lines describing the build a CSV came from: the version,
+/// the threads rayon has, whether the kernels use SIMD (they are scalar
+/// so far), the profile, and the canned zoom's size and limit.
+pub fn header() -> String {
+    let settings = settings();
+    let (width, height) = settings.dimensions();
+
+    format!(
+        "# mandelbrot-piston {} threads={} simd=off profile={}\n# canned zoom: {FRAMES} frames at {width}x{height}, limit {}, seed {SEED}\n",
+        env!("CARGO_PKG_VERSION"),
+        rayon::current_num_threads(),
+        if cfg!(debug_assertions) { "debug" } else { "release" },
+        settings.iterations,
+    )
+}
+
+/// [CSV]
+/// The records as CSV, after the header.
+pub fn csv(records: &[FrameRecord]) -> String {
+    let mut csv = header();
+    csv.push_str("frame,compute_ms,total_iterations,pixels_per_sec\n");
+    for record in records {
+        let _ = writeln!(csv, "{},{:.3},{},{:.0}", record.frame, millis(record.compute), record.total_iterations, record.pixels_per_sec);
+    }
+
+    csv
+}
+
+/// [Save CSV]
+/// Writes the records out as CSV.
+pub fn save_csv(path: &Path, records: &[FrameRecord]) -> Result<(), AppError> {
+    std::fs::write(path, csv(records)).map_err(|e| AppError::Export { path: path.to_path_buf(), reason: e.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_line_up_between_runs() {
+        let (first, second) = (run(5).unwrap(), run(5).unwrap());
+
+        let work = |records: &[FrameRecord]| records.iter().map(|r| (r.frame, r.total_iterations)).collect::<Vec<_>>();
+        assert_eq!(work(&first), work(&second));
+        assert_eq!(first.iter().map(|r| r.frame).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        // The dive goes deeper, and the work changes from frame to frame.
+        assert_ne!(first[0].total_iterations, first[4].total_iterations);
+    }
+
+    #[test]
+    fn csv_has_a_header_and_a_row_per_frame() {
+        let records = [FrameRecord { frame: 0, compute: Duration::from_micros(12_345), total_iterations: 80_000, pixels_per_sec: 6.48e6 }];
+        let csv = csv(&records);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert!(lines[0].starts_with("# mandelbrot-piston ") && lines[0].contains(" threads=") && lines[0].contains(" simd=off"));
+        assert_eq!(lines[2], "frame,compute_ms,total_iterations,pixels_per_sec");
+        assert_eq!(lines[3], "0,12.345,80000,6480000");
+        assert_eq!(lines.len(), 4);
+    }
+}
@@ -0,0 +1,336 @@
+//! [Bindings]
+//!
+//! Which key does what. Each action has a name and a default key, and
+//! a bindings file given with `--bindings` can move any of them, one
+//! per line as the action's name and the key's:
+//!
+//! ```text
+//! screenshot F12
+//! pause p
+//! ```
+//!
+//! Letters are case-sensitive, uppercase meaning with Shift held, and
+//! other keys go by name (space, tab, return, backspace, left, right,
+//! up, down, f1 to f12). Blank lines and lines starting with '#' are
+//! skipped. Two actions on the same key are refused, rather than one
+//! silently winning, and `--print-bindings` lists what is in effect.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::backend::Key;
+use crate::error::AppError;
+
+/// [Action]
+/// Everything a key can do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Pause,
+    Print,
+    PrintJson,
+    FormulaNext,
+    Screenshot,
+    ScreenshotOverlays,
+    Hud,
+    Minimap,
+    Crosshair,
+    Grid,
+    Histogram,
+    Graph,
+    Julia,
+    Inspector,
+    Legend,
+    LegendExport,
+    Tone,
+    Diff,
+    Antialias,
+    SampleMap,
+    Bookmark,
+    Tour,
+    TourLoop,
+    ZoomOut,
+    Autopilot,
+    Follow,
+    Explore,
+    ExploreInitial,
+    FastForward,
+    RealTime,
+    Slower,
+    Faster,
+    PowerSaving,
+    Crossfade,
+    CursorFirst,
+    Panel,
+    AutoLimit,
+    Skip,
+    Perturb,
+    Series,
+    ValidateSeries,
+    Split,
+    SwapSides,
+    Light,
+    LightLeft,
+    LightRight,
+    LightUp,
+    LightDown,
+    HeightMap,
+    ExportPalette,
+    Goto,
+    Nebula,
+    Lyapunov,
+    Invert,
+}
+
+/// [Defaults]
+/// Every action, with its default key and what it does.
+const DEFAULTS: [(Action, &str, Key, &str); 54] = [
+    (Action::Pause, "pause", Key::Space, "pause the simulation"),
+    (Action::Print, "print", Key::Char('p'), "print the current information"),
+    (Action::PrintJson, "print_json", Key::Char('P'), "print it as JSON"),
+    (Action::FormulaNext, "formula_next", Key::Char('f'), "switch to the next formula"),
+    (Action::Screenshot, "screenshot", Key::Char('s'), "save a screenshot of the fractal alone"),
+    (Action::ScreenshotOverlays, "screenshot_overlays", Key::Char('S'), "save a screenshot of the frame as shown, overlays and all"),
+    (Action::Hud, "hud", Key::Char('d'), "show or hide the HUD"),
+    (Action::Minimap, "minimap", Key::Char('m'), "show or hide the minimap"),
+    (Action::Crosshair, "crosshair", Key::Char('c'), "show or hide the crosshair on the zoom target"),
+    (Action::Grid, "grid", Key::Char('g'), "show or hide the coordinate grid"),
+    (Action::Histogram, "histogram", Key::Char('h'), "show or hide the iteration histogram"),
+    (Action::Graph, "graph", Key::Char('r'), "show or hide the chart of recent compute times"),
+    (Action::Julia, "julia", Key::Char('j'), "show or hide the Julia set under the cursor"),
+    (Action::Inspector, "inspector", Key::Char('i'), "show or hide the readout of the pixel under the cursor (clicking while paused prints it)"),
+    (Action::Legend, "legend", Key::Char('k'), "show or hide the colour legend"),
+    (Action::LegendExport, "legend_export", Key::Char('K'), "include the colour legend in screenshots, or stop"),
+    (Action::Tone, "tone", Key::Char('l'), "switch between sRGB and the legacy colour handling"),
+    (Action::Diff, "diff", Key::Char('e'), "compare each frame against a brute-force render, showing where they differ (slow)"),
+    (Action::Antialias, "antialias", Key::Char('x'), "anti-alias the edges, or stop"),
+    (Action::SampleMap, "sample_map", Key::Char('X'), "show which pixels get extra samples"),
+    (Action::Bookmark, "bookmark", Key::Char('b'), "bookmark the view"),
+    (Action::Tour, "tour", Key::Char('u'), "tour the bookmarks, or stop touring"),
+    (Action::TourLoop, "tour_loop", Key::Char('U'), "tour the bookmarks on a loop"),
+    (Action::ZoomOut, "zoom_out", Key::Char('o'), "zoom out, or back in"),
+    (Action::Autopilot, "autopilot", Key::Char('a'), "steer the zoom toward detail, or hold the target still"),
+    (Action::Follow, "follow", Key::Char('w'), "walk the zoom along the boundary, or hold the target still"),
+    (Action::Explore, "explore", Key::Char('t'), "search the view for a new target"),
+    (Action::ExploreInitial, "explore_initial", Key::Char('T'), "search the initial view, and restart the zoom from it"),
+    (Action::FastForward, "fast_forward", Key::Char(']'), "take twice as many zoom steps a frame, up to 8x"),
+    (Action::RealTime, "real_time", Key::Char('['), "go back to one zoom step a frame"),
+    (Action::Slower, "slower", Key::F(5), "halve the updates asked for each second, down to 1"),
+    (Action::Faster, "faster", Key::F(6), "double the updates asked for each second, up to 120"),
+    (Action::PowerSaving, "power_saving", Key::F(7), "render less often and only what has changed, idling while paused"),
+    (Action::Crossfade, "crossfade", Key::Char('v'), "fade slow frames into each other, or cut between them"),
+    (Action::CursorFirst, "cursor_first", Key::Char('y'), "compute the rows around the cursor first, or top to bottom"),
+    (Action::Panel, "panel", Key::Tab, "show or hide the panel of sliders for the limit, zoom and colouring"),
+    (Action::AutoLimit, "auto_limit", Key::Char('n'), "raise the iteration limit when frames look under-resolved, or stop"),
+    (Action::Skip, "skip_far", Key::Char('z'), "interpolate the counts far from the set rather than iterate them (inexact), or stop"),
+    (Action::Perturb, "perturb", Key::Char('Z'), "iterate against reference orbits, putting their glitches right, or directly"),
+    (Action::Series, "series", Key::Char('A'), "skip the first iterations of perturbed pixels by series approximation, or iterate them all"),
+    (Action::ValidateSeries, "validate_series", Key::Char('V'), "check each perturbed frame's series against a sample of pixels, or stop"),
+    (Action::Split, "split", Key::Char('W'), "split the frame to compare a second configuration on the right, or collapse it"),
+    (Action::SwapSides, "swap_sides", Key::Char('H'), "swap the sides of the split over, so that keys change the other"),
+    (Action::Light, "light", Key::Char('I'), "shade the counts as a height field under a light, or stop"),
+    (Action::LightLeft, "light_left", Key::Char('J'), "turn the light anticlockwise"),
+    (Action::LightRight, "light_right", Key::Char('L'), "turn the light clockwise"),
+    (Action::LightUp, "light_up", Key::Char('R'), "raise the light toward straight on"),
+    (Action::LightDown, "light_down", Key::Char('F'), "lower the light toward the frame"),
+    (Action::HeightMap, "height_map", Key::Char('D'), "show the counts as a 3D surface, orbited by dragging, or the frame"),
+    (Action::ExportPalette, "export_palette", Key::Char('E'), "save the colours as applied to a Fractint .map file, with a PNG strip of them"),
+    (Action::Goto, "goto", Key::F(2), "type a view to travel to, as 're im width' or 're+imi @ width'"),
+    (Action::Nebula, "nebula", Key::Char('N'), "show the Nebulabrot, building up while unpaused, or the zoom"),
+    (Action::Lyapunov, "lyapunov", Key::Char('Y'), "colour by the orbits' Lyapunov exponents, or by their counts"),
+    (Action::Invert, "invert", Key::Char('C'), "show the set under c -> 1/c, the outside bounded around the origin, or as it is"),
+];
+
+impl Action {
+    fn entry(&self) -> &'static (Action, &'static str, Key, &'static str) {
+        DEFAULTS.iter().find(|(action, ..)| action == self).unwrap_or_else(|| unreachable!("every action has a default"))
+    }
+
+    /// The name bindings files know the action by.
+    pub fn name(&self) -> &'static str {
+        self.entry().1
+    }
+
+    /// What the action does, for listing.
+    pub fn description(&self) -> &'static str {
+        self.entry().3
+    }
+
+    pub fn parse(name: &str) -> Option<Action> {
+        DEFAULTS.iter().find(|(_, n, ..)| *n == name).map(|(action, ..)| *action)
+    }
+}
+
+/// Keys that go by name rather than by the character they type.
+const NAMED_KEYS: [(Key, &str); 9] = [
+    (Key::Space, "space"),
+    (Key::Escape, "escape"),
+    (Key::Tab, "tab"),
+    (Key::Return, "return"),
+    (Key::Backspace, "backspace"),
+    (Key::Left, "left"),
+    (Key::Right, "right"),
+    (Key::Up, "up"),
+    (Key::Down, "down"),
+];
+
+/// [Key Name]
+/// The name of a key in a bindings file.
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char(c) => c.to_string(),
+        Key::F(n) => format!("F{n}"),
+        named => NAMED_KEYS.iter().find(|(k, _)| *k == named).map_or_else(|| format!("{named:?}"), |(_, name)| name.to_string()),
+    }
+}
+
+/// [Parse Key]
+/// The key a bindings file names, if it is one there is a binding for.
+/// Escape is kept for stopping a transition, or else closing the window.
+pub fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return c.is_ascii_graphic().then_some(Key::Char(c));
+    }
+
+    let lower = name.to_ascii_lowercase();
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse().ok()).filter(|n| (1..=12).contains(n)) {
+        return Some(Key::F(n));
+    }
+    NAMED_KEYS.iter().find(|(key, n)| *n == lower && *key != Key::Escape).map(|(key, _)| *key)
+}
+
+/// [Bindings]
+///
+/// Fields:
+/// [keys] The key each action is on, in the order of DEFAULTS.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bindings {
+    keys: Vec<(Action, Key)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        Bindings { keys: DEFAULTS.iter().map(|&(action, _, key, _)| (action, key)).collect() }
+    }
+}
+
+impl Bindings {
+    /// [Parse]
+    /// The defaults with the changes given in the text of a bindings
+    /// file, naming the first line that is not one, or the first key
+    /// left with two actions on it.
+    pub fn parse(text: &str) -> Result<Bindings, String> {
+        let mut bindings = Bindings::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((name, key)) = line.split_once(char::is_whitespace) else {
+                return Err(format!("line {}: expected 'action key', got '{line}'", i + 1));
+            };
+            let action = Action::parse(name).ok_or_else(|| format!("line {}: unknown action '{name}'", i + 1))?;
+            let key = parse_key(key.trim()).ok_or_else(|| format!("line {}: unknown key '{}'", i + 1, key.trim()))?;
+            bindings.bind(action, key);
+        }
+
+        bindings.check().map(|()| bindings)
+    }
+
+    /// Moves an action onto a key.
+    pub fn bind(&mut self, action: Action, key: Key) {
+        for (a, k) in &mut self.keys {
+            if *a == action {
+                *k = key;
+            }
+        }
+    }
+
+    /// [Check]
+    /// Refuses bindings that put two actions on one key.
+    pub fn check(&self) -> Result<(), String> {
+        let mut seen = HashMap::new();
+        for &(action, key) in &self.keys {
+            if let Some(other) = seen.insert(key, action) {
+                return Err(format!("{} is bound to both {} and {}", key_name(key), other.name(), action.name()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The action on a key, if there is one.
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.keys.iter().find(|&&(_, k)| k == key).map(|&(action, _)| action)
+    }
+
+    /// The key an action is on.
+    pub fn key(&self, action: Action) -> Key {
+        self.keys.iter().find(|&&(a, _)| a == action).map(|&(_, key)| key).unwrap_or_else(|| unreachable!("every action is bound"))
+    }
+
+    /// [Lines]
+    /// The bindings as they would be written in a file, each followed by
+    /// what it does as a comment, for `--print-bindings`.
+    pub fn lines(&self) -> Vec<String> {
+        let width = DEFAULTS.iter().map(|(_, name, ..)| name.len()).max().unwrap_or(0);
+        self.keys.iter()
+            .map(|&(action, key)| format!("{:width$} {:9} # {}", action.name(), key_name(key), action.description()))
+            .collect()
+    }
+}
+
+/// [Load]
+/// Reads the bindings file at `path` over the defaults.
+pub fn load(path: &Path) -> Result<Bindings, AppError> {
+    let error = |reason: String| AppError::Bindings { path: path.to_path_buf(), reason };
+
+    let text = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    Bindings::parse(&text).map_err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_distinct_and_named() {
+        let bindings = Bindings::default();
+
+        assert_eq!(bindings.check(), Ok(()));
+        assert_eq!(bindings.action(Key::Char('s')), Some(Action::Screenshot));
+        assert_eq!(bindings.action(Key::Char('S')), Some(Action::ScreenshotOverlays));
+        assert_eq!(bindings.action(Key::Char('Q')), None);
+        for (action, name, ..) in DEFAULTS {
+            assert_eq!(Action::parse(name), Some(action));
+        }
+    }
+
+    #[test]
+    fn files_move_actions() {
+        let bindings = Bindings::parse("# mine\n\nscreenshot F12\npause  p\nprint q\n").unwrap();
+
+        assert_eq!(bindings.action(Key::F(12)), Some(Action::Screenshot));
+        assert_eq!(bindings.action(Key::Char('s')), None);
+        assert_eq!(bindings.key(Action::Pause), Key::Char('p'));
+        assert_eq!(bindings.action(Key::Space), None);
+    }
+
+    #[test]
+    fn refuses_clashes_and_nonsense() {
+        assert_eq!(Bindings::parse("screenshot p").unwrap_err(), "p is bound to both print and screenshot");
+        assert_eq!(Bindings::parse("pause space\nfly z").unwrap_err(), "line 2: unknown action 'fly'");
+        assert_eq!(Bindings::parse("pause escape").unwrap_err(), "line 1: unknown key 'escape'");
+        assert!(Bindings::parse("pause").is_err());
+    }
+
+    #[test]
+    fn key_names_round_trip() {
+        for key in [Key::Char('x'), Key::Char('X'), Key::Char('1'), Key::Space, Key::Left, Key::F(5)] {
+            assert_eq!(parse_key(&key_name(key)), Some(key));
+        }
+        assert_eq!(parse_key("f13"), None);
+    }
+}
@@ -0,0 +1,97 @@
+//! [Bookmark]
+//!
+//! Saved views, kept one per line in a plain text file as the centre's
+//! real and imaginary parts and the width, so the file can be edited by
+//! hand. Blank lines and lines starting with '#' are skipped. B appends
+//! the current view to the file.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use num::complex::Complex as cmp;
+
+use crate::error::AppError;
+use crate::viewport::Viewport;
+
+/// [Bookmark]
+///
+/// Fields:
+/// [centre] The centre of the view;
+/// [width] The width of the view on the complex plane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bookmark {
+    pub centre: cmp<f64>,
+    pub width: f64,
+}
+
+impl Bookmark {
+    pub fn of(viewport: &Viewport) -> Bookmark {
+        Bookmark { centre: viewport.centre(), width: viewport.width() }
+    }
+
+    /// The bookmark as a line of the file, at full precision.
+    pub fn to_line(&self) -> String {
+        format!("{} {} {}", self.centre.re, self.centre.im, self.width)
+    }
+}
+
+/// [Parse]
+/// Reads the bookmarks from the text of a file, naming the first line
+/// that is not one.
+pub fn parse(text: &str) -> Result<Vec<Bookmark>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| {
+            let numbers: Vec<f64> = line.split_whitespace().map(str::parse).collect::<Result<_, _>>()
+                .map_err(|_| format!("line {}: expected numbers, got '{line}'", i + 1))?;
+
+            match numbers[..] {
+                [re, im, width] if numbers.iter().all(|x| x.is_finite()) && width > 0.0 => {
+                    Ok(Bookmark { centre: cmp::new(re, im), width })
+                }
+                _ => Err(format!("line {}: expected 're im width' with a positive width, got '{line}'", i + 1)),
+            }
+        })
+        .collect()
+}
+
+/// [Load]
+/// Reads the bookmarks file at `path`.
+pub fn load(path: &Path) -> Result<Vec<Bookmark>, AppError> {
+    let error = |reason: String| AppError::Bookmarks { path: path.to_path_buf(), reason };
+
+    let text = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    parse(&text).map_err(error)
+}
+
+/// [Append]
+/// Adds a bookmark to the end of the file at `path`, creating it if need be.
+pub fn append(path: &Path, bookmark: &Bookmark) -> Result<(), AppError> {
+    OpenOptions::new().create(true).append(true).open(path)
+        .and_then(|mut file| writeln!(file, "{}", bookmark.to_line()))
+        .map_err(|e| AppError::Bookmarks { path: path.to_path_buf(), reason: e.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_round_trip() {
+        let bookmark = Bookmark { centre: cmp::new(-0.743643887037158, 0.131825904205311), width: 1.5e-9 };
+
+        assert_eq!(parse(&bookmark.to_line()).unwrap(), vec![bookmark]);
+    }
+
+    #[test]
+    fn skips_comments_and_names_bad_lines() {
+        let text = "# seahorses\n\n-0.75 0.1 0.01\n";
+        assert_eq!(parse(text).unwrap(), vec![Bookmark { centre: cmp::new(-0.75, 0.1), width: 0.01 }]);
+
+        assert_eq!(parse("-0.75 0.1 0.01\n1 2\n").unwrap_err().split(':').next(), Some("line 2"));
+        assert!(parse("-0.75 0.1 -1").is_err());
+        assert!(parse("-0.75 zero 1").is_err());
+    }
+}
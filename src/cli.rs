@@ -0,0 +1,615 @@
+//! [CLI]
+//!
+//! Command-line options. Parsing is done by hand, since there are only
+//! a handful of flags.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::demo;
+use crate::error::AppError;
+use crate::fit::Fit;
+use crate::lighting::Light;
+use crate::lyapunov::Lyapunov;
+use crate::screen::Placing;
+
+pub const USAGE: &str = "\
+usage: mandelbrot-piston [options]
+       mandelbrot-piston bench --canned [--csv FILE]
+       mandelbrot-piston render --list FILE [--out DIR] [--size WxH]
+                         [--iterations N]
+       mandelbrot-piston render --view RE,IM,WIDTH [--out FILE] [--size WxH]
+                         [--iterations N] [--tile N] [--tile-dir DIR]
+
+options:
+  --backend NAME  presentation backend: piston (default) or pixels
+  --gl VERSION    OpenGL version to request (default 3.2, try 2.1 if
+                  the window fails to open)
+  --monitor N     open the window centred on monitor N (see
+                  --list-monitors), or the primary one if there is no N
+  --position X,Y  open the window with its top-left corner at X,Y, in
+                  physical pixels from the monitor's corner with --monitor,
+                  or the desktop's without
+  --list-monitors print the monitors and their resolutions, and exit
+  --screensaver   cycle through random dives until a key, click or mouse
+                  move, logging each to screensaver.log
+  --bookmarks FILE  the file B saves bookmarks to and U tours
+                  (default bookmarks.txt)
+  --bindings FILE move actions to other keys, a line such as
+                  'screenshot F12' at a time
+  --print-bindings
+                  list the key each action is on, and exit
+  --demo [SECONDS]
+                  run the original zoom with the built-in settings for
+                  SECONDS (default 60), ignoring input but Esc, then print
+                  the frames computed, their times and the magnification
+  --export-palette FILE
+                  write the colours the zoom starts with to FILE as a
+                  Fractint .map, and a PNG strip of them beside it, and
+                  exit (Shift+E does this for the colours on screen)
+  --antialias N   sample pixels again where neighbouring counts differ
+                  by more than N (X toggles this, Shift+X shows where)
+  --bounds RE_MIN,RE_MAX,IM_MIN,IM_MAX
+                  the initial view, by its corners; the window takes
+                  its shape
+  --view RE,IM,WIDTH
+                  the initial view, by its centre and width
+  --aspect W:H    the shape of the window for --view (default 2:1),
+                  as a ratio such as 21:9 or a number such as 1
+  --fit MODE      on resizing the window, letterbox the view (default) or
+                  extend it to the window's shape
+  --log-path FILE append a line to FILE for every frame computed: the
+                  time, frame, centre, width, limit and compute time
+  --resume-from FILE
+                  start from the last view logged to FILE by --log-path
+  --auto-limit MAX
+                  raise the iteration limit, up to MAX, whenever a frame's
+                  black looks like too few iterations rather than the set
+                  (N toggles this)
+  --light AZ,EL   shade the counts as a height field lit from AZ degrees
+                  clockwise from the right, EL degrees up (Shift+I toggles
+                  this, and P prints the light as this takes it)
+  --lyapunov EXT,INT
+                  colour by the orbits' Lyapunov exponents, EXT being the
+                  scale of the positive exponents outside the set and INT
+                  that of the negative ones inside, such as 0.6,0.4
+                  (Shift+Y toggles this, and P prints the scales)
+  --transition-frames N
+                  travel to a clicked point, or back to the start, over N
+                  frames (default 60), zooming out on the way when it is
+                  far; 0 jumps there, and Esc stops a transition
+  --tile-cache MB assemble frames from a cache of computed tiles of up to
+                  MB megabytes, so that ground already covered is instant;
+                  frames come out within a pixel of computing them
+  --tile-cache-dir DIR
+                  keep the cached tiles in DIR too, between runs
+  --power-saving  start saving power, as F7 does: at most 10 renders a
+                  second, only of what has changed, and none while paused
+                  until there is input
+  --seed N        seed the random search for targets (T), so that it
+                  finds the same points each run
+  -h, --help      print this message
+
+On Unix, SIGUSR1 pauses or resumes the zoom, SIGUSR2 saves a screenshot,
+and SIGTERM ends the run once the frame under way is done.
+
+bench:
+  --canned        run the fixed 200-frame zoom without a window, timing
+                  each frame, to compare performance between builds
+  --csv FILE      write the times to FILE rather than printing them
+
+render:
+  --list FILE     the views to render, a CSV row each:
+                  name,re,im,width[,iterations[,scalar]]
+  --view RE,IM,WIDTH
+                  a single view to render, a tile at a time, at any size
+  --out PATH      the directory to write each name.png to (default .), or
+                  the file to write the view to (default mandelbrot.png)
+  --light AZ,EL   shade the images as the zoom does with --light
+  --size WxH      the size of every image (default 800x400)
+  --iterations N  the iteration limit for rows without one (default 1200)
+  --tile N        the side of a tile of the view (default 1024)
+  --tile-dir DIR  save the view's tiles to DIR as they are done, and skip
+                  those already there, to carry on a stopped render";
+
+/// [Backend Choice]
+/// Which presentation backend to open the window with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackendChoice {
+    Piston,
+    Pixels,
+}
+
+/// [Bench]
+///
+/// Fields:
+/// [csv] Where to write the canned zoom's CSV, or None to print it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bench {
+    pub csv: Option<PathBuf>,
+}
+
+/// [Render]
+///
+/// Fields:
+/// [list] The CSV file of views to render, or None for `view`;
+/// [view] The single view to render tile by tile, as its centre's parts and its width;
+/// [out] The directory to write the images to, or the file for `view`, if not the default;
+/// [size] The size of the images, if not the window's;
+/// [iterations] The iteration limit for rows without one, if not the zoom's;
+/// [tile] The side of the tiles of `view`, if not the default;
+/// [tile_dir] The directory to keep the tiles of `view` in, if any;
+/// [light] The light the images are shaded with, if they are.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Render {
+    pub list: Option<PathBuf>,
+    pub view: Option<[f64; 3]>,
+    pub out: Option<PathBuf>,
+    pub size: Option<[usize; 2]>,
+    pub iterations: Option<u32>,
+    pub tile: Option<usize>,
+    pub tile_dir: Option<PathBuf>,
+    pub light: Option<Light>,
+}
+
+/// [Options]
+///
+/// Fields:
+/// [backend] The presentation backend;
+/// [gl] The OpenGL version to request, as "major.minor";
+/// [placing] Which monitor the window opens on, and where;
+/// [list_monitors] Whether to list the monitors and exit;
+/// [screensaver] Whether to run as a screensaver;
+/// [seed] The seed for the random search for targets;
+/// [bookmarks] The bookmarks file, if not the default;
+/// [bindings] The bindings file, if any;
+/// [print_bindings] Whether to list the bindings and exit;
+/// [demo] How long to run the demo for, if running one;
+/// [export_palette] The .map file to write the starting palette to before exiting, if any;
+/// [bounds] The initial view as its bounds: re_min, re_max, im_min, im_max;
+/// [view] The initial view as its centre's parts and its width;
+/// [aspect] The initial view's width over its height, for `view`;
+/// [fit] How the view fits a resized window;
+/// [log_path] The file to log every frame's view to, if any;
+/// [resume_from] The log to take the initial view from, if any;
+/// [antialias] The count threshold to anti-alias edges at, if enabled from the start;
+/// [auto_limit] The ceiling to raise the iteration limit to, if enabled from the start;
+/// [light] The light frames are shaded with, if enabled from the start;
+/// [lyapunov] The scales frames are coloured by their exponents on, if so from the start;
+/// [transition_frames] How many frames retargeting travels over, if not the default;
+/// [tile_cache] The tile cache's budget in megabytes, if frames are assembled from one;
+/// [tile_cache_dir] The directory the tile cache keeps tiles in, if any;
+/// [power_saving] Whether to start saving power;
+/// [bench] The benchmark to run instead of opening a window, if any;
+/// [render] The list of views to render instead of opening a window, if any;
+/// [help] Whether to print the usage and exit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub backend: BackendChoice,
+    pub gl: String,
+    pub placing: Placing,
+    pub list_monitors: bool,
+    pub screensaver: bool,
+    pub seed: Option<u64>,
+    pub bookmarks: Option<PathBuf>,
+    pub bindings: Option<PathBuf>,
+    pub print_bindings: bool,
+    pub export_palette: Option<PathBuf>,
+    pub demo: Option<Duration>,
+    pub antialias: Option<u32>,
+    pub auto_limit: Option<u32>,
+    pub light: Option<Light>,
+    pub lyapunov: Option<Lyapunov>,
+    pub transition_frames: Option<u64>,
+    pub tile_cache: Option<usize>,
+    pub tile_cache_dir: Option<PathBuf>,
+    pub power_saving: bool,
+    pub bounds: Option<[f64; 4]>,
+    pub view: Option<[f64; 3]>,
+    pub aspect: Option<f64>,
+    pub fit: Fit,
+    pub log_path: Option<PathBuf>,
+    pub resume_from: Option<PathBuf>,
+    pub bench: Option<Bench>,
+    pub render: Option<Render>,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            backend: BackendChoice::Piston,
+            gl: "3.2".to_string(),
+            placing: Placing::default(),
+            list_monitors: false,
+            screensaver: false,
+            seed: None,
+            bookmarks: None,
+            bindings: None,
+            print_bindings: false,
+            export_palette: None,
+            demo: None,
+            antialias: None,
+            auto_limit: None,
+            light: None,
+            lyapunov: None,
+            transition_frames: None,
+            tile_cache: None,
+            tile_cache_dir: None,
+            power_saving: false,
+            bounds: None,
+            view: None,
+            aspect: None,
+            fit: Fit::default(),
+            log_path: None,
+            resume_from: None,
+            bench: None,
+            render: None,
+            help: false,
+        }
+    }
+}
+
+/// [Parse]
+/// Reads the options from the arguments, excluding the program name.
+/// A first argument of `bench` runs a benchmark instead, and one of
+/// `render` renders a list of views, each taking flags of its own.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, AppError> {
+    let mut options = Options::default();
+    let mut args = args.into_iter().peekable();
+    let bench = args.next_if(|arg| arg == "bench").is_some();
+    let render = !bench && args.next_if(|arg| arg == "render").is_some();
+    let (mut canned, mut csv) = (false, None);
+    let mut batch = Render::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--backend" => {
+                options.backend = match value(&mut args, &arg)?.as_str() {
+                    "piston" => BackendChoice::Piston,
+                    "pixels" => BackendChoice::Pixels,
+                    other => return Err(AppError::Args(format!("unknown backend '{other}'"))),
+                }
+            }
+            "--gl" => options.gl = value(&mut args, &arg)?,
+            "--monitor" => {
+                let monitor = value(&mut args, &arg)?;
+                options.placing.monitor = Some(monitor.parse().map_err(|_| AppError::Args(format!("--monitor needs a whole number, got '{monitor}'")))?);
+            }
+            "--position" => {
+                let position = value(&mut args, &arg)?;
+                let [x, y] = numbers::<2>(&position, &arg)?;
+                let pixel = |n: f64| (n.fract() == 0.0 && n.abs() <= i32::MAX as f64).then_some(n as i32);
+                options.placing.position = Some(pixel(x).zip(pixel(y)).map(|(x, y)| [x, y])
+                    .ok_or_else(|| AppError::Args(format!("--position needs whole numbers of pixels, got '{position}'")))?);
+            }
+            "--list-monitors" => options.list_monitors = true,
+            "--screensaver" => options.screensaver = true,
+            "--bookmarks" => options.bookmarks = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--bindings" => options.bindings = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--print-bindings" => options.print_bindings = true,
+            "--demo" => {
+                let seconds = args.next_if(|arg| !arg.starts_with('-'));
+                options.demo = Some(match seconds {
+                    Some(seconds) => seconds.parse().ok().and_then(|s: f64| Duration::try_from_secs_f64(s).ok()).filter(|s| !s.is_zero())
+                        .ok_or_else(|| AppError::Args(format!("--demo needs a positive number of seconds, got '{seconds}'")))?,
+                    None => demo::DURATION,
+                });
+            }
+            "--export-palette" => options.export_palette = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--seed" => {
+                let seed = value(&mut args, &arg)?;
+                options.seed = Some(seed.parse().map_err(|_| AppError::Args(format!("--seed needs a whole number, got '{seed}'")))?);
+            }
+            "--antialias" => {
+                let threshold = value(&mut args, &arg)?;
+                options.antialias = Some(threshold.parse().map_err(|_| AppError::Args(format!("--antialias needs a whole number, got '{threshold}'")))?);
+            }
+            "--auto-limit" => {
+                let ceiling = value(&mut args, &arg)?;
+                options.auto_limit = Some(ceiling.parse().ok().filter(|n| *n > 0)
+                    .ok_or_else(|| AppError::Args(format!("--auto-limit needs a positive whole number, got '{ceiling}'")))?);
+            }
+            "--light" => {
+                let light = value(&mut args, &arg)?;
+                options.light = Some(Light::parse(&light)
+                    .ok_or_else(|| AppError::Args(format!("--light needs an azimuth and an elevation from 0 to 90 in degrees, such as 225,45, got '{light}'")))?);
+            }
+            "--lyapunov" => {
+                let scales = value(&mut args, &arg)?;
+                options.lyapunov = Some(Lyapunov::parse(&scales)
+                    .ok_or_else(|| AppError::Args(format!("--lyapunov needs two positive scales, for outside and inside the set, such as 0.6,0.4, got '{scales}'")))?);
+            }
+            "--transition-frames" => {
+                let frames = value(&mut args, &arg)?;
+                options.transition_frames = Some(frames.parse().map_err(|_| AppError::Args(format!("--transition-frames needs a whole number, got '{frames}'")))?);
+            }
+            "--tile-cache" => {
+                let budget = value(&mut args, &arg)?;
+                options.tile_cache = Some(budget.parse().ok().filter(|mb| *mb > 0)
+                    .ok_or_else(|| AppError::Args(format!("--tile-cache needs a positive whole number of megabytes, got '{budget}'")))?);
+            }
+            "--tile-cache-dir" => options.tile_cache_dir = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--power-saving" => options.power_saving = true,
+            "--bounds" => options.bounds = Some(numbers(&value(&mut args, &arg)?, &arg)?),
+            "--view" => options.view = Some(numbers(&value(&mut args, &arg)?, &arg)?),
+            "--aspect" => options.aspect = Some(aspect(&value(&mut args, &arg)?)?),
+            "--fit" => {
+                let name = value(&mut args, &arg)?;
+                options.fit = Fit::parse(&name).ok_or_else(|| AppError::Args(format!("unknown fit '{name}': use letterbox or extend")))?;
+            }
+            "--log-path" => options.log_path = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--resume-from" => options.resume_from = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--canned" if bench => canned = true,
+            "--csv" if bench => csv = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--list" if render => batch.list = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--out" if render => batch.out = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--size" if render => batch.size = Some(size(&value(&mut args, &arg)?)?),
+            "--iterations" if render => {
+                let limit = value(&mut args, &arg)?;
+                batch.iterations = Some(limit.parse().ok().filter(|n| *n > 0)
+                    .ok_or_else(|| AppError::Args(format!("--iterations needs a positive whole number, got '{limit}'")))?);
+            }
+            "--tile" if render => {
+                let side = value(&mut args, &arg)?;
+                batch.tile = Some(side.parse().ok().filter(|n| *n > 0)
+                    .ok_or_else(|| AppError::Args(format!("--tile needs a positive whole number of pixels, got '{side}'")))?);
+            }
+            "--tile-dir" if render => batch.tile_dir = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "-h" | "--help" => options.help = true,
+            _ => return Err(AppError::Args(format!("unknown argument '{arg}'"))),
+        }
+    }
+
+    if options.bounds.is_some() && (options.view.is_some() || options.aspect.is_some()) {
+        return Err(AppError::Args("--bounds gives the whole view, so it cannot be combined with --view or --aspect".to_string()));
+    }
+    if options.resume_from.is_some() && (options.bounds.is_some() || options.view.is_some()) {
+        return Err(AppError::Args("--resume-from gives the initial view, so it cannot be combined with --bounds or --view".to_string()));
+    }
+
+    if bench {
+        if !canned && !options.help {
+            return Err(AppError::Args("bench needs --canned, the only benchmark so far".to_string()));
+        }
+        options.bench = Some(Bench { csv });
+    }
+    if render {
+        batch.view = options.view.take();
+        batch.light = options.light.take();
+        match (&batch.list, batch.view) {
+            (Some(_), Some(_)) => return Err(AppError::Args("render takes either --list or --view, not both".to_string())),
+            (None, None) if !options.help => return Err(AppError::Args("render needs --list or --view, the views to render".to_string())),
+            (Some(_), None) if batch.tile.is_some() || batch.tile_dir.is_some() => {
+                return Err(AppError::Args("--tile and --tile-dir are for rendering a --view".to_string()));
+            }
+            _ => options.render = Some(batch),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Exactly N finite numbers, separated by commas.
+fn numbers<const N: usize>(value: &str, flag: &str) -> Result<[f64; N], AppError> {
+    let error = || AppError::Args(format!("{flag} needs {N} numbers separated by commas, got '{value}'"));
+
+    let parsed: Vec<f64> = value.split(',').map(|n| n.trim().parse()).collect::<Result<_, _>>().map_err(|_| error())?;
+    let numbers: [f64; N] = parsed.try_into().map_err(|_| error())?;
+    if numbers.iter().all(|n| n.is_finite()) { Ok(numbers) } else { Err(error()) }
+}
+
+/// An image size as WxH, both positive.
+fn size(value: &str) -> Result<[usize; 2], AppError> {
+    value.split_once('x')
+        .and_then(|(w, h)| w.trim().parse().ok().zip(h.trim().parse().ok()))
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .map(|(w, h)| [w, h])
+        .ok_or_else(|| AppError::Args(format!("--size needs a width and height such as 1920x1080, got '{value}'")))
+}
+
+/// A positive aspect ratio, as W:H or as a single number.
+fn aspect(value: &str) -> Result<f64, AppError> {
+    let ratio = match value.split_once(':') {
+        Some((w, h)) => w.trim().parse::<f64>().ok().zip(h.trim().parse::<f64>().ok()).map(|(w, h)| w / h),
+        None => value.trim().parse().ok(),
+    };
+
+    ratio.filter(|r| r.is_finite() && *r > 0.0)
+        .ok_or_else(|| AppError::Args(format!("--aspect needs a positive ratio such as 21:9, got '{value}'")))
+}
+
+/// The value following a flag.
+fn value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, AppError> {
+    args.next().ok_or_else(|| AppError::Args(format!("{flag} needs a value")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(args: &[&str]) -> Result<Options, AppError> {
+        parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(parse_str(&[]).unwrap(), Options::default());
+    }
+
+    #[test]
+    fn gl_version() {
+        assert_eq!(parse_str(&["--gl", "2.1"]).unwrap().gl, "2.1");
+        assert!(matches!(parse_str(&["--gl"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn backend_choice() {
+        assert_eq!(parse_str(&["--backend", "pixels"]).unwrap().backend, BackendChoice::Pixels);
+        assert!(matches!(parse_str(&["--backend", "sdl"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn seed() {
+        assert_eq!(parse_str(&["--seed", "42"]).unwrap().seed, Some(42));
+        assert!(matches!(parse_str(&["--seed", "-1"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn window_placing() {
+        let options = parse_str(&["--monitor", "1", "--position", "-100,40", "--list-monitors"]).unwrap();
+        assert_eq!(options.placing, Placing { monitor: Some(1), position: Some([-100, 40]) });
+        assert!(options.list_monitors);
+
+        assert!(matches!(parse_str(&["--monitor", "-1"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["--position", "10.5,0"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn screensaver() {
+        assert!(parse_str(&["--screensaver"]).unwrap().screensaver);
+        assert!(!parse_str(&[]).unwrap().screensaver);
+    }
+
+    #[test]
+    fn bookmarks_file() {
+        assert_eq!(parse_str(&["--bookmarks", "deep.txt"]).unwrap().bookmarks, Some(PathBuf::from("deep.txt")));
+    }
+
+    #[test]
+    fn bindings() {
+        let options = parse_str(&["--bindings", "keys.txt", "--print-bindings"]).unwrap();
+        assert_eq!((options.bindings, options.print_bindings), (Some(PathBuf::from("keys.txt")), true));
+    }
+
+    #[test]
+    fn transition_frames() {
+        assert_eq!(parse_str(&["--transition-frames", "0"]).unwrap().transition_frames, Some(0));
+        assert!(matches!(parse_str(&["--transition-frames", "-5"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn demo() {
+        assert_eq!(parse_str(&["--demo"]).unwrap().demo, Some(demo::DURATION));
+        assert_eq!(parse_str(&["--demo", "2.5", "--seed", "1"]).unwrap().demo, Some(Duration::from_millis(2500)));
+        assert_eq!(parse_str(&["--demo", "--seed", "1"]).unwrap().demo, Some(demo::DURATION));
+        assert!(matches!(parse_str(&["--demo", "0"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["--demo", "soon"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn export_palette() {
+        assert_eq!(parse_str(&["--export-palette", "zoom.map"]).unwrap().export_palette, Some(PathBuf::from("zoom.map")));
+        assert!(matches!(parse_str(&["--export-palette"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn antialias_threshold() {
+        assert_eq!(parse_str(&["--antialias", "12"]).unwrap().antialias, Some(12));
+        assert!(matches!(parse_str(&["--antialias", "some"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn auto_limit_ceiling() {
+        assert_eq!(parse_str(&["--auto-limit", "50000"]).unwrap().auto_limit, Some(50000));
+        assert!(matches!(parse_str(&["--auto-limit", "0"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn lyapunov_scales() {
+        assert_eq!(parse_str(&["--lyapunov", "0.6,0.4"]).unwrap().lyapunov, Some(Lyapunov { enabled: true, exterior: 0.6, interior: 0.4 }));
+        assert!(matches!(parse_str(&["--lyapunov", "0.6,-1"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn light_direction() {
+        assert_eq!(parse_str(&["--light", "225,45"]).unwrap().light, Some(Light { enabled: true, azimuth: 225.0, elevation: 45.0 }));
+        assert_eq!(parse_str(&["render", "--view", "0,0,1", "--light", "0,90"]).unwrap().render.unwrap().light.map(|light| light.to_arg()), Some("0,90".to_string()));
+        assert!(matches!(parse_str(&["--light", "225"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn tile_cache() {
+        let options = parse_str(&["--tile-cache", "64", "--tile-cache-dir", "tiles"]).unwrap();
+        assert_eq!((options.tile_cache, options.tile_cache_dir), (Some(64), Some(PathBuf::from("tiles"))));
+        assert!(matches!(parse_str(&["--tile-cache", "0"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn power_saving() {
+        assert!(parse_str(&["--power-saving"]).unwrap().power_saving);
+        assert!(!parse_str(&[]).unwrap().power_saving);
+    }
+
+    #[test]
+    fn initial_view() {
+        assert_eq!(parse_str(&["--bounds", "-2.75,1.25,-2,2"]).unwrap().bounds, Some([-2.75, 1.25, -2.0, 2.0]));
+        assert!(matches!(parse_str(&["--bounds", "-2,2,-1"]), Err(AppError::Args(_))));
+
+        let options = parse_str(&["--view", "-0.75, 0, 4", "--aspect", "21:9"]).unwrap();
+        assert_eq!((options.view, options.aspect), (Some([-0.75, 0.0, 4.0]), Some(21.0 / 9.0)));
+        assert_eq!(parse_str(&["--aspect", "1"]).unwrap().aspect, Some(1.0));
+
+        assert!(matches!(parse_str(&["--aspect", "0:1"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["--view", "0,0,nan"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["--bounds", "-2,2,-1,1", "--aspect", "1"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn fit() {
+        assert_eq!(parse_str(&["--fit", "extend"]).unwrap().fit, Fit::Extend);
+        assert!(matches!(parse_str(&["--fit", "stretch"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn path_log() {
+        let options = parse_str(&["--log-path", "zoom.log", "--resume-from", "zoom.log"]).unwrap();
+        assert_eq!((options.log_path, options.resume_from), (Some(PathBuf::from("zoom.log")), Some(PathBuf::from("zoom.log"))));
+        assert!(matches!(parse_str(&["--resume-from", "zoom.log", "--view", "0,0,1"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn canned_bench() {
+        let options = parse_str(&["bench", "--canned", "--csv", "out.csv"]).unwrap();
+        assert_eq!(options.bench, Some(Bench { csv: Some(PathBuf::from("out.csv")) }));
+        assert_eq!(parse_str(&["bench", "--canned"]).unwrap().bench, Some(Bench::default()));
+
+        assert!(matches!(parse_str(&["bench"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["--canned"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn render_list() {
+        let options = parse_str(&["render", "--list", "targets.csv", "--out", "shots", "--size", "1920x1080", "--iterations", "20000"]).unwrap();
+        assert_eq!(options.render, Some(Render {
+            list: Some(PathBuf::from("targets.csv")),
+            out: Some(PathBuf::from("shots")),
+            size: Some([1920, 1080]),
+            iterations: Some(20000),
+            ..Render::default()
+        }));
+        assert_eq!(parse_str(&["render", "--list", "targets.csv"]).unwrap().render.and_then(|render| render.out), None);
+
+        assert!(matches!(parse_str(&["render"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["render", "--list", "t.csv", "--size", "1920"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["--list", "t.csv"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["bench", "--canned", "--list", "t.csv"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn tiled_render() {
+        let options = parse_str(&["render", "--view", "-0.75,0,3", "--size", "16384x8192", "--tile", "512", "--tile-dir", "tiles"]).unwrap();
+        let render = options.render.unwrap();
+        assert_eq!((render.view, render.size, render.tile), (Some([-0.75, 0.0, 3.0]), Some([16384, 8192]), Some(512)));
+        assert_eq!((options.view, render.tile_dir), (None, Some(PathBuf::from("tiles"))));
+
+        assert!(matches!(parse_str(&["render", "--view", "0,0,1", "--list", "t.csv"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["render", "--list", "t.csv", "--tile", "256"]), Err(AppError::Args(_))));
+        assert!(matches!(parse_str(&["render", "--view", "0,0,1", "--tile", "0"]), Err(AppError::Args(_))));
+    }
+
+    #[test]
+    fn rejects_unknown() {
+        assert!(matches!(parse_str(&["--frobnicate"]), Err(AppError::Args(_))));
+    }
+}
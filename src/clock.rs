@@ -0,0 +1,87 @@
+//! [Clock]
+//!
+//! Wall-clock time since the zoom started, with time spent paused
+//! accounted for separately so that rates reflect the running time.
+
+use chrono::{DateTime, Duration, Local};
+
+/// [Run Clock]
+///
+/// Fields:
+/// [started] When the run started;
+/// [paused_since] When the current pause began, while paused;
+/// [paused_total] Time spent in earlier pauses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RunClock {
+    started: DateTime<Local>,
+    paused_since: Option<DateTime<Local>>,
+    paused_total: Duration,
+}
+
+impl RunClock {
+    pub fn new(now: DateTime<Local>) -> RunClock {
+        RunClock { started: now, paused_since: None, paused_total: Duration::zero() }
+    }
+
+    pub fn started(&self) -> DateTime<Local> {
+        self.started
+    }
+
+    pub fn pause(&mut self, now: DateTime<Local>) {
+        self.paused_since.get_or_insert(now);
+    }
+
+    pub fn resume(&mut self, now: DateTime<Local>) {
+        if let Some(since) = self.paused_since.take() {
+            self.paused_total += now - since;
+        }
+    }
+
+    /// [Total]
+    /// Time since the run started, paused or not.
+    pub fn total(&self, now: DateTime<Local>) -> Duration {
+        now - self.started
+    }
+
+    /// [Running]
+    /// Time since the run started, less any time spent paused.
+    pub fn running(&self, now: DateTime<Local>) -> Duration {
+        let paused = self.paused_total + self.paused_since.map_or(Duration::zero(), |since| now - since);
+        self.total(now) - paused
+    }
+}
+
+/// [Clock Time]
+/// A duration as hours, minutes and seconds, like "00:04:31".
+pub fn clock_time(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_are_not_running_time() {
+        let start = Local::now();
+        let at = |s: i64| start + Duration::seconds(s);
+        let mut clock = RunClock::new(start);
+
+        clock.pause(at(10));
+        assert_eq!(clock.running(at(15)), Duration::seconds(10));
+
+        clock.resume(at(20));
+        clock.pause(at(30));
+        clock.resume(at(31));
+
+        assert_eq!(clock.total(at(60)), Duration::seconds(60));
+        assert_eq!(clock.running(at(60)), Duration::seconds(49));
+    }
+
+    #[test]
+    fn formats_as_clock_time() {
+        assert_eq!(clock_time(Duration::seconds(271)), "00:04:31");
+        assert_eq!(clock_time(Duration::seconds(100_000)), "27:46:40");
+    }
+}
@@ -0,0 +1,378 @@
+//! [Colour]
+//!
+//! The mapping from escape-time results to colours, kept apart from
+//! the rendering so that palettes can be swapped without touching
+//! the render loop.
+//!
+//! Colorizers work in linear light, where averaging and blending
+//! colours is meaningful, and a `Tone` turns their colours into the
+//! sRGB-encoded bytes that every backend and exporter takes as they
+//! are: the GL backend's texture decodes them and its sRGB framebuffer
+//! encodes them again, pixels does the same, and canvases and PNGs are
+//! sRGB already. So a frame looks the same on screen as saved.
+
+/// [Iteration Result]
+/// What the kernel found for one pixel.
+///
+/// Fields:
+/// [count] Iterations taken before the orbit escaped;
+/// [limit] The iteration limit the count was computed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IterResult {
+    pub count: u32,
+    pub limit: u32,
+}
+
+impl IterResult {
+    /// Points which reached the limit are treated as members of the set.
+    pub fn interior(&self) -> bool {
+        self.count >= self.limit
+    }
+}
+
+/// [Colorizer]
+/// Turns an iteration result into an RGBA colour with channels in 0..=1.
+pub trait Colorizer {
+    fn color(&self, value: IterResult) -> [f32; 4];
+}
+
+/// [Legacy Colorizer]
+/// The original colouring: members of the set are black, and escaping
+/// points get a ramp on their iteration count, tinted by the gradient and
+/// brightened by the animated scalar until it settles below 0.05.
+///
+/// Fields:
+/// [scalar] arbitrary value that determines the colouring;
+/// [gradient] The tint of the ramp.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LegacyColorizer {
+    pub scalar: f32,
+    pub gradient: Gradient,
+}
+
+impl Colorizer for LegacyColorizer {
+    fn color(&self, value: IterResult) -> [f32; 4] {
+        if value.interior() {
+            return [0.0, 0.0, 0.0, 1.0];
+        }
+
+        let colour_mod = if self.scalar > 0.05 {
+            value.count as f32 / 100.0 * self.scalar
+        } else {
+            value.count as f32 / 100.0 * 0.05
+        };
+
+        let [r, g, b] = self.gradient.weights();
+        [colour_mod * r, colour_mod * g, colour_mod * b, 1.0]
+    }
+}
+
+/// [Gradient]
+/// The tint of the legacy ramp, as the weight each channel climbs by.
+///
+/// Variants:
+/// [Blue] The original blue-tinted ramp;
+/// [Fire] Red first, then yellow and white;
+/// [Sea] Green and blue, little red;
+/// [Grey] All channels alike.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Gradient {
+    #[default]
+    Blue,
+    Fire,
+    Sea,
+    Grey,
+}
+
+impl Gradient {
+    pub const ALL: [Gradient; 4] = [Gradient::Blue, Gradient::Fire, Gradient::Sea, Gradient::Grey];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Gradient::Blue => "blue",
+            Gradient::Fire => "fire",
+            Gradient::Sea => "sea",
+            Gradient::Grey => "grey",
+        }
+    }
+
+    pub fn next(&self) -> Gradient {
+        let i = Gradient::ALL.iter().position(|gradient| gradient == self).unwrap_or(0);
+        Gradient::ALL[(i + 1) % Gradient::ALL.len()]
+    }
+
+    pub fn weights(&self) -> [f32; 3] {
+        match self {
+            Gradient::Blue => [2.4, 2.0, 3.0],
+            Gradient::Fire => [3.6, 1.8, 0.6],
+            Gradient::Sea => [0.6, 2.4, 3.0],
+            Gradient::Grey => [2.4, 2.4, 2.4],
+        }
+    }
+}
+
+/// [Scalar Fade]
+/// The animation of the legacy colouring's scalar. Each step the scalar
+/// drops by the step factor, which shrinks tenfold as the scalar passes
+/// below each threshold, so the colours dim ever more slowly.
+///
+/// Fields:
+/// [scalar] arbitrary value that determines the colouring;
+/// [step_factor] arbitrary value that determines the change of the scalar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScalarFade {
+    pub scalar: f32,
+    pub step_factor: f32,
+}
+
+impl ScalarFade {
+    /// [Advance]
+    /// Figured out by good ol' trial and error.
+    pub fn advance(&mut self) {
+        if self.scalar > 0.000005 {
+            self.step_factor = 0.000001;
+        }
+        if self.scalar > 0.00005 {
+            self.step_factor = 0.00001;
+        }
+        if self.scalar > 0.0005 {
+            self.step_factor = 0.0001;
+        }
+        if self.scalar > 0.01 {
+            self.step_factor = 0.001;
+        }
+        if self.scalar > 0.23 {
+            self.step_factor = 0.01
+        }
+
+        self.scalar -= self.step_factor;
+    }
+}
+
+/// Where soft saturation starts to bend channels away from their value.
+const KNEE: f32 = 0.8;
+
+/// [Tone]
+/// How colours become bytes, toggled with L for comparison.
+///
+/// Variants:
+/// [Srgb] Channels over the knee are eased toward 1 rather than clipped,
+///        and the sRGB transfer function is applied;
+/// [Legacy] Channels are clipped to 0..=1 and written as they are, which
+///          is how the zoom originally looked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tone {
+    #[default]
+    Srgb,
+    Legacy,
+}
+
+impl Tone {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tone::Srgb => "srgb",
+            Tone::Legacy => "legacy",
+        }
+    }
+
+    pub fn other(&self) -> Tone {
+        match self {
+            Tone::Srgb => Tone::Legacy,
+            Tone::Legacy => Tone::Srgb,
+        }
+    }
+
+    /// [Saturate]
+    /// Brings a colour's channels into 0..=1, still in linear light.
+    pub fn saturate(&self, colour: [f32; 4]) -> [f32; 4] {
+        let [r, g, b, a] = colour;
+        match self {
+            Tone::Srgb => [soft_saturate(r), soft_saturate(g), soft_saturate(b), a.clamp(0.0, 1.0)],
+            Tone::Legacy => colour.map(|channel| channel.clamp(0.0, 1.0)),
+        }
+    }
+
+    /// [Encode]
+    /// The bytes for a saturated colour. Alpha is linear either way.
+    pub fn encode(&self, colour: [f32; 4]) -> [u8; 4] {
+        let [r, g, b, a] = colour;
+        match self {
+            Tone::Srgb => to_rgba8([srgb_encode(r), srgb_encode(g), srgb_encode(b), a]),
+            Tone::Legacy => to_rgba8(colour),
+        }
+    }
+
+    /// The bytes for a colour straight from a colorizer.
+    pub fn apply(&self, colour: [f32; 4]) -> [u8; 4] {
+        self.encode(self.saturate(colour))
+    }
+}
+
+/// [Soft Saturate]
+/// Leaves values up to the knee alone and eases those above it toward
+/// 1 (as Reinhard's operator does), so that bright channels keep some
+/// of their differences instead of all clipping to the same value.
+/// Negative values clip to 0.
+pub fn soft_saturate(x: f32) -> f32 {
+    if x <= KNEE {
+        return x.max(0.0);
+    }
+
+    let over = (x - KNEE) / (1.0 - KNEE);
+    KNEE + (1.0 - KNEE) * over / (1.0 + over)
+}
+
+/// [sRGB Encode]
+/// The sRGB transfer function, from linear light in 0..=1 to the
+/// encoded value.
+pub fn srgb_encode(linear: f32) -> f32 {
+    if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// [To RGBA8]
+/// Quantises a colour to bytes, clipping channels outside 0..=1.
+pub fn to_rgba8(colour: [f32; 4]) -> [u8; 4] {
+    colour.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+/// [Colourise]
+/// Colours a whole buffer of iteration counts into packed RGBA bytes,
+/// the form every exporter writes out.
+pub fn colourise<C: Colorizer>(colorizer: &C, vals: &[u32], limit: u32, tone: Tone) -> Vec<u8> {
+    colourise_shaded(colorizer, vals, limit, tone, None)
+}
+
+/// [Colourise Shaded]
+/// The same, each colour scaled in linear light by its pixel's shade,
+/// if there are shades, before the tone.
+pub fn colourise_shaded<C: Colorizer>(colorizer: &C, vals: &[u32], limit: u32, tone: Tone, shades: Option<&[f32]>) -> Vec<u8> {
+    vals.iter()
+        .enumerate()
+        .flat_map(|(i, &count)| tone.apply(shaded(colorizer.color(IterResult { count, limit }), shades, i)))
+        .collect()
+}
+
+/// A colour scaled by the shade of pixel `i`, if there are shades.
+pub fn shaded(colour: [f32; 4], shades: Option<&[f32]>, i: usize) -> [f32; 4] {
+    let [r, g, b, a] = colour;
+    match shades.and_then(|shades| shades.get(i)) {
+        Some(&shade) => [r * shade, g * shade, b * shade, a],
+        None => colour,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The colouring as it was written inline in render(), before the
+    /// Colorizer trait existed.
+    #[allow(clippy::unnecessary_cast)]
+    fn inline_colour(val: i16, scalar: f32) -> [f32; 4] {
+        const ITERATIONS: i16 = 1200;
+        let black: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+        let colour_mod;
+
+        if val == ITERATIONS {
+            black
+        } else {
+            if scalar > 0.05 {
+                colour_mod = val as f32 / (100 as f32) as f32 * scalar;
+            } else {
+                colour_mod = val as f32 / (100 as f64) as f32 * 0.05;
+            }
+
+            [colour_mod * 2.4, colour_mod * 2.0, colour_mod * 3.0, 1.0]
+        }
+    }
+
+    #[test]
+    fn legacy_matches_inline_colouring() {
+        let scalars = [2.0, 1.37, 0.23, 0.051, 0.05, 0.049, 0.01, 0.0005, 0.0, -0.2];
+
+        for scalar in scalars {
+            let colorizer = LegacyColorizer { scalar, gradient: Gradient::Blue };
+
+            for count in 0..=1200 {
+                let value = IterResult { count: count as u32, limit: 1200 };
+                assert_eq!(colorizer.color(value), inline_colour(count, scalar), "count={count} scalar={scalar}");
+            }
+        }
+    }
+
+    #[test]
+    fn fade_slows_at_each_threshold() {
+        let mut fade = ScalarFade { scalar: 2.0, step_factor: 0.01 };
+        let mut steps = Vec::new();
+
+        while fade.scalar > 0.0 {
+            fade.advance();
+
+            if steps.last() != Some(&fade.step_factor) {
+                steps.push(fade.step_factor);
+            }
+        }
+
+        assert_eq!(steps, [0.01, 0.001, 0.0001, 0.00001, 0.000001]);
+    }
+
+    #[test]
+    fn rgba8_clips_and_rounds() {
+        assert_eq!(to_rgba8([0.0, 0.5, 1.0, 1.0]), [0, 128, 255, 255]);
+        assert_eq!(to_rgba8([-1.0, 2.4, 0.1, 1.0]), [0, 255, 26, 255]);
+    }
+
+    #[test]
+    fn soft_saturation_is_smooth_and_bounded() {
+        assert_eq!(soft_saturate(0.5), 0.5);
+        assert_eq!(soft_saturate(-0.3), 0.0);
+
+        let bright: Vec<f32> = [0.9, 1.2, 2.0, 3.0, 100.0].into_iter().map(soft_saturate).collect();
+        assert!(bright.windows(2).all(|pair| pair[0] < pair[1] || pair[1] == 1.0), "{bright:?}");
+        assert!(bright.iter().all(|&x| x > KNEE && x <= 1.0), "{bright:?}");
+        // Unlike clipping, 1.2 and 2.0 still differ.
+        assert!(bright[2] - bright[1] > 0.02);
+    }
+
+    #[test]
+    fn srgb_encoding() {
+        assert_eq!(Tone::Srgb.apply([0.0, 1000.0, 0.0, 1.0]), [0, 255, 0, 255]);
+        // Linear mid-grey encodes to about 188, the familiar value.
+        assert_eq!(Tone::Srgb.apply([0.5, 0.5, 0.5, 0.5]), [188, 188, 188, 128]);
+        assert!((srgb_encode(0.002) - 0.002 * 12.92).abs() < 1e-7);
+    }
+
+    #[test]
+    fn legacy_tone_is_the_original_clipping() {
+        for colour in [[0.0, 0.5, 1.0, 1.0], [-1.0, 2.4, 0.1, 1.0], [0.3, 0.3, 0.3, 1.0]] {
+            assert_eq!(Tone::Legacy.apply(colour), to_rgba8(colour));
+        }
+    }
+
+    #[test]
+    fn interior_is_black() {
+        for gradient in Gradient::ALL {
+            let colorizer = LegacyColorizer { scalar: 2.0, gradient };
+
+            assert_eq!(colorizer.color(IterResult { count: 500, limit: 500 }), [0.0, 0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn gradients_cycle_through_every_tint() {
+        let mut gradient = Gradient::default();
+        let mut seen = Vec::new();
+
+        for _ in Gradient::ALL {
+            seen.push(gradient.weights());
+            gradient = gradient.next();
+        }
+
+        assert_eq!(gradient, Gradient::default());
+        assert!(seen.iter().enumerate().all(|(i, w)| seen[..i].iter().all(|other| other != w)), "{seen:?}");
+    }
+}
@@ -0,0 +1,98 @@
+//! [Crossfade]
+//!
+//! At deep zooms each frame takes long enough that the cut from one to
+//! the next jars. With the crossfade on, the frame on screen when a new
+//! one arrives is kept, and until the next is expected (as long after
+//! as this one took to follow the last) renders blend from it into the
+//! new one. Frames arriving more than a few times a second would only
+//! blur, so there is no fade between them.
+
+use std::time::{Duration, Instant};
+
+/// The shortest time between frames that they fade into each other over.
+pub const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// [Crossfade]
+///
+/// Fields:
+/// [enabled] Whether frames fade into each other;
+/// [previous] The frame as shown when the latest arrived, while enabled;
+/// [arrived] When the latest frame arrived;
+/// [interval] How long it took to follow the one before.
+#[derive(Clone, Debug, Default)]
+pub struct Crossfade {
+    pub enabled: bool,
+    previous: Vec<u8>,
+    arrived: Option<Instant>,
+    interval: Duration,
+}
+
+impl Crossfade {
+    /// [Arrive]
+    /// Notes a new frame arriving at `now`, with `shown` on screen.
+    pub fn arrive(&mut self, now: Instant, shown: &[u8]) {
+        self.interval = self.arrived.map_or(Duration::ZERO, |at| now.saturating_duration_since(at));
+        self.arrived = Some(now);
+
+        self.previous.clear();
+        if self.enabled {
+            self.previous.extend_from_slice(shown);
+        }
+    }
+
+    /// [Progress]
+    /// How far through the fade `now` is, from 0 at the previous frame
+    /// to 1 at the new one, or None when there is no fade under way.
+    pub fn progress(&self, now: Instant) -> Option<f32> {
+        let arrived = self.arrived.filter(|_| self.enabled && self.interval >= MIN_INTERVAL)?;
+        let t = now.saturating_duration_since(arrived).as_secs_f32() / self.interval.as_secs_f32();
+
+        (t < 1.0).then_some(t)
+    }
+
+    /// [Blend]
+    /// Blends the new frame's packed RGBA bytes from the previous frame
+    /// as far as the fade has got at `now`, if it is under way and the
+    /// frames are the same size.
+    pub fn blend(&self, rgba: &mut [u8], now: Instant) {
+        let Some(t) = self.progress(now).filter(|_| self.previous.len() == rgba.len()) else { return };
+
+        for (new, &old) in rgba.iter_mut().zip(&self.previous) {
+            *new = (old as f32 + (*new as f32 - old as f32) * t).round() as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slow_frames_fade_over_the_interval() {
+        let start = Instant::now();
+        let mut fade = Crossfade { enabled: true, ..Crossfade::default() };
+        fade.arrive(start, &[]);
+        fade.arrive(start + Duration::from_secs(2), &[0, 100, 200, 255]);
+
+        let mut rgba = [200, 100, 0, 255];
+        fade.blend(&mut rgba, start + Duration::from_millis(2500));
+        assert_eq!(rgba, [50, 100, 150, 255]);
+
+        let mut done = [200, 100, 0, 255];
+        fade.blend(&mut done, start + Duration::from_secs(4));
+        assert_eq!(done, [200, 100, 0, 255]);
+    }
+
+    #[test]
+    fn fast_frames_cut() {
+        let start = Instant::now();
+        let mut fade = Crossfade { enabled: true, ..Crossfade::default() };
+        fade.arrive(start, &[]);
+        fade.arrive(start + Duration::from_millis(100), &[0; 4]);
+
+        assert_eq!(fade.progress(start + Duration::from_millis(110)), None);
+        fade.enabled = false;
+        fade.arrive(start + Duration::from_secs(2), &[0; 4]);
+        assert_eq!(fade.progress(start + Duration::from_millis(2100)), None);
+    }
+}
@@ -0,0 +1,81 @@
+//! [Crosshair]
+//!
+//! A marker on the point the zoom is converging on, toggled with C,
+//! with the point's coordinates printed beside it.
+
+use num::complex::Complex as cmp;
+
+use crate::overlay::{text_box_size, Overlay};
+use crate::viewport::Viewport;
+
+const COLOUR: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+
+/// How far the readout sits from the centre of the crosshair.
+const OFFSET: f64 = 6.0;
+
+/// [Crosshair]
+///
+/// Fields:
+/// [visible] Whether the crosshair is shown.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Crosshair {
+    pub visible: bool,
+}
+
+impl Crosshair {
+    /// [Draw]
+    /// Adds the crosshair at the target's pixel, if it is visible and
+    /// the target is in view. The readout sits below and to the right of
+    /// it, moved to the other side where it would leave the frame.
+    pub fn draw(&self, overlay: &mut Overlay, viewport: &Viewport, target: cmp<f64>) {
+        if !self.visible {
+            return;
+        }
+
+        let [x, y] = viewport.complex_to_pixel(target);
+        let (width, height) = (viewport.width_px() as f64, viewport.height_px() as f64);
+        if !(0.0..=width).contains(&x) || !(0.0..=height).contains(&y) {
+            return;
+        }
+
+        overlay.crosshair([x, y], 8.0, 1.0, COLOUR);
+
+        let lines = [format!("re {:+.15}", target.re), format!("im {:+.15}", target.im)];
+        let [box_width, box_height] = text_box_size(&lines, 9.0);
+        let left = if x + OFFSET + box_width <= width { x + OFFSET } else { x - OFFSET - box_width };
+        let top = if y + OFFSET + box_height <= height { y + OFFSET } else { y - OFFSET - box_height };
+        overlay.text_box(&lines, [left, top], 9.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::Shape;
+
+    #[test]
+    fn marks_the_target_pixel() {
+        let viewport = Viewport::new(cmp::new(-0.5, 0.0), 4.0, 400, 200);
+        let target = viewport.pixel_to_complex(300.0, 50.0);
+        let mut overlay = Overlay::new();
+
+        Crosshair { visible: true }.draw(&mut overlay, &viewport, target);
+
+        let Shape::Line { from, to, .. } = &overlay.shapes()[0] else { panic!("{:?}", overlay.shapes()) };
+        assert_eq!([(from[0] + to[0]) / 2.0, from[1]], [300.0, 50.0]);
+
+        // The readout would run off the right edge, so it sits to the left.
+        let Shape::Rect { rect, .. } = &overlay.shapes()[2] else { panic!("{:?}", overlay.shapes()) };
+        assert!(rect[0] + rect[2] <= 300.0);
+    }
+
+    #[test]
+    fn skips_targets_out_of_view() {
+        let viewport = Viewport::new(cmp::new(-0.5, 0.0), 4.0, 400, 200);
+        let mut overlay = Overlay::new();
+
+        Crosshair { visible: true }.draw(&mut overlay, &viewport, cmp::new(5.0, 0.0));
+
+        assert!(overlay.is_empty());
+    }
+}
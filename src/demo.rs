@@ -0,0 +1,108 @@
+//! [Demo]
+//!
+//! The `--demo` mode: the original zoom into the magic point, with the
+//! built-in settings and nothing read from a file, for a fixed time, at
+//! the end of which it prints a line of how it went and exits. Input is
+//! ignored but for Esc, which closes the window as ever and so ends the
+//! demo early, summary and all. As no bindings, bookmarks,
+//! logs or caches are read, and the search for targets is seeded as the
+//! canned bench is, a fresh checkout runs the same demo as any other, so
+//! that the summaries of different machines can be compared.
+
+use std::time::{Duration, Instant};
+
+use crate::backend::{Event, Key};
+use crate::hud::{magnification, millis};
+
+/// How long the demo runs for, if not told.
+pub const DURATION: Duration = Duration::from_secs(60);
+
+/// [Demo]
+///
+/// Fields:
+/// [duration] How long the demo runs for;
+/// [started] When it started, from the first update;
+/// [times] How long the escape-time pass took for each frame computed.
+#[derive(Clone, Debug)]
+pub struct Demo {
+    duration: Duration,
+    started: Option<Instant>,
+    times: Vec<Duration>,
+}
+
+impl Demo {
+    pub fn new(duration: Duration) -> Demo {
+        Demo { duration, started: None, times: Vec::new() }
+    }
+
+    /// [Due]
+    /// Whether the demo has run its length, starting its clock the first
+    /// time it is asked.
+    pub fn due(&mut self, now: Instant) -> bool {
+        now - *self.started.get_or_insert(now) >= self.duration
+    }
+
+    /// Notes how long a frame took to compute.
+    pub fn record(&mut self, elapsed: Duration) {
+        self.times.push(elapsed);
+    }
+
+    /// [Ignores]
+    /// Whether the demo keeps an event from the app: any key but Esc,
+    /// typing, click or move of the mouse.
+    pub fn ignores(&self, event: &Event) -> bool {
+        match event {
+            Event::Press(key) => *key != Key::Escape,
+            Event::Text(_) | Event::Paste | Event::Click | Event::CtrlClick | Event::Release | Event::Cursor(_) => true,
+            Event::Update | Event::Render | Event::Resize(_) | Event::Scale(_) => false,
+        }
+    }
+
+    /// [Summary]
+    /// The line printed at the end: how many frames were computed, their
+    /// average and 95th-percentile compute times, and the magnification
+    /// reached.
+    pub fn summary(&self, magnified: f64) -> String {
+        let mut times = self.times.clone();
+        times.sort_unstable();
+        let average = match times.len() {
+            0 => Duration::ZERO,
+            n => times.iter().sum::<Duration>() / n as u32,
+        };
+        // The nearest-rank percentile, the time that 95% of frames took at most.
+        let p95 = times.get((times.len() * 95).div_ceil(100).saturating_sub(1)).copied().unwrap_or_default();
+        format!(
+            "demo: {} frames, {:.1} ms average, {:.1} ms 95th percentile, {} magnification",
+            times.len(), millis(average), millis(p95), magnification(magnified)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarises_the_frames_computed() {
+        let mut demo = Demo::new(DURATION);
+        for ms in (1..=20).rev() {
+            demo.record(Duration::from_millis(ms));
+        }
+        assert_eq!(demo.summary(1500.0), "demo: 20 frames, 10.5 ms average, 19.0 ms 95th percentile, 1500.0x magnification");
+        assert_eq!(Demo::new(DURATION).summary(1.0), "demo: 0 frames, 0.0 ms average, 0.0 ms 95th percentile, 1.0x magnification");
+    }
+
+    #[test]
+    fn runs_its_length_ignoring_input() {
+        let mut demo = Demo::new(Duration::from_secs(5));
+        let now = Instant::now();
+        assert!(!demo.due(now));
+        assert!(!demo.due(now + Duration::from_secs(4)));
+        assert!(demo.due(now + Duration::from_secs(5)));
+
+        assert!(demo.ignores(&Event::Press(Key::Char('q'))));
+        assert!(!demo.ignores(&Event::Press(Key::Escape)));
+        assert!(demo.ignores(&Event::Cursor([1.0, 2.0])));
+        assert!(!demo.ignores(&Event::Update));
+    }
+}
@@ -0,0 +1,168 @@
+//! [Diff]
+//!
+//! The debug view for checking shortcuts in the render against the
+//! plain escape-time loop, toggled with E. Each computed frame is
+//! rendered a second time, brute force, into a buffer of its own, and
+//! the frame is shown as a heatmap of how far the two counts differ at
+//! each pixel: black where they agree, through red and yellow to white
+//! at the largest difference. How many pixels differ, and by how much
+//! at most, is printed whenever that changes. The second render runs on
+//! the calling thread and is not timed, so it costs a great deal but
+//! leaves the compute times alone.
+
+use crate::fractal::Escaping;
+use crate::viewport::Viewport;
+
+/// [Reference]
+/// The counts of a frame computed brute force, on the calling thread.
+pub fn reference(formula: Escaping, viewport: &Viewport, limit: u32) -> Vec<u32> {
+    let mut vals = vec![0; viewport.width_px() * viewport.height_px()];
+    formula.compute_sequential(&mut vals, viewport.width_px(), |a, b| {
+        viewport.sample(a as f64, b as f64)
+    }, limit);
+
+    vals
+}
+
+/// [Frame Diff]
+/// How one frame's counts differ from another's.
+///
+/// Fields:
+/// [differences] |difference| at each pixel, row by row;
+/// [differing] How many pixels differ at all;
+/// [max] The largest difference.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameDiff {
+    differences: Vec<u32>,
+    pub differing: usize,
+    pub max: u32,
+}
+
+impl FrameDiff {
+    /// [Of]
+    /// The differences between a frame and its reference, which must be
+    /// the same size.
+    pub fn of(vals: &[u32], reference: &[u32]) -> FrameDiff {
+        let differences: Vec<u32> = vals.iter().zip(reference).map(|(&a, &b)| a.abs_diff(b)).collect();
+        let differing = differences.iter().filter(|&&d| d > 0).count();
+        let max = differences.iter().copied().max().unwrap_or(0);
+
+        FrameDiff { differences, differing, max }
+    }
+
+    pub fn len(&self) -> usize {
+        self.differences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// [Heatmap]
+    /// The differences as RGBA bytes, on a log scale up to the largest
+    /// so that lone small differences still show.
+    pub fn heatmap(&self) -> Vec<u8> {
+        let top = (self.max as f64).ln_1p();
+
+        self.differences.iter()
+            .flat_map(|&d| {
+                let t = if d == 0 { 0.0 } else { (d as f64).ln_1p() / top };
+                let channel = |from: f64| ((3.0 * t - from).clamp(0.0, 1.0) * 255.0).round() as u8;
+                [channel(0.0), channel(1.0), channel(2.0), 255]
+            })
+            .collect()
+    }
+
+    /// A line summing the differences up, for printing.
+    pub fn summary(&self) -> String {
+        format!("diff: {} of {} pixels differ, by at most {}", self.differing, self.len(), self.max)
+    }
+}
+
+/// [Diff View]
+///
+/// Fields:
+/// [enabled] Whether frames are rendered again and compared;
+/// [last] The comparison for the frame last computed, once there is one.
+#[derive(Clone, Debug, Default)]
+pub struct DiffView {
+    pub enabled: bool,
+    last: Option<FrameDiff>,
+}
+
+impl DiffView {
+    /// [Toggle]
+    /// Starts or stops comparing frames. Starting takes effect from the
+    /// next computed frame.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.last = None;
+    }
+
+    /// [Compare]
+    /// Renders the frame just computed for the viewport again and
+    /// compares the two, printing the outcome if it is not what it was
+    /// last frame. Does nothing while disabled.
+    pub fn compare(&mut self, formula: Escaping, viewport: &Viewport, vals: &[u32], limit: u32) {
+        if !self.enabled {
+            return;
+        }
+
+        let diff = FrameDiff::of(vals, &reference(formula, viewport, limit));
+        if self.last.as_ref().is_none_or(|last| (last.differing, last.max) != (diff.differing, diff.max)) {
+            println!("{}", diff.summary());
+        }
+        self.last = Some(diff);
+    }
+
+    /// The heatmap to show in place of a frame of `pixels` pixels, if
+    /// there is a comparison of that size.
+    pub fn heatmap(&self, pixels: usize) -> Option<Vec<u8>> {
+        self.last.as_ref().filter(|diff| diff.len() == pixels).map(FrameDiff::heatmap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::complex::Complex as cmp;
+
+    use super::*;
+    use crate::fractal::Formula;
+
+    #[test]
+    fn matching_frames_are_black() {
+        let diff = FrameDiff::of(&[3, 5, 1200], &[3, 5, 1200]);
+
+        assert_eq!((diff.differing, diff.max), (0, 0));
+        assert!(diff.heatmap().chunks(4).all(|pixel| pixel == [0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn differences_are_counted_and_scaled() {
+        let diff = FrameDiff::of(&[3, 9, 1200, 40], &[3, 5, 1000, 40]);
+
+        assert_eq!((diff.differing, diff.max), (2, 200));
+        let heatmap = diff.heatmap();
+        assert_eq!(heatmap[..4], [0, 0, 0, 255]);
+        assert_eq!(heatmap[8..12], [255, 255, 255, 255]);
+        // The small difference shows, but dimmer.
+        assert!(heatmap[4] > 0 && heatmap[4..8] < heatmap[8..12]);
+        assert_eq!(diff.summary(), "diff: 2 of 4 pixels differ, by at most 200");
+    }
+
+    #[test]
+    fn the_parallel_kernel_matches_its_reference() {
+        let viewport = Viewport::new(cmp::new(-0.75, 0.1), 2.5, 60, 40);
+        let mut vals = vec![0; 60 * 40];
+        Formula::Mandelbrot.compute_parallel(&mut vals, 60, |a, b| viewport.pixel_to_complex(a as f64, b as f64), 300);
+
+        let mut view = DiffView::default();
+        assert_eq!(view.heatmap(vals.len()), None);
+        view.toggle();
+        view.compare(Escaping::default(), &viewport, &vals, 300);
+
+        assert_eq!(view.last.as_ref().map(|diff| diff.differing), Some(0));
+        assert_eq!(view.heatmap(vals.len()).map(|heatmap| heatmap.len()), Some(vals.len() * 4));
+        assert_eq!(view.heatmap(10), None);
+    }
+}
@@ -0,0 +1,87 @@
+//! [Error]
+//!
+//! The application's error type. Fatal errors are returned from main,
+//! which prints them; errors during the run (an export failing to
+//! write, say) are reported and the zoom carries on.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::settings::ConfigError;
+
+/// [App Error]
+/// Everything that can go wrong outside the compute core.
+pub enum AppError {
+    /// The command line could not be understood.
+    Args(String),
+    /// The settings failed validation.
+    Config(ConfigError),
+    /// The window or its graphics context could not be created.
+    Window { api: String, reason: String },
+    /// A backend other than Piston failed to start or to draw.
+    Backend { name: &'static str, reason: String },
+    /// An image, or a log, could not be written.
+    Export { path: PathBuf, reason: String },
+    /// The bookmarks file could not be read or added to.
+    Bookmarks { path: PathBuf, reason: String },
+    /// The bindings file could not be read, or binds two actions to a key.
+    Bindings { path: PathBuf, reason: String },
+    /// The handlers for Unix signals could not be installed.
+    Signals(String),
+    /// The zoom-path log could not be resumed from.
+    Resume { path: PathBuf, reason: String },
+    /// The list of views to render could not be read.
+    Batch { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Args(message) => write!(f, "{message} (see --help)"),
+            AppError::Config(e) => write!(f, "invalid settings: {e}"),
+            AppError::Window { api, reason } => {
+                write!(f, "failed to create OpenGL {api} window: {reason}")?;
+                if api != "2.1" {
+                    write!(f, "; try --gl 2.1")?;
+                }
+                Ok(())
+            }
+            AppError::Backend { name, reason } => write!(f, "{name} backend failed: {reason}"),
+            AppError::Export { path, reason } => write!(f, "failed to write {}: {reason}", path.display()),
+            AppError::Bookmarks { path, reason } => write!(f, "bookmarks {}: {reason}", path.display()),
+            AppError::Bindings { path, reason } => write!(f, "bindings {}: {reason}", path.display()),
+            AppError::Signals(reason) => write!(f, "failed to handle signals: {reason}"),
+            AppError::Resume { path, reason } => write!(f, "cannot resume from {}: {reason}", path.display()),
+            AppError::Batch { path, reason } => write!(f, "cannot render the list {}: {reason}", path.display()),
+        }
+    }
+}
+
+// Returning an error from main prints it with Debug, so Debug gives the
+// same readable message as Display.
+impl fmt::Debug for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<ConfigError> for AppError {
+    fn from(e: ConfigError) -> AppError {
+        AppError::Config(e)
+    }
+}
+
+/// [Report]
+/// Prints a non-fatal error and carries on, so that a failed export
+/// never costs the zoom position.
+pub fn report<T>(result: Result<T, AppError>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            eprintln!("error: {e}");
+            None
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! [Explore]
+//!
+//! Finding new places to zoom into. Random points of a view are sampled
+//! in batches, and the first batch to contain a point whose count falls
+//! just below the iteration limit supplies the new target: such points
+//! sit close to the boundary of the set, where the structure is. Only
+//! the sampling is random, so a seeded generator repeats its finds.
+//!
+//! Searching takes threads and a clock, so it is not built for wasm32.
+
+use std::time::{Duration, Instant};
+
+use num::complex::Complex as cmp;
+use rand::Rng;
+
+use crate::fractal::Formula;
+use crate::viewport::Viewport;
+
+/// How many points are sampled at a time. Batches are laid out as rows
+/// of `ROW` points for the parallel kernel.
+const BATCH: usize = 4096;
+const ROW: usize = 64;
+
+/// How long a search may go on before giving up.
+pub const BUDGET: Duration = Duration::from_millis(300);
+
+/// [Find]
+///
+/// Fields:
+/// [point] The point found;
+/// [count] Its iteration count.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Find {
+    pub point: cmp<f64>,
+    pub count: u32,
+}
+
+/// [In Band]
+/// Whether a count falls in the band just below the limit: in the top
+/// half of the counts, without reaching the limit.
+pub fn in_band(count: u32, limit: u32) -> bool {
+    count < limit && count >= limit / 2
+}
+
+/// [Search]
+/// Samples random points of the view until a batch turns up any in the
+/// band, returning the highest-counting of them, or None if `budget`
+/// runs out first. Each batch's points are drawn before any are
+/// iterated, so the result depends only on the generator's state.
+/// Points are drawn from the view's plane, and iterated under its
+/// projection.
+pub fn search<R: Rng>(formula: Formula, view: &Viewport, limit: u32, rng: &mut R, budget: Duration) -> Option<Find> {
+    let started = Instant::now();
+    let (width, height) = (view.width_px() as f64, view.height_px() as f64);
+    let mut vals = vec![0; BATCH];
+
+    while started.elapsed() < budget {
+        let points: Vec<cmp<f64>> = (0..BATCH)
+            .map(|_| view.pixel_to_complex(rng.gen::<f64>() * width, rng.gen::<f64>() * height))
+            .collect();
+
+        formula.compute_parallel(&mut vals, ROW, |a, b| view.projection().apply(points[b * ROW + a]), limit);
+
+        let best = points.iter().zip(&vals)
+            .filter(|&(_, &count)| in_band(count, limit))
+            .max_by_key(|&(_, &count)| count);
+        if let Some((&point, &count)) = best {
+            return Some(Find { point, count });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::kernel::escape_time;
+    use crate::fractal::Mandelbrot;
+
+    fn whole_set() -> Viewport {
+        Viewport::new(cmp::new(-0.75, 0.0), 3.0, 300, 200)
+    }
+
+    #[test]
+    fn finds_points_near_the_boundary() {
+        let find = search(Formula::Mandelbrot, &whole_set(), 200, &mut StdRng::seed_from_u64(7), Duration::from_secs(5)).unwrap();
+
+        assert!(in_band(find.count, 200), "{find:?}");
+        assert_eq!(escape_time(&Mandelbrot, find.point, 200), find.count);
+    }
+
+    #[test]
+    fn seeds_repeat_their_finds() {
+        let find = |seed| search(Formula::Mandelbrot, &whole_set(), 200, &mut StdRng::seed_from_u64(seed), Duration::from_secs(5));
+
+        assert_eq!(find(42), find(42));
+        assert_ne!(find(42), find(43));
+    }
+
+    #[test]
+    fn gives_up_on_views_without_a_boundary() {
+        // Everything here escapes at once.
+        let view = Viewport::new(cmp::new(10.0, 10.0), 1.0, 100, 100);
+
+        assert_eq!(search(Formula::Mandelbrot, &view, 200, &mut StdRng::seed_from_u64(7), Duration::from_millis(20)), None);
+    }
+}
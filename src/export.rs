@@ -0,0 +1,18 @@
+//! [Export]
+//!
+//! Writing rendered frames out as images.
+
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// [Save PNG]
+/// Writes packed RGBA bytes, `width` pixels per row, to a PNG file.
+pub fn save_png(path: &Path, width: usize, height: usize, rgba: &[u8]) -> Result<(), AppError> {
+    let error = |reason: String| AppError::Export { path: path.to_path_buf(), reason };
+
+    let image = image::RgbaImage::from_raw(width as u32, height as u32, rgba.to_vec())
+        .ok_or_else(|| error(format!("{} bytes is not a {width}x{height} RGBA image", rgba.len())))?;
+
+    image.save(path).map_err(|e| error(e.to_string()))
+}
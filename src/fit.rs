@@ -0,0 +1,125 @@
+//! [Fit]
+//!
+//! How a frame is fitted to a window of a different shape, such as one
+//! the user or the window manager has resized. Either way the image is
+//! never stretched. Backends always letterbox: the frame is scaled by
+//! the same amount both ways, centred, and the rest of the window is
+//! cleared to black, with pointer positions mapped back through the
+//! same offset and scale. With `--fit extend` the application reshapes
+//! the frame to the window instead, keeping the size of a pixel on the
+//! complex plane, so the view's bounds grow or shrink to cover it and
+//! there are no bars to draw.
+
+/// [Fit]
+///
+/// Variants:
+/// [Letterbox] The view keeps its bounds, with black bars around it;
+/// [Extend] The view's bounds change with the window's shape.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fit {
+    #[default]
+    Letterbox,
+    Extend,
+}
+
+impl Fit {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Fit::Letterbox => "letterbox",
+            Fit::Extend => "extend",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Fit> {
+        match name {
+            "letterbox" => Some(Fit::Letterbox),
+            "extend" => Some(Fit::Extend),
+            _ => None,
+        }
+    }
+}
+
+/// [Placement]
+/// Where a frame sits in a window: frame pixel (x, y) lands on window
+/// point offset + scale * (x, y).
+///
+/// Fields:
+/// [offset] The window point the frame's top-left corner lands on;
+/// [scale] Window pixels per frame pixel, the same both ways.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Placement {
+    pub offset: [f64; 2],
+    pub scale: f64,
+}
+
+impl Placement {
+    /// [Letterbox]
+    /// The largest placement of a frame that fits in the window, centred
+    /// between bars on either the sides or the top and bottom.
+    pub fn letterbox(frame: [usize; 2], window: [f64; 2]) -> Placement {
+        let [width, height] = frame.map(|side| side.max(1) as f64);
+        let [window_width, window_height] = window;
+        let scale = (window_width / width).min(window_height / height).max(0.0);
+
+        Placement {
+            offset: [(window_width - width * scale) / 2.0, (window_height - height * scale) / 2.0],
+            scale,
+        }
+    }
+
+    /// [To Window]
+    /// Where a point of the frame, in frame pixels, lands in the window.
+    pub fn to_window(&self, at: [f64; 2]) -> [f64; 2] {
+        [self.offset[0] + at[0] * self.scale, self.offset[1] + at[1] * self.scale]
+    }
+
+    /// [To Frame]
+    /// The frame point under a window point, or None if it is on a bar.
+    pub fn to_frame(&self, at: [f64; 2], frame: [usize; 2]) -> Option<[f64; 2]> {
+        if self.scale <= 0.0 {
+            return None;
+        }
+
+        let [x, y] = [(at[0] - self.offset[0]) / self.scale, (at[1] - self.offset[1]) / self.scale];
+        let inside = (0.0..frame[0] as f64).contains(&x) && (0.0..frame[1] as f64).contains(&y);
+        inside.then_some([x, y])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_windows_get_pillarboxed() {
+        let placement = Placement::letterbox([400, 200], [1000.0, 400.0]);
+
+        assert_eq!(placement, Placement { offset: [100.0, 0.0], scale: 2.0 });
+        assert_eq!(placement.to_window([400.0, 200.0]), [900.0, 400.0]);
+    }
+
+    #[test]
+    fn tall_windows_get_letterboxed() {
+        let placement = Placement::letterbox([400, 200], [400.0, 400.0]);
+
+        assert_eq!(placement, Placement { offset: [0.0, 100.0], scale: 1.0 });
+    }
+
+    #[test]
+    fn pointer_positions_map_back_past_the_bars() {
+        let placement = Placement::letterbox([400, 200], [1000.0, 400.0]);
+
+        assert_eq!(placement.to_frame([300.0, 100.0], [400, 200]), Some([100.0, 50.0]));
+        assert_eq!(placement.to_frame([50.0, 100.0], [400, 200]), None);
+        assert_eq!(placement.to_frame([950.0, 100.0], [400, 200]), None);
+        assert_eq!(Placement::letterbox([400, 200], [0.0, 0.0]).to_frame([0.0, 0.0], [400, 200]), None);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for fit in [Fit::Letterbox, Fit::Extend] {
+            assert_eq!(Fit::parse(fit.name()), Some(fit));
+        }
+        assert_eq!(Fit::parse("stretch"), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! [Follow]
+//!
+//! A zoom that walks along the boundary of the set, toggled with W.
+//! Each frame the target is nudged toward the nearest of the pixels
+//! around it where the counts change the most, which is where the
+//! boundary runs, so the dive meanders along it instead of settling
+//! into flat interior or exterior. Steps are a share of the view's
+//! width, so the walk slows as the view closes in rather than jittering.
+//! Clicking to retarget takes over from the walk for a moment.
+
+use num::complex::Complex as cmp;
+
+use crate::viewport::Viewport;
+
+/// The furthest the target moves in one frame, as a share of the
+/// view's width.
+const STEP: f64 = 0.002;
+
+/// How far around the target to look, as a share of the frame's
+/// shorter side.
+const REACH: f64 = 0.125;
+
+/// How many frames a click holds the walk off for.
+const HOLD: u64 = 120;
+
+/// [Follower]
+///
+/// Fields:
+/// [enabled] Whether the target walks along the boundary;
+/// [held] How many more frames to leave the target alone for.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Follower {
+    pub enabled: bool,
+    held: u64,
+}
+
+impl Follower {
+    /// [Hold]
+    /// Leaves the target where it has been put for a while, so that a
+    /// manual retarget is not walked straight away from.
+    pub fn hold(&mut self) {
+        self.held = HOLD;
+    }
+
+    /// [Steer]
+    /// The target for the next frame: `target` moved at most STEP of the
+    /// view's width toward the nearest pixel of the steepest gradient in
+    /// vals around it. The target is left alone while the follower is
+    /// disabled or held, or there is no gradient to follow.
+    pub fn steer(&mut self, vals: &[u32], viewport: &Viewport, target: cmp<f64>) -> cmp<f64> {
+        if !self.enabled {
+            return target;
+        }
+        if self.held > 0 {
+            self.held -= 1;
+            return target;
+        }
+
+        let Some([x, y]) = steepest_nearby(vals, viewport, viewport.complex_to_pixel(target)) else { return target };
+        let offset = viewport.pixel_to_complex(x, y) - target;
+        let step = STEP * viewport.width();
+        if offset.norm() <= step {
+            target + offset
+        } else {
+            target + offset * (step / offset.norm())
+        }
+    }
+}
+
+/// How much the count changes from a pixel to its right and lower
+/// neighbours, which is high along the boundary.
+fn gradient(vals: &[u32], width: usize, a: usize, b: usize) -> u32 {
+    let here = vals[b * width + a];
+    vals[b * width + a + 1].abs_diff(here) + vals[(b + 1) * width + a].abs_diff(here)
+}
+
+/// [Steepest Nearby]
+/// The pixel within REACH of `at` with the steepest gradient, the
+/// nearest to `at` where several are as steep, or None if `at` is off
+/// the frame or the counts around it are flat.
+pub fn steepest_nearby(vals: &[u32], viewport: &Viewport, at: [f64; 2]) -> Option<[f64; 2]> {
+    let (width, height) = (viewport.width_px(), viewport.height_px());
+    let [x, y] = at;
+    if !(x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64) || width < 2 || height < 2 {
+        return None;
+    }
+
+    let reach = (width.min(height) as f64 * REACH).max(1.0);
+    let span = |centre: f64, len: usize| (centre - reach).max(0.0) as usize..((centre + reach) as usize).min(len - 1);
+
+    let mut steepest: Option<([f64; 2], u32, f64)> = None;
+    for b in span(y, height) {
+        for a in span(x, width) {
+            let g = gradient(vals, width, a, b);
+            let distance = (a as f64 - x).hypot(b as f64 - y);
+            let better = steepest.is_none_or(|(_, most, nearest)| g > most || (g == most && distance < nearest));
+            if g > 0 && better {
+                steepest = Some(([a as f64, b as f64], g, distance));
+            }
+        }
+    }
+
+    steepest.map(|(pixel, _, _)| pixel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewport() -> Viewport {
+        Viewport::new(cmp::new(0.0, 0.0), 4.0, 80, 40)
+    }
+
+    /// Interior to the left of column `edge`, fast escapes to the right.
+    fn edge_at(edge: usize) -> Vec<u32> {
+        (0..80 * 40).map(|i| if i % 80 < edge { 100 } else { 1 }).collect()
+    }
+
+    #[test]
+    fn finds_the_nearest_edge() {
+        // The gradient is steepest in the column just before the edge.
+        assert_eq!(steepest_nearby(&edge_at(44), &viewport(), [40.0, 20.0]), Some([43.0, 20.0]));
+        assert_eq!(steepest_nearby(&vec![5; 80 * 40], &viewport(), [40.0, 20.0]), None);
+    }
+
+    #[test]
+    fn steps_shrink_with_the_view() {
+        let mut follower = Follower { enabled: true, ..Follower::default() };
+
+        for width in [4.0, 4e-6] {
+            let viewport = Viewport::new(cmp::new(0.0, 0.0), width, 80, 40);
+            let target = viewport.pixel_to_complex(40.0, 20.0);
+
+            let next = follower.steer(&edge_at(44), &viewport, target);
+
+            assert!(((next - target).norm() - STEP * width).abs() < width * 1e-9, "{width}");
+            assert!(next.re > target.re);
+        }
+    }
+
+    #[test]
+    fn holding_leaves_the_target() {
+        let viewport = viewport();
+        let target = viewport.pixel_to_complex(40.0, 20.0);
+        let mut follower = Follower { enabled: true, ..Follower::default() };
+
+        follower.hold();
+        for _ in 0..HOLD {
+            assert_eq!(follower.steer(&edge_at(44), &viewport, target), target);
+        }
+        assert_ne!(follower.steer(&edge_at(44), &viewport, target), target);
+    }
+}
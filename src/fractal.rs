@@ -0,0 +1,415 @@
+//! [Fractal]
+//!
+//! Escape-time formulas. Each formula describes how the orbit of a
+//! point starts, how it advances one iteration, and when it is
+//! considered to have escaped. The kernels are generic over the
+//! `Fractal` trait, so every formula gets its own monomorphised
+//! loop and no dynamic dispatch happens per iteration. Formulas are
+//! also generic over the scalar type T they iterate in, and can be
+//! dispatched to escape at another radius than their own.
+
+use num::complex::Complex as cmp;
+
+use crate::histogram::Histogram;
+use crate::kernel;
+use crate::progress::Progress;
+use crate::real::Real;
+
+/// The escape radius shared by the built-in formulas.
+pub const ESCAPE_RADIUS: f64 = 2.0;
+
+/// Its square, as orbits are tested against (|z| >= 2).
+const BOUND_SQR: f64 = ESCAPE_RADIUS * ESCAPE_RADIUS;
+
+/// [Fractal]
+/// An escape-time formula, iterated in the scalar type T.
+///
+/// Methods:
+/// [init] The orbit state before the first iteration, for the point c;
+/// [step] Advances the orbit state by one iteration;
+/// [escaped] Whether the orbit has left the escape radius;
+/// [z] Where the orbit state is on the plane.
+pub trait Fractal<T: Real>: Sync {
+    type State: Copy;
+
+    fn init(&self, c: cmp<T>) -> Self::State;
+    fn step(&self, state: &mut Self::State, c: cmp<T>);
+    fn escaped(&self, state: &Self::State) -> bool;
+    fn z(&self, state: &Self::State) -> cmp<T>;
+}
+
+/// [Mandelbrot]
+/// The classic set: z starts at 0 and iterates z^2 + c.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mandelbrot;
+
+impl<T: Real> Fractal<T> for Mandelbrot {
+    type State = cmp<T>;
+
+    #[inline(always)]
+    fn init(&self, _c: cmp<T>) -> cmp<T> {
+        cmp::new(T::zero(), T::zero())
+    }
+
+    #[inline(always)]
+    fn step(&self, z: &mut cmp<T>, c: cmp<T>) {
+        *z = *z * *z + c;
+    }
+
+    #[inline(always)]
+    fn escaped(&self, z: &cmp<T>) -> bool {
+        z.norm_sqr() >= T::from_f64(BOUND_SQR)
+    }
+
+    #[inline(always)]
+    fn z(&self, z: &cmp<T>) -> cmp<T> {
+        *z
+    }
+}
+
+/// [Burning Ship]
+/// Like the Mandelbrot set, but both components of z are folded
+/// to their absolute values before squaring: (|Re z| + i|Im z|)^2 + c.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BurningShip;
+
+impl<T: Real> Fractal<T> for BurningShip {
+    type State = cmp<T>;
+
+    #[inline(always)]
+    fn init(&self, _c: cmp<T>) -> cmp<T> {
+        cmp::new(T::zero(), T::zero())
+    }
+
+    #[inline(always)]
+    fn step(&self, z: &mut cmp<T>, c: cmp<T>) {
+        let folded = cmp::new(z.re.abs(), z.im.abs());
+        *z = folded * folded + c;
+    }
+
+    #[inline(always)]
+    fn escaped(&self, z: &cmp<T>) -> bool {
+        z.norm_sqr() >= T::from_f64(BOUND_SQR)
+    }
+
+    #[inline(always)]
+    fn z(&self, z: &cmp<T>) -> cmp<T> {
+        *z
+    }
+}
+
+/// [Julia]
+/// The Julia set of the quadratic map for a fixed parameter: z starts
+/// at the point being iterated and advances by z^2 + c, with c the
+/// parameter rather than the point.
+///
+/// Fields:
+/// [c] The parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Julia {
+    pub c: cmp<f64>,
+}
+
+impl<T: Real> Fractal<T> for Julia {
+    type State = cmp<T>;
+
+    #[inline(always)]
+    fn init(&self, z: cmp<T>) -> cmp<T> {
+        z
+    }
+
+    #[inline(always)]
+    fn step(&self, z: &mut cmp<T>, _point: cmp<T>) {
+        *z = *z * *z + cmp::new(T::from_f64(self.c.re), T::from_f64(self.c.im));
+    }
+
+    #[inline(always)]
+    fn escaped(&self, z: &cmp<T>) -> bool {
+        z.norm_sqr() >= T::from_f64(BOUND_SQR)
+    }
+
+    #[inline(always)]
+    fn z(&self, z: &cmp<T>) -> cmp<T> {
+        *z
+    }
+}
+
+/// [Bounded]
+/// A formula escaping at another radius than its own: the same orbits,
+/// counted until they leave the larger (or smaller) circle.
+///
+/// Fields:
+/// [fractal] The formula;
+/// [bound_sqr] The squared radius its orbits escape at.
+#[derive(Clone, Copy, Debug)]
+pub struct Bounded<F> {
+    pub fractal: F,
+    pub bound_sqr: f64,
+}
+
+impl<T: Real, F: Fractal<T>> Fractal<T> for Bounded<F> {
+    type State = F::State;
+
+    #[inline(always)]
+    fn init(&self, c: cmp<T>) -> F::State {
+        self.fractal.init(c)
+    }
+
+    #[inline(always)]
+    fn step(&self, state: &mut F::State, c: cmp<T>) {
+        self.fractal.step(state, c);
+    }
+
+    #[inline(always)]
+    fn escaped(&self, state: &F::State) -> bool {
+        self.fractal.z(state).norm_sqr() >= T::from_f64(self.bound_sqr)
+    }
+
+    #[inline(always)]
+    fn z(&self, state: &F::State) -> cmp<T> {
+        self.fractal.z(state)
+    }
+}
+
+/// [Formula]
+/// Runtime selection of the formula being rendered. Each variant
+/// dispatches once per frame to the kernel monomorphised for it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Formula {
+    #[default]
+    Mandelbrot,
+    BurningShip,
+}
+
+impl Formula {
+    /// Every formula, in the order they are cycled through.
+    pub const ALL: [Formula; 2] = [Formula::Mandelbrot, Formula::BurningShip];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Formula::Mandelbrot => "mandelbrot",
+            Formula::BurningShip => "burning-ship",
+        }
+    }
+
+    /// [Overview]
+    /// The centre and width of a 2:1 view taking in the whole set, with
+    /// a margin around it.
+    pub fn overview(&self) -> (cmp<f64>, f64) {
+        match self {
+            Formula::Mandelbrot => (cmp::new(-0.75, 0.0), 5.0),
+            Formula::BurningShip => (cmp::new(-0.5, -0.55), 5.0),
+        }
+    }
+
+    /// The formula after this one, wrapping around.
+    pub fn next(&self) -> Formula {
+        let i = Formula::ALL.iter().position(|f| f == self).unwrap_or(0);
+        Formula::ALL[(i + 1) % Formula::ALL.len()]
+    }
+
+    /// [Escaping]
+    /// The formula with the radius its orbits escape at.
+    pub fn escaping(self, radius: f64) -> Escaping {
+        Escaping { formula: self, radius }
+    }
+
+    /// [Compute Parallel]
+    /// Fills vals using the rayon kernel for this formula, returning the
+    /// histogram of the counts.
+    pub fn compute_parallel<T, M>(&self, vals: &mut [u32], width: usize, map: M, limit: u32) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T> + Sync,
+    {
+        self.escaping(ESCAPE_RADIUS).compute_parallel(vals, width, map, limit)
+    }
+
+    /// [Compute Parallel Counted]
+    /// The same, counting each row on `progress` as it is done, and
+    /// working outward from row `first` if there is one.
+    pub fn compute_parallel_counted<T, M>(&self, vals: &mut [u32], width: usize, map: M, limit: u32, progress: &Progress, first: Option<usize>) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T> + Sync,
+    {
+        self.escaping(ESCAPE_RADIUS).compute_parallel_counted(vals, width, map, limit, progress, first)
+    }
+
+    /// [Compute Heights]
+    /// The same, with each pixel's smooth count into heights.
+    pub fn compute_heights<T, M>(&self, vals: &mut [u32], heights: &mut Vec<f32>, width: usize, map: M, limit: u32) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T> + Sync,
+    {
+        self.escaping(ESCAPE_RADIUS).compute_heights(vals, heights, width, map, limit)
+    }
+
+    /// [Orbit]
+    /// The orbit of the point c under this formula, as the kernel
+    /// iterates it, with whether it escaped.
+    pub fn orbit(&self, c: cmp<f64>, limit: u32) -> (Vec<cmp<f64>>, bool) {
+        self.escaping(ESCAPE_RADIUS).orbit(c, limit)
+    }
+
+    /// [Compute Sequential]
+    /// Fills vals using the single-threaded kernel for this formula,
+    /// returning the histogram of the counts.
+    pub fn compute_sequential<T, M>(&self, vals: &mut [u32], width: usize, map: M, limit: u32) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T>,
+    {
+        self.escaping(ESCAPE_RADIUS).compute_sequential(vals, width, map, limit)
+    }
+}
+
+/// [Escaping]
+/// A formula with the radius its orbits escape at, which the kernels
+/// are dispatched for. At the formulas' own radius of 2 the counts are
+/// theirs; larger radii carry each orbit on a few iterations further,
+/// and smaller ones cut it short.
+///
+/// Fields:
+/// [formula] The formula;
+/// [radius] The escape radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Escaping {
+    pub formula: Formula,
+    pub radius: f64,
+}
+
+impl Default for Escaping {
+    fn default() -> Escaping {
+        Formula::default().escaping(ESCAPE_RADIUS)
+    }
+}
+
+/// Runs `$run` with `$fractal` bound to the formula at the radius.
+macro_rules! dispatch {
+    ($escaping:expr, $fractal:ident => $run:expr) => {{
+        let bound_sqr = $escaping.radius * $escaping.radius;
+        match $escaping.formula {
+            Formula::Mandelbrot => {
+                let $fractal = &Bounded { fractal: Mandelbrot, bound_sqr };
+                $run
+            }
+            Formula::BurningShip => {
+                let $fractal = &Bounded { fractal: BurningShip, bound_sqr };
+                $run
+            }
+        }
+    }};
+}
+
+impl Escaping {
+    /// Whether orbits escape at the formulas' own radius.
+    pub fn is_default(&self) -> bool {
+        self.radius == ESCAPE_RADIUS
+    }
+
+    /// [Compute Parallel]
+    /// Fills vals using the rayon kernel for the formula, returning the
+    /// histogram of the counts.
+    pub fn compute_parallel<T, M>(&self, vals: &mut [u32], width: usize, map: M, limit: u32) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T> + Sync,
+    {
+        dispatch!(self, fractal => kernel::compute_parallel(fractal, vals, width, map, limit))
+    }
+
+    /// [Compute Parallel Counted]
+    /// The same, counting each row on `progress` as it is done, and
+    /// working outward from row `first` if there is one.
+    pub fn compute_parallel_counted<T, M>(&self, vals: &mut [u32], width: usize, map: M, limit: u32, progress: &Progress, first: Option<usize>) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T> + Sync,
+    {
+        dispatch!(self, fractal => kernel::compute_parallel_counted(fractal, vals, width, map, limit, progress, first))
+    }
+
+    /// [Compute Stats Counted]
+    /// The same, with each pixel's Lyapunov exponent and smooth count
+    /// into stats, as far as LYAPUNOV and SMOOTH ask for them.
+    pub fn compute_stats_counted<T, M, const LYAPUNOV: bool, const SMOOTH: bool>(&self, vals: &mut [u32], stats: &mut [(f32, f32)], width: usize, map: M, limit: u32, progress: &Progress) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T> + Sync,
+    {
+        dispatch!(self, fractal => kernel::compute_stats_counted::<T, _, M, LYAPUNOV, SMOOTH>(fractal, vals, stats, width, map, limit, progress))
+    }
+
+    /// [Compute Heights]
+    /// Fills vals using the rayon kernel, and heights with each pixel's
+    /// smooth count, returning the histogram of the counts.
+    pub fn compute_heights<T, M>(&self, vals: &mut [u32], heights: &mut Vec<f32>, width: usize, map: M, limit: u32) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T> + Sync,
+    {
+        let mut stats = vec![(0.0, 0.0); vals.len()];
+        let histogram = self.compute_stats_counted::<T, M, false, true>(vals, &mut stats, width, map, limit, &Progress::default());
+        *heights = stats.into_iter().map(|(_, smooth)| smooth).collect();
+        histogram
+    }
+
+    /// [Compute Points]
+    /// Fills vals with the counts of the points `map` gives for each
+    /// index, in parallel, for samples that do not lie on a grid.
+    pub fn compute_points<T, M>(&self, vals: &mut [u32], map: M, limit: u32)
+    where
+        T: Real,
+        M: Fn(usize) -> cmp<T> + Sync,
+    {
+        dispatch!(self, fractal => kernel::compute_points_parallel(fractal, vals, map, limit))
+    }
+
+    /// [Orbit]
+    /// The orbit of the point c under the formula, as the kernel
+    /// iterates it, with whether it escaped.
+    pub fn orbit(&self, c: cmp<f64>, limit: u32) -> (Vec<cmp<f64>>, bool) {
+        dispatch!(self, fractal => kernel::orbit(fractal, c, limit))
+    }
+
+    /// [Compute Sequential]
+    /// Fills vals using the single-threaded kernel for the formula,
+    /// returning the histogram of the counts.
+    pub fn compute_sequential<T, M>(&self, vals: &mut [u32], width: usize, map: M, limit: u32) -> Histogram
+    where
+        T: Real,
+        M: Fn(usize, usize) -> cmp<T>,
+    {
+        dispatch!(self, fractal => kernel::compute_sequential(fractal, vals, width, map, limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::viewport::Viewport;
+
+    #[test]
+    fn larger_escape_radii_carry_orbits_further() {
+        let viewport = Viewport::new(cmp::new(-0.745, 0.1), 0.02, 64, 48);
+        let map = |a: usize, b: usize| viewport.pixel_to_complex(a as f64, b as f64);
+        let (mut own, mut at_two, mut wide) = (vec![0; 64 * 48], vec![0; 64 * 48], vec![0; 64 * 48]);
+
+        let histogram = Formula::Mandelbrot.compute_parallel(&mut own, 64, map, 500);
+        Formula::Mandelbrot.escaping(ESCAPE_RADIUS).compute_sequential(&mut at_two, 64, map, 500);
+        let widened = Formula::Mandelbrot.escaping(100.0).compute_parallel(&mut wide, 64, map, 500);
+
+        assert!(own == at_two);
+        assert!(own.iter().zip(&wide).all(|(own, wide)| own <= wide));
+        assert!(own.iter().zip(&wide).any(|(own, wide)| own < wide));
+        // Orbits that stay within 2 stay within 100, and a few more with them.
+        assert!(widened.interior() >= histogram.interior());
+
+        let c = cmp::new(0.5, 0.5);
+        let (orbit, escaped) = Formula::Mandelbrot.escaping(100.0).orbit(c, 500);
+        assert!(escaped && orbit.last().unwrap().norm() >= 100.0);
+        assert!(orbit[..orbit.len() - 1].iter().all(|z| z.norm() < 100.0));
+    }
+}
@@ -0,0 +1,197 @@
+//! [Goto]
+//!
+//! The prompt F2 opens for going to exact coordinates: a line of text
+//! typed over the frame, taking a view as `RE IM WIDTH` or as
+//! `RE+IMi @ WIDTH`, the forms P prints and bookmarks are written in.
+//! While it is open, typing goes to it rather than to the key bindings;
+//! Enter travels to the view typed, as a click does, and Esc closes it.
+//! A line that does not parse stays as typed, with what is wrong with it
+//! shown under it, to be put right. Ctrl+V pastes into it, from whichever
+//! of the usual clipboard tools the system has (not on wasm32).
+
+use num::complex::Complex as cmp;
+
+use crate::backend::Key;
+use crate::bookmark::Bookmark;
+use crate::overlay::{text_box_size, Overlay};
+
+const SIZE: f64 = 11.0;
+
+/// [Entry]
+/// What a key does to the prompt.
+///
+/// Variants:
+/// [Open] The prompt stays open, edited or not;
+/// [Cancelled] It was closed without going anywhere;
+/// [Go] It was closed on a view to go to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Entry {
+    Open,
+    Cancelled,
+    Go(Bookmark),
+}
+
+/// [Prompt]
+///
+/// Fields:
+/// [text] What has been typed;
+/// [cursor] Where the next character goes, in characters from the start;
+/// [error] What was wrong with the text when Enter was last pressed, until it is edited.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Prompt {
+    text: String,
+    cursor: usize,
+    error: Option<String>,
+}
+
+impl Prompt {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// [Insert]
+    /// Types text at the cursor, leaving out line breaks and other
+    /// control characters, as pasted text may have.
+    pub fn insert(&mut self, text: &str) {
+        for c in text.chars().filter(|c| !c.is_control()) {
+            let at = self.byte(self.cursor);
+            self.text.insert(at, c);
+            self.cursor += 1;
+        }
+        self.error = None;
+    }
+
+    /// Shows a problem under the text, as a failed parse does.
+    pub fn fail(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// [Key]
+    /// Edits the text, or closes the prompt, by a key. Characters arrive
+    /// as typed text instead, so their presses do nothing here.
+    pub fn key(&mut self, key: Key) -> Entry {
+        match key {
+            Key::Escape => return Entry::Cancelled,
+            Key::Return => match parse(&self.text) {
+                Ok(view) => return Entry::Go(view),
+                Err(error) => self.error = Some(error),
+            },
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let at = self.byte(self.cursor);
+                self.text.remove(at);
+                self.error = None;
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.text.chars().count()),
+            _ => {}
+        }
+        Entry::Open
+    }
+
+    /// The byte offset of a character, or the end of the text.
+    fn byte(&self, chars: usize) -> usize {
+        self.text.char_indices().nth(chars).map_or(self.text.len(), |(at, _)| at)
+    }
+
+    /// [Draw]
+    /// Adds the prompt a third of the way down a frame of the given
+    /// logical size, centred across it, the cursor shown as a bar.
+    pub fn draw(&self, overlay: &mut Overlay, frame: [f64; 2]) {
+        let (before, after) = self.text.split_at(self.byte(self.cursor));
+        let mut lines = vec!["goto RE IM WIDTH, or RE+IMi @ WIDTH".to_string(), format!("> {before}|{after}")];
+        lines.extend(self.error.as_ref().map(|error| format!("  {error}")));
+
+        let [width, _] = text_box_size(&lines, SIZE);
+        overlay.text_box(&lines, [((frame[0] - width) / 2.0).max(0.0), frame[1] / 3.0], SIZE);
+    }
+}
+
+/// [Parse]
+/// The view a line gives: its centre and width as `RE IM WIDTH`, commas
+/// allowed between, or as `RE+IMi @ WIDTH`.
+pub fn parse(text: &str) -> Result<Bookmark, String> {
+    let number = |part: &str, what: &str| part.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+        .ok_or_else(|| format!("the {what} must be a number, not '{}'", part.trim()));
+
+    let (centre, width) = match text.split_once('@') {
+        Some((centre, width)) => (complex(centre.trim()).ok_or_else(|| format!("'{}' is not a point such as -0.75+0.1i", centre.trim()))?, number(width, "width")?),
+        None => {
+            let parts: Vec<&str> = text.split(|c: char| c.is_whitespace() || c == ',').filter(|part| !part.is_empty()).collect();
+            let [re, im, width] = parts[..] else { return Err("needs three numbers, or a point @ a width".to_string()) };
+            (cmp::new(number(re, "real part")?, number(im, "imaginary part")?), number(width, "width")?)
+        }
+    };
+    if width <= 0.0 {
+        return Err("the width must be more than 0".to_string());
+    }
+    Ok(Bookmark { centre, width })
+}
+
+/// A complex number written as RE+IMi or RE-IMi, the sign being the last
+/// that is not at the start or in an exponent.
+fn complex(text: &str) -> Option<cmp<f64>> {
+    let body = text.strip_suffix('i')?;
+    let bytes = body.as_bytes();
+    let split = (1..bytes.len()).rev().find(|&i| matches!(bytes[i], b'+' | b'-') && !matches!(bytes[i - 1], b'e' | b'E'))?;
+    let (re, im) = (body[..split].trim().parse::<f64>().ok()?, body[split..].replace(' ', "").parse::<f64>().ok()?);
+    (re.is_finite() && im.is_finite()).then(|| cmp::new(re, im))
+}
+
+/// [Clipboard]
+/// The text on the clipboard, from the first of the usual tools for it
+/// there is, or None if there is none or the clipboard holds no text.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn clipboard() -> Option<String> {
+    const TOOLS: [&[&str]; 5] = [
+        &["wl-paste", "--no-newline"],
+        &["xclip", "-out", "-selection", "clipboard"],
+        &["xsel", "--output", "--clipboard"],
+        &["pbpaste"],
+        &["powershell", "-NoProfile", "-Command", "Get-Clipboard"],
+    ];
+    TOOLS.iter().find_map(|tool| {
+        let output = std::process::Command::new(tool[0]).args(&tool[1..]).output().ok()?;
+        output.status.success().then(|| String::from_utf8(output.stdout).ok()).flatten()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_both_forms() {
+        let view = Bookmark { centre: cmp::new(-0.7436, 0.1318), width: 1e-6 };
+        assert_eq!(parse("-0.7436 0.1318 1e-6"), Ok(view));
+        assert_eq!(parse(" -0.7436, 0.1318, 0.000001 "), Ok(view));
+        assert_eq!(parse("-0.7436+0.1318i @ 1e-6"), Ok(view));
+        assert_eq!(parse("-7.436e-1 + 1.318e-1i@1e-6"), Ok(view));
+        assert_eq!(parse("0.3-0.5i @ 2").map(|view| view.centre), Ok(cmp::new(0.3, -0.5)));
+
+        assert!(parse("-0.7436 0.1318").is_err());
+        assert!(parse("-0.7436 0.1318 0").is_err());
+        assert!(parse("-0.7436 up 1").unwrap_err().contains("imaginary part"));
+        assert!(parse("-0.7436 @ 1").is_err());
+    }
+
+    #[test]
+    fn edits_at_the_cursor_and_keeps_text_that_fails() {
+        let mut prompt = Prompt::default();
+        prompt.insert("0 0 2\n");
+        assert_eq!(prompt.key(Key::Left), Entry::Open);
+        prompt.key(Key::Backspace);
+        prompt.insert("-");
+        assert_eq!(prompt.text(), "0 0-2");
+
+        assert_eq!(prompt.key(Key::Return), Entry::Open);
+        assert!(prompt.error.is_some());
+        assert_eq!(prompt.text(), "0 0-2");
+
+        prompt.key(Key::Backspace);
+        prompt.insert(" ");
+        assert_eq!(prompt.error, None);
+        assert_eq!(prompt.key(Key::Return), Entry::Go(Bookmark { centre: cmp::new(0.0, 0.0), width: 2.0 }));
+        assert_eq!(prompt.key(Key::Escape), Entry::Cancelled);
+    }
+}
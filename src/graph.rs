@@ -0,0 +1,158 @@
+//! [Graph]
+//!
+//! The strip chart of recent compute times toggled with R, in the
+//! manner of a game engine's profiler: one bar per update for the last
+//! few seconds, against a line at the time an update has before the
+//! next is due. It shows at a glance the spikes a single average hides,
+//! such as when the view crosses into interior-heavy ground.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::backend::UPDATES_PER_SECOND;
+use crate::hud::millis;
+use crate::overlay::{Overlay, Shape};
+
+/// How many updates the chart goes back.
+pub const HISTORY: usize = 200;
+
+/// The colours of bars within and over the budget, and of its line.
+const WITHIN: [f32; 4] = [0.3, 0.9, 0.4, 0.9];
+const OVER: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+const BUDGET_LINE: [f32; 4] = [1.0, 0.9, 0.2, 0.9];
+
+/// [Budget]
+/// The time each update has before the next one is due.
+pub fn budget() -> Duration {
+    Duration::from_secs(1) / UPDATES_PER_SECOND as u32
+}
+
+/// [Frame History]
+/// How long the escape-time pass took for each of the last HISTORY
+/// updates, oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct FrameHistory {
+    times: VecDeque<Duration>,
+}
+
+impl FrameHistory {
+    pub fn record(&mut self, elapsed: Duration) {
+        if self.times.len() == HISTORY {
+            self.times.pop_front();
+        }
+        self.times.push_back(elapsed);
+    }
+
+    pub fn times(&self) -> &VecDeque<Duration> {
+        &self.times
+    }
+
+    /// How many of the recorded updates went over the budget.
+    pub fn over_budget(&self) -> usize {
+        self.times.iter().filter(|&&time| time > budget()).count()
+    }
+}
+
+/// [Frame Graph]
+///
+/// Fields:
+/// [visible] Whether the chart is shown.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameGraph {
+    pub visible: bool,
+}
+
+impl FrameGraph {
+    /// [Draw]
+    /// Adds the chart to the right-hand edge of a frame of the given
+    /// width, `top` pixels down, if it is visible. Bars are scaled so
+    /// that the budget line sits halfway up until something goes over
+    /// twice the budget, and the newest bar is on the right.
+    pub fn draw(&self, overlay: &mut Overlay, history: &FrameHistory, frame_width: f64, top: f64) {
+        if !self.visible {
+            return;
+        }
+
+        let width = (frame_width / 3.0).min(HISTORY as f64);
+        let height = 48.0;
+        let left = frame_width - width - 4.0;
+        let (label, plot) = (8.0, height - 8.0 * 1.5 - 2.0);
+        let base = top + 2.0 + plot;
+
+        overlay.push(Shape::Rect { rect: [left, top, width, height], colour: [0.0, 0.0, 0.0, 0.6] });
+
+        let budget = millis(budget());
+        let scale = history.times().iter().copied().map(millis).fold(2.0 * budget, f64::max);
+        let bar = width / HISTORY as f64;
+        let first = HISTORY - history.times().len();
+        for (i, &time) in history.times().iter().enumerate() {
+            let h = plot * millis(time) / scale;
+            let colour = if millis(time) > budget { OVER } else { WITHIN };
+            overlay.push(Shape::Rect { rect: [left + (first + i) as f64 * bar, base - h, bar, h], colour });
+        }
+
+        let line = base - plot * budget / scale;
+        overlay.push(Shape::Line { from: [left, line], to: [left + width, line], width: 1.0, colour: BUDGET_LINE });
+
+        let last = history.times().back().map_or("-".to_string(), |&time| format!("{:.1} ms", millis(time)));
+        overlay.push(Shape::Text {
+            text: format!("{last}, budget {budget:.1} ms, {} over", history.over_budget()),
+            at: [left + 2.0, top + height - 2.0],
+            size: label,
+            colour: [1.0, 1.0, 1.0, 1.0],
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(overlay: &Overlay) -> Vec<[f64; 4]> {
+        overlay.shapes().iter()
+            .skip(1)
+            .filter_map(|shape| match shape {
+                Shape::Rect { rect, .. } => Some(*rect),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_last_updates() {
+        let mut history = FrameHistory::default();
+        for ms in 0..HISTORY as u64 + 50 {
+            history.record(Duration::from_millis(ms));
+        }
+
+        assert_eq!(history.times().len(), HISTORY);
+        assert_eq!(history.times().front(), Some(&Duration::from_millis(50)));
+
+        // 8.3 ms an update at 120 updates a second, so 9 ms and up go over.
+        let mut history = FrameHistory::default();
+        for ms in 0..20 {
+            history.record(Duration::from_millis(ms));
+        }
+        assert_eq!(history.over_budget(), 11);
+    }
+
+    #[test]
+    fn bars_grow_to_the_right_against_the_budget() {
+        let mut history = FrameHistory::default();
+        let mut overlay = Overlay::new();
+        FrameGraph { visible: true }.draw(&mut overlay, &history, 600.0, 10.0);
+        assert!(bars(&overlay).is_empty());
+
+        history.record(budget() / 2);
+        history.record(budget() * 4);
+        let mut overlay = Overlay::new();
+        FrameGraph { visible: true }.draw(&mut overlay, &history, 600.0, 10.0);
+
+        let bars = bars(&overlay);
+        assert_eq!(bars.len(), 2);
+        // The newest bar is at the right-hand end, and the spike fills the plot.
+        assert_eq!(bars[1][0] + bars[1][2], 600.0 - 4.0);
+        assert!((bars[1][3] / bars[0][3] - 8.0).abs() < 1e-5);
+        assert!(overlay.shapes().contains(&Shape::Rect { rect: bars[1], colour: OVER }));
+    }
+}
@@ -0,0 +1,162 @@
+//! [Grid]
+//!
+//! Gridlines at round values of the real and imaginary parts, toggled
+//! with G and labelled along the bottom and left edges. The lines are
+//! placed in the complex plane and mapped through the live viewport, so
+//! they stay on the same values as the view closes in.
+
+use num::complex::Complex as cmp;
+
+use crate::overlay::{Overlay, Shape};
+use crate::viewport::Viewport;
+
+/// Roughly how many lines to aim for across the width of the view.
+const LINES: f64 = 8.0;
+
+/// Labels need about this many significant digits beyond the step to
+/// tell neighbouring lines apart, out of the ~16 f64 holds.
+const RESOLUTION: f64 = 1e-12;
+
+const LINE: [f32; 4] = [1.0, 1.0, 1.0, 0.3];
+const AXIS: [f32; 4] = [1.0, 1.0, 1.0, 0.7];
+const LABEL: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+const LABEL_SIZE: f64 = 9.0;
+
+/// [Nice Step]
+/// The spacing between gridlines for a view `span` units across: the
+/// first of 1, 2 and 5 times a power of ten that gives no more than
+/// about `LINES` lines.
+pub fn nice_step(span: f64) -> f64 {
+    let raw = span / LINES;
+    let magnitude = 10f64.powf(raw.log10().floor());
+
+    [1.0, 2.0, 5.0, 10.0].into_iter()
+        .map(|m| m * magnitude)
+        .find(|&step| step >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// [Grid]
+///
+/// Fields:
+/// [visible] Whether the grid is shown.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Grid {
+    pub visible: bool,
+}
+
+impl Grid {
+    /// [Draw]
+    ///
+    /// Adds the grid to the overlay, if it is visible. The lines run
+    /// between the extremes of the view in the plane, so a rotated view
+    /// gets a rotated grid. Once the step is too small relative to the
+    /// coordinates for f64 labels to tell lines apart, the grid is
+    /// replaced by a note saying so.
+    pub fn draw(&self, overlay: &mut Overlay, viewport: &Viewport) {
+        if !self.visible {
+            return;
+        }
+
+        let (width, height) = (viewport.width_px() as f64, viewport.height_px() as f64);
+        let corners = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)]
+            .map(|(x, y)| viewport.pixel_to_complex(x, y));
+        let (re_min, re_max) = extent(corners.map(|c| c.re));
+        let (im_min, im_max) = extent(corners.map(|c| c.im));
+
+        let step = nice_step(viewport.width());
+        let largest = re_min.abs().max(re_max.abs()).max(im_min.abs()).max(im_max.abs());
+        if !(step.is_finite() && step > largest * RESOLUTION) {
+            overlay.text_box(&["grid: beyond f64 resolution"], [4.0, height - 4.0 - 2.0 * LABEL_SIZE], LABEL_SIZE);
+            return;
+        }
+
+        let decimals = (-step.log10().floor()).max(0.0) as usize;
+        let values = |min: f64, max: f64| ((min / step).ceil() as i64..=(max / step).floor() as i64).map(|k| k as f64 * step);
+
+        for re in values(re_min, re_max) {
+            let from = viewport.complex_to_pixel(cmp::new(re, im_min));
+            let to = viewport.complex_to_pixel(cmp::new(re, im_max));
+            overlay.push(Shape::Line { from, to, width: 1.0, colour: if re == 0.0 { AXIS } else { LINE } });
+
+            if let Some(x) = crossing(from, to, 1, height) {
+                label(overlay, format!("{re:.decimals$}"), [x + 2.0, height - 3.0]);
+            }
+        }
+
+        for im in values(im_min, im_max) {
+            let from = viewport.complex_to_pixel(cmp::new(re_min, im));
+            let to = viewport.complex_to_pixel(cmp::new(re_max, im));
+            overlay.push(Shape::Line { from, to, width: 1.0, colour: if im == 0.0 { AXIS } else { LINE } });
+
+            if let Some(y) = crossing(from, to, 0, 0.0) {
+                label(overlay, format!("{im:.decimals$}i"), [2.0, y - 2.0]);
+            }
+        }
+    }
+}
+
+fn extent(values: [f64; 4]) -> (f64, f64) {
+    (values.into_iter().fold(f64::INFINITY, f64::min), values.into_iter().fold(f64::NEG_INFINITY, f64::max))
+}
+
+/// Where the segment from `from` to `to` crosses the line where
+/// coordinate `axis` equals `at`, as the other coordinate.
+fn crossing(from: [f64; 2], to: [f64; 2], axis: usize, at: f64) -> Option<f64> {
+    let (a, b) = (from[axis], to[axis]);
+    if (a - at) * (b - at) > 0.0 || a == b {
+        return None;
+    }
+
+    let t = (at - a) / (b - a);
+    Some(from[1 - axis] + t * (to[1 - axis] - from[1 - axis]))
+}
+
+fn label(overlay: &mut Overlay, text: String, at: [f64; 2]) {
+    overlay.push(Shape::Text { text, at, size: LABEL_SIZE, colour: LABEL });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_round() {
+        assert_eq!(nice_step(4.0), 0.5);
+        assert_eq!(nice_step(8.0), 1.0);
+        assert_eq!(nice_step(1.0), 0.2);
+        assert!((nice_step(3.0e-6) - 5.0e-7).abs() < 1e-20);
+    }
+
+    #[test]
+    fn lines_sit_on_their_values() {
+        let viewport = Viewport::new(cmp::new(0.36, -0.64), 0.003, 400, 200);
+        let mut overlay = Overlay::new();
+
+        Grid { visible: true }.draw(&mut overlay, &viewport);
+
+        let step = nice_step(0.003);
+        let mut lines = 0;
+        for shape in overlay.shapes() {
+            if let Shape::Line { from, to, .. } = shape {
+                let (a, b) = (viewport.pixel_to_complex(from[0], from[1]), viewport.pixel_to_complex(to[0], to[1]));
+                let value = if (a.re - b.re).abs() < 1e-12 { a.re } else { a.im };
+                assert!(((value / step).round() * step - value).abs() < 1e-12, "{value}");
+                lines += 1;
+            }
+        }
+
+        assert!((8..=20).contains(&lines), "{lines}");
+    }
+
+    #[test]
+    fn gives_up_past_f64_resolution() {
+        let viewport = Viewport::new(cmp::new(0.36, -0.64), 1e-14, 400, 200);
+        let mut overlay = Overlay::new();
+
+        Grid { visible: true }.draw(&mut overlay, &viewport);
+
+        assert!(!overlay.shapes().iter().any(|s| matches!(s, Shape::Line { .. })));
+        assert!(overlay.shapes().iter().any(|s| matches!(s, Shape::Text { text, .. } if text.contains("resolution"))));
+    }
+}
@@ -0,0 +1,227 @@
+//! [Height Map]
+//!
+//! The counts as a 3D surface, toggled with Shift+D: a grid of vertices,
+//! one for every STEP pixels each way, is raised by each count's log,
+//! interior points forming a plateau at the top, and drawn in place of
+//! the frame from a camera that can be orbited by dragging with the
+//! mouse. It is drawn by a small software renderer, a z-buffer and two
+//! flat-coloured triangles to each cell of the grid, so that every
+//! backend shows it, as the bytes of a frame like any other. Each cell
+//! takes the colour its corner has in the frame as coloured, so the
+//! surface has whatever colouring and shading the frame would. The
+//! counts go on being computed as the zoom goes on, and toggling back
+//! shows the frame as it was coloured again.
+
+use std::f64::consts::FRAC_PI_2;
+
+/// The pixels between vertices of the grid, each way.
+pub const STEP: usize = 2;
+
+/// How high the plateau of the interior stands, against a width of 2.
+const HEIGHT: f64 = 0.4;
+
+/// How far the camera is from the middle of the surface.
+const DISTANCE: f64 = 3.0;
+
+/// How many radians the camera turns for each logical pixel dragged.
+const SENSITIVITY: f64 = 0.01;
+
+/// The camera's pitch is kept between these, in radians above the surface.
+const PITCH_RANGE: (f64, f64) = (0.1, FRAC_PI_2);
+
+const BACKGROUND: [u8; 4] = [0, 0, 0, 255];
+
+/// [Camera]
+///
+/// Fields:
+/// [yaw] How far the camera has turned round the surface, in radians;
+/// [pitch] How far above the surface it looks down from, in radians.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub yaw: f64,
+    pub pitch: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera { yaw: 0.0, pitch: 0.6 }
+    }
+}
+
+impl Camera {
+    /// [Orbit]
+    /// Turns the camera by a drag of `by` logical pixels, across turning it
+    /// round and down tilting it further over.
+    pub fn orbit(&mut self, by: [f64; 2]) {
+        self.yaw += by[0] * SENSITIVITY;
+        self.pitch = (self.pitch + by[1] * SENSITIVITY).clamp(PITCH_RANGE.0, PITCH_RANGE.1);
+    }
+
+    /// A point of the surface as seen from the camera: across and up
+    /// from its line of sight, and how far along it.
+    fn view(&self, [x, y, z]: [f64; 3]) -> [f64; 3] {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (x, z) = (x * cos_yaw - z * sin_yaw, x * sin_yaw + z * cos_yaw);
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        [x, y * cos_pitch + z * sin_pitch, z * cos_pitch - y * sin_pitch + DISTANCE]
+    }
+}
+
+/// [Height Map]
+///
+/// Fields:
+/// [visible] Whether the surface is shown in place of the frame;
+/// [camera] Where it is seen from;
+/// [grab] Where the drag orbiting the camera last was, while there is one.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HeightMap {
+    pub visible: bool,
+    pub camera: Camera,
+    pub grab: Option<[f64; 2]>,
+}
+
+impl HeightMap {
+    /// Starts orbiting from a press at a point, in logical pixels.
+    pub fn press(&mut self, at: [f64; 2]) {
+        self.grab = self.visible.then_some(at);
+    }
+
+    /// [Drag]
+    /// Orbits the camera as the pointer moves, in logical pixels, while a
+    /// drag is under way, returning whether it moved.
+    pub fn drag(&mut self, at: [f64; 2]) -> bool {
+        let Some(from) = self.grab.replace(at).filter(|_| self.visible) else { return false };
+        self.camera.orbit([at[0] - from[0], at[1] - from[1]]);
+        true
+    }
+
+    pub fn release(&mut self) {
+        self.grab = None;
+    }
+
+    /// [Render]
+    /// The surface of a buffer of counts, `width` pixels per row, as
+    /// packed RGBA bytes of the same size, each cell coloured as its
+    /// corner is in `colours`, the frame coloured.
+    pub fn render(&self, vals: &[u32], width: usize, limit: u32, colours: &[u8]) -> Vec<u8> {
+        let height = vals.len() / width.max(1);
+        let mut rgba = BACKGROUND.repeat(width * height);
+        if width < 2 || height < 2 {
+            return rgba;
+        }
+
+        // The grid, in screen pixels and distance from the camera.
+        let (columns, rows) = (vertices(width), vertices(height));
+        let focal = 0.5 * width as f64 * DISTANCE / 1.2;
+        let log_limit = (limit.max(1) as f64).ln_1p();
+        let project = |a: usize, b: usize| {
+            let count = vals[b * width + a].min(limit);
+            let point = [
+                (a as f64 / (width - 1) as f64 - 0.5) * 2.0,
+                (count as f64).ln_1p() / log_limit * HEIGHT,
+                (b as f64 / (height - 1) as f64 - 0.5) * 2.0 * height as f64 / width as f64,
+            ];
+            let [x, y, z] = self.camera.view(point);
+            [width as f64 / 2.0 + focal * x / z, height as f64 / 2.0 - focal * y / z, z]
+        };
+        let grid: Vec<Vec<[f64; 3]>> = rows.iter().map(|&b| columns.iter().map(|&a| project(a, b)).collect()).collect();
+
+        let mut depth = vec![f64::INFINITY; width * height];
+        for (j, &b) in rows.iter().enumerate().take(rows.len() - 1) {
+            for (i, &a) in columns.iter().enumerate().take(columns.len() - 1) {
+                let colour: [u8; 4] = colours[(b * width + a) * 4..][..4].try_into().unwrap_or(BACKGROUND);
+                let [tl, tr, bl, br] = [grid[j][i], grid[j][i + 1], grid[j + 1][i], grid[j + 1][i + 1]];
+                for triangle in [[tl, tr, bl], [tr, br, bl]] {
+                    fill(triangle, colour, &mut rgba, &mut depth, width, height);
+                }
+            }
+        }
+        rgba
+    }
+}
+
+/// The positions of the vertices along a side of `len` pixels, the last
+/// on the edge whatever the step.
+fn vertices(len: usize) -> Vec<usize> {
+    let mut at: Vec<usize> = (0..len).step_by(STEP).collect();
+    if at.last() != Some(&(len - 1)) {
+        at.push(len - 1);
+    }
+    at
+}
+
+/// [Fill]
+/// Draws a triangle of screen points, nearer parts of the surface
+/// covering farther ones. Triangles reaching behind the camera are left out.
+fn fill(triangle: [[f64; 3]; 3], colour: [u8; 4], rgba: &mut [u8], depth: &mut [f64], width: usize, height: usize) {
+    let [p, q, r] = triangle;
+    if triangle.iter().any(|point| point[2] <= 0.1) {
+        return;
+    }
+    let area = (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0]);
+    if area.abs() < 1e-12 {
+        return;
+    }
+
+    // The pixels the triangle's bounds cover, as a range, empty off the frame.
+    let span = |axis: usize, len: usize| {
+        let low = triangle.iter().map(|point| point[axis]).fold(f64::INFINITY, f64::min);
+        let high = triangle.iter().map(|point| point[axis]).fold(f64::NEG_INFINITY, f64::max);
+        low.floor().clamp(0.0, len as f64) as usize..high.ceil().clamp(0.0, len as f64) as usize
+    };
+    for y in span(1, height) {
+        for x in span(0, width) {
+            let (sx, sy) = (x as f64 + 0.5, y as f64 + 0.5);
+            let u = ((q[0] - sx) * (r[1] - sy) - (q[1] - sy) * (r[0] - sx)) / area;
+            let v = ((r[0] - sx) * (p[1] - sy) - (r[1] - sy) * (p[0] - sx)) / area;
+            let w = 1.0 - u - v;
+            if u < 0.0 || v < 0.0 || w < 0.0 {
+                continue;
+            }
+
+            let z = u * p[2] + v * q[2] + w * r[2];
+            let i = y * width + x;
+            if z < depth[i] {
+                depth[i] = z;
+                rgba[i * 4..i * 4 + 4].copy_from_slice(&colour);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_the_surface_over_the_background() {
+        let (width, height) = (40, 20);
+        let vals = vec![5; width * height];
+        let colours = [200, 100, 50, 255].repeat(width * height);
+        let map = HeightMap { visible: true, ..HeightMap::default() };
+
+        let rgba = map.render(&vals, width, 100, &colours);
+        let drawn = rgba.chunks(4).filter(|pixel| *pixel == [200, 100, 50, 255]).count();
+        assert!(drawn > width * height / 4, "{drawn}");
+        // The middle of the frame looks at the middle of the surface.
+        let middle = (height / 2 * width + width / 2) * 4;
+        assert_eq!(&rgba[middle..middle + 4], &[200, 100, 50, 255]);
+        assert!(rgba.chunks(4).any(|pixel| pixel == BACKGROUND));
+    }
+
+    #[test]
+    fn orbits_while_dragged() {
+        let mut map = HeightMap::default();
+        map.press([10.0, 10.0]);
+        assert!(!map.drag([20.0, 10.0]));
+
+        map.visible = true;
+        map.press([10.0, 10.0]);
+        assert!(map.drag([30.0, 1000.0]));
+        assert!((map.camera.yaw - 0.2).abs() < 1e-12);
+        assert_eq!(map.camera.pitch, FRAC_PI_2);
+
+        map.release();
+        assert!(!map.drag([0.0, 0.0]));
+    }
+}
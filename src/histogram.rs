@@ -0,0 +1,206 @@
+//! [Histogram]
+//!
+//! How the iteration counts of a frame are distributed, gathered by the
+//! kernels as they write the counts, and the panel (toggled with H)
+//! that draws it. The share of pixels at the limit is what tells you
+//! whether the iteration limit needs raising.
+
+use crate::overlay::{Overlay, Shape};
+
+/// How many buckets the counts below the limit are spread over.
+pub const BUCKETS: usize = 64;
+
+/// [Histogram]
+///
+/// Fields:
+/// [buckets] Counts of escaping pixels, in equal ranges of iterations from 0 to the limit;
+/// [interior] How many pixels reached the limit;
+/// [limit] The iteration limit the counts were computed with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Histogram {
+    buckets: [u64; BUCKETS],
+    interior: u64,
+    limit: u32,
+}
+
+impl Histogram {
+    pub fn new(limit: u32) -> Histogram {
+        Histogram { buckets: [0; BUCKETS], interior: 0, limit }
+    }
+
+    pub fn buckets(&self) -> &[u64; BUCKETS] {
+        &self.buckets
+    }
+
+    pub fn interior(&self) -> u64 {
+        self.interior
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub fn total(&self) -> u64 {
+        self.interior + self.buckets.iter().sum::<u64>()
+    }
+
+    /// The share of pixels which reached the limit, or 0 before any
+    /// have been counted.
+    pub fn interior_fraction(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.interior as f64 / total as f64,
+        }
+    }
+
+    #[inline(always)]
+    pub fn add(&mut self, count: u32) {
+        if count >= self.limit {
+            self.interior += 1;
+        } else {
+            self.buckets[(count as u64 * BUCKETS as u64 / self.limit as u64) as usize] += 1;
+        }
+    }
+
+    /// [Merge]
+    /// Adds another histogram of the same limit into this one, as when
+    /// combining the histograms of separate threads.
+    pub fn merge(mut self, other: Histogram) -> Histogram {
+        for (bucket, count) in self.buckets.iter_mut().zip(other.buckets) {
+            *bucket += count;
+        }
+        self.interior += other.interior;
+
+        self
+    }
+}
+
+/// Where the panel goes in a frame of the given width, as
+/// [x, y, width, height].
+fn panel(frame_width: f64) -> [f64; 4] {
+    let width = (frame_width / 3.0).min(160.0);
+    [frame_width - width - 4.0, 4.0, width, width * 0.45]
+}
+
+/// [Histogram Panel]
+///
+/// Fields:
+/// [visible] Whether the panel is shown.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HistogramPanel {
+    pub visible: bool,
+}
+
+impl HistogramPanel {
+    /// [Draw]
+    /// Adds the panel to the top-right corner of a frame of the given
+    /// width, if it is visible. Bars are log-scaled, as a few buckets
+    /// usually hold most of the pixels, and the pixels at the limit get a
+    /// red bar of their own beyond a marker line.
+    pub fn draw(&self, overlay: &mut Overlay, histogram: &Histogram, frame_width: f64) {
+        if !self.visible {
+            return;
+        }
+
+        let [left, top, width, height] = panel(frame_width);
+        let label = 8.0;
+        let plot = height - label * 1.5;
+
+        overlay.push(Shape::Rect { rect: [left, top, width, height], colour: [0.0, 0.0, 0.0, 0.6] });
+
+        let tallest = histogram.buckets().iter().copied().chain([histogram.interior()]).max().unwrap_or(0);
+        let bar_height = |n: u64| match tallest {
+            0 => 0.0,
+            tallest => plot * (n as f64).ln_1p() / (tallest as f64).ln_1p(),
+        };
+
+        // One slot per bucket, one for the gap at the marker, one for the limit.
+        let slot = width / (BUCKETS + 2) as f64;
+        let base = top + plot + 2.0;
+        for (i, &n) in histogram.buckets().iter().enumerate() {
+            let h = bar_height(n);
+            overlay.push(Shape::Rect { rect: [left + i as f64 * slot, base - h, slot, h], colour: [0.6, 0.7, 1.0, 0.9] });
+        }
+
+        let marker = left + (BUCKETS as f64 + 0.5) * slot;
+        overlay.push(Shape::Line { from: [marker, top + 2.0], to: [marker, base], width: 1.0, colour: [1.0, 1.0, 1.0, 0.8] });
+
+        let h = bar_height(histogram.interior());
+        overlay.push(Shape::Rect { rect: [left + (BUCKETS + 1) as f64 * slot, base - h, slot, h], colour: [1.0, 0.3, 0.3, 1.0] });
+
+        overlay.push(Shape::Text {
+            text: format!("{:.1}% at limit {}", histogram.interior_fraction() * 100.0, histogram.limit()),
+            at: [left + 2.0, top + height - 2.0],
+            size: label,
+            colour: [1.0, 1.0, 1.0, 1.0],
+        });
+    }
+
+    /// [Bounds]
+    /// The rectangle the panel covers, as [x, y, width, height], if it
+    /// is visible.
+    pub fn bounds(&self, frame_width: f64) -> Option<[f64; 4]> {
+        self.visible.then(|| panel(frame_width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal::Formula;
+    use crate::settings::{Settings, ITERATIONS};
+
+    #[test]
+    fn counts_land_in_their_buckets() {
+        let mut histogram = Histogram::new(640);
+        for count in [0, 9, 10, 639, 640, 700] {
+            histogram.add(count);
+        }
+
+        assert_eq!(histogram.buckets()[0], 2);
+        assert_eq!(histogram.buckets()[1], 1);
+        assert_eq!(histogram.buckets()[BUCKETS - 1], 1);
+        assert_eq!(histogram.interior(), 2);
+        assert_eq!(histogram.total(), 6);
+        assert!((histogram.interior_fraction() - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn merging_adds_buckets() {
+        let (mut a, mut b) = (Histogram::new(100), Histogram::new(100));
+        a.add(5);
+        b.add(5);
+        b.add(100);
+
+        let merged = a.merge(b);
+
+        assert_eq!(merged.buckets()[3], 2);
+        assert_eq!(merged.interior(), 1);
+    }
+
+    #[test]
+    fn limits_below_the_bucket_count() {
+        let mut histogram = Histogram::new(3);
+        for count in 0..=3 {
+            histogram.add(count);
+        }
+
+        assert_eq!(histogram.total(), 4);
+        assert_eq!(histogram.interior(), 1);
+    }
+
+    #[test]
+    fn histograms_agree() {
+        let viewport = Settings::default().validate().unwrap();
+        let size = viewport.width_px() * viewport.height_px();
+        let map = |a: usize, b: usize| viewport.pixel_to_complex(a as f64, b as f64);
+
+        let mut vals = vec![0; size];
+        let parallel = Formula::Mandelbrot.compute_parallel(&mut vals, viewport.width_px(), map, ITERATIONS);
+        let sequential = Formula::Mandelbrot.compute_sequential(&mut vals, viewport.width_px(), map, ITERATIONS);
+
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel.total(), size as u64);
+        assert_eq!(parallel.interior(), vals.iter().filter(|&&count| count == ITERATIONS).count() as u64);
+    }
+}
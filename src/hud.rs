@@ -0,0 +1,303 @@
+//! [HUD]
+//!
+//! The heads-up display toggled with D: where the zoom is, how deep it
+//! has gone, and how quickly frames are being computed and shown.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::area::AreaEstimate;
+use crate::clock::clock_time;
+use crate::overlay::{text_box_size, Overlay};
+use crate::pause::Pause;
+use crate::progress::Phase;
+use crate::viewport::Viewport;
+
+/// [Rate Meter]
+/// Measures how often something happens, averaged over roughly the
+/// last second.
+///
+/// Fields:
+/// [since] The start of the current measurement, once anything has happened;
+/// [count] How many times it has happened since then;
+/// [rate] The rate over the last complete measurement, per second.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RateMeter {
+    since: Option<Instant>,
+    count: u32,
+    rate: f64,
+}
+
+impl RateMeter {
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// [Tick]
+    /// Records that it happened at `now`.
+    pub fn tick(&mut self, now: Instant) {
+        let Some(since) = self.since else {
+            self.since = Some(now);
+            return;
+        };
+
+        self.count += 1;
+        let elapsed = now.duration_since(since);
+        if elapsed >= Duration::from_secs(1) {
+            self.rate = self.count as f64 / elapsed.as_secs_f64();
+            self.count = 0;
+            self.since = Some(now);
+        }
+    }
+}
+
+/// [Frame Times]
+/// How long the escape-time pass took, for the last frame and on
+/// average over the last `WINDOW` frames.
+#[derive(Clone, Debug, Default)]
+pub struct FrameTimes {
+    recent: VecDeque<Duration>,
+}
+
+impl FrameTimes {
+    pub const WINDOW: usize = 30;
+
+    pub fn record(&mut self, elapsed: Duration) {
+        if self.recent.len() == Self::WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(elapsed);
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.recent.back().copied()
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        let total: Duration = self.recent.iter().sum();
+        (!self.recent.is_empty()).then(|| total / self.recent.len() as u32)
+    }
+
+    /// [Summary]
+    /// The last and average times in milliseconds, as "12.3 ms (avg 11.8 ms)".
+    pub fn summary(&self) -> String {
+        match (self.last(), self.average()) {
+            (Some(last), Some(average)) => format!("{:.1} ms (avg {:.1} ms)", millis(last), millis(average)),
+            _ => "-".to_string(),
+        }
+    }
+}
+
+/// A duration in (fractional) milliseconds.
+pub fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// [Magnification]
+/// A magnification for display: plain up to a million, and in
+/// scientific notation beyond, where the digits stop being readable.
+pub fn magnification(value: f64) -> String {
+    if value < 1e6 {
+        format!("{value:.1}x")
+    } else {
+        format!("{value:.2e}x")
+    }
+}
+
+/// [Window Title]
+/// The essentials of the zoom on one line, for the window title.
+pub fn window_title(stats: &HudStats, pause: Option<Pause>) -> String {
+    let mut title = format!(
+        "Mandelbrot | {} | limit {} | {} | {} frame {}",
+        magnification(stats.initial_width / stats.viewport.width()),
+        stats.limit,
+        stats.compute.summary(),
+        clock_time(stats.running),
+        stats.frames,
+    );
+    if stats.speed > 1 {
+        title.push_str(&format!(" | {}x", stats.speed));
+    }
+    if let Some(phase) = stats.progress {
+        title.push_str(" | ");
+        title.push_str(&phase.label());
+    }
+    if let Some(pause) = pause {
+        title.push_str(" | ");
+        title.push_str(pause.label());
+    }
+
+    title
+}
+
+/// [HUD]
+///
+/// Fields:
+/// [visible] Whether the HUD is shown;
+/// [fps] Frames presented per second;
+/// [ups] Updates per second.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Hud {
+    pub visible: bool,
+    pub fps: RateMeter,
+    pub ups: RateMeter,
+}
+
+/// [HUD Stats]
+/// What the HUD reports about the zoom itself.
+///
+/// Fields:
+/// [viewport] The current view;
+/// [initial_width] The width of the view the zoom started from;
+/// [limit] The iteration limit;
+/// [frames] How many frames have been computed;
+/// [speed] How many zoom steps each of them takes;
+/// [progress] How far the next has got, if it is slow enough to say;
+/// [compute] How long they took to compute;
+/// [total] Time since the zoom started;
+/// [running] The same, less time spent paused;
+/// [area] The estimated area of the set in view.
+#[derive(Clone, Copy, Debug)]
+pub struct HudStats<'a> {
+    pub viewport: &'a Viewport,
+    pub initial_width: f64,
+    pub limit: u32,
+    pub frames: u64,
+    pub speed: u32,
+    pub progress: Option<Phase>,
+    pub compute: &'a FrameTimes,
+    pub total: chrono::Duration,
+    pub running: chrono::Duration,
+    pub area: AreaEstimate,
+}
+
+impl HudStats<'_> {
+    /// Frames computed per second of running time.
+    pub fn average_ups(&self) -> f64 {
+        match self.running.num_milliseconds() {
+            0 => 0.0,
+            ms => self.frames as f64 * 1000.0 / ms as f64,
+        }
+    }
+}
+
+impl Hud {
+    /// [Lines]
+    /// The HUD's text, one entry per line.
+    pub fn lines(&self, stats: &HudStats) -> Vec<String> {
+        let centre = stats.viewport.centre();
+        let speed = if stats.speed > 1 { format!(" at {}x", stats.speed) } else { String::new() };
+
+        vec![
+            format!("zoom   {:.3e}x", stats.initial_width / stats.viewport.width()),
+            format!("re     {:+.16}", centre.re),
+            format!("im     {:+.16}", centre.im),
+            format!("limit  {}", stats.limit),
+            format!("frames {} (~{:.1} ups avg){speed}", stats.frames, stats.average_ups()),
+            format!("time   {} (running {})", clock_time(stats.total), clock_time(stats.running)),
+            format!("fps    {:.1}  ups {:.1}", self.fps.rate(), self.ups.rate()),
+            format!("calc   {}", stats.compute.summary()),
+            format!("area   {:.4e} +/- {:.1e}", stats.area.area, stats.area.uncertainty),
+        ]
+    }
+
+    /// [Draw]
+    /// Adds the HUD to the overlay, if it is visible.
+    pub fn draw(&self, overlay: &mut Overlay, stats: &HudStats) {
+        if self.visible {
+            overlay.text_box(&self.lines(stats), [4.0, 4.0], 11.0);
+        }
+    }
+
+    /// [Bounds]
+    /// The rectangle the HUD covers, as [x, y, width, height], if it is
+    /// visible, for laying other things out around it.
+    pub fn bounds(&self, stats: &HudStats) -> Option<[f64; 4]> {
+        let [width, height] = text_box_size(&self.lines(stats), 11.0);
+        self.visible.then_some([4.0, 4.0, width, height])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::complex::Complex as cmp;
+
+    #[test]
+    fn rate_meter_counts_per_second() {
+        let start = Instant::now();
+        let mut meter = RateMeter::default();
+
+        for i in 0..=120 {
+            meter.tick(start + Duration::from_millis(i * 1000 / 60));
+        }
+
+        assert!((meter.rate() - 60.0).abs() < 1.0, "{}", meter.rate());
+    }
+
+    #[test]
+    fn magnification_is_relative_to_the_start() {
+        let viewport = Viewport::new(cmp::new(-0.5, 0.0), 4.0e-6, 400, 200);
+        let compute = FrameTimes::default();
+        let stats = HudStats {
+            viewport: &viewport,
+            initial_width: 4.0,
+            limit: 1200,
+            frames: 7,
+            speed: 1,
+            progress: None,
+            compute: &compute,
+            total: chrono::Duration::seconds(3),
+            running: chrono::Duration::seconds(2),
+            area: AreaEstimate { area: 0.25, uncertainty: 0.01 },
+        };
+
+        let lines = Hud::default().lines(&stats);
+
+        assert_eq!(lines[0], "zoom   1.000e6x");
+        assert_eq!(lines[4], "frames 7 (~3.5 ups avg)");
+        assert_eq!(Hud::default().lines(&HudStats { speed: 4, ..stats })[4], "frames 7 (~3.5 ups avg) at 4x");
+        assert_eq!(lines[5], "time   00:00:03 (running 00:00:02)");
+        assert_eq!(lines[8], "area   2.5000e-1 +/- 1.0e-2");
+    }
+
+    #[test]
+    fn title_switches_to_scientific() {
+        let compute = FrameTimes::default();
+        let shallow = Viewport::new(cmp::new(-0.5, 0.0), 0.4, 400, 200);
+        let deep = Viewport::new(cmp::new(-0.5, 0.0), 4.0e-9, 400, 200);
+
+        let title = |viewport, speed, progress, pause| window_title(&HudStats {
+            viewport,
+            initial_width: 4.0,
+            limit: 1200,
+            frames: 2714,
+            speed,
+            progress,
+            compute: &compute,
+            total: chrono::Duration::seconds(300),
+            running: chrono::Duration::seconds(271),
+            area: AreaEstimate::default(),
+        }, pause);
+
+        assert_eq!(title(&shallow, 1, None, None), "Mandelbrot | 10.0x | limit 1200 | - | 00:04:31 frame 2714");
+        assert_eq!(title(&deep, 1, None, Some(Pause::User)), "Mandelbrot | 1.00e9x | limit 1200 | - | 00:04:31 frame 2714 | paused");
+        assert_eq!(title(&shallow, 8, None, None), "Mandelbrot | 10.0x | limit 1200 | - | 00:04:31 frame 2714 | 8x");
+        assert_eq!(title(&shallow, 1, Some(Phase::Counts(0.42)), None), "Mandelbrot | 10.0x | limit 1200 | - | 00:04:31 frame 2714 | computing 42%");
+    }
+
+    #[test]
+    fn frame_times_average_the_window() {
+        let mut times = FrameTimes::default();
+        assert_eq!(times.summary(), "-");
+
+        for ms in 1..=40 {
+            times.record(Duration::from_millis(ms));
+        }
+
+        // Only 11..=40 remain, averaging 25.5 ms.
+        assert_eq!(times.last(), Some(Duration::from_millis(40)));
+        assert_eq!(times.average(), Some(Duration::from_micros(25_500)));
+        assert_eq!(times.summary(), "40.0 ms (avg 25.5 ms)");
+    }
+}
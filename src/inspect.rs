@@ -0,0 +1,208 @@
+//! [Inspect]
+//!
+//! The data behind the pixel under the cursor: the point it sampled,
+//! its iteration count, whether it counted as interior, and for an
+//! interior point the period of the cycle its orbit settles into, which
+//! is the period of the bulb or minibrot it is in. Shown beside the
+//! cursor while hovering, toggled with I, and printed to the terminal
+//! on a click while the zoom is paused, for checking the colouring
+//! against the counts and for picking exact coordinates.
+//!
+//! The cycle is found by iterating past the transient, then looking for
+//! the first iterate after it that comes back to within a hair of where
+//! it started. Both steps are capped, so it takes the same bounded time
+//! for each hover however high the limit. Near the edge of a component
+//! the orbit closes in on its cycle too slowly to come back that close
+//! in time, and the period is given as unsettled rather than guessed.
+
+use num::complex::Complex as cmp;
+
+use crate::fractal::Formula;
+use crate::overlay::{text_box_size, Overlay};
+use crate::viewport::Viewport;
+
+/// How far the readout sits from the cursor.
+const OFFSET: f64 = 12.0;
+
+/// The most iterations taken to let an orbit settle before its period is looked for.
+const MAX_TRANSIENT: u32 = 10_000;
+
+/// The longest cycle looked for.
+const MAX_PERIOD: u32 = 1024;
+
+/// How close an iterate must come back to count as a cycle, relative to
+/// its size where that is over 1.
+const EPSILON: f64 = 1e-9;
+
+/// [Period]
+///
+/// Variants:
+/// [Cycle] The orbit settles into a cycle of this period;
+/// [Escaping] The orbit escapes, so there is no cycle;
+/// [Unsettled] No cycle was found in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Cycle(u32),
+    Escaping,
+    Unsettled,
+}
+
+impl Period {
+    /// [Of]
+    /// The period of the cycle the orbit of `point` settles into, under
+    /// the formula, iterating it no further past the limit than the
+    /// longest cycle looked for, however high the limit.
+    pub fn of(formula: Formula, point: cmp<f64>, limit: u32) -> Period {
+        let transient = limit.min(MAX_TRANSIENT);
+        let (points, escaped) = formula.orbit(point, transient + MAX_PERIOD);
+        if escaped {
+            return Period::Escaping;
+        }
+
+        let start = points[transient as usize];
+        let near = EPSILON * start.norm().max(1.0);
+        (1..=MAX_PERIOD)
+            .find(|&p| (points[(transient + p) as usize] - start).norm() < near)
+            .map_or(Period::Unsettled, Period::Cycle)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Period::Cycle(p) => format!("period {p}"),
+            Period::Escaping => "period ∞ (escaping)".to_string(),
+            Period::Unsettled => "period ? (not settled)".to_string(),
+        }
+    }
+}
+
+/// [Pixel Info]
+///
+/// Fields:
+/// [pixel] The pixel's column and row in the frame;
+/// [point] The point of the plane its count was computed for;
+/// [count] Its iteration count;
+/// [interior] Whether the count reached the limit;
+/// [period] The period of the cycle its orbit settles into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelInfo {
+    pub pixel: [usize; 2],
+    pub point: cmp<f64>,
+    pub count: u32,
+    pub interior: bool,
+    pub period: Period,
+}
+
+impl PixelInfo {
+    /// [At]
+    /// The data of the pixel containing the frame point `at`, or None
+    /// off the frame. The cursor arrives in frame pixels, whatever size
+    /// the window is, so the pixel is just the cell the point falls in;
+    /// its count sampled the cell's top-left corner, the point being the
+    /// one iterated there, under the view's projection. A pixel that
+    /// escaped within the limit is not iterated again for its period.
+    pub fn at(formula: Formula, viewport: &Viewport, vals: &[u32], limit: u32, at: [f64; 2]) -> Option<PixelInfo> {
+        let [x, y] = at;
+        if !(x >= 0.0 && y >= 0.0) {
+            return None;
+        }
+
+        let (a, b) = (x as usize, y as usize);
+        if a >= viewport.width_px() || b >= viewport.height_px() {
+            return None;
+        }
+
+        let count = *vals.get(b * viewport.width_px() + a)?;
+        let point = viewport.sample(a as f64, b as f64);
+        let interior = count >= limit;
+        Some(PixelInfo {
+            pixel: [a, b],
+            point,
+            count,
+            interior,
+            period: if interior { Period::of(formula, point, limit) } else { Period::Escaping },
+        })
+    }
+
+    /// The readout, with the point at full f64 precision.
+    pub fn lines(&self) -> [String; 5] {
+        [
+            format!("pixel {} {}", self.pixel[0], self.pixel[1]),
+            format!("re    {}", self.point.re),
+            format!("im    {}", self.point.im),
+            format!("count {}{}", self.count, if self.interior { " (interior)" } else { "" }),
+            self.period.label(),
+        ]
+    }
+}
+
+/// [Inspector]
+///
+/// Fields:
+/// [visible] Whether the readout follows the cursor.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Inspector {
+    pub visible: bool,
+}
+
+impl Inspector {
+    /// [Draw]
+    /// Adds the readout beside the cursor, if it is visible, moved to
+    /// the other side of the cursor where it would leave the frame.
+    pub fn draw(&self, overlay: &mut Overlay, viewport: &Viewport, at: [f64; 2], info: &PixelInfo) {
+        if !self.visible {
+            return;
+        }
+
+        let lines = info.lines();
+        let [x, y] = at;
+        let [box_width, box_height] = text_box_size(&lines, 9.0);
+        let (width, height) = (viewport.width_px() as f64, viewport.height_px() as f64);
+        let left = if x + OFFSET + box_width <= width { x + OFFSET } else { x - OFFSET - box_width };
+        let top = if y + OFFSET + box_height <= height { y + OFFSET } else { y - OFFSET - box_height };
+        overlay.text_box(&lines, [left, top], 9.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_the_pixel_under_the_point() {
+        let viewport = Viewport::new(cmp::new(0.0, 0.0), 4.0, 4, 2);
+        let vals = [0, 1, 2, 3, 4, 5, 6, 100];
+
+        let info = PixelInfo::at(Formula::Mandelbrot, &viewport, &vals, 100, [3.7, 1.2]).unwrap();
+
+        assert_eq!(info.pixel, [3, 1]);
+        assert_eq!(info.count, 100);
+        assert!(info.interior);
+        assert_eq!(info.point, viewport.pixel_to_complex(3.0, 1.0));
+        let outside = PixelInfo::at(Formula::Mandelbrot, &viewport, &vals, 100, [0.5, 0.5]).unwrap();
+        assert!(!outside.interior);
+        assert_eq!(outside.lines()[4], "period ∞ (escaping)");
+    }
+
+    #[test]
+    fn finds_the_period_of_the_bulb() {
+        let period = |re, im| Period::of(Formula::Mandelbrot, cmp::new(re, im), 1000);
+
+        assert_eq!(period(0.0, 0.0), Period::Cycle(1));
+        assert_eq!(period(-1.0, 0.0), Period::Cycle(2));
+        assert_eq!(period(-0.122, 0.745), Period::Cycle(3));
+        assert_eq!(period(-1.755, 0.0), Period::Cycle(3));
+        assert_eq!(period(0.5, 0.5), Period::Escaping);
+        // Right on the cusp the orbit creeps toward its fixed point.
+        assert_eq!(period(0.25, 0.0), Period::Unsettled);
+    }
+
+    #[test]
+    fn nothing_off_the_frame() {
+        let viewport = Viewport::new(cmp::new(0.0, 0.0), 4.0, 4, 2);
+        let vals = [0; 8];
+
+        for at in [[-0.1, 0.0], [4.0, 0.0], [0.0, 2.0], [f64::NAN, 0.0]] {
+            assert_eq!(PixelInfo::at(Formula::Mandelbrot, &viewport, &vals, 100, at), None, "{at:?}");
+        }
+    }
+}
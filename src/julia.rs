@@ -0,0 +1,143 @@
+//! [Julia]
+//!
+//! A small preview of the Julia set for the point under the cursor,
+//! toggled with J and drawn in the bottom-left corner. Each point of
+//! the Mandelbrot set has a connected Julia set and each point outside
+//! it a dust, so moving the cursor across the boundary shows how the
+//! two sets relate. The preview is cheap enough (a fixed low limit at
+//! thumbnail size) to follow the cursor at the render rate.
+
+use std::sync::Arc;
+
+use num::complex::Complex as cmp;
+
+use crate::colour::{colourise, Gradient, LegacyColorizer, Tone};
+use crate::fractal::Julia;
+use crate::kernel;
+use crate::overlay::{Bitmap, Overlay, Shape};
+use crate::viewport::Viewport;
+
+/// The preview's size in pixels.
+const PREVIEW_WIDTH: usize = 160;
+const PREVIEW_HEIGHT: usize = 120;
+
+/// Enough iterations to show the shape of a Julia set at preview size.
+const PREVIEW_LIMIT: u32 = 100;
+
+/// How much of the plane the preview covers; every quadratic Julia set
+/// that is not dust lies within |z| <= 2.
+const PREVIEW_SPAN: f64 = 4.0;
+
+/// [Julia Preview]
+///
+/// Fields:
+/// [visible] Whether the preview is shown;
+/// [parameter] The point the preview was last computed for;
+/// [tone] The tone it was last coloured in;
+/// [bitmap] The preview, once it has been computed.
+#[derive(Clone, Debug, Default)]
+pub struct JuliaPreview {
+    pub visible: bool,
+    parameter: Option<cmp<f64>>,
+    tone: Tone,
+    bitmap: Option<Arc<Bitmap>>,
+}
+
+impl JuliaPreview {
+    pub fn parameter(&self) -> Option<cmp<f64>> {
+        self.parameter
+    }
+
+    /// [Set Parameter]
+    /// Recomputes the preview for a new parameter, coloured in the tone
+    /// the frame is, if it is visible and the parameter or the tone has
+    /// changed. Hidden previews cost nothing.
+    pub fn set_parameter(&mut self, c: cmp<f64>, tone: Tone) {
+        if !self.visible || (self.parameter, self.tone) == (Some(c), tone) {
+            return;
+        }
+
+        let view = Viewport::new(cmp::new(0.0, 0.0), PREVIEW_SPAN * 4.0 / 3.0, PREVIEW_WIDTH, PREVIEW_HEIGHT);
+        let mut vals = vec![0; PREVIEW_WIDTH * PREVIEW_HEIGHT];
+        kernel::compute_parallel(&Julia { c }, &mut vals, PREVIEW_WIDTH, |a, b| view.pixel_to_complex(a as f64, b as f64), PREVIEW_LIMIT);
+        let rgba = colourise(&LegacyColorizer { scalar: 2.0, gradient: Gradient::default() }, &vals, PREVIEW_LIMIT, tone);
+
+        (self.parameter, self.tone) = (Some(c), tone);
+        self.bitmap = Some(Arc::new(Bitmap { width: PREVIEW_WIDTH, height: PREVIEW_HEIGHT, rgba }));
+    }
+
+    /// [Draw]
+    /// Adds the preview to the bottom-left corner of a frame of the
+    /// given size, labelled with its parameter, if it is visible and has
+    /// been computed.
+    pub fn draw(&self, overlay: &mut Overlay, frame_width: f64, frame_height: f64) {
+        let (true, Some(bitmap), Some(c)) = (self.visible, &self.bitmap, self.parameter) else { return };
+
+        let width = (frame_width / 4.0).min(PREVIEW_WIDTH as f64);
+        let height = width * PREVIEW_HEIGHT as f64 / PREVIEW_WIDTH as f64;
+        let (left, top) = (4.0, frame_height - height - 4.0);
+
+        overlay.push(Shape::Image { bitmap: bitmap.clone(), rect: [left, top, width, height] });
+        overlay.push(Shape::Text {
+            text: format!("julia {:+.4} {:+.4}i", c.re, c.im),
+            at: [left + 2.0, top + 9.0],
+            size: 8.0,
+            colour: [1.0, 1.0, 1.0, 1.0],
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_recomputes_for_a_new_parameter() {
+        let mut preview = JuliaPreview { visible: true, ..JuliaPreview::default() };
+
+        preview.set_parameter(cmp::new(-0.8, 0.156), Tone::Srgb);
+        let first = preview.bitmap.clone().unwrap();
+        preview.set_parameter(cmp::new(-0.8, 0.156), Tone::Srgb);
+        assert!(Arc::ptr_eq(&first, preview.bitmap.as_ref().unwrap()));
+
+        preview.set_parameter(cmp::new(0.285, 0.01), Tone::Srgb);
+        assert!(!Arc::ptr_eq(&first, preview.bitmap.as_ref().unwrap()));
+        assert_eq!(preview.parameter(), Some(cmp::new(0.285, 0.01)));
+    }
+
+    #[test]
+    fn colours_in_the_frames_tone() {
+        let mut preview = JuliaPreview { visible: true, ..JuliaPreview::default() };
+        preview.set_parameter(cmp::new(-0.8, 0.156), Tone::Srgb);
+        let srgb = preview.bitmap.clone().unwrap();
+
+        // The same parameter in another tone is coloured again.
+        preview.set_parameter(cmp::new(-0.8, 0.156), Tone::Legacy);
+        let legacy = preview.bitmap.clone().unwrap();
+        assert!(!Arc::ptr_eq(&srgb, &legacy));
+        assert!(srgb.rgba != legacy.rgba);
+    }
+
+    #[test]
+    fn hidden_computes_and_draws_nothing() {
+        let mut preview = JuliaPreview::default();
+        preview.set_parameter(cmp::new(-0.8, 0.156), Tone::Srgb);
+
+        let mut overlay = Overlay::new();
+        preview.draw(&mut overlay, 400.0, 200.0);
+
+        assert_eq!(preview.parameter(), None);
+        assert!(overlay.is_empty());
+    }
+
+    #[test]
+    fn julia_of_zero_is_the_unit_disc() {
+        let julia = Julia { c: cmp::new(0.0, 0.0) };
+        let limit = 50;
+
+        assert_eq!(kernel::escape_time(&julia, cmp::new(0.5, 0.5), limit), limit);
+        assert_eq!(kernel::escape_time(&julia, cmp::new(0.0, -0.99), limit), limit);
+        assert!(kernel::escape_time(&julia, cmp::new(1.01, 0.0), limit) < limit);
+        assert_eq!(kernel::escape_time(&julia, cmp::new(1.5, 0.0), limit), 1);
+    }
+}
@@ -0,0 +1,307 @@
+//! [Kernel]
+//!
+//! The escape-time loops. Both kernels fill a row-major buffer of
+//! iteration counts, `width` values per row, using a mapping from
+//! pixel coordinates (a, b) to points on the complex plane. The
+//! scalar type of the mapping decides the precision of the loop.
+//! Both also return a histogram of the counts they wrote, which costs
+//! next to nothing while each count is still at hand, and the parallel
+//! one can count the rows it has done for a frame's progress, and take
+//! them nearest a given row first, for the part of the frame under the
+//! cursor to come in before the rest. A third kernel keeps each
+//! orbit's Lyapunov exponent or its smooth count alongside its count,
+//! or both, what it takes being left out of the other two's loop
+//! altogether.
+//!
+//! There are no threads on wasm32, so there the parallel kernel runs
+//! its rows on the calling thread instead.
+
+use num::complex::Complex as cmp;
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+use crate::fractal::Fractal;
+use crate::histogram::Histogram;
+use crate::progress::Progress;
+use crate::real::Real;
+
+/// The fewest scattered points a rayon task is given, about a row's worth,
+/// so that cheap points are not handed out one at a time.
+#[cfg(not(target_arch = "wasm32"))]
+const POINT_CHUNK: usize = 256;
+
+/// [Escape Time]
+/// Iterates the formula for the point c until its orbit escapes or
+/// the limit is reached, returning the number of iterations taken.
+/// Points which never escape report exactly the limit.
+#[inline(always)]
+pub fn escape_time<T: Real, F: Fractal<T>>(fractal: &F, c: cmp<T>, limit: u32) -> u32 {
+    escape_stats::<T, F, false, false>(fractal, c, limit).0
+}
+
+/// [Escape Stats]
+/// The loop of escape_time, which with LYAPUNOV also sums ln|2z| over
+/// the states the orbit passes through, returning the count, the sum
+/// over the count and the smooth count: the orbit's Lyapunov exponent,
+/// every formula's step stretching by |2z| as z^2 + c does. With SMOOTH
+/// the smooth count of an escaping orbit is its count made continuous,
+/// n + 1 - log2(ln|z|), by how far past the radius it ended, every
+/// formula squaring |z| a step; an orbit that stays has the limit.
+/// Without them the exponent and the smooth count are 0, and what they
+/// take is compiled out.
+#[inline(always)]
+pub fn escape_stats<T: Real, F: Fractal<T>, const LYAPUNOV: bool, const SMOOTH: bool>(fractal: &F, c: cmp<T>, limit: u32) -> (u32, f32, f32) {
+    let mut state = fractal.init(c);
+    let mut count = 0;
+    let mut sum = 0.0;
+
+    while count < limit {
+        fractal.step(&mut state, c);
+        count += 1;
+
+        if LYAPUNOV {
+            // An orbit landing on 0 would sum to minus infinity.
+            sum += (2.0 * fractal.z(&state).norm().to_f64()).max(f64::MIN_POSITIVE).ln();
+        }
+        if fractal.escaped(&state) {
+            break;
+        }
+    }
+
+    let exponent = if LYAPUNOV && count > 0 { (sum / count as f64) as f32 } else { 0.0 };
+    let smooth = if !SMOOTH {
+        0.0
+    } else if fractal.escaped(&state) {
+        (count as f64 + 1.0 - fractal.z(&state).norm().to_f64().ln().log2()).max(0.0) as f32
+    } else {
+        limit as f32
+    };
+    (count, exponent, smooth)
+}
+
+/// [Orbit]
+/// The points of the orbit of c, from the first state to the one that
+/// escaped or the limit's, iterated step for step as escape_time does,
+/// so that there are always one more of them than the count, with
+/// whether the last escaped.
+pub fn orbit<T: Real, F: Fractal<T>>(fractal: &F, c: cmp<T>, limit: u32) -> (Vec<cmp<T>>, bool) {
+    let mut state = fractal.init(c);
+    let mut points = vec![fractal.z(&state)];
+
+    while points.len() <= limit as usize {
+        fractal.step(&mut state, c);
+        points.push(fractal.z(&state));
+
+        if fractal.escaped(&state) {
+            return (points, true);
+        }
+    }
+
+    (points, false)
+}
+
+/// [Compute Parallel]
+/// Fills vals one row per rayon task. Each thread keeps its own
+/// histogram, and they are merged once the rows are done.
+pub fn compute_parallel<T, F, M>(fractal: &F, vals: &mut [u32], width: usize, map: M, limit: u32) -> Histogram
+where
+    T: Real,
+    F: Fractal<T>,
+    M: Fn(usize, usize) -> cmp<T> + Sync,
+{
+    compute_parallel_counted(fractal, vals, width, map, limit, &Progress::default(), None)
+}
+
+/// [Compute Parallel Counted]
+/// The parallel kernel, counting each row on `progress` as it is done.
+/// Once the frame is cancelled, the rows not yet started are left as
+/// they were, so the counts are only whole if it never was.
+/// Given a row to start from, the rows are handed out from a queue in
+/// order of their distance from it, rather than split between threads
+/// top to bottom, so that that part of the frame is done first. The
+/// counts come out the same either way.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn compute_parallel_counted<T, F, M>(fractal: &F, vals: &mut [u32], width: usize, map: M, limit: u32, progress: &Progress, first: Option<usize>) -> Histogram
+where
+    T: Real,
+    F: Fractal<T>,
+    M: Fn(usize, usize) -> cmp<T> + Sync,
+{
+    let fill = |mut histogram: Histogram, (b, row): (usize, &mut [u32])| {
+        if progress.is_cancelled() {
+            return histogram;
+        }
+        for (a, val) in row.iter_mut().enumerate() {
+            *val = escape_time(fractal, map(a, b), limit);
+            histogram.add(*val);
+        }
+        progress.row();
+        histogram
+    };
+
+    match first {
+        None => vals.par_chunks_mut(width).enumerate().fold(|| Histogram::new(limit), fill).reduce(|| Histogram::new(limit), Histogram::merge),
+        Some(first) => {
+            let mut rows: Vec<_> = vals.chunks_mut(width).enumerate().collect();
+            rows.sort_by_key(|&(b, _)| b.abs_diff(first));
+            rows.into_iter().par_bridge().fold(|| Histogram::new(limit), fill).reduce(|| Histogram::new(limit), Histogram::merge)
+        }
+    }
+}
+
+/// [Compute Parallel Counted]
+/// Fills vals on the calling thread, as wasm32 has no rayon, counting
+/// the rows once they are all done, from the top whatever the first.
+#[cfg(target_arch = "wasm32")]
+pub fn compute_parallel_counted<T, F, M>(fractal: &F, vals: &mut [u32], width: usize, map: M, limit: u32, progress: &Progress, _first: Option<usize>) -> Histogram
+where
+    T: Real,
+    F: Fractal<T>,
+    M: Fn(usize, usize) -> cmp<T> + Sync,
+{
+    let histogram = compute_sequential(fractal, vals, width, map, limit);
+    progress.rows.fetch_add(vals.len() / width.max(1), std::sync::atomic::Ordering::Relaxed);
+    histogram
+}
+
+/// [Compute Stats Counted]
+/// The parallel kernel, filling stats with each pixel's Lyapunov
+/// exponent and smooth count, as escape_stats finds them, as it fills
+/// vals, and counting the rows on `progress`. The rows are split
+/// between threads top to bottom.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn compute_stats_counted<T, F, M, const LYAPUNOV: bool, const SMOOTH: bool>(fractal: &F, vals: &mut [u32], stats: &mut [(f32, f32)], width: usize, map: M, limit: u32, progress: &Progress) -> Histogram
+where
+    T: Real,
+    F: Fractal<T>,
+    M: Fn(usize, usize) -> cmp<T> + Sync,
+{
+    vals.par_chunks_mut(width)
+        .zip(stats.par_chunks_mut(width))
+        .enumerate()
+        .fold(|| Histogram::new(limit), |mut histogram, (b, (row, stats))| {
+            if progress.is_cancelled() {
+                return histogram;
+            }
+            for (a, (val, (exponent, smooth))) in row.iter_mut().zip(stats).enumerate() {
+                (*val, *exponent, *smooth) = escape_stats::<T, F, LYAPUNOV, SMOOTH>(fractal, map(a, b), limit);
+                histogram.add(*val);
+            }
+            progress.row();
+            histogram
+        })
+        .reduce(|| Histogram::new(limit), Histogram::merge)
+}
+
+/// [Compute Stats Counted]
+/// The same on the calling thread, as wasm32 has no rayon, counting the
+/// rows once they are all done.
+#[cfg(target_arch = "wasm32")]
+pub fn compute_stats_counted<T, F, M, const LYAPUNOV: bool, const SMOOTH: bool>(fractal: &F, vals: &mut [u32], stats: &mut [(f32, f32)], width: usize, map: M, limit: u32, progress: &Progress) -> Histogram
+where
+    T: Real,
+    F: Fractal<T>,
+    M: Fn(usize, usize) -> cmp<T> + Sync,
+{
+    let mut histogram = Histogram::new(limit);
+    for (i, (val, (exponent, smooth))) in vals.iter_mut().zip(stats).enumerate() {
+        (*val, *exponent, *smooth) = escape_stats::<T, F, LYAPUNOV, SMOOTH>(fractal, map(i % width, i / width), limit);
+        histogram.add(*val);
+    }
+    progress.rows.fetch_add(vals.len() / width.max(1), std::sync::atomic::Ordering::Relaxed);
+    histogram
+}
+
+/// [Compute Points Parallel]
+/// Fills vals with the counts of scattered points rather than a grid,
+/// `map` giving the point for each index, in parallel chunks.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn compute_points_parallel<T, F, M>(fractal: &F, vals: &mut [u32], map: M, limit: u32)
+where
+    T: Real,
+    F: Fractal<T>,
+    M: Fn(usize) -> cmp<T> + Sync,
+{
+    vals.par_iter_mut()
+        .with_min_len(POINT_CHUNK)
+        .enumerate()
+        .for_each(|(i, val)| *val = escape_time(fractal, map(i), limit));
+}
+
+/// [Compute Points Parallel]
+/// Fills vals with the counts of scattered points on the calling thread,
+/// as wasm32 has no rayon.
+#[cfg(target_arch = "wasm32")]
+pub fn compute_points_parallel<T, F, M>(fractal: &F, vals: &mut [u32], map: M, limit: u32)
+where
+    T: Real,
+    F: Fractal<T>,
+    M: Fn(usize) -> cmp<T> + Sync,
+{
+    for (i, val) in vals.iter_mut().enumerate() {
+        *val = escape_time(fractal, map(i), limit);
+    }
+}
+
+/// [Compute Sequential]
+/// Fills vals on the calling thread.
+pub fn compute_sequential<T, F, M>(fractal: &F, vals: &mut [u32], width: usize, map: M, limit: u32) -> Histogram
+where
+    T: Real,
+    F: Fractal<T>,
+    M: Fn(usize, usize) -> cmp<T>,
+{
+    let mut histogram = Histogram::new(limit);
+
+    for (b, row) in vals.chunks_mut(width).enumerate() {
+        for (a, val) in row.iter_mut().enumerate() {
+            *val = escape_time(fractal, map(a, b), limit);
+            histogram.add(*val);
+        }
+    }
+
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal::Formula;
+    use crate::progress::Phase;
+    use crate::settings::ITERATIONS;
+    use crate::viewport::Viewport;
+
+    #[test]
+    fn far_out_views_escape_at_once() {
+        // The whole set is a few pixels across, with |c| reaching past 100.
+        let viewport = Viewport::new(cmp::new(0.0, 0.0), 300.0, 300, 200);
+        let map = |a: usize, b: usize| viewport.pixel_to_complex(a as f64, b as f64);
+
+        for formula in Formula::ALL {
+            let mut vals = vec![0; 300 * 200];
+            formula.compute_parallel(&mut vals, 300, map, ITERATIONS);
+
+            for (i, &count) in vals.iter().enumerate() {
+                let c = map(i % 300, i / 300);
+                if c.norm() > 10.0 {
+                    assert_eq!(count, 1, "{} at {c}", formula.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rows_from_the_cursor_come_out_the_same() {
+        let viewport = Viewport::new(cmp::new(-0.745, 0.1), 0.02, 64, 48);
+        let map = |a: usize, b: usize| viewport.pixel_to_complex(a as f64, b as f64);
+        let (mut plain, mut ordered) = (vec![0; 64 * 48], vec![0; 64 * 48]);
+
+        let histogram = Formula::Mandelbrot.compute_parallel(&mut plain, 64, map, 500);
+        let progress = Progress::default();
+        let from_cursor = Formula::Mandelbrot.compute_parallel_counted(&mut ordered, 64, map, 500, &progress, Some(40));
+
+        assert!(plain == ordered);
+        assert_eq!(histogram, from_cursor);
+        assert_eq!(progress.phase(48), Phase::Counts(1.0));
+    }
+}
@@ -0,0 +1,186 @@
+//! [Legend]
+//!
+//! The strip along the bottom of the frame, toggled with K, showing
+//! which colour each iteration count gets: a gradient from no
+//! iterations to the limit, on a log scale as the colours change most
+//! among the low counts, with ticks at powers of ten, and a swatch of
+//! the interior's colour at the end. It is drawn from the same
+//! colorizer and tone as the frame, so it follows the colouring as it
+//! fades. It stays clear of the HUD, and out of screenshots unless
+//! Shift+K asks for it.
+
+use std::sync::Arc;
+
+use crate::colour::{Colorizer, IterResult, Tone};
+use crate::overlay::{Bitmap, Overlay, Shape, ADVANCE};
+
+/// How many colours the gradient is drawn with.
+const STEPS: usize = 256;
+
+/// The height of the gradient, and of the tick labels under it.
+const BAR: f64 = 8.0;
+const LABEL: f64 = 8.0;
+
+/// Space between the strip and the edges of the frame, and inside it.
+const MARGIN: f64 = 4.0;
+const PADDING: f64 = 3.0;
+
+/// The narrowest the strip is drawn at.
+const NARROWEST: f64 = 60.0;
+
+/// [Legend]
+///
+/// Fields:
+/// [visible] Whether the legend is drawn over the frame;
+/// [exported] Whether screenshots include it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Legend {
+    pub visible: bool,
+    pub exported: bool,
+}
+
+impl Legend {
+    /// [Draw]
+    /// Adds the legend along the bottom of a frame of the given size, if
+    /// it is visible, beside rather than over the box at `avoid`.
+    pub fn draw<C: Colorizer>(&self, overlay: &mut Overlay, colorizer: &C, tone: Tone, limit: u32, frame: [f64; 2], avoid: Option<[f64; 4]>) {
+        if self.visible {
+            draw_legend(overlay, colorizer, tone, limit, frame, avoid);
+        }
+    }
+}
+
+/// [Position]
+/// Where a count falls along the gradient, from 0 to 1.
+pub fn position(count: u32, limit: u32) -> f64 {
+    (count as f64).ln_1p() / (limit.max(1) as f64).ln_1p()
+}
+
+/// [Count At]
+/// The count shown at a position along the gradient.
+pub fn count_at(position: f64, limit: u32) -> u32 {
+    ((position * (limit.max(1) as f64).ln_1p()).exp_m1().round() as u32).min(limit)
+}
+
+/// [Ticks]
+/// The counts marked under the gradient: none, and the powers of ten
+/// below the limit.
+pub fn ticks(limit: u32) -> Vec<u32> {
+    std::iter::once(0)
+        .chain(std::iter::successors(Some(1u32), |&n| n.checked_mul(10)))
+        .take_while(|&n| n < limit)
+        .collect()
+}
+
+/// [Draw Legend]
+/// Adds the legend to the overlay whether or not it is toggled on, as
+/// screenshots need it drawn on its own.
+pub fn draw_legend<C: Colorizer>(overlay: &mut Overlay, colorizer: &C, tone: Tone, limit: u32, frame: [f64; 2], avoid: Option<[f64; 4]>) {
+    let [frame_width, frame_height] = frame;
+    let height = PADDING + BAR + 2.0 + LABEL + PADDING;
+    let top = frame_height - MARGIN - height;
+
+    // Beside the box to avoid, if the strip would run into it.
+    let left = match avoid {
+        Some([x, y, w, h]) if y < top + height && top < y + h && x < frame_width - MARGIN => (x + w + MARGIN).max(MARGIN),
+        _ => MARGIN,
+    };
+    let width = frame_width - MARGIN - left;
+    if width < NARROWEST {
+        return;
+    }
+
+    overlay.push(Shape::Rect { rect: [left, top, width, height], colour: [0.0, 0.0, 0.0, 0.6] });
+
+    let colour_of = |count: u32| tone.apply(colorizer.color(IterResult { count, limit }));
+    let swatch = BAR * 2.0;
+    let (bar_left, bar_top) = (left + PADDING, top + PADDING);
+    let bar_width = width - 3.0 * PADDING - swatch;
+
+    let rgba = (0..STEPS).flat_map(|i| colour_of(count_at(i as f64 / (STEPS - 1) as f64, limit))).collect();
+    let bitmap = Arc::new(Bitmap { width: STEPS, height: 1, rgba });
+    overlay.push(Shape::Image { bitmap, rect: [bar_left, bar_top, bar_width, BAR] });
+
+    // The interior, apart from the gradient, ringed so that black shows.
+    let swatch_left = bar_left + bar_width + PADDING;
+    let interior = colour_of(limit).map(|channel| channel as f32 / 255.0);
+    overlay.push(Shape::Rect { rect: [swatch_left, bar_top, swatch, BAR], colour: interior });
+    overlay.polygon(&[[swatch_left, bar_top], [swatch_left + swatch, bar_top], [swatch_left + swatch, bar_top + BAR], [swatch_left, bar_top + BAR]], 1.0, [1.0, 1.0, 1.0, 0.8]);
+
+    let label = |overlay: &mut Overlay, text: String, centre: f64| {
+        let half = text.chars().count() as f64 * LABEL * ADVANCE / 2.0;
+        overlay.push(Shape::Text { text, at: [centre - half, top + height - PADDING], size: LABEL, colour: [1.0, 1.0, 1.0, 1.0] });
+        centre + half
+    };
+
+    // Labels that would run into the last one drawn are left out.
+    let mut drawn_to = f64::NEG_INFINITY;
+    for count in ticks(limit) {
+        let x = bar_left + position(count, limit) * bar_width;
+        overlay.push(Shape::Line { from: [x, bar_top + BAR], to: [x, bar_top + BAR + 2.0], width: 1.0, colour: [1.0, 1.0, 1.0, 0.8] });
+
+        let text = count.to_string();
+        if x - text.chars().count() as f64 * LABEL * ADVANCE / 2.0 > drawn_to + LABEL * ADVANCE {
+            drawn_to = label(overlay, text, x);
+        }
+    }
+    label(overlay, "in".to_string(), swatch_left + swatch / 2.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colour::{Gradient, LegacyColorizer};
+
+    fn gradient(overlay: &Overlay) -> Arc<Bitmap> {
+        overlay.shapes().iter()
+            .find_map(|shape| match shape {
+                Shape::Image { bitmap, .. } => Some(bitmap.clone()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn positions_and_counts_agree() {
+        for count in [0, 1, 9, 10, 100, 1199, 1200] {
+            assert_eq!(count_at(position(count, 1200), 1200), count);
+        }
+        assert_eq!(ticks(1200), [0, 1, 10, 100, 1000]);
+        assert_eq!(ticks(10), [0, 1]);
+    }
+
+    #[test]
+    fn follows_the_colouring() {
+        let draw = |scalar: f32| {
+            let mut overlay = Overlay::new();
+            draw_legend(&mut overlay, &LegacyColorizer { scalar, gradient: Gradient::default() }, Tone::Srgb, 1200, [400.0, 200.0], None);
+            overlay
+        };
+        let (bright, faded) = (draw(2.0), draw(0.05));
+
+        assert_ne!(gradient(&bright).rgba, gradient(&faded).rgba);
+        // The gradient starts at no iterations, which is black either way.
+        assert_eq!(gradient(&bright).rgba[..4], [0, 0, 0, 255]);
+        // The interior swatch.
+        assert!(bright.shapes().contains(&Shape::Rect { rect: [400.0 - 4.0 - 3.0 - 16.0, 200.0 - 4.0 - 24.0 + 3.0, 16.0, 8.0], colour: [0.0, 0.0, 0.0, 1.0] }));
+    }
+
+    #[test]
+    fn keeps_clear_of_the_hud() {
+        let backing = |avoid: Option<[f64; 4]>| {
+            let mut overlay = Overlay::new();
+            draw_legend(&mut overlay, &LegacyColorizer { scalar: 1.0, gradient: Gradient::default() }, Tone::Srgb, 1200, [400.0, 200.0], avoid);
+            overlay.shapes().first().cloned()
+        };
+
+        // A HUD reaching all the way down pushes the strip to its right.
+        let Some(Shape::Rect { rect: [left, ..], .. }) = backing(Some([4.0, 4.0, 150.0, 196.0])) else { panic!() };
+        assert_eq!(left, 158.0);
+        // One that ends higher up leaves it where it was.
+        let Some(Shape::Rect { rect: [left, ..], .. }) = backing(Some([4.0, 4.0, 150.0, 100.0])) else { panic!() };
+        assert_eq!(left, MARGIN);
+        // And one too wide to get past hides it.
+        assert_eq!(backing(Some([4.0, 4.0, 360.0, 196.0])), None);
+    }
+}
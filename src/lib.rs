@@ -0,0 +1,143 @@
+/*****************************************************************/
+//! [Mandelbrot Core]
+/*****************************************************************/
+//!
+//! The Mandelbrot zoom, kept separate from the windowing code so
+//! that it can be benchmarked and driven without a window. Nothing
+//! in the library depends on Piston; the binary supplies backends.
+//!
+//! [antialias] Extra samples for the pixels along edges;
+//! [app]     The zoom's state, update step, and key handling;
+//! [area]    Estimates of the area of the set;
+//! [autolimit] Raising the iteration limit as the zoom outgrows it;
+//! [autopilot] Steering the zoom toward detail;
+//! [backend] The boundary to whatever presents frames;
+//! [batch]   Rendering a list of views to images (not on wasm32);
+//! [bench]   The canned zoom for tracking performance (not on wasm32);
+//! [bindings] Which key does what, and the file that changes it;
+//! [bookmark] Saved views, and the file they are kept in;
+//! [cli]     Command-line options;
+//! [clock]   Elapsed time, with pauses accounted for;
+//! [colour]  The mapping from iteration counts to colours;
+//! [crossfade] Blending from one slow frame into the next;
+//! [crosshair] The marker on the zoom target;
+//! [demo]    Replaying the original zoom for a while, and summing it up;
+//! [diff]    Comparing frames against a brute-force render;
+//! [error]   The application error type;
+//! [explore] The random search for new targets (not on wasm32);
+//! [export]  Writing frames out as images;
+//! [fit]     Fitting frames to windows of another shape;
+//! [follow]  A zoom that walks along the boundary;
+//! [fractal] The escape-time formulas, and the runtime selection
+//!           between them;
+//! [goto]    The prompt for going to exact coordinates;
+//! [graph]   The chart of recent compute times;
+//! [grid]    Gridlines at round coordinates;
+//! [heightmap] The 3D view of the counts as a surface;
+//! [histogram] The distribution of iteration counts, and its panel;
+//! [hud]     The heads-up display;
+//! [inspect] The data behind the pixel under the cursor;
+//! [julia]   The preview of the Julia set under the cursor;
+//! [kernel]  The sequential and parallel escape-time loops;
+//! [legend]  The strip showing which colour each count gets;
+//! [lighting] Slope shading, lighting the smooth counts as a height field;
+//! [lyapunov] Colouring by the orbits' Lyapunov exponents;
+//! [minimap] The thumbnail of the whole set, marking the current view;
+//! [nebula]  The Nebulabrot, orbit densities in three channels (not on wasm32);
+//! [orbit]   The orbit of a clicked point, and the CSV it is written to;
+//! [overlay] Shapes drawn over the frame by the backend;
+//! [palette] The colours as a lookup table, and the .map file it is exported to;
+//! [panel]   The side panel of sliders for tuning the zoom with the mouse;
+//! [pathlog] The log of every frame's view, and resuming from it;
+//! [perturb] Perturbation rendering, and putting its glitches right;
+//! [pause]   Why the zoom is paused, and the indicator saying so;
+//! [progress] How far a slow frame has got, and the bar showing it;
+//! [real]    The scalar types the kernel can compute in;
+//! [screensaver] Cycling through random dives (not on wasm32);
+//! [skip]    Interpolating the counts of pixels far from the boundary;
+//! [split]   The split view comparing two configurations side by side;
+//! [signals] Pausing, screenshots and stopping on Unix signals (Unix only);
+//! [screen]  Which monitor the window opens on, and where;
+//! [settings] Validated configuration and the original defaults;
+//! [stats]   Summary statistics of a frame's iteration counts;
+//! [tilecache] Reusing the counts of ground already covered;
+//! [tiles]   Rendering one large image a tile at a time (not on wasm32);
+//! [tour]    Visiting bookmarks in turn;
+//! [transition] Travelling smoothly from one view to another;
+//! [viewport] The mapping between pixels and the complex plane, and its projections;
+//! [watchdog] Checking the view after every step, and recovering it;
+//! [web]     The WebAssembly entry points (wasm32 only);
+//! [worker]  Computing frames off the event loop;
+//! [zoomer]  The zoom animation.
+/*****************************************************************/
+
+pub mod antialias;
+pub mod app;
+pub mod area;
+pub mod autolimit;
+pub mod autopilot;
+pub mod backend;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench;
+pub mod bindings;
+pub mod bookmark;
+pub mod cli;
+pub mod clock;
+pub mod colour;
+pub mod crossfade;
+pub mod crosshair;
+pub mod demo;
+pub mod diff;
+pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod explore;
+pub mod export;
+pub mod fit;
+pub mod follow;
+pub mod fractal;
+pub mod goto;
+pub mod graph;
+pub mod grid;
+pub mod heightmap;
+pub mod histogram;
+pub mod hud;
+pub mod inspect;
+pub mod julia;
+pub mod kernel;
+pub mod legend;
+pub mod lighting;
+pub mod lyapunov;
+pub mod minimap;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod nebula;
+pub mod orbit;
+pub mod overlay;
+pub mod palette;
+pub mod panel;
+pub mod pathlog;
+pub mod pause;
+pub mod perturb;
+pub mod progress;
+pub mod real;
+pub mod screen;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod screensaver;
+pub mod settings;
+#[cfg(unix)]
+pub mod signals;
+pub mod skip;
+pub mod split;
+pub mod stats;
+pub mod tilecache;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tiles;
+pub mod tour;
+pub mod transition;
+pub mod viewport;
+pub mod watchdog;
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+pub mod worker;
+pub mod zoomer;
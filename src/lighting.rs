@@ -0,0 +1,162 @@
+//! [Lighting]
+//!
+//! Slope shading, the "3D-lit" look: the smooth counts are taken as a
+//! height field, the log of each being its height, and each pixel is
+//! lit by a distant light as if the field were a surface, its normal
+//! found from the differences between the heights of its neighbours.
+//! The smooth counts are continuous where the counts step from band to
+//! band, so the surface is lit as a slope rather than as terraces; the
+//! kernel keeps them only while this is on, in an f32 per pixel beside
+//! the counts, so lit frames are iterated in full. The shade
+//! scales the pixel's colour in linear light, before the tone, so it goes
+//! with any colouring. Pixels on the edges of the buffer use the
+//! differences on their inner side only. Toggled with Shift+I, the light
+//! turned with Shift+J and Shift+L and raised and lowered with Shift+R
+//! and Shift+F; --light starts with it on, and P prints the direction as
+//! --light takes it, so that a render can be made again as it was lit.
+
+/// How many degrees each key turns or tilts the light by.
+pub const STEP: f64 = 15.0;
+
+/// How much of a colour is left on slopes facing away from the light.
+const AMBIENT: f32 = 0.3;
+
+/// How steep the field is, in heights per pixel per unit of log smooth count.
+const RELIEF: f64 = 4.0;
+
+/// [Light]
+///
+/// Fields:
+/// [enabled] Whether frames are shaded;
+/// [azimuth] The light's bearing in degrees, clockwise from the right of the frame;
+/// [elevation] Its height above the frame in degrees, 90 being straight on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    pub enabled: bool,
+    pub azimuth: f64,
+    pub elevation: f64,
+}
+
+impl Default for Light {
+    /// Lit from the top left, as relief maps are.
+    fn default() -> Light {
+        Light { enabled: false, azimuth: 225.0, elevation: 45.0 }
+    }
+}
+
+impl Light {
+    /// [Parse]
+    /// A light from 'AZIMUTH,ELEVATION' in degrees, as --light takes it.
+    pub fn parse(text: &str) -> Option<Light> {
+        let (azimuth, elevation) = text.split_once(',')?;
+        let (azimuth, elevation): (f64, f64) = (azimuth.trim().parse().ok()?, elevation.trim().parse().ok()?);
+        (azimuth.is_finite() && (0.0..=90.0).contains(&elevation))
+            .then(|| Light { enabled: true, azimuth: azimuth.rem_euclid(360.0), elevation })
+    }
+
+    /// The direction as --light takes it.
+    pub fn to_arg(&self) -> String {
+        format!("{},{}", self.azimuth, self.elevation)
+    }
+
+    /// Turns the light by `degrees` clockwise.
+    pub fn turn(&mut self, degrees: f64) {
+        self.azimuth = (self.azimuth + degrees).rem_euclid(360.0);
+    }
+
+    /// Raises the light by `degrees`, or lowers it, from the frame up to straight on.
+    pub fn raise(&mut self, degrees: f64) {
+        self.elevation = (self.elevation + degrees).clamp(0.0, 90.0);
+    }
+
+    /// The unit vector toward the light, x to the right of the frame, y
+    /// down it and z out of it.
+    fn direction(&self) -> [f64; 3] {
+        let (azimuth, elevation) = (self.azimuth.to_radians(), self.elevation.to_radians());
+        [elevation.cos() * azimuth.cos(), elevation.cos() * azimuth.sin(), elevation.sin()]
+    }
+
+    /// [Shades]
+    /// How much each pixel of a buffer of smooth counts, `width` pixels
+    /// per row, is lit, from AMBIENT on slopes facing away to 1 facing
+    /// the light, or None if frames are not shaded or there are no
+    /// smooth counts to shade from.
+    pub fn shades(&self, heights: &[f32], width: usize, limit: u32) -> Option<Vec<f32>> {
+        if !self.enabled || width == 0 || heights.is_empty() {
+            return None;
+        }
+        let height = heights.len() / width;
+        let [lx, ly, lz] = self.direction();
+        let at = |a: usize, b: usize| (heights[b * width + a].min(limit as f32) as f64).ln_1p();
+
+        let shade = |i: usize| {
+            let (a, b) = (i % width, i / width);
+            let (left, right) = (a.saturating_sub(1), (a + 1).min(width - 1));
+            let (up, down) = (b.saturating_sub(1), (b + 1).min(height - 1));
+            let dx = (at(right, b) - at(left, b)) / (right - left).max(1) as f64;
+            let dy = (at(a, down) - at(a, up)) / (down - up).max(1) as f64;
+
+            let normal = [-RELIEF * dx, -RELIEF * dy, 1.0];
+            let length = normal.iter().map(|n| n * n).sum::<f64>().sqrt();
+            let lit = (normal[0] * lx + normal[1] * ly + normal[2] * lz) / length;
+            AMBIENT + (1.0 - AMBIENT) * lit.max(0.0) as f32
+        };
+        Some((0..width * height).map(shade).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::complex::Complex as cmp;
+
+    use super::*;
+    use crate::fractal::Formula;
+    use crate::viewport::Viewport;
+
+    #[test]
+    fn lights_slopes_facing_the_light() {
+        // Counts rising to the right: the slope faces left.
+        let vals = [1.0, 10.0, 100.0, 1.0, 10.0, 100.0];
+        let from_left = Light { enabled: true, azimuth: 180.0, elevation: 30.0 };
+        let shades = from_left.shades(&vals, 3, 1000).unwrap();
+        let from_right = Light { azimuth: 0.0, ..from_left }.shades(&vals, 3, 1000).unwrap();
+
+        assert!(shades.iter().zip(&from_right).all(|(left, right)| left > right), "{shades:?} against {from_right:?}");
+        assert!(shades.iter().chain(&from_right).all(|shade| (AMBIENT..=1.0).contains(shade)));
+
+        // A flat field is lit by the height of the light alone.
+        let flat = Light { elevation: 90.0, ..from_left }.shades(&[7.0; 4], 2, 1000).unwrap();
+        assert_eq!(flat, vec![1.0; 4]);
+        assert_eq!(Light::default().shades(&vals, 3, 1000), None);
+        assert_eq!(from_left.shades(&[], 3, 1000), None);
+    }
+
+    #[test]
+    fn shades_bands_as_a_slope() {
+        // Within a band of counts, the counts alone are flat, where the
+        // smooth counts still slope.
+        let viewport = Viewport::new(cmp::new(-2.5, 0.0), 1.0, 64, 1);
+        let (mut vals, mut heights) = (vec![0; 64], Vec::new());
+        Formula::Mandelbrot.compute_heights(&mut vals, &mut heights, 64, |a, b| viewport.pixel_to_complex(a as f64, b as f64), 1000);
+        let light = Light { enabled: true, azimuth: 0.0, elevation: 30.0 };
+        let counts: Vec<f32> = vals.iter().map(|&count| count as f32).collect();
+        let (smooth, stepped) = (light.shades(&heights, 64, 1000).unwrap(), light.shades(&counts, 64, 1000).unwrap());
+
+        let bands: Vec<usize> = (1..63).filter(|&a| vals[a - 1] == vals[a] && vals[a] == vals[a + 1]).collect();
+        assert!(!bands.is_empty(), "{vals:?}");
+        assert!(bands.iter().all(|&a| stepped[a] == stepped[bands[0]]));
+        assert!(bands.iter().any(|&a| smooth[a] != stepped[a]), "{smooth:?}");
+    }
+
+    #[test]
+    fn parses_and_adjusts_the_direction() {
+        assert_eq!(Light::parse("-90, 30"), Some(Light { enabled: true, azimuth: 270.0, elevation: 30.0 }));
+        assert_eq!(Light::parse("90,91"), None);
+        assert_eq!(Light::parse("90"), None);
+
+        let mut light = Light::default();
+        light.turn(150.0);
+        light.raise(60.0);
+        assert_eq!(light.to_arg(), "15,90");
+    }
+}
@@ -0,0 +1,121 @@
+//! [Lyapunov]
+//!
+//! Colouring by the orbit's Lyapunov exponent, the average of ln|2z|
+//! over its iterations: how fast nearby orbits move apart. Escaping
+//! orbits have positive exponents, shown in warm colours brightening
+//! with it; orbits that stay have negative ones, the log of how hard
+//! their cycle attracts over its period, shown in cool colours
+//! brightening with its magnitude, so each bulb of the interior comes
+//! out in a shade of its own rather than all black. Exponents near 0,
+//! along the boundary, are dark. Each range has its own scale, being
+//! the exponent at which its colours are most of the way up.
+//!
+//! The kernel keeps the exponents only while this is on, in an f32 per
+//! pixel beside the counts. Toggled with Shift+Y; --lyapunov starts with
+//! it on, at the scales given, and P prints the scales as it takes them.
+
+use crate::colour::{shaded, Tone};
+
+/// [Lyapunov]
+///
+/// Fields:
+/// [enabled] Whether frames are coloured by their exponents;
+/// [exterior] The scale of the positive exponents of escaping orbits;
+/// [interior] The scale of the magnitudes of the negative exponents of orbits that stay.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lyapunov {
+    pub enabled: bool,
+    pub exterior: f32,
+    pub interior: f32,
+}
+
+impl Default for Lyapunov {
+    /// Scales that spread the exponents of the whole set across the colours.
+    fn default() -> Lyapunov {
+        Lyapunov { enabled: false, exterior: 0.6, interior: 0.4 }
+    }
+}
+
+impl Lyapunov {
+    /// [Parse]
+    /// Scales from 'EXTERIOR,INTERIOR', as --lyapunov takes them.
+    pub fn parse(text: &str) -> Option<Lyapunov> {
+        let (exterior, interior) = text.split_once(',')?;
+        let (exterior, interior): (f32, f32) = (exterior.trim().parse().ok()?, interior.trim().parse().ok()?);
+        [exterior, interior].iter().all(|scale| scale.is_finite() && *scale > 0.0)
+            .then_some(Lyapunov { enabled: true, exterior, interior })
+    }
+
+    /// The scales as --lyapunov takes them.
+    pub fn to_arg(&self) -> String {
+        format!("{},{}", self.exterior, self.interior)
+    }
+
+    /// [Colour]
+    /// The colour, in linear light, of a pixel of the given count and
+    /// exponent. Exponents on the wrong side of 0 for the pixel, as
+    /// orbits caught short by the limit can have, are taken as 0.
+    pub fn colour(&self, count: u32, limit: u32, exponent: f32) -> [f32; 4] {
+        if count >= limit {
+            let t = 1.0 - (exponent.min(0.0) / self.interior).exp();
+            [0.1 * t, 0.35 * t, t, 1.0]
+        } else {
+            let t = 1.0 - (-exponent.max(0.0) / self.exterior).exp();
+            [t, 0.55 * t * t, 0.15 * t * t * t, 1.0]
+        }
+    }
+
+    /// [Colourise]
+    /// Colours a buffer of counts by the exponents beside them, shaded
+    /// as colourise_shaded does, returning RGBA bytes.
+    pub fn colourise(&self, vals: &[u32], exponents: &[f32], limit: u32, tone: Tone, shades: Option<&[f32]>) -> Vec<u8> {
+        vals.iter().zip(exponents).enumerate()
+            .flat_map(|(i, (&count, &exponent))| tone.apply(shaded(self.colour(count, limit, exponent), shades, i)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::complex::Complex as cmp;
+
+    use super::*;
+    use crate::fractal::Mandelbrot;
+    use crate::kernel::{escape_stats, escape_time};
+
+    #[test]
+    fn escaping_orbits_spread_and_attracted_ones_converge() {
+        let exponent = |re: f64, im: f64| {
+            let (count, exponent, _) = escape_stats::<f64, _, true, false>(&Mandelbrot, cmp::new(re, im), 1000);
+            (count, exponent)
+        };
+
+        // The main cardioid and the period-2 bulb, then two points outside.
+        let [(a, cardioid), (b, bulb)] = [exponent(-0.1, 0.1), exponent(-1.05, 0.05)];
+        assert!(a == 1000 && b == 1000 && cardioid < 0.0 && bulb < 0.0, "{cardioid} {bulb}");
+        assert!((cardioid - bulb).abs() > 0.05, "{cardioid} {bulb}");
+        for (re, im) in [(0.5, 0.5), (-0.75, 0.2)] {
+            let (count, exponent) = exponent(re, im);
+            assert!(count < 1000 && exponent > 0.0, "{re}+{im}i: {count} {exponent}");
+        }
+
+        // The plain loop counts the same, without the exponent.
+        assert_eq!(escape_stats::<f64, _, false, false>(&Mandelbrot, cmp::new(0.5, 0.5), 1000), (escape_time(&Mandelbrot, cmp::new(0.5, 0.5), 1000), 0.0, 0.0));
+    }
+
+    #[test]
+    fn colours_each_side_on_its_own_scale() {
+        let lyapunov = Lyapunov::parse("0.5, 0.25").unwrap();
+        assert_eq!(lyapunov.to_arg(), "0.5,0.25");
+        assert_eq!(Lyapunov::parse("0.5,0"), None);
+        assert_eq!(Lyapunov::parse(&Lyapunov::default().to_arg()).map(|l| l.exterior), Some(Lyapunov::default().exterior));
+
+        let [inside, outside] = [lyapunov.colour(100, 100, -0.25), lyapunov.colour(10, 100, 0.5)];
+        assert!(inside[2] > inside[0] && outside[0] > outside[2]);
+        assert!((inside[2] - outside[0]).abs() < 1e-6, "one scale in, each is as bright");
+        assert_eq!(lyapunov.colour(10, 100, -1.0), [0.0, 0.0, 0.0, 1.0]);
+
+        let rgba = lyapunov.colourise(&[100, 10], &[-0.25, 0.5], 100, Tone::Srgb, None);
+        assert_eq!(rgba.len(), 8);
+    }
+}
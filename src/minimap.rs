@@ -0,0 +1,164 @@
+//! [Minimap]
+//!
+//! A thumbnail of the whole set in a corner of the window, toggled with
+//! M, marking where the current view sits within it. Under the inverted
+//! projection, the thumbnail shows the whole of the inverted figure.
+
+use std::sync::Arc;
+
+use num::complex::Complex as cmp;
+
+use crate::colour::{colourise, Gradient, LegacyColorizer, Tone};
+use crate::fractal::Formula;
+use crate::overlay::{Bitmap, Overlay, Shape, ADVANCE};
+use crate::viewport::{Projection, Viewport};
+
+/// The thumbnail's size in pixels.
+const THUMB_WIDTH: usize = 200;
+const THUMB_HEIGHT: usize = 100;
+
+/// Enough iterations for the outline of the set at thumbnail size.
+const THUMB_LIMIT: u32 = 200;
+
+/// Below this size on the minimap, in pixels, the view's outline is
+/// drawn as a crosshair instead.
+const MIN_OUTLINE: f64 = 4.0;
+
+const MARKER: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+
+/// [Minimap]
+///
+/// Fields:
+/// [visible] Whether the minimap is shown;
+/// [thumbnail] The whole set, as computed for the formula below;
+/// [formula] The formula the thumbnail shows;
+/// [view] The part of the plane the thumbnail covers, under the projection it shows.
+#[derive(Clone, Debug)]
+pub struct Minimap {
+    pub visible: bool,
+    thumbnail: Arc<Bitmap>,
+    formula: Formula,
+    view: Viewport,
+}
+
+impl Minimap {
+    /// [New]
+    /// Computes the thumbnail for the formula under the projection,
+    /// which at this size takes a few milliseconds.
+    pub fn new(formula: Formula, projection: Projection) -> Minimap {
+        let (centre, width) = projection.overview(formula);
+        let mut view = Viewport::new(centre, width, THUMB_WIDTH, THUMB_HEIGHT);
+        view.set_projection(projection);
+
+        let mut vals = vec![0; THUMB_WIDTH * THUMB_HEIGHT];
+        formula.compute_parallel(&mut vals, THUMB_WIDTH, |a, b| view.sample(a as f64, b as f64), THUMB_LIMIT);
+        let rgba = colourise(&LegacyColorizer { scalar: 2.0, gradient: Gradient::default() }, &vals, THUMB_LIMIT, Tone::default());
+
+        Minimap {
+            visible: false,
+            thumbnail: Arc::new(Bitmap { width: THUMB_WIDTH, height: THUMB_HEIGHT, rgba }),
+            formula,
+            view,
+        }
+    }
+
+    /// [Set]
+    /// Recomputes the thumbnail if the formula or the projection has changed.
+    pub fn set(&mut self, formula: Formula, projection: Projection) {
+        if formula != self.formula || projection != self.view.projection() {
+            *self = Minimap { visible: self.visible, ..Minimap::new(formula, projection) };
+        }
+    }
+
+    /// [Draw]
+    ///
+    /// Adds the minimap to the bottom-right corner of a frame of the
+    /// given size, if it is visible. The current view is outlined on the
+    /// thumbnail, or, once it is too small to see, marked with a
+    /// crosshair labelled with the magnification.
+    pub fn draw(&self, overlay: &mut Overlay, current: &Viewport, magnification: f64) {
+        if !self.visible {
+            return;
+        }
+
+        let (frame_width, frame_height) = (current.width_px() as f64, current.height_px() as f64);
+        let width = (frame_width / 4.0).min(THUMB_WIDTH as f64);
+        let height = width * THUMB_HEIGHT as f64 / THUMB_WIDTH as f64;
+        let (left, top) = (frame_width - width - 4.0, frame_height - height - 4.0);
+        let scale = width / THUMB_WIDTH as f64;
+
+        overlay.push(Shape::Image { bitmap: self.thumbnail.clone(), rect: [left, top, width, height] });
+
+        let to_inset = |c: cmp<f64>| {
+            let [x, y] = self.view.complex_to_pixel(c);
+            [left + x * scale, top + y * scale]
+        };
+
+        // Zoomed out past the thumbnail, the outline runs around its edge.
+        let corners = [(0.0, 0.0), (frame_width, 0.0), (frame_width, frame_height), (0.0, frame_height)]
+            .map(|(x, y)| to_inset(current.pixel_to_complex(x, y)))
+            .map(|[x, y]| [x.clamp(left, left + width), y.clamp(top, top + height)]);
+
+        let span = |i: usize| corners.iter().map(|p| p[i]).fold(f64::NEG_INFINITY, f64::max)
+            - corners.iter().map(|p| p[i]).fold(f64::INFINITY, f64::min);
+
+        if span(0).max(span(1)) >= MIN_OUTLINE {
+            overlay.polygon(&corners, 1.0, MARKER);
+        } else {
+            let [x, y] = to_inset(current.centre());
+            overlay.crosshair([x, y], 5.0, 1.0, MARKER);
+
+            let label = format!("{magnification:.1e}x");
+            let size = 8.0;
+            let label_width = label.len() as f64 * size * ADVANCE;
+            let label_x = (x - label_width / 2.0).clamp(left, left + width - label_width);
+            overlay.push(Shape::Text { text: label, at: [label_x, (y + 6.0 + size).min(top + height - 1.0)], size, colour: MARKER });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(overlay: &Overlay) -> usize {
+        overlay.shapes().iter().filter(|s| matches!(s, Shape::Line { .. })).count()
+    }
+
+    #[test]
+    fn shallow_view_is_outlined() {
+        let mut minimap = Minimap::new(Formula::Mandelbrot, Projection::Plane);
+        minimap.visible = true;
+
+        let mut overlay = Overlay::new();
+        let view = Viewport::new(cmp::new(-0.5, 0.0), 2.0, 400, 200);
+        minimap.draw(&mut overlay, &view, 2.0);
+
+        assert!(matches!(overlay.shapes()[0], Shape::Image { .. }));
+        assert_eq!(lines(&overlay), 4);
+        assert!(!overlay.shapes().iter().any(|s| matches!(s, Shape::Text { .. })));
+    }
+
+    #[test]
+    fn deep_view_is_a_labelled_crosshair() {
+        let mut minimap = Minimap::new(Formula::Mandelbrot, Projection::Plane);
+        minimap.visible = true;
+
+        let mut overlay = Overlay::new();
+        let view = Viewport::new(cmp::new(-0.5, 0.0), 4.0e-6, 400, 200);
+        minimap.draw(&mut overlay, &view, 1.0e6);
+
+        assert_eq!(lines(&overlay), 2);
+        assert!(overlay.shapes().iter().any(|s| matches!(s, Shape::Text { text, .. } if text == "1.0e6x")));
+    }
+
+    #[test]
+    fn hidden_draws_nothing() {
+        let mut overlay = Overlay::new();
+        let view = Viewport::new(cmp::new(-0.5, 0.0), 2.0, 400, 200);
+
+        Minimap::new(Formula::Mandelbrot, Projection::Plane).draw(&mut overlay, &view, 2.0);
+
+        assert!(overlay.is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+//! [Monitors]
+//!
+//! The displays winit reports, for `--list-monitors`, and putting a
+//! newly opened window where `--monitor` and `--position` ask. Both
+//! backends open their windows through winit, so this serves both.
+//! Some window systems (Wayland among them) do not let a window pick
+//! its position, and there it stays where the compositor puts it.
+
+use mandelbrot_piston::error::AppError;
+use mandelbrot_piston::screen::{Monitor, Placing};
+use winit::dpi::PhysicalPosition;
+use winit::event_loop::EventLoop;
+use winit::monitor::MonitorHandle;
+use winit::window::Window;
+
+use crate::catch_windowing_panic;
+
+/// [Describe]
+/// The monitors, in the order winit lists them.
+fn describe(handles: impl Iterator<Item = MonitorHandle>, primary: Option<MonitorHandle>) -> Vec<Monitor> {
+    handles
+        .map(|handle| {
+            let (position, size) = (handle.position(), handle.size());
+            Monitor {
+                name: handle.name(),
+                position: [position.x, position.y],
+                size: [size.width, size.height],
+                scale: handle.scale_factor(),
+                primary: primary.as_ref() == Some(&handle),
+            }
+        })
+        .collect()
+}
+
+/// [List]
+/// The monitors there are, for `--list-monitors`.
+pub fn list() -> Result<Vec<Monitor>, AppError> {
+    let event_loop = catch_windowing_panic(EventLoop::new)
+        .map_err(|reason| AppError::Backend { name: "winit", reason: format!("could not list the monitors: {reason}") })?;
+
+    Ok(describe(event_loop.available_monitors(), event_loop.primary_monitor()))
+}
+
+/// [Place]
+/// Moves a window that is already open to where it was asked to be.
+pub fn place(window: &Window, placing: &Placing) {
+    if *placing == Placing::default() {
+        return;
+    }
+
+    let monitors = describe(window.available_monitors(), window.primary_monitor());
+    let size = window.outer_size();
+    let (position, warning) = placing.window_position(&monitors, [size.width, size.height]);
+    if let Some(warning) = warning {
+        eprintln!("{warning}");
+    }
+
+    if let Some([x, y]) = position {
+        window.set_outer_position(PhysicalPosition::new(x, y));
+    }
+}
@@ -0,0 +1,220 @@
+//! [Nebula]
+//!
+//! The Nebulabrot: rather than colouring points by how fast they escape,
+//! random points of the whole set's region are iterated and every point
+//! of the orbits that escape is counted where it lands in the view. The
+//! counts are kept three times over, for orbits escaping within three
+//! caps, the highest counted in red, the middle in green and the lowest
+//! in blue, so that long orbits glow red over the blue haze of short
+//! ones. Each channel is shown on a log scale, normalised to its own
+//! brightest pixel.
+//!
+//! The density builds up over many updates, a batch of samples at a
+//! time in parallel, each task into a histogram of its own; it stops
+//! building while the zoom is paused, and starts over should the view
+//! change. Sampling takes threads and a clock, so it is not built for
+//! wasm32.
+
+use std::time::{Duration, Instant};
+
+use num::complex::Complex as cmp;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::viewport::Viewport;
+
+/// The iteration caps of the red, green and blue channels.
+pub const CAPS: [u32; 3] = [50_000, 5_000, 500];
+
+/// How long an update may spend sampling.
+pub const BUDGET: Duration = Duration::from_millis(25);
+
+/// How many samples a task takes, and how many tasks make a round.
+const SAMPLES: usize = 2048;
+const TASKS: usize = 32;
+
+/// The region samples are drawn from, as [re min, re max, im min, im max],
+/// which the whole set lies within.
+const REGION: [f64; 4] = [-2.0, 1.0, -1.5, 1.5];
+
+/// [Nebula]
+///
+/// Fields:
+/// [enabled] Whether the Nebulabrot is shown in place of the zoom;
+/// [caps] The iteration caps of the red, green and blue channels;
+/// [view] The view the density is counted in;
+/// [density] How many orbit points have landed in each pixel, per channel;
+/// [samples] How many points have been sampled into it.
+#[derive(Clone, Debug)]
+pub struct Nebula {
+    pub enabled: bool,
+    caps: [u32; 3],
+    view: Option<Viewport>,
+    density: Vec<[u32; 3]>,
+    samples: u64,
+}
+
+impl Default for Nebula {
+    fn default() -> Nebula {
+        Nebula::new(CAPS)
+    }
+}
+
+impl Nebula {
+    pub fn new(caps: [u32; 3]) -> Nebula {
+        Nebula { enabled: false, caps, view: None, density: Vec::new(), samples: 0 }
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// [Toggle]
+    /// Shows the Nebulabrot or the zoom, starting the density afresh.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.view = None;
+        self.density = Vec::new();
+        self.samples = 0;
+    }
+
+    /// [Sample]
+    /// Adds rounds of samples to the density for as long as `budget`
+    /// allows, starting it over if the view is not the one it was counted
+    /// in. Each task draws its points from a generator of its own, seeded
+    /// from `rng`, so a seeded generator repeats the density.
+    pub fn sample<R: Rng>(&mut self, viewport: &Viewport, rng: &mut R, budget: Duration) {
+        if self.view != Some(*viewport) {
+            self.view = Some(*viewport);
+            self.density = vec![[0; 3]; viewport.width_px() * viewport.height_px()];
+            self.samples = 0;
+        }
+
+        let started = Instant::now();
+        while started.elapsed() < budget {
+            let seeds: Vec<u64> = (0..TASKS).map(|_| rng.gen()).collect();
+            let (caps, len) = (self.caps, self.density.len());
+
+            let round = seeds.par_iter()
+                .fold(|| vec![[0; 3]; len], |mut local, &seed| {
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    for _ in 0..SAMPLES {
+                        let c = cmp::new(rng.gen_range(REGION[0]..REGION[1]), rng.gen_range(REGION[2]..REGION[3]));
+                        plot(c, caps, viewport, &mut local);
+                    }
+                    local
+                })
+                .reduce(|| vec![[0; 3]; len], |mut a, b| {
+                    merge(&mut a, &b);
+                    a
+                });
+
+            merge(&mut self.density, &round);
+            self.samples += (TASKS * SAMPLES) as u64;
+        }
+    }
+
+    /// [Image]
+    /// The density as RGBA, each channel scaled by the log of its count
+    /// over the log of its highest.
+    pub fn image(&self) -> Vec<u8> {
+        let mut highest = [0u32; 3];
+        for pixel in &self.density {
+            for k in 0..3 {
+                highest[k] = highest[k].max(pixel[k]);
+            }
+        }
+        let scale = highest.map(|n| if n > 0 { 255.0 / (n as f64).ln_1p() } else { 0.0 });
+
+        self.density.iter()
+            .flat_map(|pixel| {
+                let [r, g, b] = [0, 1, 2].map(|k| ((pixel[k] as f64).ln_1p() * scale[k]).round() as u8);
+                [r, g, b, 255]
+            })
+            .collect()
+    }
+}
+
+/// Adds one histogram's counts to another's.
+fn merge(into: &mut [[u32; 3]], from: &[[u32; 3]]) {
+    for (a, b) in into.iter_mut().zip(from) {
+        for k in 0..3 {
+            a[k] = a[k].saturating_add(b[k]);
+        }
+    }
+}
+
+/// Whether a point lies in the main cardioid or the period-2 bulb, whose
+/// orbits never escape, so need not be iterated.
+fn interior(c: cmp<f64>) -> bool {
+    let q = (c.re - 0.25).powi(2) + c.im * c.im;
+    q * (q + c.re - 0.25) <= 0.25 * c.im * c.im || (c.re + 1.0).powi(2) + c.im * c.im <= 0.0625
+}
+
+/// [Plot]
+/// Iterates a point to find whether its orbit escapes within the highest
+/// cap, and if it does, iterates it again, counting each point of the
+/// orbit that lands in the view in every channel whose cap it escaped
+/// within.
+fn plot(c: cmp<f64>, caps: [u32; 3], viewport: &Viewport, density: &mut [[u32; 3]]) {
+    if interior(c) {
+        return;
+    }
+    let cap = caps.iter().copied().max().unwrap_or(0);
+    let mut z = cmp::new(0.0, 0.0);
+    let Some(escaped) = (1..=cap).find(|_| {
+        z = z * z + c;
+        z.norm_sqr() > 4.0
+    }) else { return };
+
+    let channels = caps.map(|cap| escaped <= cap);
+    let (width, height) = (viewport.width_px(), viewport.height_px());
+    let mut z = cmp::new(0.0, 0.0);
+    for _ in 0..escaped {
+        z = z * z + c;
+        let [x, y] = viewport.complex_to_pixel(viewport.projection().apply(z));
+        if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+            continue;
+        }
+        let pixel = &mut density[y as usize * width + x as usize];
+        for k in 0..3 {
+            if channels[k] {
+                pixel[k] += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longer_caps_count_everything_shorter_ones_do() {
+        let viewport = Viewport::new(cmp::new(-0.5, 0.0), 3.0, 48, 48);
+        let mut nebula = Nebula::new([400, 100, 20]);
+        nebula.sample(&viewport, &mut StdRng::seed_from_u64(7), Duration::ZERO);
+        assert_eq!(nebula.samples(), 0);
+
+        nebula.sample(&viewport, &mut StdRng::seed_from_u64(7), Duration::from_millis(1));
+        assert!(nebula.samples() > 0);
+        assert!(nebula.density.iter().all(|[r, g, b]| r >= g && g >= b));
+        assert!(nebula.density.iter().any(|[r, _, b]| r > b));
+
+        let image = nebula.image();
+        assert_eq!(image.len(), 48 * 48 * 4);
+        assert!(image.chunks(4).any(|pixel| pixel[0] == 255));
+
+        // A view other than the one counted in starts over.
+        nebula.sample(&Viewport::new(cmp::new(-0.5, 0.0), 3.0, 24, 24), &mut StdRng::seed_from_u64(7), Duration::ZERO);
+        assert_eq!((nebula.samples(), nebula.density.len()), (0, 24 * 24));
+    }
+
+    #[test]
+    fn skips_only_points_that_never_escape() {
+        assert!(interior(cmp::new(0.0, 0.0)));
+        assert!(interior(cmp::new(-1.0, 0.1)));
+        assert!(!interior(cmp::new(0.3, 0.0)));
+        assert!(!interior(cmp::new(-0.75, 0.2)));
+    }
+}
@@ -0,0 +1,93 @@
+//! [Orbit]
+//!
+//! The orbit of a single point, written out on Ctrl+click for working
+//! with the numbers behind a pixel. The point is the one the pixel's
+//! count was computed for, and the orbit comes from the kernel's own
+//! loop, formula and escape radius, so it ends exactly where the count
+//! does. It is written as CSV, one row per iterate from z_0, to a file
+//! in the working directory named from the point.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use num::complex::Complex as cmp;
+
+use crate::error::AppError;
+use crate::fractal::Escaping;
+
+/// [Orbit]
+///
+/// Fields:
+/// [point] The point iterated;
+/// [points] z_0 onward, up to the first to escape or z at the limit;
+/// [escaped] Whether the last of them escaped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Orbit {
+    pub point: cmp<f64>,
+    pub points: Vec<cmp<f64>>,
+    pub escaped: bool,
+}
+
+impl Orbit {
+    /// [Of]
+    /// The orbit of `point` under the formula, escaping at its radius,
+    /// up to the limit.
+    pub fn of(formula: Escaping, point: cmp<f64>, limit: u32) -> Orbit {
+        let (points, escaped) = formula.orbit(point, limit);
+        Orbit { point, points, escaped }
+    }
+
+    /// The iteration it escaped at, if it did.
+    pub fn escaped_at(&self) -> Option<usize> {
+        self.escaped.then_some(self.points.len() - 1)
+    }
+
+    /// The file it is written to, named from the point at full precision.
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("orbit_re{}_im{}.csv", self.point.re, self.point.im))
+    }
+
+    /// The orbit as CSV, with a header row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("n,re,im,abs\n");
+        for (n, z) in self.points.iter().enumerate() {
+            csv.push_str(&format!("{n},{},{},{}\n", z.re, z.im, z.norm()));
+        }
+        csv
+    }
+
+    /// [Save]
+    /// Writes the CSV to `path`.
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        fs::write(path, self.to_csv()).map_err(|e| AppError::Export { path: path.to_path_buf(), reason: e.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal::Mandelbrot;
+    use crate::kernel::escape_time;
+
+    #[test]
+    fn ends_where_the_count_does() {
+        let orbit = Orbit::of(Escaping::default(), cmp::new(1.0, 0.0), 100);
+        assert_eq!(orbit.to_csv(), "n,re,im,abs\n0,0,0,0\n1,1,0,1\n2,2,0,2\n");
+        assert_eq!(orbit.escaped_at(), Some(2));
+
+        for c in [cmp::new(-0.745, 0.1), cmp::new(0.3, 0.5), cmp::new(-1.0, 0.0)] {
+            let orbit = Orbit::of(Escaping::default(), c, 500);
+            assert_eq!(orbit.points.len() - 1, escape_time(&Mandelbrot, c, 500) as usize);
+        }
+    }
+
+    #[test]
+    fn interior_points_run_to_the_limit() {
+        let orbit = Orbit::of(Escaping::default(), cmp::new(-1.0, 0.0), 10);
+
+        assert_eq!(orbit.points.len(), 11);
+        assert_eq!(orbit.points[9], cmp::new(-1.0, 0.0));
+        assert_eq!(orbit.escaped_at(), None);
+        assert_eq!(orbit.path(), PathBuf::from("orbit_re-1_im0.csv"));
+    }
+}
@@ -0,0 +1,277 @@
+//! [Overlay]
+//!
+//! Things drawn over the fractal, such as the HUD. An overlay is a list
+//! of shapes in frame pixel coordinates (the same grid as the iteration
+//! counts), which each backend scales to its window and draws however
+//! suits it. Overlays are never part of the frame itself, so they stay
+//! out of screenshots unless composited into one on purpose.
+
+use std::sync::{Arc, OnceLock};
+
+use rusttype::{point, Font, Scale};
+
+use crate::colour::to_rgba8;
+
+/// The monospace font overlay text is drawn in.
+pub const FONT: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+
+/// The advance of one character in the HUD's monospace font, as a
+/// fraction of the font size.
+pub const ADVANCE: f64 = 0.6;
+
+/// [Bitmap]
+/// A small RGBA image drawn by an overlay, such as the minimap's
+/// thumbnail. Bitmaps are shared, so that a backend can keep one
+/// uploaded for as long as the same bitmap keeps being drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+/// [Shape]
+///
+/// Variants:
+/// [Rect] A filled rectangle, as [x, y, width, height];
+/// [Line] A straight line `width` pixels thick;
+/// [Text] A line of text in the monospace font, `size` pixels tall,
+///        starting from the left end of its baseline at `at`;
+/// [Image] A bitmap, stretched to fill a rectangle.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Rect { rect: [f64; 4], colour: [f32; 4] },
+    Line { from: [f64; 2], to: [f64; 2], width: f64, colour: [f32; 4] },
+    Text { text: String, at: [f64; 2], size: f64, colour: [f32; 4] },
+    Image { bitmap: Arc<Bitmap>, rect: [f64; 4] },
+}
+
+/// [Overlay]
+/// Shapes to draw over a frame, in order, so later shapes cover earlier ones.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overlay {
+    shapes: Vec<Shape>,
+}
+
+impl Overlay {
+    pub fn new() -> Overlay {
+        Overlay::default()
+    }
+
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    pub fn push(&mut self, shape: Shape) {
+        self.shapes.push(shape);
+    }
+
+    /// [Scale]
+    /// Scales every shape about the frame's top-left corner, for an
+    /// overlay laid out in logical pixels over a frame of physical ones.
+    pub fn scale(&mut self, factor: f64) {
+        for shape in &mut self.shapes {
+            match shape {
+                Shape::Rect { rect, .. } | Shape::Image { rect, .. } => *rect = rect.map(|v| v * factor),
+                Shape::Line { from, to, width, .. } => {
+                    (*from, *to, *width) = (from.map(|v| v * factor), to.map(|v| v * factor), *width * factor);
+                }
+                Shape::Text { at, size, .. } => (*at, *size) = (at.map(|v| v * factor), *size * factor),
+            }
+        }
+    }
+
+    /// [Polygon]
+    /// The outline of a closed polygon.
+    pub fn polygon(&mut self, points: &[[f64; 2]], width: f64, colour: [f32; 4]) {
+        for (i, &from) in points.iter().enumerate() {
+            let to = points[(i + 1) % points.len()];
+            self.push(Shape::Line { from, to, width, colour });
+        }
+    }
+
+    /// [Crosshair]
+    /// A plus sign centred on `at`, `arm` pixels from the centre to each end.
+    pub fn crosshair(&mut self, at: [f64; 2], arm: f64, width: f64, colour: [f32; 4]) {
+        let [x, y] = at;
+        self.push(Shape::Line { from: [x - arm, y], to: [x + arm, y], width, colour });
+        self.push(Shape::Line { from: [x, y - arm], to: [x, y + arm], width, colour });
+    }
+
+    /// [Text Box]
+    /// Lines of text on a translucent backing box, with its top-left
+    /// corner at `at`, so that the text reads over bright regions.
+    pub fn text_box<S: AsRef<str>>(&mut self, lines: &[S], at: [f64; 2], size: f64) {
+        let line_height = size * 1.25;
+        let padding = size * 0.4;
+        let [width, height] = text_box_size(lines, size);
+
+        self.push(Shape::Rect { rect: [at[0], at[1], width, height], colour: [0.0, 0.0, 0.0, 0.6] });
+
+        for (i, line) in lines.iter().enumerate() {
+            self.push(Shape::Text {
+                text: line.as_ref().to_string(),
+                at: [at[0] + padding, at[1] + padding + size + i as f64 * line_height],
+                size,
+                colour: [1.0, 1.0, 1.0, 1.0],
+            });
+        }
+    }
+
+    /// [Composite]
+    /// Draws the shapes into a frame of packed RGBA bytes, `width` pixels
+    /// per row, blended by their alpha, for exporting them with it. A
+    /// pixel is drawn when its centre is inside a shape, and text is
+    /// blended by how much of each pixel its glyphs cover, so that it
+    /// looks as it does in the window.
+    pub fn composite(&self, rgba: &mut [u8], width: usize, height: usize) {
+        for shape in &self.shapes {
+            match shape {
+                Shape::Rect { rect, colour } => {
+                    let colour = to_rgba8(*colour);
+                    fill(rgba, width, height, *rect, |_, _| Some(colour));
+                }
+                Shape::Line { from: [x1, y1], to: [x2, y2], width: thickness, colour } => {
+                    let (colour, half) = (to_rgba8(*colour), thickness / 2.0);
+                    let bounds = [x1.min(*x2) - half, y1.min(*y2) - half, (x2 - x1).abs() + thickness, (y2 - y1).abs() + thickness];
+                    let (dx, dy) = (x2 - x1, y2 - y1);
+                    let length = dx * dx + dy * dy;
+
+                    fill(rgba, width, height, bounds, |x, y| {
+                        let t = if length > 0.0 { (((x - x1) * dx + (y - y1) * dy) / length).clamp(0.0, 1.0) } else { 0.0 };
+                        ((x - x1 - t * dx).hypot(y - y1 - t * dy) <= half).then_some(colour)
+                    });
+                }
+                Shape::Image { bitmap, rect: rect @ [left, top, w, h] } => {
+                    fill(rgba, width, height, *rect, |x, y| {
+                        let a = (((x - left) / w * bitmap.width as f64) as usize).min(bitmap.width.checked_sub(1)?);
+                        let b = (((y - top) / h * bitmap.height as f64) as usize).min(bitmap.height.checked_sub(1)?);
+                        let i = (b * bitmap.width + a) * 4;
+                        bitmap.rgba.get(i..i + 4)?.try_into().ok()
+                    });
+                }
+                Shape::Text { text, at: [x, y], size, colour } => {
+                    let Some(font) = font() else { continue };
+                    let [r, g, b, alpha] = to_rgba8(*colour);
+
+                    for glyph in font.layout(text, Scale::uniform(*size as f32), point(*x as f32, *y as f32)) {
+                        let Some(bounds) = glyph.pixel_bounding_box() else { continue };
+                        glyph.draw(|gx, gy, coverage| {
+                            let (px, py) = (bounds.min.x + gx as i32, bounds.min.y + gy as i32);
+                            if (0..width as i32).contains(&px) && (0..height as i32).contains(&py) {
+                                let i = (py as usize * width + px as usize) * 4;
+                                blend(&mut rgba[i..i + 3], [r, g, b, (alpha as f32 * coverage).round() as u8]);
+                            }
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Blends `colour_at` over the pixels of the frame whose centres fall in
+/// `rect`, for those it gives a colour.
+fn fill(rgba: &mut [u8], width: usize, height: usize, rect: [f64; 4], colour_at: impl Fn(f64, f64) -> Option<[u8; 4]>) {
+    let [x, y, w, h] = rect;
+    let span = |from: f64, to: f64, len: usize| (from - 0.5).ceil().max(0.0) as usize..((to - 0.5).ceil().max(0.0) as usize).min(len);
+
+    for b in span(y, y + h, height) {
+        for a in span(x, x + w, width) {
+            let Some(colour) = colour_at(a as f64 + 0.5, b as f64 + 0.5) else { continue };
+            blend(&mut rgba[(b * width + a) * 4..(b * width + a) * 4 + 3], colour);
+        }
+    }
+}
+
+/// Blends a colour over the red, green and blue bytes of a pixel.
+fn blend(pixel: &mut [u8], [r, g, b, alpha]: [u8; 4]) {
+    for (channel, over) in pixel.iter_mut().zip([r, g, b]) {
+        *channel = ((over as u32 * alpha as u32 + *channel as u32 * (255 - alpha as u32) + 127) / 255) as u8;
+    }
+}
+
+/// The overlay font, parsed the first time text is composited.
+fn font() -> Option<&'static Font<'static>> {
+    static PARSED: OnceLock<Option<Font<'static>>> = OnceLock::new();
+    PARSED.get_or_init(|| Font::try_from_bytes(FONT)).as_ref()
+}
+
+/// [Text Box Size]
+/// The width and height of the box `text_box` draws for these lines.
+pub fn text_box_size<S: AsRef<str>>(lines: &[S], size: f64) -> [f64; 2] {
+    let widest = lines.iter().map(|line| line.as_ref().chars().count()).max().unwrap_or(0);
+    let padding = size * 0.4;
+
+    [widest as f64 * size * ADVANCE + 2.0 * padding, lines.len() as f64 * size * 1.25 + 2.0 * padding]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(rgba: &[u8], width: usize, a: usize, b: usize) -> [u8; 4] {
+        rgba[(b * width + a) * 4..(b * width + a) * 4 + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn composites_rects_lines_and_images() {
+        let mut overlay = Overlay::new();
+        overlay.push(Shape::Rect { rect: [1.0, 1.0, 2.0, 2.0], colour: [1.0, 0.0, 0.0, 1.0] });
+        overlay.push(Shape::Line { from: [0.0, 5.5], to: [8.0, 5.5], width: 1.0, colour: [0.0, 1.0, 0.0, 1.0] });
+        let bitmap = Arc::new(Bitmap { width: 1, height: 1, rgba: vec![0, 0, 255, 255] });
+        overlay.push(Shape::Image { bitmap, rect: [6.0, 6.0, 2.0, 2.0] });
+
+        let mut rgba = [0, 0, 0, 255].repeat(64);
+        overlay.composite(&mut rgba, 8, 8);
+
+        assert_eq!(pixel(&rgba, 8, 1, 2), [255, 0, 0, 255]);
+        assert_eq!(pixel(&rgba, 8, 3, 3), [0, 0, 0, 255]);
+        assert_eq!(pixel(&rgba, 8, 4, 5), [0, 255, 0, 255]);
+        assert_eq!(pixel(&rgba, 8, 4, 4), [0, 0, 0, 255]);
+        assert_eq!(pixel(&rgba, 8, 7, 7), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn text_is_drawn_above_its_baseline() {
+        let mut overlay = Overlay::new();
+        overlay.push(Shape::Text { text: "HUD".to_string(), at: [2.0, 14.0], size: 16.0, colour: [1.0; 4] });
+
+        let mut rgba = [0, 0, 0, 255].repeat(40 * 20);
+        overlay.composite(&mut rgba, 40, 20);
+
+        let lit: Vec<(usize, usize)> = (0..40 * 20).filter(|i| rgba[i * 4] > 0).map(|i| (i % 40, i / 40)).collect();
+        assert!(lit.len() > 20);
+        // Three characters of about 0.6 em each, from x = 2, between the
+        // cap height and the baseline.
+        assert!(lit.iter().all(|&(x, y)| (2..2 + 30).contains(&x) && (2..15).contains(&y)), "{lit:?}");
+    }
+
+    #[test]
+    fn translucent_shapes_blend() {
+        let mut overlay = Overlay::new();
+        overlay.push(Shape::Rect { rect: [0.0, 0.0, 1.0, 1.0], colour: [1.0, 1.0, 1.0, 0.5] });
+
+        let mut rgba = vec![0, 0, 0, 255];
+        overlay.composite(&mut rgba, 1, 1);
+
+        assert_eq!(rgba, [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn scaling_keeps_shapes_in_place() {
+        let mut overlay = Overlay::new();
+        overlay.push(Shape::Line { from: [1.0, 2.0], to: [3.0, 2.0], width: 1.0, colour: [1.0; 4] });
+        overlay.text_box(&["hud"], [4.0, 4.0], 8.0);
+        overlay.scale(2.0);
+
+        assert_eq!(overlay.shapes()[0], Shape::Line { from: [2.0, 4.0], to: [6.0, 4.0], width: 2.0, colour: [1.0; 4] });
+        let mut doubled = Overlay::new();
+        doubled.text_box(&["hud"], [8.0, 8.0], 16.0);
+        assert_eq!(&overlay.shapes()[1..], doubled.shapes());
+    }
+}
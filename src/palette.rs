@@ -0,0 +1,125 @@
+//! [Palette]
+//!
+//! The colours as a lookup table, written out as a Fractint .map file,
+//! with a PNG strip of the gradient beside it, for reloading or for use
+//! in other software. The table is taken from the colorizer and tone as
+//! they are applied to the frame at the time, the scalar and all, so the
+//! bytes are the frame's own: entry 0 is the colour of the interior, as
+//! Fractint's inside colour is, and entry n that of a count of n, which
+//! is where Fractint colours a count of n for counts below 256. Counts
+//! at or over the limit take the interior's colour here as elsewhere.
+//! Shift+E exports the palette on screen, and --export-palette the one
+//! the zoom starts with.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::colour::{Colorizer, IterResult, Tone};
+use crate::error::AppError;
+use crate::export::save_png;
+
+/// How many colours a .map file holds.
+pub const ENTRIES: usize = 256;
+
+/// The height of the PNG strip of the gradient.
+const STRIP: usize = 16;
+
+/// [Palette]
+///
+/// Fields:
+/// [colours] The colour of each entry, as sRGB-encoded bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub colours: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    /// [Of]
+    /// The table of the colours a colorizer and tone give, at a limit.
+    pub fn of<C: Colorizer>(colorizer: &C, tone: Tone, limit: u32) -> Palette {
+        let colour = |count: u32| {
+            let [r, g, b, _] = tone.apply(colorizer.color(IterResult { count, limit }));
+            [r, g, b]
+        };
+        let interior = colour(limit);
+        let colours = (0..ENTRIES as u32).map(|n| if n == 0 || n >= limit { interior } else { colour(n) }).collect();
+        Palette { colours }
+    }
+
+    /// [Parse Map]
+    /// A palette from the text of a .map file: a line of three channels
+    /// from 0 to 255 for each entry, anything after them on a line being
+    /// a comment, as Fractint writes them. Files of fewer than ENTRIES
+    /// lines leave the rest black.
+    pub fn parse_map(text: &str) -> Result<Palette, String> {
+        let mut colours = Vec::with_capacity(ENTRIES);
+        for (n, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let channels: Vec<u8> = line.split_whitespace().take(3).map(str::parse).collect::<Result<_, _>>()
+                .map_err(|_| format!("line {}: channels must be whole numbers from 0 to 255, got '{line}'", n + 1))?;
+            let [r, g, b] = channels[..].try_into().map_err(|_| format!("line {}: needs red, green and blue, got '{line}'", n + 1))?;
+            colours.push([r, g, b]);
+        }
+        if colours.len() > ENTRIES {
+            return Err(format!("{} colours, more than the {ENTRIES} a .map holds", colours.len()));
+        }
+        colours.resize(ENTRIES, [0, 0, 0]);
+        Ok(Palette { colours })
+    }
+
+    /// The palette as the text of a .map file.
+    pub fn to_map(&self) -> String {
+        self.colours.iter().map(|[r, g, b]| format!("{r:>3} {g:>3} {b:>3}\n")).collect()
+    }
+
+    /// The strip of the gradient, as packed RGBA bytes, an entry to a
+    /// column and STRIP rows high.
+    pub fn strip(&self) -> Vec<u8> {
+        let row: Vec<u8> = self.colours.iter().flat_map(|&[r, g, b]| [r, g, b, 255]).collect();
+        row.repeat(STRIP)
+    }
+
+    /// [Save]
+    /// Writes the .map file to `path`, and the strip to a PNG beside it,
+    /// returning where the strip went.
+    pub fn save(&self, path: &Path) -> Result<PathBuf, AppError> {
+        fs::write(path, self.to_map()).map_err(|e| AppError::Export { path: path.to_path_buf(), reason: e.to_string() })?;
+        let strip = path.with_extension("png");
+        save_png(&strip, self.colours.len(), STRIP, &self.strip())?;
+        Ok(strip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colour::{colourise, Gradient, LegacyColorizer};
+
+    #[test]
+    fn round_trips_the_colours_as_applied() {
+        let colorizer = LegacyColorizer { scalar: 0.7, gradient: Gradient::default() };
+        for tone in [Tone::Srgb, Tone::Legacy] {
+            let palette = Palette::of(&colorizer, tone, 1000);
+            assert_eq!(Palette::parse_map(&palette.to_map()), Ok(palette.clone()));
+
+            // The entries are the bytes the frame gets for each count.
+            let counts = [1000, 1, 7, 255];
+            let frame = colourise(&colorizer, &counts, 1000, tone);
+            for (&count, pixel) in counts.iter().zip(frame.chunks(4)) {
+                let entry = if count >= 1000 { 0 } else { count as usize };
+                assert_eq!(palette.colours[entry], pixel[..3]);
+            }
+        }
+    }
+
+    #[test]
+    fn reads_maps_as_fractint_writes_them() {
+        let palette = Palette::parse_map("0 0 0  inside\n255 128 7\n\n").unwrap();
+        assert_eq!(palette.colours.len(), ENTRIES);
+        assert_eq!(palette.colours[1], [255, 128, 7]);
+        assert_eq!(palette.colours[2], [0, 0, 0]);
+
+        assert!(Palette::parse_map("1 2").is_err());
+        assert!(Palette::parse_map("1 2 256").is_err());
+        assert!(Palette::parse_map(&"1 2 3\n".repeat(ENTRIES + 1)).is_err());
+    }
+}
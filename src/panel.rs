@@ -0,0 +1,441 @@
+//! [Panel]
+//!
+//! The side panel, toggled with Tab, for tuning the zoom with the mouse
+//! rather than a key per nudge: sliders for the iteration limit, the
+//! escape radius, the zoom step and the colour scalar, choosers for the
+//! fast-forward speed, the render scale, the tone, the formula and the
+//! gradient, and switches for the Lyapunov colouring and the light.
+//! Like everything else over the frame it is made of overlay shapes,
+//! laid out afresh from the app's values on every render, and presses
+//! are tested against the same layout, so there is no widget state to
+//! fall out of step but for the slider held. A slider jumps to where it
+//! is pressed and follows the pointer until it is let go; a chooser
+//! steps to its next choice. Clicking the title folds the panel up to
+//! it. A click anywhere on the panel is the panel's, and never retargets
+//! the zoom. Changes go straight into the app, so the frame under way is
+//! found stale, and the next computed with them, on the next update.
+//!
+//! The panel is not egui: the default backend draws with
+//! opengl_graphics, which egui would have to paint alongside through
+//! egui_glow, with a bridge for Piston's events written here, and the
+//! pixels backend would need egui-wgpu as well. Made of overlay shapes,
+//! the panel comes out the same in either backend, and in screenshots
+//! taken with the overlays, for the few controls it has.
+
+use crate::colour::{Gradient, Tone};
+use crate::fractal::Formula;
+use crate::overlay::{Overlay, Shape};
+
+/// Where the panel's left edge is, and its width, in logical pixels.
+const LEFT: f64 = 4.0;
+const WIDTH: f64 = 150.0;
+
+/// The heights of the title and of each control.
+const TITLE: f64 = 14.0;
+const ROW: f64 = 22.0;
+
+const TEXT: f64 = 8.0;
+const PADDING: f64 = 4.0;
+const TRACK: f64 = 4.0;
+
+const BACKING: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const TRACK_COLOUR: [f32; 4] = [1.0, 1.0, 1.0, 0.25];
+const KNOB_COLOUR: [f32; 4] = [0.6, 0.7, 1.0, 1.0];
+const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// The speeds the chooser offers, as fast-forwarding doubles through them.
+const SPEEDS: [u32; 4] = [1, 2, 4, 8];
+
+/// The render scales the chooser offers, in frame pixels per window pixel.
+const RENDER_SCALES: [f64; 4] = [0.25, 0.5, 1.0, 2.0];
+
+/// [Params]
+/// The values the panel shows and changes.
+///
+/// Fields:
+/// [limit] The iteration limit;
+/// [radius] The radius orbits escape at;
+/// [zoom_step] The share of the view's width each zoom step trims off;
+/// [scalar] The colour scalar;
+/// [speed] How many zoom steps each frame takes;
+/// [render_scale] The frame's pixels per physical pixel of the window;
+/// [tone] How the colouring becomes bytes;
+/// [formula] The escape-time formula;
+/// [gradient] The tint of the colouring's ramp;
+/// [lyapunov] Whether frames are coloured by their Lyapunov exponents;
+/// [light] Whether the counts are shaded as a height field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Params {
+    pub limit: u32,
+    pub radius: f64,
+    pub zoom_step: f64,
+    pub scalar: f32,
+    pub speed: u32,
+    pub render_scale: f64,
+    pub tone: Tone,
+    pub formula: Formula,
+    pub gradient: Gradient,
+    pub lyapunov: bool,
+    pub light: bool,
+}
+
+/// [Change]
+/// A value changed on the panel, for the app to apply.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Change {
+    Limit(u32),
+    Radius(f64),
+    ZoomStep(f64),
+    Scalar(f32),
+    Speed(u32),
+    RenderScale(f64),
+    Tone(Tone),
+    Formula(Formula),
+    Gradient(Gradient),
+    Lyapunov(bool),
+    Light(bool),
+}
+
+/// [Range]
+/// The values a slider spans, evenly or on a log scale.
+#[derive(Clone, Copy, Debug)]
+struct Range {
+    min: f64,
+    max: f64,
+    log: bool,
+}
+
+impl Range {
+    /// Where a value falls along the slider, from 0 to 1.
+    fn position(&self, value: f64) -> f64 {
+        let t = if self.log { (value / self.min).ln() / (self.max / self.min).ln() } else { (value - self.min) / (self.max - self.min) };
+        if t.is_finite() { t.clamp(0.0, 1.0) } else { 0.0 }
+    }
+
+    /// The value at a position along the slider.
+    fn value(&self, position: f64) -> f64 {
+        let t = position.clamp(0.0, 1.0);
+        if self.log { self.min * (self.max / self.min).powf(t) } else { self.min + (self.max - self.min) * t }
+    }
+}
+
+/// [Control]
+///
+/// Variants:
+/// [Slider] A value anywhere in a range;
+/// [Chooser] One of a few choices, on or off for a switch.
+#[derive(Clone, Copy, Debug)]
+enum Control {
+    Slider(Range),
+    Chooser,
+}
+
+/// [Param]
+/// Each row of the panel, top to bottom.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Param {
+    Limit,
+    Radius,
+    ZoomStep,
+    Scalar,
+    Speed,
+    RenderScale,
+    Tone,
+    Formula,
+    Gradient,
+    Lyapunov,
+    Light,
+}
+
+const PARAMS: [Param; 11] = [
+    Param::Limit,
+    Param::Radius,
+    Param::ZoomStep,
+    Param::Scalar,
+    Param::Speed,
+    Param::RenderScale,
+    Param::Tone,
+    Param::Formula,
+    Param::Gradient,
+    Param::Lyapunov,
+    Param::Light,
+];
+
+impl Param {
+    fn control(&self) -> Control {
+        match self {
+            Param::Limit => Control::Slider(Range { min: 50.0, max: 100_000.0, log: true }),
+            Param::Radius => Control::Slider(Range { min: 2.0, max: 1000.0, log: true }),
+            Param::ZoomStep => Control::Slider(Range { min: 0.001, max: 0.5, log: true }),
+            Param::Scalar => Control::Slider(Range { min: 0.0, max: 3.0, log: false }),
+            Param::Speed | Param::RenderScale | Param::Tone | Param::Formula | Param::Gradient | Param::Lyapunov | Param::Light => Control::Chooser,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Param::Limit => "limit",
+            Param::Radius => "escape radius",
+            Param::ZoomStep => "zoom step",
+            Param::Scalar => "colour scalar",
+            Param::Speed => "speed",
+            Param::RenderScale => "render scale",
+            Param::Tone => "tone",
+            Param::Formula => "formula",
+            Param::Gradient => "gradient",
+            Param::Lyapunov => "lyapunov",
+            Param::Light => "light",
+        }
+    }
+
+    /// The value as the panel gives it.
+    fn shown(&self, params: &Params) -> String {
+        match self {
+            Param::Limit => params.limit.to_string(),
+            Param::Radius => format!("{:.1}", params.radius),
+            Param::ZoomStep => format!("{:.2}%", params.zoom_step * 100.0),
+            Param::Scalar => format!("{:.3}", params.scalar),
+            Param::Speed => format!("{}x", params.speed),
+            Param::RenderScale => format!("{}x", params.render_scale),
+            Param::Tone => params.tone.name().to_string(),
+            Param::Formula => params.formula.name().to_string(),
+            Param::Gradient => params.gradient.name().to_string(),
+            Param::Lyapunov => switch(params.lyapunov).to_string(),
+            Param::Light => switch(params.light).to_string(),
+        }
+    }
+
+    /// A slider's value, as a number.
+    fn number(&self, params: &Params) -> f64 {
+        match self {
+            Param::Limit => params.limit as f64,
+            Param::Radius => params.radius,
+            Param::ZoomStep => params.zoom_step,
+            Param::Scalar => params.scalar as f64,
+            Param::Speed | Param::RenderScale | Param::Tone | Param::Formula | Param::Gradient | Param::Lyapunov | Param::Light => 0.0,
+        }
+    }
+
+    /// [Set]
+    /// The change a slider makes to `value`.
+    fn set(&self, value: f64) -> Option<Change> {
+        match self {
+            Param::Limit => Some(Change::Limit(value.round() as u32)),
+            Param::Radius => Some(Change::Radius(value)),
+            Param::ZoomStep => Some(Change::ZoomStep(value)),
+            Param::Scalar => Some(Change::Scalar(value as f32)),
+            Param::Speed | Param::RenderScale | Param::Tone | Param::Formula | Param::Gradient | Param::Lyapunov | Param::Light => None,
+        }
+    }
+
+    /// [Next]
+    /// The change a chooser makes to its next choice.
+    fn next(&self, params: &Params) -> Option<Change> {
+        match self {
+            Param::Speed => {
+                let i = SPEEDS.iter().position(|&speed| speed == params.speed).map_or(0, |i| (i + 1) % SPEEDS.len());
+                Some(Change::Speed(SPEEDS[i]))
+            }
+            Param::RenderScale => {
+                let i = RENDER_SCALES.iter().position(|&scale| scale == params.render_scale).map_or(0, |i| (i + 1) % RENDER_SCALES.len());
+                Some(Change::RenderScale(RENDER_SCALES[i]))
+            }
+            Param::Tone => Some(Change::Tone(params.tone.other())),
+            Param::Formula => Some(Change::Formula(params.formula.next())),
+            Param::Gradient => Some(Change::Gradient(params.gradient.next())),
+            Param::Lyapunov => Some(Change::Lyapunov(!params.lyapunov)),
+            Param::Light => Some(Change::Light(!params.light)),
+            Param::Limit | Param::Radius | Param::ZoomStep | Param::Scalar => None,
+        }
+    }
+}
+
+/// [Panel]
+///
+/// Fields:
+/// [visible] Whether the panel is shown;
+/// [collapsed] Whether it is folded up to its title;
+/// [held] The slider the pointer was pressed on, until it is let go.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Panel {
+    pub visible: bool,
+    pub collapsed: bool,
+    held: Option<Param>,
+}
+
+impl Panel {
+    /// [Bounds]
+    /// The rectangle the panel covers, as [x, y, width, height], with
+    /// its top at `top`, if it is visible.
+    pub fn bounds(&self, top: f64) -> Option<[f64; 4]> {
+        let rows = if self.collapsed { 0 } else { PARAMS.len() };
+        self.visible.then_some([LEFT, top, WIDTH, TITLE + rows as f64 * ROW + PADDING])
+    }
+
+    /// Whether a point, in logical pixels, is on the panel.
+    pub fn contains(&self, at: [f64; 2], top: f64) -> bool {
+        self.bounds(top).is_some_and(|[x, y, w, h]| (x..x + w).contains(&at[0]) && (y..y + h).contains(&at[1]))
+    }
+
+    /// [Draw]
+    /// Adds the panel down the left of the frame from `top`, if it is
+    /// visible.
+    pub fn draw(&self, overlay: &mut Overlay, params: &Params, top: f64) {
+        let Some([left, top, width, height]) = self.bounds(top) else { return };
+
+        overlay.push(Shape::Rect { rect: [left, top, width, height], colour: BACKING });
+        let title = if self.collapsed { "[+] parameters" } else { "[-] parameters (tab hides)" };
+        overlay.push(Shape::Text { text: title.to_string(), at: [left + PADDING, top + PADDING + TEXT], size: TEXT, colour: WHITE });
+        if self.collapsed {
+            return;
+        }
+
+        for (i, param) in PARAMS.iter().enumerate() {
+            let row = top + TITLE + i as f64 * ROW;
+            let text = match param.control() {
+                Control::Slider(_) => format!("{} {}", param.label(), param.shown(params)),
+                Control::Chooser => format!("{} < {} >", param.label(), param.shown(params)),
+            };
+            overlay.push(Shape::Text { text, at: [left + PADDING, row + PADDING + TEXT], size: TEXT, colour: WHITE });
+
+            if let Control::Slider(range) = param.control() {
+                let [x, y, w, h] = track(left, row);
+                overlay.push(Shape::Rect { rect: [x, y, w, h], colour: TRACK_COLOUR });
+                overlay.push(Shape::Rect { rect: [x, y, w * range.position(param.number(params)), h], colour: KNOB_COLOUR });
+            }
+        }
+    }
+
+    /// [Click]
+    /// Handles a click at a point on the panel, in logical pixels,
+    /// returning the change it makes, if any. The title folds the panel
+    /// up or out, and a slider is held for dragging.
+    pub fn click(&mut self, at: [f64; 2], params: &Params, top: f64) -> Option<Change> {
+        if !self.contains(at, top) {
+            return None;
+        }
+        if at[1] < top + TITLE {
+            self.collapsed = !self.collapsed;
+            return None;
+        }
+
+        let param = PARAMS.get(((at[1] - top - TITLE) / ROW) as usize).filter(|_| !self.collapsed)?;
+        match param.control() {
+            Control::Slider(range) => {
+                self.held = Some(*param);
+                param.set(range.value(along(at[0])))
+            }
+            Control::Chooser => param.next(params),
+        }
+    }
+
+    /// [Drag]
+    /// Moves the slider held to the pointer at `x`, in logical pixels,
+    /// wherever it has gone, returning the change if it makes one.
+    pub fn drag(&mut self, x: f64, params: &Params) -> Option<Change> {
+        let param = self.held?;
+        let Control::Slider(range) = param.control() else { return None };
+        let change = param.set(range.value(along(x)))?;
+        (Some(change) != param.set(param.number(params))).then_some(change)
+    }
+
+    /// Lets go of the slider held, if one is.
+    pub fn release(&mut self) {
+        self.held = None;
+    }
+}
+
+/// How a switch shows.
+fn switch(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
+
+/// How far along the sliders' tracks a point at `x` is, from 0 to 1.
+fn along(x: f64) -> f64 {
+    let [left, _, width, _] = track(LEFT, 0.0);
+    (x - left) / width
+}
+
+/// The track of a slider in the row starting at `row`, as [x, y, width, height].
+fn track(left: f64, row: f64) -> [f64; 4] {
+    [left + PADDING, row + ROW - TRACK - PADDING, WIDTH - 2.0 * PADDING, TRACK]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> Params {
+        Params { limit: 1200, radius: 2.0, zoom_step: 0.05, scalar: 2.0, speed: 1, render_scale: 1.0, tone: Tone::Srgb, formula: Formula::Mandelbrot, gradient: Gradient::Blue, lyapunov: false, light: false }
+    }
+
+    /// The middle of the given row of controls, along its track.
+    fn middle(row: usize) -> [f64; 2] {
+        [LEFT + WIDTH / 2.0, 10.0 + TITLE + row as f64 * ROW + ROW / 2.0]
+    }
+
+    #[test]
+    fn sliders_jump_and_choosers_step() {
+        let mut panel = Panel { visible: true, ..Panel::default() };
+
+        assert_eq!(panel.click(middle(0), &params(), 10.0), Some(Change::Limit(2236)));
+        assert_eq!(panel.click(middle(3), &params(), 10.0), Some(Change::Scalar(1.5)));
+        assert_eq!(panel.click(middle(4), &params(), 10.0), Some(Change::Speed(2)));
+        assert_eq!(panel.click(middle(4), &Params { speed: 8, ..params() }, 10.0), Some(Change::Speed(1)));
+        assert_eq!(panel.click(middle(5), &params(), 10.0), Some(Change::RenderScale(2.0)));
+        assert_eq!(panel.click(middle(7), &params(), 10.0), Some(Change::Formula(Formula::BurningShip)));
+        assert_eq!(panel.click([200.0, 50.0], &params(), 10.0), None);
+    }
+
+    #[test]
+    fn gradients_step_and_switches_toggle() {
+        let mut panel = Panel { visible: true, ..Panel::default() };
+        let mut overlay = Overlay::new();
+
+        assert_eq!(panel.click(middle(8), &params(), 10.0), Some(Change::Gradient(Gradient::Fire)));
+        assert_eq!(panel.click(middle(8), &Params { gradient: Gradient::Grey, ..params() }, 10.0), Some(Change::Gradient(Gradient::Blue)));
+        assert_eq!(panel.click(middle(9), &params(), 10.0), Some(Change::Lyapunov(true)));
+        assert_eq!(panel.click(middle(9), &Params { lyapunov: true, ..params() }, 10.0), Some(Change::Lyapunov(false)));
+        assert_eq!(panel.click(middle(10), &params(), 10.0), Some(Change::Light(true)));
+        assert_eq!(panel.click(middle(11), &params(), 10.0), None, "past the last row");
+
+        panel.draw(&mut overlay, &Params { light: true, ..params() }, 10.0);
+        let texts: Vec<&Shape> = overlay.shapes().iter().filter(|shape| matches!(shape, Shape::Text { .. })).collect();
+        assert!(matches!(texts.last(), Some(Shape::Text { text, .. }) if text == "light < on >"), "{texts:?}");
+    }
+
+    #[test]
+    fn held_sliders_follow_the_pointer() {
+        let mut panel = Panel { visible: true, ..Panel::default() };
+        let [x, _, w, _] = track(LEFT, 0.0);
+
+        // The escape radius, from 2 up to 1000 at the far end, and past it.
+        assert!(matches!(panel.click(middle(1), &params(), 10.0), Some(Change::Radius(_))));
+        assert_eq!(panel.drag(x + w, &params()), Some(Change::Radius(1000.0)));
+        assert_eq!(panel.drag(x + 2.0 * w, &params()), Some(Change::Radius(1000.0)));
+        assert_eq!(panel.drag(x - w, &params()), None, "already at 2");
+
+        // Choosers are not held, and nothing is once let go.
+        panel.release();
+        assert_eq!(panel.drag(x + w, &params()), None);
+        panel.click(middle(4), &params(), 10.0);
+        assert_eq!(panel.drag(x + w, &params()), None);
+    }
+
+    #[test]
+    fn folds_up_and_hides() {
+        let mut panel = Panel { visible: true, ..Panel::default() };
+        let mut overlay = Overlay::new();
+        panel.draw(&mut overlay, &params(), 10.0);
+        assert_eq!(overlay.shapes()[2], Shape::Text { text: "limit 1200".to_string(), at: [8.0, 36.0], size: TEXT, colour: WHITE });
+
+        assert_eq!(panel.click([20.0, 15.0], &params(), 10.0), None);
+        assert!(panel.collapsed);
+        assert!(panel.contains([20.0, 15.0], 10.0));
+        assert!(!panel.contains(middle(0), 10.0));
+
+        panel.visible = false;
+        assert!(!panel.contains([20.0, 15.0], 10.0));
+        assert_eq!(panel.bounds(10.0), None);
+    }
+}
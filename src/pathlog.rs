@@ -0,0 +1,224 @@
+//! [Path Log]
+//!
+//! The `--log-path` file: one line per computed frame, appended for as
+//! long as the zoom runs, with the time, the frame number, the view and
+//! how long it took, as the fields of P would give them. Each line is
+//! the timestamp followed by name=value fields separated by spaces, in
+//! a fixed order, so that awk can split it on spaces and on '='. Writes
+//! are buffered and flushed every second, so a crash loses at most the
+//! last second. While fast-forwarding, a frame's steps field says how
+//! many zoom steps ahead the next is, rather than duplicate lines
+//! standing in for the frames skipped. `--resume-from` reads a log back
+//! and starts from the last view in it, skipping a last line the crash
+//! cut short.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use num::complex::Complex as cmp;
+
+use crate::error::AppError;
+use crate::hud::millis;
+
+/// How often the buffered lines are written out.
+const FLUSH_EVERY: Duration = Duration::from_secs(1);
+
+/// [Log Entry]
+///
+/// Fields:
+/// [time] When the frame was computed;
+/// [frame] Its number, counting from 1 as P does;
+/// [centre] The centre of its view;
+/// [width] The width of its view on the complex plane;
+/// [limit] The iteration limit;
+/// [compute] How long its escape-time pass took, where there is a clock;
+/// [steps] How many zoom steps ahead of it the next frame is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LogEntry {
+    pub time: DateTime<Local>,
+    pub frame: u64,
+    pub centre: cmp<f64>,
+    pub width: f64,
+    pub limit: u32,
+    pub compute: Option<Duration>,
+    pub steps: u32,
+}
+
+impl LogEntry {
+    /// The entry as a line of the log, at full precision.
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} frame={} re={} im={} width={} limit={} compute_ms={} steps={}",
+            self.time.to_rfc3339(),
+            self.frame,
+            self.centre.re,
+            self.centre.im,
+            self.width,
+            self.limit,
+            self.compute.map_or("-".to_string(), |compute| format!("{:.3}", millis(compute))),
+            self.steps,
+        )
+    }
+
+    /// [Parse]
+    /// Reads an entry back from a line of the log, or None if it is not
+    /// a whole one. Lines logged before there were steps took one.
+    pub fn parse(line: &str) -> Option<LogEntry> {
+        let mut fields = line.split(' ');
+        let time = DateTime::parse_from_rfc3339(fields.next()?).ok()?.with_timezone(&Local);
+        let mut field = |name: &str| fields.next()?.strip_prefix(name)?.strip_prefix('=');
+
+        let frame = field("frame")?.parse().ok()?;
+        let (re, im, width): (f64, f64, f64) = (field("re")?.parse().ok()?, field("im")?.parse().ok()?, field("width")?.parse().ok()?);
+        let limit = field("limit")?.parse().ok()?;
+        let compute = match field("compute_ms")? {
+            "-" => None,
+            ms => Some(Duration::from_secs_f64(ms.parse::<f64>().ok().filter(|ms| *ms >= 0.0)? / 1000.0)),
+        };
+        let steps = match fields.next() {
+            Some(field) => field.strip_prefix("steps=")?.parse().ok().filter(|steps| *steps > 0)?,
+            None => 1,
+        };
+
+        let finite = re.is_finite() && im.is_finite() && width.is_finite() && width > 0.0;
+        finite.then_some(LogEntry { time, frame, centre: cmp::new(re, im), width, limit, compute, steps })
+    }
+}
+
+/// [Path Log]
+///
+/// Fields:
+/// [path] The file being appended to;
+/// [writer] The buffer in front of it;
+/// [flushed] When the buffer was last written out.
+#[derive(Debug)]
+pub struct PathLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    flushed: Instant,
+}
+
+impl PathLog {
+    /// [Open]
+    /// Opens the log at `path` for appending, creating it if need be.
+    pub fn open(path: &Path) -> Result<PathLog, AppError> {
+        let file = OpenOptions::new().create(true).append(true).open(path).map_err(|e| export_error(path, e))?;
+
+        Ok(PathLog { path: path.to_path_buf(), writer: BufWriter::new(file), flushed: Instant::now() })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// [Record]
+    /// Adds an entry, writing the buffer out if it is due.
+    pub fn record(&mut self, entry: &LogEntry) -> Result<(), AppError> {
+        writeln!(self.writer, "{}", entry.to_line()).map_err(|e| export_error(&self.path, e))?;
+        self.tick()
+    }
+
+    /// [Tick]
+    /// Writes the buffer out if it has been a second since it last was.
+    /// Called on every update as well as every record, so that the last
+    /// lines before a pause or a long frame are written out all the same.
+    pub fn tick(&mut self) -> Result<(), AppError> {
+        if self.flushed.elapsed() >= FLUSH_EVERY {
+            self.flushed = Instant::now();
+            self.writer.flush().map_err(|e| export_error(&self.path, e))?;
+        }
+        Ok(())
+    }
+}
+
+fn export_error(path: &Path, e: std::io::Error) -> AppError {
+    AppError::Export { path: path.to_path_buf(), reason: e.to_string() }
+}
+
+/// [Last Entry]
+/// The last whole entry in the text of a log, if there is one.
+pub fn last_entry(text: &str) -> Option<LogEntry> {
+    text.lines().rev().find_map(LogEntry::parse)
+}
+
+/// [Resume]
+/// The last entry in the log at `path`, to carry on from.
+pub fn resume(path: &Path) -> Result<LogEntry, AppError> {
+    let error = |reason: String| AppError::Resume { path: path.to_path_buf(), reason };
+
+    let text = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    last_entry(&text).ok_or_else(|| error("no views logged".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(frame: u64) -> LogEntry {
+        LogEntry {
+            time: DateTime::parse_from_rfc3339("2024-04-11T09:30:00.125+01:00").unwrap().with_timezone(&Local),
+            frame,
+            centre: cmp::new(0.3602404434376143, -0.6413130610648032),
+            width: 1.25e-7,
+            limit: 1200,
+            compute: Some(Duration::from_micros(12_345)),
+            steps: 1,
+        }
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        let line = entry(42).to_line();
+
+        assert!(line.ends_with(" frame=42 re=0.3602404434376143 im=-0.6413130610648032 width=0.000000125 limit=1200 compute_ms=12.345 steps=1"), "{line}");
+        assert_eq!(LogEntry::parse(&line), Some(entry(42)));
+        assert_eq!(LogEntry::parse(&LogEntry { compute: None, ..entry(42) }.to_line()).map(|e| e.compute), Some(None));
+        assert_eq!(LogEntry::parse(&LogEntry { steps: 8, ..entry(42) }.to_line()).map(|e| e.steps), Some(8));
+        assert_eq!(LogEntry::parse(line.trim_end_matches(" steps=1")), Some(entry(42)));
+    }
+
+    #[test]
+    fn resumes_past_a_cut_off_line() {
+        let (first, second) = (entry(1).to_line(), entry(2).to_line());
+        let text = format!("{first}\n{second}\n{}", &second[..second.len() - 20]);
+
+        assert_eq!(last_entry(&text), Some(entry(2)));
+        assert_eq!(last_entry("not a log\n"), None);
+    }
+
+    #[test]
+    fn appends_and_reads_back() {
+        let path = std::env::temp_dir().join(format!("mandelbrot-pathlog-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut log = PathLog::open(&path).unwrap();
+        log.record(&entry(1)).unwrap();
+        log.record(&entry(2)).unwrap();
+        drop(log);
+
+        let resumed = resume(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(resumed.unwrap(), entry(2));
+        assert!(matches!(resume(&path), Err(AppError::Resume { .. })));
+    }
+
+    #[test]
+    fn writes_out_on_the_tick_without_another_record() {
+        let path = std::env::temp_dir().join(format!("mandelbrot-pathlog-tick-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut log = PathLog::open(&path).unwrap();
+        log.record(&entry(1)).unwrap();
+        log.tick().unwrap();
+        let buffered = fs::read_to_string(&path).unwrap();
+
+        log.flushed -= FLUSH_EVERY;
+        log.tick().unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!((buffered, last_entry(&written)), (String::new(), Some(entry(1))));
+    }
+}
@@ -0,0 +1,84 @@
+//! [Pause]
+//!
+//! Why the zoom is paused, and the indicator drawn over the frame while
+//! it is. A paused frame is a still like any other, so without the
+//! indicator a pause is easily mistaken for a slow frame.
+
+use crate::overlay::{Overlay, Shape, ADVANCE};
+
+/// How thick the border around a paused frame is, in pixels.
+const BORDER: f64 = 2.0;
+
+/// [Pause]
+///
+/// Variants:
+/// [User] Paused with Space;
+/// [Degenerate] Paused because the frame is all interior or all fast
+///              escapes, with nothing left to zoom into;
+/// [Recovered] Paused because the view went wrong and was put right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pause {
+    User,
+    Degenerate,
+    Recovered,
+}
+
+impl Pause {
+    /// What the indicator and the window title call the pause.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Pause::User => "paused",
+            Pause::Degenerate => "paused: empty frame",
+            Pause::Recovered => "paused: view recovered",
+        }
+    }
+
+    /// The colour of the indicator, so pauses for different reasons can
+    /// be told apart at a glance.
+    pub fn colour(&self) -> [f32; 4] {
+        match self {
+            Pause::User => [1.0, 0.75, 0.1, 0.9],
+            Pause::Degenerate => [0.3, 0.6, 1.0, 0.9],
+            Pause::Recovered => [1.0, 0.3, 0.25, 0.9],
+        }
+    }
+
+    /// [Draw]
+    /// Adds the indicator to a frame of the given size: a thin border in
+    /// the pause's colour, and a pause glyph with its label at the top
+    /// centre, clear of the corner panels.
+    pub fn draw(&self, overlay: &mut Overlay, frame_width: f64, frame_height: f64) {
+        let colour = self.colour();
+        let half = BORDER / 2.0;
+        let corners = [[half, half], [frame_width - half, half], [frame_width - half, frame_height - half], [half, frame_height - half]];
+        overlay.polygon(&corners, BORDER, colour);
+
+        let size = 9.0;
+        let label = self.label();
+        let (glyph_width, gap) = (size * 0.8, size * 0.5);
+        let width = glyph_width + gap + label.len() as f64 * size * ADVANCE;
+        let (left, top) = ((frame_width - width) / 2.0, BORDER + 4.0);
+
+        let bar = glyph_width / 3.0;
+        overlay.push(Shape::Rect { rect: [left, top, bar, size], colour });
+        overlay.push(Shape::Rect { rect: [left + 2.0 * bar, top, bar, size], colour });
+        overlay.push(Shape::Text { text: label.to_string(), at: [left + glyph_width + gap, top + size], size, colour });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borders_the_frame_and_labels_the_pause() {
+        let mut overlay = Overlay::new();
+
+        Pause::User.draw(&mut overlay, 400.0, 200.0);
+
+        let lines = overlay.shapes().iter().filter(|s| matches!(s, Shape::Line { .. })).count();
+        assert_eq!(lines, 4);
+        assert!(overlay.shapes().iter().any(|s| matches!(s, Shape::Text { text, colour, .. }
+            if text == "paused" && *colour == Pause::User.colour())));
+    }
+}
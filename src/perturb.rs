@@ -0,0 +1,450 @@
+//! [Perturb]
+//!
+//! Perturbation rendering, with its glitches found and put right. Rather
+//! than iterating each pixel's point, a single reference orbit Z_n is
+//! iterated from the view's centre, and each pixel iterates only its
+//! difference from it, δ_{n+1} = 2·Z_n·δ_n + δ_n² + δc, where δc is the
+//! pixel's offset from the reference, taken from the view and never
+//! rounded to the centre's precision. The pixel's z_n is Z_n + δ_n, and
+//! it escapes as the kernel's own loop would. This is the basis of every
+//! deep-zoom renderer, and added here so that its well-known glitches can
+//! be handled: blobs of wrong counts wherever a pixel's orbit strays too
+//! far from the reference's for the difference to keep its precision.
+//!
+//! Glitches are found by Pauldelbrot's criterion: a pixel is glitched at
+//! the first iteration where |z_n| falls below TOLERANCE·|Z_n|, as then
+//! its z has lost the digits the reference had. A pixel whose reference
+//! escaped before it did has run out of orbit to follow, and is glitched
+//! too. After the frame, glitched pixels are gathered into blobs of
+//! 4-connected pixels, and each blob is iterated again against a secondary
+//! reference of its own, at the blob's pixel nearest its centroid, only
+//! δc changing. Passes go on until no pixel is glitched or MAX_PASSES are
+//! done, and whatever is still glitched then is iterated directly.
+//!
+//! Deep down, most of every pixel's iterations only follow its reference
+//! closely, and they are skipped by series approximation: δ_n is, to the
+//! third order in δc, A_n·δc + B_n·δc² + C_n·δc³, where
+//! A_{n+1} = 2·Z_n·A_n + 1, B_{n+1} = 2·Z_n·B_n + A_n² and
+//! C_{n+1} = 2·Z_n·C_n + 2·A_n·B_n, all iterated once with the reference.
+//! The series is followed for as long as the cubic term, at the largest
+//! δc of the frame, stays below SERIES_TOLERANCE of the linear one, and
+//! no pixel could yet have escaped, and every pixel then starts from it at
+//! that iteration rather than from δ_0. Validating (V) iterates every
+//! VALIDATE_EVERY-th pixel again without the series, and counts those
+//! whose counts differ. What a frame took is kept as its Perturbed, shown
+//! with P.
+//!
+//! The differences are in f64, as they keep their precision however
+//! small they are, but the reference orbits are iterated in double-double
+//! from the view's centre as the viewport keeps it, and only rounded to
+//! f64 as they are stored: an orbit's rounding errors grow as its
+//! differences do, so it needs the precision the pixels do, where f64
+//! would smear views past about 10^-13. Double-double keeps views some
+//! 10^-30 across, where the series skips tens of thousands of iterations
+//! a pixel, in reach; the pixels still glitched at the end are iterated
+//! in double-double too. Only the Mandelbrot set is perturbed; other
+//! formulas are iterated in full.
+//! Toggled with Shift+Z, off by default, and the series with Shift+A, on
+//! by default.
+
+use num::complex::Complex as cmp;
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+use crate::fractal::Mandelbrot;
+use crate::histogram::Histogram;
+use crate::kernel::escape_time;
+use crate::progress::Progress;
+use crate::real::{DoubleDouble, Real};
+use crate::viewport::Viewport;
+
+/// How far below the reference's |Z_n| a pixel's |z_n| may fall before
+/// its count is no longer trusted.
+pub const TOLERANCE: f64 = 1e-3;
+
+/// The most passes a frame takes, the first against the centre's orbit
+/// included, before what is still glitched is iterated directly.
+pub const MAX_PASSES: u32 = 8;
+
+/// The most secondary references taken in a pass, from its largest blobs.
+const MAX_REFERENCES: usize = 64;
+
+/// How large the cubic term of the series may grow against the linear
+/// one before the series is no longer followed.
+pub const SERIES_TOLERANCE: f64 = 1e-6;
+
+/// One pixel in how many is iterated again without the series, when
+/// validating.
+pub const VALIDATE_EVERY: usize = 61;
+
+/// Marks a pixel as glitched in the counts until it is put right.
+const GLITCHED: u32 = u32::MAX;
+
+/// [Perturbed]
+/// What a perturbed frame took.
+///
+/// Fields:
+/// [first] The pixels glitched against the centre's orbit;
+/// [passes] The passes taken, the first included;
+/// [references] The secondary references iterated;
+/// [remaining] The pixels still glitched after the last pass, iterated directly;
+/// [skipped] The iterations every pixel skipped by the centre's series;
+/// [checked] The pixels iterated again without the series, if validating;
+/// [mismatched] How many of those came out with different counts.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Perturbed {
+    pub first: usize,
+    pub passes: u32,
+    pub references: usize,
+    pub remaining: usize,
+    pub skipped: usize,
+    pub checked: usize,
+    pub mismatched: usize,
+}
+
+/// [Reference]
+/// A reference orbit.
+///
+/// Fields:
+/// [offset] Its point's offset from the view's centre;
+/// [orbit] Z_0 onward, up to the first to escape or Z at the limit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reference {
+    pub offset: cmp<f64>,
+    pub orbit: Vec<cmp<f64>>,
+}
+
+impl Reference {
+    /// [New]
+    /// The orbit of the point `offset` from `centre`, up to the limit,
+    /// iterated at the centre's precision.
+    pub fn new<T: Real>(centre: cmp<T>, offset: cmp<f64>, limit: u32) -> Reference {
+        let c = centre + lift(offset);
+        let mut z = cmp::new(T::zero(), T::zero());
+        let mut orbit = vec![cmp::new(0.0, 0.0)];
+
+        while orbit.len() <= limit as usize && z.norm_sqr() < T::from_f64(4.0) {
+            z = z * z + c;
+            orbit.push(cmp::new(z.re.to_f64(), z.im.to_f64()));
+        }
+        Reference { offset, orbit }
+    }
+
+    /// [Count]
+    /// The escape-time count of the point `dc` from the reference's,
+    /// starting from the series, or None if it is glitched against it.
+    pub fn count(&self, dc: cmp<f64>, series: &Series, limit: u32) -> Option<u32> {
+        let mut dz = series.delta(dc);
+
+        for n in series.skip..limit as usize {
+            dz = (self.orbit[n] * 2.0 + dz) * dz + dc;
+            let reference = *self.orbit.get(n + 1)?;
+            let z = reference + dz;
+
+            if z.norm_sqr() >= 4.0 {
+                return Some(n as u32 + 1);
+            }
+            if z.norm_sqr() < TOLERANCE * TOLERANCE * reference.norm_sqr() {
+                return None;
+            }
+        }
+        Some(limit)
+    }
+}
+
+/// [Series]
+/// The series approximation of δ_n against a reference.
+///
+/// Fields:
+/// [skip] The iteration n it holds to, which pixels start from;
+/// [a] A_n, the coefficient of δc;
+/// [b] B_n, of δc²;
+/// [c] C_n, of δc³.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Series {
+    pub skip: usize,
+    pub a: cmp<f64>,
+    pub b: cmp<f64>,
+    pub c: cmp<f64>,
+}
+
+impl Series {
+    /// [Fit]
+    /// The series against a reference for the points within `radius`
+    /// of it, followed for as long as it can be trusted.
+    pub fn fit(reference: &Reference, radius: f64) -> Series {
+        let mut series = Series::default();
+        let (mut a, mut b, mut c) = (series.a, series.b, series.c);
+
+        for (n, pair) in reference.orbit.windows(2).enumerate() {
+            let z = pair[0] * 2.0;
+            (a, b, c) = (z * a + 1.0, z * b + a * a, z * c + a * b * 2.0);
+
+            let truncated = c.norm() * radius * radius > SERIES_TOLERANCE * a.norm();
+            let escaping = pair[1].norm() + radius * (a.norm() + radius * (b.norm() + radius * c.norm())) >= 2.0;
+            if truncated || escaping {
+                break;
+            }
+            series = Series { skip: n + 1, a, b, c };
+        }
+        series
+    }
+
+    /// δ_n at the iteration it holds to, for the point `dc` from the reference's.
+    pub fn delta(&self, dc: cmp<f64>) -> cmp<f64> {
+        ((self.c * dc + self.b) * dc + self.a) * dc
+    }
+}
+
+/// [Compute]
+/// Fills vals with the Mandelbrot counts of the view by perturbation,
+/// putting its glitches right, and skipping iterations by series
+/// approximation if `series`, counting each row of the first pass on
+/// `progress` and giving up once the frame is cancelled as the kernel
+/// does. Validates the series against a sample of pixels if `validate`.
+/// Returns the histogram of the counts with what the frame took.
+pub fn compute(viewport: &Viewport, vals: &mut [u32], limit: u32, series: bool, validate: bool, progress: &Progress) -> (Histogram, Perturbed) {
+    let width = viewport.width_px();
+    let centre = viewport.precise_centre();
+    let offset = |i: usize| viewport.pixel_offset((i % width) as f64, (i / width) as f64);
+    let fit = |reference: &Reference, radius: f64| if series { Series::fit(reference, radius) } else { Series::default() };
+
+    let primary = Reference::new(centre, cmp::new(0.0, 0.0), limit);
+    let primary_series = fit(&primary, viewport.pixel_offset(0.0, 0.0).norm());
+    let fill = |(b, row): (usize, &mut [u32])| {
+        if progress.is_cancelled() {
+            return;
+        }
+        for (a, val) in row.iter_mut().enumerate() {
+            *val = primary.count(offset(b * width + a), &primary_series, limit).unwrap_or(GLITCHED);
+        }
+        progress.row();
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    vals.par_chunks_mut(width.max(1)).enumerate().for_each(fill);
+    #[cfg(target_arch = "wasm32")]
+    vals.chunks_mut(width.max(1)).enumerate().for_each(fill);
+
+    let mut glitches = Perturbed { passes: 1, skipped: primary_series.skip, ..Perturbed::default() };
+    if progress.is_cancelled() {
+        return (Histogram::new(limit), glitches);
+    }
+    glitches.first = vals.iter().filter(|&&val| val == GLITCHED).count();
+
+    while glitches.passes < MAX_PASSES && vals.contains(&GLITCHED) {
+        let mut blobs = blobs(vals, width);
+        blobs.sort_by_key(|blob| std::cmp::Reverse(blob.len()));
+        blobs.truncate(MAX_REFERENCES);
+
+        for blob in blobs {
+            let reference = Reference::new(centre, offset(nearest_centroid(&blob, width)), limit);
+            let radius = blob.iter().map(|&i| (offset(i) - reference.offset).norm()).fold(0.0, f64::max);
+            let series = fit(&reference, radius);
+            let counts = blob.iter().map(|&i| reference.count(offset(i) - reference.offset, &series, limit).unwrap_or(GLITCHED));
+            for (&i, count) in blob.iter().zip(counts.collect::<Vec<_>>()) {
+                vals[i] = count;
+            }
+            glitches.references += 1;
+        }
+        glitches.passes += 1;
+    }
+
+    let mut histogram = Histogram::new(limit);
+    for (i, val) in vals.iter_mut().enumerate() {
+        if *val == GLITCHED {
+            *val = escape_time(&Mandelbrot, centre + lift::<DoubleDouble>(offset(i)), limit);
+            glitches.remaining += 1;
+        }
+        histogram.add(*val);
+    }
+
+    if validate {
+        let unseries = Series::default();
+        for i in (0..vals.len()).step_by(VALIDATE_EVERY) {
+            if let Some(count) = primary.count(offset(i), &unseries, limit) {
+                glitches.checked += 1;
+                glitches.mismatched += (count != vals[i]) as usize;
+            }
+        }
+    }
+    (histogram, glitches)
+}
+
+/// A point of f64s at another precision.
+fn lift<T: Real>(c: cmp<f64>) -> cmp<T> {
+    cmp::new(T::from_f64(c.re), T::from_f64(c.im))
+}
+
+/// [Blobs]
+/// The glitched pixels, by index, gathered into 4-connected blobs.
+fn blobs(vals: &[u32], width: usize) -> Vec<Vec<usize>> {
+    let mut seen = vec![false; vals.len()];
+    let mut blobs = Vec::new();
+
+    for start in 0..vals.len() {
+        if vals[start] != GLITCHED || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let (mut blob, mut stack) = (Vec::new(), vec![start]);
+        while let Some(i) = stack.pop() {
+            blob.push(i);
+            let (a, b) = (i % width, i / width);
+            let left = (a > 0).then(|| i - 1);
+            let right = (a + 1 < width).then_some(i + 1);
+            let up = (b > 0).then(|| i - width);
+            let down = Some(i + width).filter(|&j| j < vals.len());
+            for j in [left, right, up, down].into_iter().flatten() {
+                if vals[j] == GLITCHED && !seen[j] {
+                    seen[j] = true;
+                    stack.push(j);
+                }
+            }
+        }
+        blobs.push(blob);
+    }
+    blobs
+}
+
+/// The pixel of a blob nearest its centroid, which for a blob curled
+/// round on itself may be some way from the centroid, but is always in it.
+fn nearest_centroid(blob: &[usize], width: usize) -> usize {
+    let n = blob.len() as f64;
+    let (x, y) = blob.iter().fold((0.0, 0.0), |(x, y), &i| (x + (i % width) as f64 / n, y + (i / width) as f64 / n));
+    let distance = |i: usize| ((i % width) as f64 - x).powi(2) + ((i / width) as f64 - y).powi(2);
+    blob.iter().copied().min_by(|&i, &j| distance(i).total_cmp(&distance(j))).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigInt;
+
+    use super::*;
+
+    fn brute(viewport: &Viewport, limit: u32) -> Vec<u32> {
+        let mut vals = vec![0; viewport.width_px() * viewport.height_px()];
+        crate::kernel::compute_sequential(&Mandelbrot, &mut vals, viewport.width_px(), |a, b| viewport.pixel_to_complex(a as f64, b as f64), limit);
+        vals
+    }
+
+    #[test]
+    fn counts_as_the_kernel_does() {
+        let reference = Reference::new(cmp::new(-0.745, 0.1), cmp::new(0.0, 0.0), 500);
+        assert_eq!(reference.count(cmp::new(0.0, 0.0), &Series::default(), 500), Some(escape_time(&Mandelbrot, cmp::new(-0.745, 0.1), 500)));
+
+        let viewport = Viewport::new(cmp::new(-0.745, 0.1), 0.01, 64, 48);
+        let mut vals = vec![0; 64 * 48];
+        let (histogram, glitches) = compute(&viewport, &mut vals, 500, false, false, &Progress::default());
+
+        let exact = brute(&viewport, 500);
+        let matching = vals.iter().zip(&exact).filter(|(x, y)| x == y).count();
+        assert!(matching * 100 >= vals.len() * 99, "{matching} of {}", vals.len());
+        assert_eq!(histogram.total(), vals.len() as u64);
+        assert_eq!(glitches.remaining, 0);
+    }
+
+    #[test]
+    fn puts_glitches_right_against_secondary_references() {
+        // The centre escapes early, so the interior around the cusp runs
+        // out of reference orbit and is glitched on the first pass.
+        let viewport = Viewport::new(cmp::new(0.3, 0.0), 0.4, 60, 40);
+        let mut vals = vec![0; 60 * 40];
+        let (_, glitches) = compute(&viewport, &mut vals, 200, false, false, &Progress::default());
+
+        assert!(glitches.first > 0);
+        assert!(glitches.passes > 1 && glitches.references > 0, "{glitches:?}");
+        let exact = brute(&viewport, 200);
+        let matching = vals.iter().zip(&exact).filter(|(x, y)| x == y).count();
+        assert!(matching * 100 >= vals.len() * 99, "{matching} of {}", vals.len());
+    }
+
+    #[test]
+    fn series_skips_what_perturbation_would_iterate() {
+        let viewport = Viewport::new(cmp::new(-0.743643887037151, 0.131825904205330), 1e-9, 48, 32);
+        let mut approximated = vec![0; 48 * 32];
+        let (_, took) = compute(&viewport, &mut approximated, 3000, true, true, &Progress::default());
+        let mut perturbed = vec![0; 48 * 32];
+        compute(&viewport, &mut perturbed, 3000, false, false, &Progress::default());
+
+        assert!(took.skipped > 100, "{took:?}");
+        assert!(took.checked > 0 && took.mismatched * 100 <= took.checked, "{took:?}");
+        let matching = approximated.iter().zip(&perturbed).filter(|(x, y)| x == y).count();
+        assert!(matching * 100 >= approximated.len() * 99, "{matching} of {}", approximated.len());
+
+        // The series reproduces the perturbed δ_n it stands for.
+        let reference = Reference::new(viewport.centre(), cmp::new(0.0, 0.0), 3000);
+        let series = Series::fit(&reference, 1e-9);
+        let dc = cmp::new(3e-10, -2e-10);
+        let mut dz = cmp::new(0.0, 0.0);
+        for z in &reference.orbit[..series.skip] {
+            dz = (z * 2.0 + dz) * dz + dc;
+        }
+        assert!((series.delta(dc) - dz).norm() <= 1e-6 * dz.norm(), "{} against {}", series.delta(dc), dz);
+    }
+
+    /// A number as fixed point, with BITS bits after the point.
+    const BITS: usize = 256;
+
+    fn fixed(x: f64) -> BigInt {
+        let (mantissa, exponent, sign) = num::Float::integer_decode(x);
+        let shift = exponent as i64 + BITS as i64;
+        let magnitude = if shift >= 0 { BigInt::from(mantissa) << shift as usize } else { BigInt::from(mantissa) >> (-shift) as usize };
+        magnitude * sign as i64
+    }
+
+    /// The escape-time count of c = re + i·im, each a sum of f64s, at BITS bits.
+    fn exact(re: &[f64], im: &[f64], limit: u32) -> u32 {
+        let (cr, ci): (BigInt, BigInt) = (re.iter().map(|&x| fixed(x)).sum(), im.iter().map(|&x| fixed(x)).sum());
+        let four = BigInt::from(4) << BITS;
+        let (mut zr, mut zi) = (BigInt::from(0), BigInt::from(0));
+
+        for n in 1..=limit {
+            let zri = (&zr * &zi) >> (BITS - 1);
+            zr = ((&zr * &zr - &zi * &zi) >> BITS) + &cr;
+            zi = zri + &ci;
+            if ((&zr * &zr + &zi * &zi) >> BITS) >= four {
+                return n;
+            }
+        }
+        limit
+    }
+
+    #[test]
+    fn views_30_digits_deep_match_exact_arithmetic() {
+        // Just off the Misiurewicz point i, which has detail at every depth.
+        let mut viewport = Viewport::new(cmp::new(0.0, 1.0), 1e-30, 32, 16);
+        viewport.pan(cmp::new(3e-31, 2e-31));
+        let mut vals = vec![0; 32 * 16];
+        compute(&viewport, &mut vals, 1000, true, false, &Progress::default());
+
+        let centre = viewport.precise_centre();
+        let exact: Vec<u32> = (0..vals.len()).map(|i| {
+            let offset = viewport.pixel_offset((i % 32) as f64, (i / 32) as f64);
+            exact(&[centre.re.hi(), centre.re.lo(), offset.re], &[centre.im.hi(), centre.im.lo(), offset.im], 1000)
+        }).collect();
+        let matching = vals.iter().zip(&exact).filter(|(x, y)| x == y).count();
+        assert!(matching * 100 >= vals.len() * 99, "{matching} of {}", vals.len());
+        assert!(exact.iter().min() < exact.iter().max(), "the view has detail");
+
+        // The centre rounded to f64 is another view altogether.
+        let mut rounded = vec![0; 32 * 16];
+        compute(&Viewport::new(viewport.centre(), 1e-30, 32, 16), &mut rounded, 1000, true, false, &Progress::default());
+        let matching = rounded.iter().zip(&exact).filter(|(x, y)| x == y).count();
+        assert!(matching * 2 < vals.len(), "{matching} of {}", vals.len());
+    }
+
+    #[test]
+    fn gathers_blobs_by_their_edges() {
+        let g = GLITCHED;
+        let vals = [
+            g, g, 1, g,
+            1, g, 1, g,
+            g, 1, 1, 1,
+        ];
+        let mut blobs = blobs(&vals, 4);
+        blobs.iter_mut().for_each(|blob| blob.sort());
+
+        assert_eq!(blobs, vec![vec![0, 1, 5], vec![3, 7], vec![8]]);
+        assert_eq!(nearest_centroid(&[0, 1, 5], 4), 1);
+    }
+}
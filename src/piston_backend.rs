@@ -0,0 +1,304 @@
+//! [Piston Backend]
+//!
+//! The default backend: a Glutin window driven by Piston's event loop,
+//! with frames drawn through OpenGL as a single texture and overlays
+//! drawn over it at window resolution. Frames are letterboxed to the
+//! window, whatever shape it is. Buffers are swapped by a present
+//! rather than after every render event, so that a render the
+//! application skips leaves the last frame on screen.
+
+use std::sync::Arc;
+
+use glutin_window::GlutinWindow as Window;
+use graphics::{ImageSize, Transformed};
+use image::RgbaImage;
+use mandelbrot_piston::backend::{Backend, Event, Key, Pacing};
+use mandelbrot_piston::error::AppError;
+use mandelbrot_piston::fit::Placement;
+use mandelbrot_piston::overlay::{Bitmap, Overlay, Shape, FONT};
+use mandelbrot_piston::screen::Placing;
+use opengl_graphics::{Filter, GlGraphics, GlyphCache, OpenGL, Texture, TextureSettings};
+use piston::event_loop::{EventLoop, EventSettings, Events};
+use piston::input::{Button, MouseButton, MouseCursorEvent, PressEvent, ReleaseEvent, RenderArgs, RenderEvent, ResizeEvent, TextEvent, UpdateEvent};
+use piston::window::{AdvancedWindow, Window as _, WindowSettings};
+
+use crate::catch_windowing_panic;
+
+/// [Piston Backend]
+///
+/// Fields:
+/// [window] The Glutin window;
+/// [gl] OpenGL graphics backend;
+/// [events] Piston's event loop, which paces updates and renders;
+/// [texture] The frame texture, created on the first present;
+/// [glyphs] Rendered glyphs of the overlay font;
+/// [bitmaps] Textures of the bitmaps overlays have drawn, while they are in use;
+/// [args] The render arguments of the render event being serviced;
+/// [placement] Where the frame sat in the window, and its size, as of the last present;
+/// [scale] The scale factor last reported;
+/// [pending] An event held back to follow the one just returned;
+/// [shift] Whether either Shift key is held;
+/// [ctrl] Whether either Ctrl key is held.
+pub struct PistonBackend {
+    window: Window,
+    gl: GlGraphics,
+    events: Events,
+    texture: Option<Texture>,
+    glyphs: GlyphCache<'static>,
+    bitmaps: Vec<(Arc<Bitmap>, Texture)>,
+    args: Option<RenderArgs>,
+    placement: Option<(Placement, [usize; 2])>,
+    scale: f64,
+    pending: Option<Event>,
+    shift: bool,
+    ctrl: bool,
+}
+
+impl PistonBackend {
+    /// [New]
+    /// Opens a window of the given size, using the given OpenGL version
+    /// (as "major.minor"), where it was asked to open.
+    pub fn new(title: &str, width: usize, height: usize, api: &str, placing: &Placing) -> Result<PistonBackend, AppError> {
+        let opengl: OpenGL = api.parse()
+            .map_err(|_| AppError::Args(format!("unsupported OpenGL version '{api}'")))?;
+
+        // Create a Glutin window.
+        let window = build_window(
+            WindowSettings::new(title, [width as f64, height as f64])
+                .graphics_api(opengl)
+                // Esc is the app's, to stop a transition or else to close the window.
+                .exit_on_esc(false),
+            api,
+        )?;
+        crate::monitors::place(&window.window, placing);
+
+        Ok(PistonBackend {
+            window,
+            gl: GlGraphics::new(opengl),
+            events: Events::new(pacing(EventSettings::new().swap_buffers(false), Pacing::default())),
+            texture: None,
+            glyphs: GlyphCache::from_bytes(FONT, (), TextureSettings::new())
+                .map_err(|e| AppError::Backend { name: "piston", reason: format!("could not load the overlay font: {e:?}") })?,
+            bitmaps: Vec::new(),
+            args: None,
+            placement: None,
+            scale: 1.0,
+            pending: None,
+            shift: false,
+            ctrl: false,
+        })
+    }
+}
+
+impl Backend for PistonBackend {
+    fn next_event(&mut self) -> Option<Event> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+
+        while let Some(e) = self.events.next(&mut self.window) {
+            // Piston only tells the scale factor through the sizes of a
+            // render, so a change is reported just ahead of the render.
+            if let Some(args) = e.render_args() {
+                self.args = Some(args);
+                let scale = args.draw_size[0] as f64 / args.window_size[0];
+                if scale.is_finite() && scale > 0.0 && scale != self.scale {
+                    self.scale = scale;
+                    self.pending = Some(Event::Render);
+                    return Some(Event::Scale(scale));
+                }
+                return Some(Event::Render);
+            }
+
+            if e.update_args().is_some() {
+                return Some(Event::Update);
+            }
+
+            if let Some(args) = e.resize_args() {
+                return Some(Event::Resize(args.window_size));
+            }
+
+            // The pointer can only be placed on the frame once one is
+            // shown, and not on the bars around it.
+            if let (Some(at), Some((placement, frame))) = (e.mouse_cursor_args(), self.placement) {
+                if let Some(at) = placement.to_frame(at, frame) {
+                    return Some(Event::Cursor(at));
+                }
+                continue;
+            }
+
+            // Glutin reports each character typed as text of its own.
+            if let Some(c) = e.text_args().and_then(|text| text.chars().next()) {
+                return Some(Event::Text(c));
+            }
+
+            use piston::input::Key as K;
+            match e.release_args() {
+                Some(Button::Keyboard(K::LShift | K::RShift)) => self.shift = false,
+                Some(Button::Keyboard(K::LCtrl | K::RCtrl)) => self.ctrl = false,
+                Some(Button::Mouse(MouseButton::Left)) => return Some(Event::Release),
+                _ => {}
+            }
+
+            match e.press_args() {
+                Some(Button::Keyboard(K::LShift | K::RShift)) => self.shift = true,
+                Some(Button::Keyboard(K::LCtrl | K::RCtrl)) => self.ctrl = true,
+                Some(Button::Mouse(MouseButton::Left)) => return Some(if self.ctrl { Event::CtrlClick } else { Event::Click }),
+                Some(Button::Keyboard(K::V)) if self.ctrl => return Some(Event::Paste),
+                Some(Button::Keyboard(key)) => {
+                    if let Some(key) = map_key(key, self.shift) {
+                        return Some(Event::Press(key));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// [Present]
+    /// The render event is where all calls to OpenGL happen. The frame is
+    /// uploaded to a texture and drawn as large as fits in the window,
+    /// with nearest-neighbour filtering so pixels stay crisp, after the
+    /// whole window is cleared so the bars never show an old frame. The
+    /// overlay is drawn afterwards, placed the same way, without touching
+    /// the texture.
+    /// The frame is sRGB already: the texture (SRGB_ALPHA, as gamma is
+    /// not converted) decodes it and the sRGB framebuffer encodes it
+    /// again, so the screen shows the bytes a screenshot saves.
+    fn present(&mut self, rgba: &[u8], width: usize, height: usize, overlay: &Overlay) {
+        let Some(args) = self.args.take() else { return };
+        let Some(frame) = RgbaImage::from_raw(width as u32, height as u32, rgba.to_vec()) else { return };
+
+        let texture = match &mut self.texture {
+            Some(texture) if texture.get_size() == (width as u32, height as u32) => {
+                texture.update(&frame);
+                texture
+            }
+            slot => slot.insert(Texture::from_image(&frame, &TextureSettings::new().filter(Filter::Nearest))),
+        };
+
+        // Bitmaps are uploaded the first time they are drawn, and dropped
+        // once nothing but this cache holds them.
+        self.bitmaps.retain(|(bitmap, _)| Arc::strong_count(bitmap) > 1);
+        for shape in overlay.shapes() {
+            if let Shape::Image { bitmap, .. } = shape {
+                if !self.bitmaps.iter().any(|(b, _)| Arc::ptr_eq(b, bitmap)) {
+                    if let Some(image) = RgbaImage::from_raw(bitmap.width as u32, bitmap.height as u32, bitmap.rgba.clone()) {
+                        self.bitmaps.push((bitmap.clone(), Texture::from_image(&image, &TextureSettings::new())));
+                    }
+                }
+            }
+        }
+
+        let (glyphs, bitmaps) = (&mut self.glyphs, &self.bitmaps);
+        let placement = Placement::letterbox([width, height], args.window_size);
+        self.placement = Some((placement, [width, height]));
+        let ([ox, oy], sx, sy) = (placement.offset, placement.scale, placement.scale);
+        self.gl.draw(args.viewport(), |c, gl| {
+            graphics::clear([0.0, 0.0, 0.0, 1.0], gl);
+
+            // Shapes are placed from the frame's corner, at window resolution.
+            let c = c.trans(ox, oy);
+            graphics::image(texture, c.transform.scale(sx, sy), gl);
+
+            for shape in overlay.shapes() {
+                match shape {
+                    Shape::Rect { rect: [x, y, w, h], colour } => {
+                        graphics::rectangle(*colour, [x * sx, y * sy, w * sx, h * sy], c.transform, gl);
+                    }
+                    Shape::Line { from: [x1, y1], to: [x2, y2], width, colour } => {
+                        graphics::line(*colour, width * sx.min(sy) / 2.0, [x1 * sx, y1 * sy, x2 * sx, y2 * sy], c.transform, gl);
+                    }
+                    Shape::Image { bitmap, rect: [x, y, w, h] } => {
+                        if let Some((_, texture)) = bitmaps.iter().find(|(b, _)| Arc::ptr_eq(b, bitmap)) {
+                            let transform = c.transform.trans(x * sx, y * sy)
+                                .scale(w * sx / bitmap.width as f64, h * sy / bitmap.height as f64);
+                            graphics::image(texture, transform, gl);
+                        }
+                    }
+                    Shape::Text { text, at: [x, y], size, colour } => {
+                        // Text too small to draw would only fill the cache.
+                        let size = (size * sy).round() as u32;
+                        if size > 0 {
+                            let transform = c.transform.trans(x * sx, y * sy);
+                            let _ = graphics::text(*colour, size, text, glyphs, transform, gl);
+                        }
+                    }
+                }
+            }
+        });
+        self.window.swap_buffers();
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title.to_string());
+    }
+
+    fn set_pacing(&mut self, pacing: Pacing) {
+        self.events.set_ups(pacing.ups);
+        self.events.set_max_fps(pacing.max_fps);
+        self.events.set_lazy(pacing.lazy);
+    }
+}
+
+/// The event settings with a pacing's rates.
+fn pacing(settings: EventSettings, pacing: Pacing) -> EventSettings {
+    settings.ups(pacing.ups).max_fps(pacing.max_fps).lazy(pacing.lazy)
+}
+
+/// Piston reports keys with SDL keycodes, where letters and digits are
+/// their lowercase ASCII values.
+fn map_key(key: piston::input::Key, shift: bool) -> Option<Key> {
+    use piston::input::Key as K;
+
+    let code = key as u32;
+    if (b'a' as u32..=b'z' as u32).contains(&code) {
+        let c = char::from_u32(code)?;
+        return Some(Key::Char(if shift { c.to_ascii_uppercase() } else { c }));
+    }
+    if (b'0' as u32..=b'9' as u32).contains(&code) {
+        return char::from_u32(code).map(Key::Char);
+    }
+
+    Some(match key {
+        K::Space => Key::Space,
+        K::Escape => Key::Escape,
+        K::Tab => Key::Tab,
+        K::Return => Key::Return,
+        K::Backspace => Key::Backspace,
+        K::Left => Key::Left,
+        K::Right => Key::Right,
+        K::Up => Key::Up,
+        K::Down => Key::Down,
+        K::LeftBracket => Key::Char('['),
+        K::RightBracket => Key::Char(']'),
+        K::F1 => Key::F(1),
+        K::F2 => Key::F(2),
+        K::F3 => Key::F(3),
+        K::F4 => Key::F(4),
+        K::F5 => Key::F(5),
+        K::F6 => Key::F(6),
+        K::F7 => Key::F(7),
+        K::F8 => Key::F(8),
+        K::F9 => Key::F(9),
+        K::F10 => Key::F(10),
+        K::F11 => Key::F(11),
+        K::F12 => Key::F(12),
+        _ => return None,
+    })
+}
+
+/// [Build Window]
+/// Creates the Glutin window, reporting a failure to create it (or
+/// winit's panic when there is no display) as a window error.
+fn build_window(settings: WindowSettings, api: &str) -> Result<Window, AppError> {
+    let reason = match catch_windowing_panic(|| settings.build::<Window>()) {
+        Ok(Ok(window)) => return Ok(window),
+        Ok(Err(e)) => e.to_string(),
+        Err(reason) => reason,
+    };
+
+    Err(AppError::Window { api: api.to_string(), reason })
+}
@@ -0,0 +1,271 @@
+//! [Pixels Backend]
+//!
+//! An alternative to the Piston stack, for machines where its OpenGL
+//! window fails to open: a plain winit window with frames uploaded
+//! through the pixels crate (wgpu underneath). Built with the `pixels`
+//! feature and selected with `--backend pixels`. Overlays are not
+//! drawn yet: this backend shows the bare frame. pixels letterboxes the
+//! frame itself, clearing the bars each frame, and maps the pointer
+//! back through the same placement. The scale factor is reported when
+//! the window opens and whenever it moves to a display with another.
+//!
+//! winit normally owns the event loop, so events are pumped with
+//! `run_return` and queued, and updates and renders are paced here at
+//! the rates the application asks for, waiting on input alone when it
+//! asks for lazy pacing as Piston does.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use mandelbrot_piston::backend::{Backend, Event, Key, Pacing};
+use mandelbrot_piston::error::{report, AppError};
+use mandelbrot_piston::overlay::Overlay;
+use mandelbrot_piston::screen::Placing;
+use pixels::{Pixels, SurfaceTexture};
+use winit::dpi::LogicalSize;
+use winit::event::{ElementState, Event as WinitEvent, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::platform::run_return::EventLoopExtRunReturn;
+use winit::window::{Window, WindowBuilder};
+
+use crate::catch_windowing_panic;
+
+/// [Pixels Backend]
+///
+/// Fields:
+/// [event_loop] The winit event loop, pumped on demand;
+/// [window] The winit window;
+/// [pixels] The pixel buffer and its wgpu surface;
+/// [queue] Events already pumped but not yet handed out;
+/// [update_period] The time between updates;
+/// [render_period] The shortest time between renders;
+/// [lazy] Whether updates and renders wait on input;
+/// [next_update] When the next update is due;
+/// [next_render] When the next render is due;
+/// [shift] Whether either Shift key is held;
+/// [ctrl] Whether either Ctrl key is held;
+/// [closed] Whether the window has been closed.
+pub struct PixelsBackend {
+    event_loop: EventLoop<()>,
+    window: Window,
+    pixels: Pixels,
+    queue: VecDeque<Event>,
+    update_period: Duration,
+    render_period: Duration,
+    lazy: bool,
+    next_update: Instant,
+    next_render: Instant,
+    shift: bool,
+    ctrl: bool,
+    closed: bool,
+}
+
+impl PixelsBackend {
+    /// [New]
+    /// Opens a window of the given size, where it was asked to open.
+    pub fn new(title: &str, width: usize, height: usize, placing: &Placing) -> Result<PixelsBackend, AppError> {
+        let error = |reason: String| AppError::Backend { name: "pixels", reason };
+
+        let event_loop = catch_windowing_panic(EventLoop::new).map_err(error)?;
+        let window = WindowBuilder::new()
+            .with_title(title)
+            .with_inner_size(LogicalSize::new(width as f64, height as f64))
+            .build(&event_loop)
+            .map_err(|e| error(e.to_string()))?;
+        crate::monitors::place(&window, placing);
+
+        let size = window.inner_size();
+        let surface = SurfaceTexture::new(size.width, size.height, &window);
+        let pixels = Pixels::new(width as u32, height as u32, surface).map_err(|e| error(e.to_string()))?;
+
+        let (scale, now) = (window.scale_factor(), Instant::now());
+        Ok(PixelsBackend {
+            event_loop,
+            window,
+            pixels,
+            queue: VecDeque::from([Event::Scale(scale)]),
+            update_period: period(Pacing::default().ups),
+            render_period: period(Pacing::default().max_fps),
+            lazy: false,
+            next_update: now,
+            next_render: now,
+            shift: false,
+            ctrl: false,
+            closed: false,
+        })
+    }
+
+    /// [Pump]
+    /// Runs the winit loop until either input arrives or the next update
+    /// or render falls due, queueing whatever happened. Paced lazily, it
+    /// waits on input alone, and follows it with a render.
+    fn pump(&mut self) {
+        let deadline = (!self.lazy).then(|| self.next_update.min(self.next_render));
+        let PixelsBackend { event_loop, window, pixels, queue, shift, ctrl, closed, .. } = self;
+
+        event_loop.run_return(|event, _, flow| {
+            *flow = deadline.map_or(ControlFlow::Wait, ControlFlow::WaitUntil);
+
+            match event {
+                WinitEvent::WindowEvent { event, .. } => match event {
+                    WindowEvent::CloseRequested => *closed = true,
+                    WindowEvent::ModifiersChanged(modifiers) => (*shift, *ctrl) = (modifiers.shift(), modifiers.ctrl()),
+                    WindowEvent::Resized(size) => {
+                        report(pixels.resize_surface(size.width, size.height)
+                            .map_err(|e| AppError::Backend { name: "pixels", reason: e.to_string() }));
+
+                        let logical = size.to_logical::<f64>(window.scale_factor());
+                        queue.push_back(Event::Resize([logical.width, logical.height]));
+                    }
+                    WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                        report(pixels.resize_surface(new_inner_size.width, new_inner_size.height)
+                            .map_err(|e| AppError::Backend { name: "pixels", reason: e.to_string() }));
+
+                        queue.push_back(Event::Scale(scale_factor));
+                    }
+                    WindowEvent::CursorMoved { position, .. } => {
+                        if let Ok((x, y)) = pixels.window_pos_to_pixel((position.x as f32, position.y as f32)) {
+                            queue.push_back(Event::Cursor([x as f64, y as f64]));
+                        }
+                    }
+                    WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+                        queue.push_back(if *ctrl { Event::CtrlClick } else { Event::Click });
+                    }
+                    WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } => {
+                        queue.push_back(Event::Release);
+                    }
+                    WindowEvent::KeyboardInput {
+                        input: KeyboardInput { state: ElementState::Pressed, virtual_keycode: Some(code), .. },
+                        ..
+                    } => match map_key(code, *shift) {
+                        Some(Key::Char('v' | 'V')) if *ctrl => queue.push_back(Event::Paste),
+                        Some(key) => queue.push_back(Event::Press(key)),
+                        None => {}
+                    },
+                    WindowEvent::ReceivedCharacter(c) => queue.push_back(Event::Text(c)),
+                    _ => {}
+                },
+                WinitEvent::MainEventsCleared if *closed || !queue.is_empty() || deadline.is_some_and(|at| Instant::now() >= at) => {
+                    *flow = ControlFlow::Exit;
+                }
+                _ => {}
+            }
+        });
+
+        if self.lazy {
+            if !self.queue.is_empty() {
+                self.queue.push_back(Event::Render);
+            }
+            return;
+        }
+
+        // Falling behind skips ahead rather than bursting to catch up.
+        let now = Instant::now();
+        if now >= self.next_update {
+            self.queue.push_back(Event::Update);
+            self.next_update = (self.next_update + self.update_period).max(now);
+        }
+        if now >= self.next_render {
+            self.queue.push_back(Event::Render);
+            self.next_render = (self.next_render + self.render_period).max(now);
+        }
+    }
+}
+
+impl Backend for PixelsBackend {
+    fn next_event(&mut self) -> Option<Event> {
+        loop {
+            if self.closed {
+                return None;
+            }
+            if let Some(event) = self.queue.pop_front() {
+                return Some(event);
+            }
+
+            self.pump();
+        }
+    }
+
+    fn present(&mut self, rgba: &[u8], width: usize, height: usize, _overlay: &Overlay) {
+        let error = |reason: String| AppError::Backend { name: "pixels", reason };
+
+        let size = self.pixels.texture().size();
+        if (size.width, size.height) != (width as u32, height as u32)
+            && report(self.pixels.resize_buffer(width as u32, height as u32).map_err(|e| error(e.to_string()))).is_none()
+        {
+            return;
+        }
+
+        // The pixel buffer is an sRGB texture, so the frame's bytes are
+        // shown as they are, as they are saved.
+        let frame = self.pixels.frame_mut();
+        if frame.len() == rgba.len() {
+            frame.copy_from_slice(rgba);
+            report(self.pixels.render().map_err(|e| error(e.to_string())));
+        }
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    /// An update or render already waited for comes no later than the
+    /// new rates would have it.
+    fn set_pacing(&mut self, pacing: Pacing) {
+        let now = Instant::now();
+        self.update_period = period(pacing.ups);
+        self.render_period = period(pacing.max_fps);
+        self.next_update = self.next_update.min(now + self.update_period);
+        self.next_render = self.next_render.min(now + self.render_period);
+        self.lazy = pacing.lazy;
+    }
+}
+
+/// The time between events at a rate of so many a second.
+fn period(rate: u64) -> Duration {
+    Duration::from_nanos(1_000_000_000 / rate.max(1))
+}
+
+fn map_key(code: VirtualKeyCode, shift: bool) -> Option<Key> {
+    use VirtualKeyCode as V;
+
+    const LETTERS: [(VirtualKeyCode, char); 36] = [
+        (V::A, 'a'), (V::B, 'b'), (V::C, 'c'), (V::D, 'd'), (V::E, 'e'), (V::F, 'f'),
+        (V::G, 'g'), (V::H, 'h'), (V::I, 'i'), (V::J, 'j'), (V::K, 'k'), (V::L, 'l'),
+        (V::M, 'm'), (V::N, 'n'), (V::O, 'o'), (V::P, 'p'), (V::Q, 'q'), (V::R, 'r'),
+        (V::S, 's'), (V::T, 't'), (V::U, 'u'), (V::V, 'v'), (V::W, 'w'), (V::X, 'x'),
+        (V::Y, 'y'), (V::Z, 'z'), (V::Key0, '0'), (V::Key1, '1'), (V::Key2, '2'),
+        (V::Key3, '3'), (V::Key4, '4'), (V::Key5, '5'), (V::Key6, '6'), (V::Key7, '7'),
+        (V::Key8, '8'), (V::Key9, '9'),
+    ];
+    if let Some(&(_, c)) = LETTERS.iter().find(|(v, _)| *v == code) {
+        return Some(Key::Char(if shift { c.to_ascii_uppercase() } else { c }));
+    }
+
+    Some(match code {
+        V::Space => Key::Space,
+        V::Escape => Key::Escape,
+        V::Tab => Key::Tab,
+        V::Return => Key::Return,
+        V::Back => Key::Backspace,
+        V::Left => Key::Left,
+        V::Right => Key::Right,
+        V::Up => Key::Up,
+        V::Down => Key::Down,
+        V::LBracket => Key::Char('['),
+        V::RBracket => Key::Char(']'),
+        V::F1 => Key::F(1),
+        V::F2 => Key::F(2),
+        V::F3 => Key::F(3),
+        V::F4 => Key::F(4),
+        V::F5 => Key::F(5),
+        V::F6 => Key::F(6),
+        V::F7 => Key::F(7),
+        V::F8 => Key::F(8),
+        V::F9 => Key::F(9),
+        V::F10 => Key::F(10),
+        V::F11 => Key::F(11),
+        V::F12 => Key::F(12),
+        _ => return None,
+    })
+}
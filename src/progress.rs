@@ -0,0 +1,126 @@
+//! [Progress]
+//!
+//! How far a slow frame has got. The kernel counts the rows it has
+//! finished on an atomic shared with the app, and the pass after it
+//! (the extra samples along edges) says so when it starts, which costs
+//! one atomic add a row. Once a frame has been under way long enough to
+//! be worth reporting, a thin bar along the bottom of the last frame
+//! and the window title say how far it is. The same shared state
+//! carries the other way the request to give up on a frame whose view
+//! has gone, which the kernel checks before each row.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::overlay::{Overlay, Shape};
+
+/// How long a frame is under way before its progress is shown.
+pub const SHOW_AFTER: Duration = Duration::from_millis(250);
+
+/// The height of the bar, in logical pixels.
+const BAR_HEIGHT: f64 = 3.0;
+
+const BAR_COLOUR: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+const TRACK_COLOUR: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+
+/// [Progress]
+///
+/// Fields:
+/// [rows] How many rows of counts are done;
+/// [edges] Whether the counts are done and the edges are being sampled;
+/// [cancelled] Whether the frame is no longer wanted.
+#[derive(Debug, Default)]
+pub struct Progress {
+    pub rows: AtomicUsize,
+    pub edges: AtomicBool,
+    pub cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// Counts a row as done.
+    pub fn row(&self) {
+        self.rows.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Starts counting again from no rows, for a second frame computed
+    /// under the same progress.
+    pub fn restart(&self) {
+        self.rows.store(0, Ordering::Relaxed);
+        self.edges.store(false, Ordering::Relaxed);
+    }
+
+    /// Asks for the frame to be given up on.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Where a frame `height` rows high has got.
+    pub fn phase(&self, height: usize) -> Phase {
+        if self.edges.load(Ordering::Relaxed) {
+            return Phase::Edges;
+        }
+        Phase::Counts((self.rows.load(Ordering::Relaxed) as f64 / height.max(1) as f64).min(1.0))
+    }
+}
+
+/// [Phase]
+///
+/// Variants:
+/// [Counts] The counts are being computed, this share of them done;
+/// [Edges] The extra samples along edges are being taken.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Counts(f64),
+    Edges,
+}
+
+impl Phase {
+    /// The phase as the window title gives it.
+    pub fn label(&self) -> String {
+        match self {
+            Phase::Counts(done) => format!("computing {:.0}%", done * 100.0),
+            Phase::Edges => "sampling edges".to_string(),
+        }
+    }
+
+    /// [Draw]
+    /// Adds the bar along the bottom of a frame `frame` logical pixels
+    /// across and down, full while the edges are sampled.
+    pub fn draw(&self, overlay: &mut Overlay, frame: [f64; 2]) {
+        let done = match self {
+            Phase::Counts(done) => *done,
+            Phase::Edges => 1.0,
+        };
+        let [width, height] = frame;
+        let top = height - BAR_HEIGHT;
+
+        overlay.push(Shape::Rect { rect: [0.0, top, width, BAR_HEIGHT], colour: TRACK_COLOUR });
+        overlay.push(Shape::Rect { rect: [0.0, top, width * done, BAR_HEIGHT], colour: BAR_COLOUR });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_count_toward_the_bar() {
+        let progress = Progress::default();
+        for _ in 0..50 {
+            progress.row();
+        }
+        let phase = progress.phase(200);
+
+        let mut overlay = Overlay::new();
+        phase.draw(&mut overlay, [400.0, 100.0]);
+
+        assert_eq!(phase.label(), "computing 25%");
+        assert_eq!(overlay.shapes()[1], Shape::Rect { rect: [0.0, 97.0, 100.0, 3.0], colour: BAR_COLOUR });
+        progress.edges.store(true, Ordering::Relaxed);
+        assert_eq!(progress.phase(200), Phase::Edges);
+    }
+}
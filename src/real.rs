@@ -0,0 +1,457 @@
+//! [Real]
+//!
+//! The scalar types the escape-time kernel computes in. Everything the
+//! loop needs (arithmetic, comparison, absolute values) comes from
+//! `num::Float`; this trait only adds conversion from the f64 world
+//! the viewport lives in.
+//!
+//! Besides f64 and f32 there is DoubleDouble, a pair of f64s whose sum
+//! carries about 32 significant digits, for the reference orbits of
+//! perturbation, which have to be iterated at more precision than the
+//! views they stand for are wide. Its arithmetic and square roots are
+//! exact to that precision (after Dekker, and Hida, Li and Bailey); the
+//! rest of `num::Float`, which no orbit needs, is only as exact as f64.
+
+use std::cmp::Ordering;
+use std::num::FpCategory;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use num::traits::{Num, NumCast, One, ParseFloatError, ToPrimitive, Zero};
+use num::Float;
+
+/// [Real]
+/// A floating point type the kernel can be instantiated for.
+pub trait Real: Float + Send + Sync + std::fmt::Debug + 'static {
+    fn from_f64(x: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+impl Real for f64 {
+    #[inline(always)]
+    fn from_f64(x: f64) -> f64 {
+        x
+    }
+
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Real for f32 {
+    #[inline(always)]
+    fn from_f64(x: f64) -> f32 {
+        x as f32
+    }
+
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Real for DoubleDouble {
+    #[inline(always)]
+    fn from_f64(x: f64) -> DoubleDouble {
+        DoubleDouble::of(x)
+    }
+
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        self.hi
+    }
+}
+
+/// [Double Double]
+/// A number kept as the unevaluated sum of two f64s, the low one no
+/// more than half a unit in the last place of the high one.
+///
+/// Fields:
+/// [hi] The number rounded to f64;
+/// [lo] What rounding it left off.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+/// The sum of two f64s with the error of rounding it, exactly.
+#[inline(always)]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// The same, for |a| >= |b|.
+#[inline(always)]
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// The product of two f64s with the error of rounding it, exactly.
+#[inline(always)]
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+impl DoubleDouble {
+    /// [New]
+    /// The sum of two f64s, whatever their sizes.
+    pub fn new(hi: f64, lo: f64) -> DoubleDouble {
+        DoubleDouble::normalised(two_sum(hi, lo))
+    }
+
+    /// An f64, as it is.
+    pub const fn of(x: f64) -> DoubleDouble {
+        DoubleDouble { hi: x, lo: 0.0 }
+    }
+
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+
+    /// A sum already split as `quick_two_sum` leaves it, or an infinite
+    /// or NaN one, whose error means nothing.
+    #[inline(always)]
+    fn normalised((hi, lo): (f64, f64)) -> DoubleDouble {
+        if hi.is_finite() { DoubleDouble { hi, lo } } else { DoubleDouble { hi, lo: 0.0 } }
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = DoubleDouble;
+
+    #[inline(always)]
+    fn add(self, other: DoubleDouble) -> DoubleDouble {
+        let (s, e) = two_sum(self.hi, other.hi);
+        let (t, f) = two_sum(self.lo, other.lo);
+        let (s, e) = quick_two_sum(s, e + t);
+        DoubleDouble::normalised(quick_two_sum(s, e + f))
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = DoubleDouble;
+
+    #[inline(always)]
+    fn sub(self, other: DoubleDouble) -> DoubleDouble {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = DoubleDouble;
+
+    #[inline(always)]
+    fn mul(self, other: DoubleDouble) -> DoubleDouble {
+        let (p, e) = two_prod(self.hi, other.hi);
+        DoubleDouble::normalised(quick_two_sum(p, e + (self.hi * other.lo + self.lo * other.hi)))
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = DoubleDouble;
+
+    /// Long division, a digit of f64 at a time.
+    fn div(self, other: DoubleDouble) -> DoubleDouble {
+        let q1 = self.hi / other.hi;
+        if !q1.is_finite() {
+            return DoubleDouble::of(q1);
+        }
+        let r = self - other * DoubleDouble::of(q1);
+        let q2 = r.hi / other.hi;
+        let r = r - other * DoubleDouble::of(q2);
+        let q3 = r.hi / other.hi;
+        DoubleDouble::normalised(quick_two_sum(q1, q2)) + DoubleDouble::of(q3)
+    }
+}
+
+impl Rem for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn rem(self, other: DoubleDouble) -> DoubleDouble {
+        self - other * (self / other).trunc()
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = DoubleDouble;
+
+    #[inline(always)]
+    fn neg(self) -> DoubleDouble {
+        DoubleDouble { hi: -self.hi, lo: -self.lo }
+    }
+}
+
+impl Zero for DoubleDouble {
+    fn zero() -> DoubleDouble {
+        DoubleDouble::of(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.hi == 0.0
+    }
+}
+
+impl One for DoubleDouble {
+    fn one() -> DoubleDouble {
+        DoubleDouble::of(1.0)
+    }
+}
+
+impl Num for DoubleDouble {
+    type FromStrRadixErr = ParseFloatError;
+
+    /// Only to f64's precision.
+    fn from_str_radix(s: &str, radix: u32) -> Result<DoubleDouble, ParseFloatError> {
+        <f64 as Num>::from_str_radix(s, radix).map(DoubleDouble::of)
+    }
+}
+
+impl ToPrimitive for DoubleDouble {
+    fn to_i64(&self) -> Option<i64> {
+        self.to_i128().and_then(|n| n.try_into().ok())
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.to_i128().and_then(|n| n.try_into().ok())
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        let DoubleDouble { hi, lo } = self.trunc();
+        hi.to_i128()?.checked_add(lo.to_i128()?)
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.hi)
+    }
+}
+
+impl NumCast for DoubleDouble {
+    fn from<N: ToPrimitive>(n: N) -> Option<DoubleDouble> {
+        n.to_f64().map(DoubleDouble::of)
+    }
+}
+
+/// Functions of a DoubleDouble taken as f64 functions of its high part.
+macro_rules! in_f64 {
+    ($($name:ident),*) => {
+        $(fn $name(self) -> DoubleDouble {
+            DoubleDouble::of(self.hi.$name())
+        })*
+    };
+}
+
+impl Float for DoubleDouble {
+    fn nan() -> DoubleDouble {
+        DoubleDouble::of(f64::NAN)
+    }
+
+    fn infinity() -> DoubleDouble {
+        DoubleDouble::of(f64::INFINITY)
+    }
+
+    fn neg_infinity() -> DoubleDouble {
+        DoubleDouble::of(f64::NEG_INFINITY)
+    }
+
+    fn neg_zero() -> DoubleDouble {
+        DoubleDouble::of(-0.0)
+    }
+
+    fn min_value() -> DoubleDouble {
+        DoubleDouble::of(f64::MIN)
+    }
+
+    fn min_positive_value() -> DoubleDouble {
+        DoubleDouble::of(f64::MIN_POSITIVE)
+    }
+
+    fn max_value() -> DoubleDouble {
+        DoubleDouble::of(f64::MAX)
+    }
+
+    /// What the low part can tell apart from 1: 2^-104.
+    fn epsilon() -> DoubleDouble {
+        DoubleDouble::of(f64::EPSILON * f64::EPSILON)
+    }
+
+    fn is_nan(self) -> bool {
+        self.hi.is_nan()
+    }
+
+    fn is_infinite(self) -> bool {
+        self.hi.is_infinite()
+    }
+
+    fn is_finite(self) -> bool {
+        self.hi.is_finite()
+    }
+
+    fn is_normal(self) -> bool {
+        self.hi.is_normal()
+    }
+
+    fn classify(self) -> FpCategory {
+        self.hi.classify()
+    }
+
+    fn floor(self) -> DoubleDouble {
+        let hi = self.hi.floor();
+        if hi == self.hi { DoubleDouble::new(hi, self.lo.floor()) } else { DoubleDouble::of(hi) }
+    }
+
+    fn ceil(self) -> DoubleDouble {
+        -(-self).floor()
+    }
+
+    fn round(self) -> DoubleDouble {
+        if self.hi < 0.0 { -(-self).round() } else { (self + DoubleDouble::of(0.5)).floor() }
+    }
+
+    fn trunc(self) -> DoubleDouble {
+        if self.hi < 0.0 { self.ceil() } else { self.floor() }
+    }
+
+    fn fract(self) -> DoubleDouble {
+        self - self.trunc()
+    }
+
+    fn abs(self) -> DoubleDouble {
+        if self.hi < 0.0 { -self } else { self }
+    }
+
+    fn signum(self) -> DoubleDouble {
+        DoubleDouble::of(self.hi.signum())
+    }
+
+    fn is_sign_positive(self) -> bool {
+        self.hi.is_sign_positive()
+    }
+
+    fn is_sign_negative(self) -> bool {
+        self.hi.is_sign_negative()
+    }
+
+    fn mul_add(self, a: DoubleDouble, b: DoubleDouble) -> DoubleDouble {
+        self * a + b
+    }
+
+    fn recip(self) -> DoubleDouble {
+        DoubleDouble::one() / self
+    }
+
+    fn powi(self, n: i32) -> DoubleDouble {
+        let (mut base, mut power, mut exponent) = (self, DoubleDouble::one(), n.unsigned_abs());
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                power = power * base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        if n < 0 { power.recip() } else { power }
+    }
+
+    fn powf(self, n: DoubleDouble) -> DoubleDouble {
+        DoubleDouble::of(self.hi.powf(n.hi))
+    }
+
+    /// One Newton step from the f64 root, which doubles its digits.
+    fn sqrt(self) -> DoubleDouble {
+        if self.hi <= 0.0 || !self.hi.is_finite() {
+            return DoubleDouble::of(self.hi.sqrt());
+        }
+        let x = self.hi.sqrt();
+        let (square, error) = two_prod(x, x);
+        let residual = (self - DoubleDouble::new(square, error)).hi;
+        DoubleDouble::normalised(quick_two_sum(x, residual / (2.0 * x)))
+    }
+
+    fn log(self, base: DoubleDouble) -> DoubleDouble {
+        DoubleDouble::of(self.hi.log(base.hi))
+    }
+
+    fn max(self, other: DoubleDouble) -> DoubleDouble {
+        match self.partial_cmp(&other) {
+            Some(Ordering::Less) => other,
+            None if self.is_nan() => other,
+            _ => self,
+        }
+    }
+
+    fn min(self, other: DoubleDouble) -> DoubleDouble {
+        match self.partial_cmp(&other) {
+            Some(Ordering::Greater) => other,
+            None if self.is_nan() => other,
+            _ => self,
+        }
+    }
+
+    fn abs_sub(self, other: DoubleDouble) -> DoubleDouble {
+        (self - other).max(DoubleDouble::zero())
+    }
+
+    fn hypot(self, other: DoubleDouble) -> DoubleDouble {
+        (self * self + other * other).sqrt()
+    }
+
+    fn atan2(self, other: DoubleDouble) -> DoubleDouble {
+        DoubleDouble::of(self.hi.atan2(other.hi))
+    }
+
+    fn sin_cos(self) -> (DoubleDouble, DoubleDouble) {
+        (self.sin(), self.cos())
+    }
+
+    fn integer_decode(self) -> (u64, i16, i8) {
+        self.hi.integer_decode()
+    }
+
+    in_f64!(exp, exp2, ln, log2, log10, cbrt, sin, cos, tan, asin, acos, atan, exp_m1, ln_1p, sinh, cosh, tanh, asinh, acosh, atanh);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_doubles_keep_what_f64_rounds_off() {
+        let tiny = DoubleDouble::of(1e-20);
+        let sum = DoubleDouble::one() + tiny;
+
+        assert_eq!((sum.hi(), sum.lo()), (1.0, 1e-20));
+        assert_eq!((sum - DoubleDouble::one()).hi(), 1e-20);
+        // (1 + 1e-20)^2 = 1 + 2e-20 + 1e-40, the last beyond even this.
+        assert_eq!((sum * sum - DoubleDouble::one()).hi(), 2e-20);
+    }
+
+    #[test]
+    fn division_and_roots_invert_products() {
+        let third = DoubleDouble::one() / DoubleDouble::of(3.0);
+        let error = (third * DoubleDouble::of(3.0) - DoubleDouble::one()).abs().hi();
+        assert!(error < 1e-31, "{error:e}");
+
+        let two = DoubleDouble::of(2.0);
+        let root = two.sqrt();
+        assert!((root * root - two).abs().hi() < 1e-31, "{root:?}");
+        assert_eq!(root.hi(), std::f64::consts::SQRT_2);
+    }
+
+    #[test]
+    fn rounding_looks_at_the_low_part() {
+        let just_under = DoubleDouble::new(3.0, -1e-20);
+
+        assert_eq!(just_under.floor(), DoubleDouble::of(2.0));
+        assert_eq!(just_under.ceil(), DoubleDouble::of(3.0));
+        assert_eq!((-just_under).trunc(), DoubleDouble::of(-2.0));
+        assert_eq!(just_under.to_i64(), Some(2));
+    }
+}
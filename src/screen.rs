@@ -0,0 +1,117 @@
+//! [Screen]
+//!
+//! Where the window opens, for `--monitor` and `--position`: the
+//! choice of display and the point on the desktop its top-left corner
+//! goes to, worked out from the displays the backend reports. A window
+//! put on a monitor without a position is centred on it; a position
+//! with a monitor is taken from that monitor's top-left corner, and one
+//! without is a point on the whole desktop. Without either the window
+//! manager places the window as usual. A monitor that is not there is
+//! warned about, and the primary one used instead. There is no
+//! fullscreen mode yet; one should go by the same choice of monitor.
+
+/// [Monitor]
+///
+/// Fields:
+/// [name] What the system calls the display, if anything;
+/// [position] Its top-left corner on the desktop, in physical pixels;
+/// [size] Its resolution, in physical pixels;
+/// [scale] Physical pixels per logical pixel;
+/// [primary] Whether it is the primary display.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    pub name: Option<String>,
+    pub position: [i32; 2],
+    pub size: [u32; 2],
+    pub scale: f64,
+    pub primary: bool,
+}
+
+impl Monitor {
+    /// The monitor as a line of `--list-monitors`.
+    pub fn describe(&self, index: usize) -> String {
+        format!(
+            "{index}: {} {}x{} at {},{} (scale {}){}",
+            self.name.as_deref().unwrap_or("unnamed"),
+            self.size[0],
+            self.size[1],
+            self.position[0],
+            self.position[1],
+            self.scale,
+            if self.primary { ", primary" } else { "" },
+        )
+    }
+}
+
+/// [Placing]
+/// Where the window was asked to open.
+///
+/// Fields:
+/// [monitor] The index of the monitor, as `--list-monitors` numbers them;
+/// [position] The top-left corner, from the monitor's if there is one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Placing {
+    pub monitor: Option<usize>,
+    pub position: Option<[i32; 2]>,
+}
+
+impl Placing {
+    /// [Window Position]
+    /// Where on the desktop the top-left corner of a window of `window`
+    /// physical pixels goes, or None to leave it to the window manager,
+    /// and a warning if the monitor asked for is not there.
+    pub fn window_position(&self, monitors: &[Monitor], window: [u32; 2]) -> (Option<[i32; 2]>, Option<String>) {
+        let Some(index) = self.monitor else { return (self.position, None) };
+
+        let (monitor, warning) = match monitors.get(index) {
+            Some(monitor) => (Some(monitor), None),
+            None => (
+                monitors.iter().find(|monitor| monitor.primary).or(monitors.first()),
+                Some(format!("warning: there is no monitor {index} ({} found); using the primary one", monitors.len())),
+            ),
+        };
+        let Some(monitor) = monitor else { return (self.position, warning) };
+
+        let [x, y] = self.position.unwrap_or_else(|| {
+            let centred = |side: usize| (monitor.size[side].saturating_sub(window[side]) / 2) as i32;
+            [centred(0), centred(1)]
+        });
+        (Some([monitor.position[0] + x, monitor.position[1] + y]), warning)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitors() -> Vec<Monitor> {
+        vec![
+            Monitor { name: Some("DP-1".to_string()), position: [0, 0], size: [2560, 1440], scale: 1.0, primary: false },
+            Monitor { name: None, position: [2560, 200], size: [1920, 1080], scale: 1.5, primary: true },
+        ]
+    }
+
+    #[test]
+    fn windows_centre_on_their_monitor() {
+        let placing = Placing { monitor: Some(1), position: None };
+
+        assert_eq!(placing.window_position(&monitors(), [800, 400]), (Some([2560 + 560, 200 + 340]), None));
+    }
+
+    #[test]
+    fn positions_are_from_the_monitor_if_there_is_one() {
+        assert_eq!(Placing { monitor: Some(1), position: Some([10, 20]) }.window_position(&monitors(), [800, 400]).0, Some([2570, 220]));
+        assert_eq!(Placing { monitor: None, position: Some([10, 20]) }.window_position(&monitors(), [800, 400]).0, Some([10, 20]));
+        assert_eq!(Placing::default().window_position(&monitors(), [800, 400]), (None, None));
+    }
+
+    #[test]
+    fn missing_monitors_fall_back_to_the_primary() {
+        let (position, warning) = Placing { monitor: Some(5), position: Some([0, 0]) }.window_position(&monitors(), [800, 400]);
+
+        assert_eq!(position, Some([2560, 200]));
+        assert!(warning.unwrap().contains("no monitor 5 (2 found)"));
+        assert_eq!(Placing { monitor: Some(0), position: None }.window_position(&[], [800, 400]).0, None);
+        assert_eq!(monitors()[1].describe(1), "1: unnamed 1920x1080 at 2560,200 (scale 1.5), primary");
+    }
+}
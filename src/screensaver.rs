@@ -0,0 +1,160 @@
+//! [Screensaver]
+//!
+//! The `--screensaver` mode: dive into a random point near the boundary,
+//! cut to another after a minute (or sooner, before the view gets deep
+//! enough for f64 to smear it), and carry on until a key, a click or a
+//! move of the mouse, as screensavers do. Each new segment gets its own
+//! colouring, a random gradient and scalar, and is logged so that a good
+//! one can be found again.
+//!
+//! Finding points takes the random search, so this is not built for
+//! wasm32.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use num::complex::Complex as cmp;
+use rand::Rng;
+
+use crate::backend::Event;
+use crate::colour::{Gradient, LegacyColorizer};
+use crate::error::AppError;
+use crate::viewport::Viewport;
+
+/// How long a segment lasts, at most.
+pub const SEGMENT: Duration = Duration::from_secs(60);
+
+/// A segment ends once a pixel is this small relative to the
+/// coordinates, well before neighbouring pixels share an f64.
+pub const PRECISION_FLOOR: f64 = 1e-13;
+
+/// How far, in frame pixels, the mouse may drift before it counts as moved.
+const WAKE_DISTANCE: f64 = 4.0;
+
+/// [Screensaver]
+///
+/// Fields:
+/// [log] The file each segment is appended to;
+/// [segment_started] When the current segment began, once one has;
+/// [anchor] Where the pointer was first seen, to tell movement from its arrival.
+#[derive(Clone, Debug)]
+pub struct Screensaver {
+    log: PathBuf,
+    segment_started: Option<Instant>,
+    anchor: Option<[f64; 2]>,
+}
+
+impl Screensaver {
+    pub fn new(log: PathBuf) -> Screensaver {
+        Screensaver { log, segment_started: None, anchor: None }
+    }
+
+    pub fn log(&self) -> &Path {
+        &self.log
+    }
+
+    /// [Due]
+    /// Whether it is time for a new segment: none has started, the
+    /// current one has run its length, or the view has reached the floor.
+    pub fn due(&self, now: Instant, viewport: &Viewport) -> bool {
+        let floor = viewport.centre().norm().max(1.0) * PRECISION_FLOOR;
+
+        self.segment_started.is_none_or(|started| now - started >= SEGMENT) || viewport.pixel_size() < floor
+    }
+
+    /// [Cut]
+    /// Ends the current segment early, so the next update begins another.
+    pub fn cut(&mut self) {
+        self.segment_started = None;
+    }
+
+    /// [Begin]
+    /// Marks the start of a segment diving into `target` with `colouring`,
+    /// appending it to the log.
+    pub fn begin(&mut self, now: Instant, target: cmp<f64>, colouring: LegacyColorizer) -> Result<(), AppError> {
+        self.segment_started = Some(now);
+
+        let LegacyColorizer { scalar, gradient } = colouring;
+        let line = format!("{} re={} im={} gradient={} scalar={scalar}\n", Local::now().to_rfc3339(), target.re, target.im, gradient.name());
+        OpenOptions::new().create(true).append(true).open(&self.log)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| AppError::Export { path: self.log.clone(), reason: e.to_string() })
+    }
+
+    /// [Wakes]
+    /// Whether an event ends the screensaver: any key or click, or the
+    /// pointer moving more than a few pixels from where it first appeared.
+    pub fn wakes(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Press(_) | Event::Text(_) | Event::Paste | Event::Click | Event::CtrlClick | Event::Release => true,
+            Event::Cursor([x, y]) => {
+                let [ax, ay] = *self.anchor.get_or_insert([*x, *y]);
+                (x - ax).hypot(y - ay) > WAKE_DISTANCE
+            }
+            Event::Update | Event::Render | Event::Resize(_) | Event::Scale(_) => false,
+        }
+    }
+}
+
+/// [Colouring]
+/// A random colouring for a segment: any of the gradients, with the
+/// scalar starting somewhere from dim to bright.
+pub fn colouring<R: Rng>(rng: &mut R) -> LegacyColorizer {
+    let gradient = Gradient::ALL[rng.gen_range(0..Gradient::ALL.len())];
+
+    LegacyColorizer { scalar: rng.gen_range(1.0..3.0), gradient }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Key;
+    use crate::colour::{Colorizer, IterResult};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn only_real_movement_wakes() {
+        let mut saver = Screensaver::new(PathBuf::from("unused.log"));
+
+        assert!(!saver.wakes(&Event::Cursor([100.0, 50.0])));
+        assert!(!saver.wakes(&Event::Cursor([102.0, 51.0])));
+        assert!(!saver.wakes(&Event::Update));
+        assert!(saver.wakes(&Event::Cursor([110.0, 50.0])));
+        assert!(saver.wakes(&Event::Press(Key::Char('q'))));
+    }
+
+    #[test]
+    fn segments_end_on_time_or_at_the_floor() {
+        let path = std::env::temp_dir().join(format!("mandelbrot-screensaver-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut saver = Screensaver::new(path.clone());
+        let now = Instant::now();
+        let shallow = Viewport::new(cmp::new(-0.75, 0.1), 1e-6, 400, 200);
+        let deep = Viewport::new(cmp::new(-0.75, 0.1), 1e-12, 400, 200);
+
+        assert!(saver.due(now, &shallow));
+        saver.begin(now, shallow.centre(), LegacyColorizer { scalar: 2.0, gradient: Gradient::Fire }).unwrap();
+
+        assert!(!saver.due(now + Duration::from_secs(30), &shallow));
+        assert!(saver.due(now + SEGMENT, &shallow));
+        assert!(saver.due(now, &deep));
+        assert!(std::fs::read_to_string(saver.log()).unwrap().contains("re=-0.75 im=0.1 gradient=fire scalar=2"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn seeds_colour_segments_differently() {
+        let colours = |seed| {
+            let colouring = colouring(&mut StdRng::seed_from_u64(seed));
+            (0..100).map(|count| colouring.color(IterResult { count, limit: 100 })).collect::<Vec<_>>()
+        };
+        let gradients: Vec<Gradient> = (0..64).map(|seed| colouring(&mut StdRng::seed_from_u64(seed)).gradient).collect();
+
+        assert_ne!(colours(1), colours(2));
+        assert!(Gradient::ALL.iter().all(|gradient| gradients.contains(gradient)), "{gradients:?}");
+    }
+}
@@ -0,0 +1,357 @@
+//! [Settings]
+//!
+//! The user-facing configuration of a zoom, and the validation that
+//! turns it into the derived state the application actually runs on.
+//! Defaults reproduce the original hard-coded zoom.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use num::complex::Complex as cmp;
+
+use crate::bindings::Bindings;
+use crate::antialias::{AntiAliasing, GRIDS};
+use crate::autolimit::AutoLimit;
+use crate::colour::{Gradient, Tone};
+use crate::fit::Fit;
+use crate::fractal::Formula;
+use crate::lighting::Light;
+use crate::lyapunov::Lyapunov;
+use crate::tilecache::CacheSettings;
+use crate::transition;
+use crate::viewport::{Viewport, WidthLimits};
+
+// Graph scale controls window size, and
+// iterations controls zoom depth
+pub const GRAPH_SCALE: f64 = 100.0;
+pub const ITERATIONS: u32 = 1200;
+
+// Arbitrary point defined on the complex
+// plane which generates a visually appealing
+// zoom
+#[allow(clippy::excessive_precision)]
+pub const MAGIC_RE: f64 = 0.3602404434376143632361252444495453084826;
+#[allow(clippy::excessive_precision)]
+pub const MAGIC_IM: f64 = -0.641313061064803174860375015179302066579;
+
+// Real and Imaginary domains defined mathematically
+pub const RE1: f64 = MAGIC_RE - 2.0;
+pub const RE2: f64 = MAGIC_RE + 2.0;
+
+pub const IM1: f64 = MAGIC_IM - 1.0;
+pub const IM2: f64 = MAGIC_IM + 1.0;
+
+/// [Settings]
+///
+/// Fields:
+/// [re_min] The initial minimum domain (real);
+/// [re_max] The initial maximum domain (real);
+/// [im_min] The initial minimum domain (imaginary);
+/// [im_max] The initial maximum domain (imaginary);
+/// [dimensions] Window size in pixels, or None to derive it from the graph scale;
+/// [graph_scale] Pixels per unit on the complex plane, when deriving the dimensions;
+/// [zoom] Initial zoom amount;
+/// [scalar] Initial value that determines the colouring;
+/// [step_factor] Initial change of the scalar per frame;
+/// [iterations] The iteration limit;
+/// [formula] The escape-time formula;
+/// [seed] Seed for the random search for targets, or None to seed it from the system;
+/// [degenerate_interior] The share of interior pixels above which a frame is empty;
+/// [degenerate_fast_escape] The share of fast escapes above which a frame is empty;
+/// [width_limits] The narrowest and widest the view may get, on the complex plane;
+/// [bookmarks] The file bookmarks are saved to and toured from;
+/// [bindings] Which key does what;
+/// [antialias] Whether and how pixels along edges get extra samples;
+/// [auto_limit] Whether and how the iteration limit is raised as the zoom outgrows it;
+/// [tone] How colours are turned into the bytes shown and saved;
+/// [gradient] The tint of the colouring's ramp;
+/// [light] Whether and from where the counts are shaded as a height field;
+/// [lyapunov] Whether frames are coloured by their Lyapunov exponents, and on what scales;
+/// [transition_frames] How many frames retargeting travels to the new view over, 0 jumping there;
+/// [fit] Whether resizing the window letterboxes the view or extends it;
+/// [tile_cache] The cache frames are assembled from, if they are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub re_min: f64,
+    pub re_max: f64,
+    pub im_min: f64,
+    pub im_max: f64,
+    pub dimensions: Option<(usize, usize)>,
+    pub graph_scale: f64,
+    pub zoom: f64,
+    pub scalar: f32,
+    pub step_factor: f32,
+    pub iterations: u32,
+    pub formula: Formula,
+    pub seed: Option<u64>,
+    pub degenerate_interior: f64,
+    pub degenerate_fast_escape: f64,
+    pub width_limits: WidthLimits,
+    pub bookmarks: PathBuf,
+    pub bindings: Bindings,
+    pub antialias: AntiAliasing,
+    pub auto_limit: AutoLimit,
+    pub tone: Tone,
+    pub gradient: Gradient,
+    pub light: Light,
+    pub lyapunov: Lyapunov,
+    pub transition_frames: u64,
+    pub fit: Fit,
+    pub tile_cache: Option<CacheSettings>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            re_min: RE1,
+            re_max: RE2,
+            im_min: IM1,
+            im_max: IM2,
+            dimensions: None,
+            graph_scale: GRAPH_SCALE,
+            zoom: 0.10,
+            scalar: 2.0,
+            step_factor: 0.01,
+            iterations: ITERATIONS,
+            formula: Formula::Mandelbrot,
+            seed: None,
+            degenerate_interior: 0.999,
+            degenerate_fast_escape: 0.999,
+            // Wide enough for |c| in the thousands, where everything
+            // escapes at once.
+            width_limits: WidthLimits { min: f64::MIN_POSITIVE, max: 1e4 },
+            bookmarks: PathBuf::from("bookmarks.txt"),
+            bindings: Bindings::default(),
+            antialias: AntiAliasing::default(),
+            auto_limit: AutoLimit::default(),
+            tone: Tone::default(),
+            gradient: Gradient::default(),
+            light: Light::default(),
+            lyapunov: Lyapunov::default(),
+            transition_frames: transition::FRAMES,
+            fit: Fit::default(),
+            tile_cache: None,
+        }
+    }
+}
+
+impl Settings {
+    /// [Framed]
+    /// The settings with the initial view given by its centre, its width
+    /// on the complex plane, and its aspect ratio (width over height),
+    /// rather than by its bounds. The window keeps its width in pixels,
+    /// however narrow the view, and takes the view's shape.
+    pub fn framed(self, centre: cmp<f64>, width: f64, aspect: f64) -> Settings {
+        let height = width / aspect;
+        let (width_px, _) = self.dimensions();
+
+        Settings {
+            re_min: centre.re - width / 2.0,
+            re_max: centre.re + width / 2.0,
+            im_min: centre.im - height / 2.0,
+            im_max: centre.im + height / 2.0,
+            dimensions: Some((width_px, (width_px as f64 / aspect).round() as usize)),
+            graph_scale: width_px as f64 / width,
+            ..self
+        }
+    }
+
+    /// [Dimensions]
+    /// The window size in pixels, either as given or derived from the
+    /// bounds and the graph scale.
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.dimensions.unwrap_or((
+            ((self.re_max - self.re_min) * self.graph_scale) as usize,
+            ((self.im_max - self.im_min) * self.graph_scale) as usize,
+        ))
+    }
+
+    /// [Validate]
+    /// Checks the settings for consistency, returning the initial
+    /// viewport they describe.
+    pub fn validate(&self) -> Result<Viewport, ConfigError> {
+        let bounds = [self.re_min, self.re_max, self.im_min, self.im_max];
+        if bounds.iter().any(|x| !x.is_finite()) {
+            return Err(ConfigError::NonFinite("bounds"));
+        }
+        if self.re_min >= self.re_max {
+            return Err(ConfigError::EmptyDomain { min: self.re_min, max: self.re_max });
+        }
+        if self.im_min >= self.im_max {
+            return Err(ConfigError::EmptyRange { min: self.im_min, max: self.im_max });
+        }
+        if self.dimensions.is_none() && !(self.graph_scale.is_finite() && self.graph_scale > 0.0) {
+            return Err(ConfigError::NonFinite("graph scale"));
+        }
+
+        let (width_px, height_px) = self.dimensions();
+        if width_px == 0 || height_px == 0 {
+            return Err(ConfigError::ZeroDimensions { width: width_px, height: height_px });
+        }
+
+        // Pixels are square, so the imaginary extent has to agree with
+        // the height the real extent implies (to within a pixel).
+        let viewport = Viewport::new(
+            cmp::new((self.re_min + self.re_max) / 2.0, (self.im_min + self.im_max) / 2.0),
+            self.re_max - self.re_min,
+            width_px,
+            height_px,
+        );
+        if ((self.im_max - self.im_min) * viewport.scale() - height_px as f64).abs() > 1.0 {
+            return Err(ConfigError::AspectMismatch { width: width_px, height: height_px });
+        }
+
+        let WidthLimits { min, max } = self.width_limits;
+        if !(min > 0.0 && min <= viewport.width() && viewport.width() <= max && max.is_finite()) {
+            return Err(ConfigError::WidthOutOfLimits { width: viewport.width(), min, max });
+        }
+
+        if !(self.zoom.is_finite() && self.zoom > 0.0) {
+            return Err(ConfigError::NonPositiveZoom(self.zoom));
+        }
+        if !(self.scalar.is_finite() && self.step_factor.is_finite()) {
+            return Err(ConfigError::NonFinite("colour scalar"));
+        }
+        if !(self.degenerate_interior.is_finite() && self.degenerate_fast_escape.is_finite()) {
+            return Err(ConfigError::NonFinite("degenerate frame thresholds"));
+        }
+        if self.iterations == 0 {
+            return Err(ConfigError::ZeroIterations);
+        }
+        if !GRIDS.contains(&self.antialias.grid) {
+            return Err(ConfigError::AntiAliasGrid(self.antialias.grid));
+        }
+
+        Ok(viewport)
+    }
+}
+
+/// [Config Error]
+/// Reasons a set of settings cannot be run.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigError {
+    EmptyDomain { min: f64, max: f64 },
+    EmptyRange { min: f64, max: f64 },
+    ZeroDimensions { width: usize, height: usize },
+    AspectMismatch { width: usize, height: usize },
+    WidthOutOfLimits { width: f64, min: f64, max: f64 },
+    NonPositiveZoom(f64),
+    ZeroIterations,
+    AntiAliasGrid(usize),
+    NonFinite(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::EmptyDomain { min, max } => write!(f, "real bounds are empty: re_min={min} must be below re_max={max}"),
+            ConfigError::EmptyRange { min, max } => write!(f, "imaginary bounds are empty: im_min={min} must be below im_max={max}"),
+            ConfigError::ZeroDimensions { width, height } => write!(f, "window dimensions {width}x{height} must both be non-zero"),
+            ConfigError::AspectMismatch { width, height } => write!(f, "bounds do not have the aspect ratio of a {width}x{height} window"),
+            ConfigError::WidthOutOfLimits { width, min, max } => {
+                write!(f, "width limits must be positive and finite, and hold the initial width: {min} <= {width} <= {max}")
+            }
+            ConfigError::NonPositiveZoom(zoom) => write!(f, "zoom must be positive, got {zoom}"),
+            ConfigError::ZeroIterations => write!(f, "the iteration limit must be at least 1"),
+            ConfigError::AntiAliasGrid(grid) => {
+                write!(f, "anti-aliasing takes {} to {} sub-samples a side, got {grid}", GRIDS.start(), GRIDS.end())
+            }
+            ConfigError::NonFinite(what) => write!(f, "{what} must be finite"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_reproduce_original_zoom() {
+        let settings = Settings::default();
+        let viewport = settings.validate().unwrap();
+
+        assert_eq!(settings.dimensions(), (400, 200));
+        assert_eq!(viewport, Viewport::new(cmp::new((RE1 + RE2) / 2.0, (IM1 + IM2) / 2.0), 4.0, 400, 200));
+        assert!((viewport.centre() - cmp::new(MAGIC_RE, MAGIC_IM)).norm() < 1e-15);
+        assert_eq!(viewport.scale(), GRAPH_SCALE);
+        assert_eq!(viewport.pixel_to_complex(0.0, 0.0), cmp::new(RE1, IM1));
+        assert_eq!((settings.zoom, settings.scalar, settings.step_factor), (0.10, 2.0, 0.01));
+        assert_eq!(settings.iterations, 1200);
+    }
+
+    #[test]
+    fn rejects_empty_bounds() {
+        let settings = Settings { re_max: RE1, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::EmptyDomain { .. })));
+
+        let settings = Settings { re_min: 1.0, re_max: -1.0, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::EmptyDomain { .. })));
+
+        let settings = Settings { im_min: IM2, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::EmptyRange { .. })));
+    }
+
+    #[test]
+    fn rejects_zero_dimensions() {
+        let settings = Settings { dimensions: Some((0, 200)), ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::ZeroDimensions { .. })));
+
+        let settings = Settings { graph_scale: 0.1, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::ZeroDimensions { .. })));
+    }
+
+    #[test]
+    fn rejects_mismatched_aspect() {
+        let settings = Settings { dimensions: Some((400, 400)), ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::AspectMismatch { .. })));
+
+        let settings = Settings { dimensions: Some((800, 400)), ..Settings::default() };
+        assert_eq!(settings.validate().unwrap().scale(), 200.0);
+    }
+
+    #[test]
+    fn framed_views_shape_the_window() {
+        // The whole set, square.
+        let square = Settings::default().framed(cmp::new(-0.75, 0.0), 4.0, 1.0);
+        assert_eq!(square.dimensions(), (400, 400));
+        assert_eq!(square.validate().unwrap(), Viewport::new(cmp::new(-0.75, 0.0), 4.0, 400, 400));
+
+        let ultrawide = Settings::default().framed(cmp::new(MAGIC_RE, MAGIC_IM), 4.2, 21.0 / 9.0);
+        assert_eq!(ultrawide.dimensions(), (400, 171));
+        assert!(ultrawide.validate().is_ok());
+
+        // Deep views keep the window's width.
+        let deep = Settings::default().framed(cmp::new(MAGIC_RE, MAGIC_IM), 1e-9, 2.0);
+        assert_eq!(deep.dimensions(), (400, 200));
+        assert!(deep.validate().is_ok());
+
+        let flipped = Settings::default().framed(cmp::new(0.0, 0.0), 4.0, -1.0);
+        assert!(matches!(flipped.validate(), Err(ConfigError::EmptyRange { .. })));
+    }
+
+    #[test]
+    fn rejects_degenerate_parameters() {
+        let settings = Settings { zoom: 0.0, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::NonPositiveZoom(_))));
+
+        let settings = Settings { iterations: 0, ..Settings::default() };
+        assert_eq!(settings.validate(), Err(ConfigError::ZeroIterations));
+
+        let settings = Settings { re_min: f64::NAN, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::NonFinite(_))));
+
+        let settings = Settings { width_limits: WidthLimits { min: 5.0, max: 10.0 }, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::WidthOutOfLimits { .. })));
+
+        let settings = Settings { width_limits: WidthLimits { min: 0.0, max: 10.0 }, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::WidthOutOfLimits { .. })));
+
+        let settings = Settings { degenerate_interior: f64::NAN, ..Settings::default() };
+        assert!(matches!(settings.validate(), Err(ConfigError::NonFinite(_))));
+
+        let settings = Settings { antialias: AntiAliasing { grid: 4, ..AntiAliasing::default() }, ..Settings::default() };
+        assert_eq!(settings.validate(), Err(ConfigError::AntiAliasGrid(4)));
+    }
+}
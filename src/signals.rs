@@ -0,0 +1,100 @@
+//! [Signals]
+//!
+//! Poking a long-running zoom from outside, on Unix: SIGUSR1 pauses or
+//! resumes it, SIGUSR2 saves a screenshot, and SIGTERM lets the frame
+//! under way finish and then ends the run cleanly, so that logs are
+//! flushed rather than cut off mid-write. A second SIGTERM, should the
+//! run be stuck, ends it at once as usual. The handlers only set flags,
+//! which the main loop takes before each event, so nothing that is not
+//! safe in a signal handler happens in one.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use signal_hook::consts::{SIGTERM, SIGUSR1, SIGUSR2};
+use signal_hook::flag;
+
+/// [Request]
+///
+/// Variants:
+/// [Pause] SIGUSR1 arrived: pause or resume;
+/// [Screenshot] SIGUSR2 arrived: save a screenshot;
+/// [Terminate] SIGTERM arrived: stop after this frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Request {
+    Pause,
+    Screenshot,
+    Terminate,
+}
+
+/// [Signals]
+///
+/// Fields:
+/// [flags] The flag each signal's handler sets, by the request it makes.
+#[derive(Debug)]
+pub struct Signals {
+    flags: [(Request, Arc<AtomicBool>); 3],
+}
+
+impl Signals {
+    /// [Register]
+    /// Installs the handlers.
+    pub fn register() -> io::Result<Signals> {
+        let signals = Signals::unregistered();
+        let [(_, pause), (_, screenshot), (_, terminate)] = &signals.flags;
+
+        // Registered first, so it sees the flag before the second
+        // handler sets it: a signal with the flag already set ends the run.
+        flag::register_conditional_default(SIGTERM, terminate.clone())?;
+        flag::register(SIGTERM, terminate.clone())?;
+        flag::register(SIGUSR1, pause.clone())?;
+        flag::register(SIGUSR2, screenshot.clone())?;
+
+        Ok(signals)
+    }
+
+    fn unregistered() -> Signals {
+        Signals {
+            flags: [Request::Pause, Request::Screenshot, Request::Terminate].map(|request| (request, Arc::new(AtomicBool::new(false)))),
+        }
+    }
+
+    /// [Take]
+    /// The requests made since the last take, in a fixed order. A signal
+    /// sent several times in between makes its request once. Termination
+    /// is never cleared, so that a second SIGTERM still ends the run.
+    pub fn take(&self) -> Vec<Request> {
+        self.flags.iter()
+            .filter(|(request, flag)| match request {
+                Request::Terminate => flag.load(Ordering::Relaxed),
+                _ => flag.swap(false, Ordering::Relaxed),
+            })
+            .map(|&(request, _)| request)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_taken_once() {
+        let signals = Signals::unregistered();
+        assert_eq!(signals.take(), []);
+
+        signals.flags[1].1.store(true, Ordering::Relaxed);
+        signals.flags[0].1.store(true, Ordering::Relaxed);
+        assert_eq!(signals.take(), [Request::Pause, Request::Screenshot]);
+        assert_eq!(signals.take(), []);
+    }
+
+    #[test]
+    fn sigusr1_reaches_the_flag() {
+        let signals = Signals::register().unwrap();
+
+        signal_hook::low_level::raise(SIGUSR1).unwrap();
+        assert_eq!(signals.take(), [Request::Pause]);
+    }
+}
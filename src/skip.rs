@@ -0,0 +1,198 @@
+//! [Skip]
+//!
+//! Skipping the iterations of pixels far from the boundary. Most of a
+//! view away from the boundary is smooth gradient, where one pixel's
+//! count is much like its neighbours', yet every pixel costs a full run
+//! of iterations. Here a coarse pass first samples every STRIDE-th
+//! pixel each way, along with an estimate of its distance from the set
+//! from the derivative of its orbit. The true distance is at least a
+//! quarter of the estimate, so where all four corners of a cell of the
+//! coarse grid are estimated FAR cell widths or more away, no point of
+//! the set lies in the cell, and its pixels take counts interpolated
+//! between the corners'. Only the pixels of cells near the boundary are
+//! iterated in full. The last row and column of samples are taken on
+//! the frame's edge, whatever the stride, so that no pixel is left
+//! outside a cell.
+//!
+//! Interpolated counts can be a count or so off those iterated, so this
+//! is a toggle (Z), off by default, and batch and tiled renders never
+//! use it, iterating every pixel for exact exports. The estimate needs
+//! the derivative of z^2 + c, so only the Mandelbrot set is skipped
+//! through; other formulas are iterated in full.
+
+use num::complex::Complex as cmp;
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+use crate::fractal::Mandelbrot;
+use crate::histogram::Histogram;
+use crate::kernel::escape_time;
+use crate::progress::Progress;
+use crate::viewport::Viewport;
+
+/// The pixels between coarse samples, each way.
+pub const STRIDE: usize = 4;
+
+/// How many cell widths from the set all four corners of a cell must be
+/// estimated to be for its pixels to be interpolated, which leaves the
+/// set clear of the cell, diagonal and all, by the quarter of the
+/// estimate the distance is sure to be.
+const FAR: f64 = 6.0;
+
+/// The squared radius orbits are carried on to once escaped, for the
+/// estimate, which is only good far beyond the escape radius.
+const ESTIMATE_RADIUS_SQR: f64 = 1e12;
+
+/// The most iterations taken past escape for the estimate.
+const ESTIMATE_STEPS: u32 = 64;
+
+/// [Estimate]
+/// The escape-time count of c, iterated as the kernel does, with an
+/// estimate of its distance from the Mandelbrot set, which is 0 for
+/// points that never escape.
+pub fn estimate(c: cmp<f64>, limit: u32) -> (u32, f64) {
+    let (mut z, mut dz) = (cmp::new(0.0, 0.0), cmp::new(0.0, 0.0));
+    let mut count = 0;
+
+    while count < limit {
+        dz = z * dz * 2.0 + 1.0;
+        z = z * z + c;
+        count += 1;
+
+        if z.norm_sqr() >= 4.0 {
+            break;
+        }
+    }
+    if z.norm_sqr() < 4.0 {
+        return (count, 0.0);
+    }
+
+    for _ in 0..ESTIMATE_STEPS {
+        if z.norm_sqr() >= ESTIMATE_RADIUS_SQR {
+            break;
+        }
+        dz = z * dz * 2.0 + 1.0;
+        z = z * z + c;
+    }
+    let r = z.norm();
+    (count, 2.0 * r * r.ln() / dz.norm())
+}
+
+/// The positions of the coarse samples along a side of `len` pixels.
+fn samples(len: usize) -> Vec<usize> {
+    let mut at: Vec<usize> = (0..len).step_by(STRIDE).collect();
+    if len > 0 && at.last() != Some(&(len - 1)) {
+        at.push(len - 1);
+    }
+    at
+}
+
+/// [Compute]
+/// Fills vals with the Mandelbrot counts of the view, interpolating
+/// them far from the boundary, counting each row on `progress` and
+/// giving up on rows once the frame is cancelled as the kernel does.
+/// Returns the histogram of the counts with how many were interpolated.
+pub fn compute(viewport: &Viewport, vals: &mut [u32], limit: u32, progress: &Progress) -> (Histogram, usize) {
+    let (width, height) = (viewport.width_px(), viewport.height_px());
+    let (xs, ys) = (samples(width), samples(height));
+    let map = |a: usize, b: usize| viewport.pixel_to_complex(a as f64, b as f64);
+
+    let coarse_row = |&b: &usize| xs.iter().map(|&a| estimate(map(a, b), limit)).collect::<Vec<_>>();
+    #[cfg(not(target_arch = "wasm32"))]
+    let coarse: Vec<Vec<(u32, f64)>> = ys.par_iter().map(coarse_row).collect();
+    #[cfg(target_arch = "wasm32")]
+    let coarse: Vec<Vec<(u32, f64)>> = ys.iter().map(coarse_row).collect();
+
+    let far = FAR * STRIDE as f64 * viewport.pixel_size();
+    // The cell a pixel falls in, by the index of its top or left samples.
+    let cell = |at: &[usize], p: usize| at.partition_point(|&s| s <= p).saturating_sub(1).min(at.len().saturating_sub(2));
+
+    let fill = |(mut histogram, mut interpolated): (Histogram, usize), (b, row): (usize, &mut [u32])| {
+        if progress.is_cancelled() {
+            return (histogram, interpolated);
+        }
+
+        let j = cell(&ys, b);
+        for (a, val) in row.iter_mut().enumerate() {
+            let i = cell(&xs, a);
+            *val = match corners(&coarse, &xs, &ys, i, j).filter(|corners| corners.iter().all(|&(_, d)| d >= far)) {
+                Some(corners) => {
+                    interpolated += 1;
+                    interpolate(corners, &xs, &ys, i, j, a, b)
+                }
+                None => escape_time(&Mandelbrot, map(a, b), limit),
+            };
+            histogram.add(*val);
+        }
+        progress.row();
+        (histogram, interpolated)
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    return vals.par_chunks_mut(width)
+        .enumerate()
+        .fold(|| (Histogram::new(limit), 0), fill)
+        .reduce(|| (Histogram::new(limit), 0), |(x, m), (y, n)| (x.merge(y), m + n));
+    #[cfg(target_arch = "wasm32")]
+    return vals.chunks_mut(width).enumerate().fold((Histogram::new(limit), 0), fill);
+}
+
+/// The samples at the corners of cell (i, j), top-left, top-right,
+/// bottom-left and bottom-right, or None for a frame too small to have
+/// cells.
+fn corners(coarse: &[Vec<(u32, f64)>], xs: &[usize], ys: &[usize], i: usize, j: usize) -> Option<[(u32, f64); 4]> {
+    if xs.len() < 2 || ys.len() < 2 {
+        return None;
+    }
+    Some([coarse[j][i], coarse[j][i + 1], coarse[j + 1][i], coarse[j + 1][i + 1]])
+}
+
+/// The count at pixel (a, b), bilinearly interpolated between the
+/// corners of its cell, which it matches exactly on them.
+fn interpolate(corners: [(u32, f64); 4], xs: &[usize], ys: &[usize], i: usize, j: usize, a: usize, b: usize) -> u32 {
+    let [tl, tr, bl, br] = corners.map(|(count, _)| count as f64);
+    let s = (a - xs[i]) as f64 / (xs[i + 1] - xs[i]) as f64;
+    let t = (b - ys[j]) as f64 / (ys[j + 1] - ys[j]) as f64;
+
+    let top = tl + (tr - tl) * s;
+    let bottom = bl + (br - bl) * s;
+    (top + (bottom - top) * t).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_count_as_the_kernel_does() {
+        for c in [cmp::new(-0.745, 0.1), cmp::new(0.3, 0.5), cmp::new(-1.0, 0.0), cmp::new(2.0, 2.0)] {
+            assert_eq!(estimate(c, 500).0, escape_time(&Mandelbrot, c, 500));
+        }
+
+        // Points on the real axis past 1/4 are the distance beyond it, give or take the factor of 4.
+        let (_, d) = estimate(cmp::new(1.25, 0.0), 500);
+        assert!((1.0 / 4.0..=4.0).contains(&d), "{d}");
+        assert_eq!(estimate(cmp::new(-1.0, 0.0), 500).1, 0.0);
+    }
+
+    #[test]
+    fn skips_far_pixels_and_iterates_near_ones() {
+        let viewport = Viewport::new(cmp::new(-0.75, 0.0), 5.0, 203, 101);
+        let limit = 300;
+        let mut brute = vec![0; 203 * 101];
+        crate::kernel::compute_sequential(&Mandelbrot, &mut brute, 203, |a, b| viewport.pixel_to_complex(a as f64, b as f64), limit);
+
+        let mut vals = vec![0; 203 * 101];
+        let (histogram, interpolated) = compute(&viewport, &mut vals, limit, &Progress::default());
+
+        assert!(interpolated > vals.len() / 4, "{interpolated}");
+        assert_eq!(histogram.total(), vals.len() as u64);
+        for (i, (&skipped, &exact)) in vals.iter().zip(&brute).enumerate() {
+            // Nothing near the set is interpolated, and nothing interpolated is far out.
+            if exact >= 20 {
+                assert_eq!(skipped, exact, "pixel {i}");
+            }
+            assert!(skipped.abs_diff(exact) <= 2, "pixel {i}: {skipped} against {exact}");
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! [Split]
+//!
+//! A split view for comparing two configurations side by side, toggled
+//! with Shift+W. The frame is divided at a divider, which can be dragged,
+//! the left half shown as the app is set up and the right half as the
+//! other side is, starting as a copy of the left. Both halves are of the
+//! same view, zoomed and panned together, so they stay registered. Sides
+//! differing only in colouring are coloured from the same counts; sides
+//! differing in formula or in how the counts are computed have a second
+//! buffer computed alongside the first. Keys and the panel only ever
+//! change the left side, so Shift+H swaps the sides over, counts and all,
+//! for the other to be changed.
+
+use crate::colour::Tone;
+use crate::fractal::Formula;
+use crate::overlay::{text_box_size, Overlay, Shape};
+
+/// How near the divider, in logical pixels, a press grabs it.
+const GRAB: f64 = 6.0;
+
+/// How close to either edge the divider can be dragged, as a share of the width.
+const MARGIN: f64 = 0.05;
+
+const DIVIDER: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+const LABEL: f64 = 9.0;
+
+/// [Side]
+/// What one side of the split is computed and coloured with.
+///
+/// Fields:
+/// [formula] The escape-time formula;
+/// [perturb] Whether the counts are iterated against reference orbits;
+/// [scalar] The colour scalar;
+/// [tone] How the colouring becomes bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Side {
+    pub formula: Formula,
+    pub perturb: bool,
+    pub scalar: f32,
+    pub tone: Tone,
+}
+
+impl Side {
+    /// Whether the counts computed for one side do for the other, the two
+    /// differing only in colouring.
+    pub fn shares_counts(&self, other: &Side) -> bool {
+        self.formula == other.formula && self.perturb == other.perturb
+    }
+
+    /// What the side shows, for its label.
+    fn label(&self) -> String {
+        let perturbed = if self.perturb { ", perturbed" } else { "" };
+        format!("{}{perturbed}, {} {:.2}", self.formula.name(), self.tone.name(), self.scalar)
+    }
+}
+
+/// [Split]
+///
+/// Fields:
+/// [enabled] Whether the frame is split;
+/// [divider] Where the divider is, as a share of the width from the left;
+/// [dragging] Whether the divider is being dragged;
+/// [other] The right side, once the frame has been split.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Split {
+    pub enabled: bool,
+    pub divider: f64,
+    pub dragging: bool,
+    pub other: Option<Side>,
+}
+
+impl Default for Split {
+    fn default() -> Split {
+        Split { enabled: false, divider: 0.5, dragging: false, other: None }
+    }
+}
+
+impl Split {
+    /// [Toggle]
+    /// Splits the frame, the right side starting as a copy of the left
+    /// the first time, or collapses it back to the left side alone.
+    pub fn toggle(&mut self, left: Side) {
+        self.enabled = !self.enabled;
+        self.dragging = false;
+        if self.enabled && self.other.is_none() {
+            self.other = Some(left);
+        }
+    }
+
+    /// The right side, while the frame is split.
+    pub fn right(&self) -> Option<Side> {
+        self.other.filter(|_| self.enabled)
+    }
+
+    /// The first column of a frame `width` pixels wide on the right side.
+    pub fn column(&self, width: usize) -> usize {
+        ((self.divider * width as f64).round() as usize).min(width)
+    }
+
+    /// [Compose]
+    /// Copies the right side's columns of `right` over those of `left`,
+    /// both packed RGBA, `width` pixels per row.
+    pub fn compose(&self, left: &mut [u8], right: &[u8], width: usize) {
+        let from = self.column(width) * 4;
+        for (row, other) in left.chunks_mut(width * 4).zip(right.chunks(width * 4)) {
+            row[from..].copy_from_slice(&other[from..]);
+        }
+    }
+
+    /// [Press]
+    /// Grabs the divider if the press, in logical pixels across a frame
+    /// `width` wide, is on it, returning whether it was.
+    pub fn press(&mut self, at: [f64; 2], width: f64) -> bool {
+        self.dragging = self.right().is_some() && (at[0] - self.divider * width).abs() <= GRAB;
+        self.dragging
+    }
+
+    /// [Drag]
+    /// Moves the divider under the pointer, in logical pixels across a
+    /// frame `width` wide, while it is grabbed, returning whether it moved.
+    pub fn drag(&mut self, x: f64, width: f64) -> bool {
+        if !self.dragging || width <= 0.0 {
+            return false;
+        }
+        self.divider = (x / width).clamp(MARGIN, 1.0 - MARGIN);
+        true
+    }
+
+    /// Lets go of the divider.
+    pub fn release(&mut self) {
+        self.dragging = false;
+    }
+
+    /// [Draw]
+    /// Adds the divider down a frame of the given logical size, with each
+    /// side labelled beside it, while the frame is split.
+    pub fn draw(&self, overlay: &mut Overlay, left: &Side, frame: [f64; 2]) {
+        let Some(right) = self.right() else { return };
+
+        let x = self.divider * frame[0];
+        overlay.push(Shape::Line { from: [x, 0.0], to: [x, frame[1]], width: 2.0, colour: DIVIDER });
+
+        let [left_label, right_label] = [[left.label()], [right.label()]];
+        let [width, height] = text_box_size(&left_label, LABEL);
+        let top = frame[1] - height - 4.0;
+        overlay.text_box(&left_label, [x - width - 4.0, top], LABEL);
+        overlay.text_box(&right_label, [x + 4.0, top], LABEL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side() -> Side {
+        Side { formula: Formula::Mandelbrot, perturb: false, scalar: 2.0, tone: Tone::Srgb }
+    }
+
+    #[test]
+    fn splits_as_a_copy_and_composes_by_column() {
+        let mut split = Split::default();
+        split.toggle(side());
+        assert_eq!(split.right(), Some(side()));
+        assert!(side().shares_counts(&Side { tone: Tone::Srgb.other(), ..side() }));
+        assert!(!side().shares_counts(&Side { formula: Formula::BurningShip, ..side() }));
+
+        let mut left = vec![0; 4 * 4 * 2];
+        split.compose(&mut left, &[9; 4 * 4 * 2], 4);
+        assert_eq!(&left[..16], &[0, 0, 0, 0, 0, 0, 0, 0, 9, 9, 9, 9, 9, 9, 9, 9]);
+
+        split.toggle(side());
+        assert_eq!(split.right(), None);
+    }
+
+    #[test]
+    fn drags_from_the_divider_only() {
+        let mut split = Split::default();
+        assert!(!split.press([100.0, 10.0], 200.0));
+        split.toggle(side());
+
+        assert!(!split.press([80.0, 10.0], 200.0));
+        assert!(!split.drag(150.0, 200.0));
+        assert!(split.press([104.0, 10.0], 200.0));
+        assert!(split.drag(150.0, 200.0));
+        assert_eq!(split.divider, 0.75);
+        assert!(split.drag(250.0, 200.0));
+        assert_eq!(split.divider, 0.95);
+
+        split.release();
+        assert!(!split.drag(20.0, 200.0));
+        assert_eq!(split.column(200), 190);
+    }
+}
@@ -0,0 +1,134 @@
+//! [Stats]
+//!
+//! Summary statistics of a frame's iteration counts, for the P debug
+//! output. They take a pass over every count, so they are computed on
+//! demand rather than every frame.
+
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+/// Pixels escaping within this many iterations count as fast escapes.
+pub const FAST_ESCAPE: u32 = 10;
+
+/// [Frame Stats]
+///
+/// Fields:
+/// [min] The lowest count;
+/// [max] The highest count;
+/// [mean] The mean count;
+/// [interior_fraction] The share of pixels at the limit;
+/// [fast_escape_fraction] The share of pixels escaping within FAST_ESCAPE iterations;
+/// [total_iterations] The iterations executed over the whole frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameStats {
+    pub min: u32,
+    pub max: u32,
+    pub mean: f64,
+    pub interior_fraction: f64,
+    pub fast_escape_fraction: f64,
+    pub total_iterations: u64,
+}
+
+/// The running totals of one thread's share of the counts.
+#[derive(Clone, Copy)]
+struct Totals {
+    min: u32,
+    max: u32,
+    sum: u64,
+    interior: u64,
+    fast: u64,
+}
+
+impl Totals {
+    const EMPTY: Totals = Totals { min: u32::MAX, max: 0, sum: 0, interior: 0, fast: 0 };
+
+    fn add(self, count: u32, limit: u32) -> Totals {
+        Totals {
+            min: self.min.min(count),
+            max: self.max.max(count),
+            sum: self.sum + count as u64,
+            interior: self.interior + (count >= limit) as u64,
+            fast: self.fast + (count <= FAST_ESCAPE && count < limit) as u64,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn merge(self, other: Totals) -> Totals {
+        Totals {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            sum: self.sum + other.sum,
+            interior: self.interior + other.interior,
+            fast: self.fast + other.fast,
+        }
+    }
+}
+
+impl FrameStats {
+    /// [Degenerate]
+    /// Whether the frame has nothing left in it: more than `interior` of
+    /// it inside the set, or more than `fast_escape` of it escaping
+    /// within FAST_ESCAPE iterations.
+    pub fn degenerate(&self, interior: f64, fast_escape: f64) -> bool {
+        self.interior_fraction > interior || self.fast_escape_fraction > fast_escape
+    }
+
+    /// [Of]
+    /// Reduces the counts of a frame computed with the given limit. Each
+    /// escaping pixel took as many iterations as its count, and each
+    /// interior pixel ran to the limit, so the total is their sum.
+    pub fn of(vals: &[u32], limit: u32) -> FrameStats {
+        #[cfg(not(target_arch = "wasm32"))]
+        let totals = vals.par_iter()
+            .fold(|| Totals::EMPTY, |totals, &count| totals.add(count, limit))
+            .reduce(|| Totals::EMPTY, Totals::merge);
+        #[cfg(target_arch = "wasm32")]
+        let totals = vals.iter().fold(Totals::EMPTY, |totals, &count| totals.add(count, limit));
+
+        let pixels = vals.len().max(1) as f64;
+        FrameStats {
+            min: if vals.is_empty() { 0 } else { totals.min },
+            max: totals.max,
+            mean: totals.sum as f64 / pixels,
+            interior_fraction: totals.interior as f64 / pixels,
+            fast_escape_fraction: totals.fast as f64 / pixels,
+            total_iterations: totals.sum,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduces_counts() {
+        let stats = FrameStats::of(&[1, 10, 11, 100, 100, 2], 100);
+
+        assert_eq!((stats.min, stats.max), (1, 100));
+        assert_eq!(stats.total_iterations, 224);
+        assert!((stats.mean - 224.0 / 6.0).abs() < 1e-12);
+        assert!((stats.interior_fraction - 2.0 / 6.0).abs() < 1e-12);
+        assert!((stats.fast_escape_fraction - 3.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn degenerate_frames() {
+        let black = FrameStats::of(&[100; 1000], 100);
+        let mut mostly_black = [100; 1000];
+        mostly_black[0] = 50;
+
+        assert!(black.degenerate(0.999, 0.999));
+        assert!(!FrameStats::of(&mostly_black, 100).degenerate(0.999, 0.999));
+        assert!(FrameStats::of(&[1; 1000], 100).degenerate(0.999, 0.999));
+        assert!(!FrameStats::of(&[1, 50, 100], 100).degenerate(0.999, 0.999));
+    }
+
+    #[test]
+    fn empty_frame() {
+        let stats = FrameStats::of(&[], 100);
+
+        assert_eq!((stats.min, stats.max, stats.total_iterations), (0, 0, 0));
+        assert_eq!(stats.mean, 0.0);
+    }
+}
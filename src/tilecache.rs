@@ -0,0 +1,337 @@
+//! [Tile Cache]
+//!
+//! Counts already computed, kept so that going back over ground the
+//! zoom has covered (zooming out with O, or touring back to a bookmark)
+//! costs nothing. The plane is cut into a quadtree of square tiles:
+//! at level L a tile is BASE / 2^L wide and holds TILE x TILE counts,
+//! and tile (L, x, y) has its top-left sample at (x, y) tile widths
+//! from the origin. A frame is assembled from the first level whose
+//! samples are at least as close together as its pixels, each pixel
+//! taking the nearest sample, so a frame comes out within a pixel of
+//! computing it directly; only the tiles not cached are computed, all
+//! at once across the rayon pool.
+//!
+//! Tiles are dropped least recently used first once they take more
+//! than the memory budget, and all of them once the formula or the
+//! iteration limit changes. With a directory, tiles are also written
+//! there, under the formula and limit, and read back when not in
+//! memory, so they last between runs.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use num::complex::Complex as cmp;
+
+use crate::error::{report, AppError};
+use crate::fractal::Formula;
+use crate::histogram::Histogram;
+use crate::viewport::Viewport;
+
+/// The width on the plane of the one tile at level 0.
+pub const BASE: f64 = 4.0;
+
+/// The side of a tile, in samples.
+pub const TILE: usize = 64;
+
+/// The deepest level, past which samples are too close together for
+/// their positions to be told apart as f64, and frames are computed
+/// directly.
+pub const MAX_LEVEL: i32 = 40;
+
+/// The memory budget when a directory is given without one.
+pub const DEFAULT_BUDGET: usize = 256 << 20;
+
+/// [Tile Key]
+/// A tile of the quadtree, as (level, x, y).
+pub type TileKey = (i32, i64, i64);
+
+/// [Cache Settings]
+///
+/// Fields:
+/// [budget] How many bytes of counts to keep in memory;
+/// [dir] The directory to keep tiles in between runs, if any.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheSettings {
+    pub budget: usize,
+    pub dir: Option<PathBuf>,
+}
+
+/// [Tile Cache]
+///
+/// Fields:
+/// [settings] The budget and the directory;
+/// [computed_with] The formula and limit the tiles were computed with;
+/// [tiles] Each tile's counts, and when it was last used;
+/// [used] The tiles in the order they were last used, oldest first;
+/// [clock] Counts uses, to order them;
+/// [hits] How many tiles frames have needed that were cached;
+/// [misses] How many had to be computed;
+/// [disk_failed] Whether writing to the directory has failed, and so stopped.
+#[derive(Debug)]
+pub struct TileCache {
+    settings: CacheSettings,
+    computed_with: Option<(Formula, u32)>,
+    tiles: HashMap<TileKey, (u64, Arc<[u32]>)>,
+    used: BTreeMap<u64, TileKey>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+    disk_failed: bool,
+}
+
+impl TileCache {
+    pub fn new(settings: CacheSettings) -> TileCache {
+        TileCache {
+            settings,
+            computed_with: None,
+            tiles: HashMap::new(),
+            used: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+            disk_failed: false,
+        }
+    }
+
+    /// How many tiles are held in memory.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// The share of the tiles frames have needed that were cached, or
+    /// None before any have been needed.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let needed = self.hits + self.misses;
+        (needed > 0).then(|| self.hits as f64 / needed as f64)
+    }
+
+    /// [Fill]
+    /// Fills the counts of the view from the cache, computing the tiles
+    /// it lacks, and returns their histogram, or None for a view too
+    /// deep for the quadtree, which is left for the caller to compute.
+    pub fn fill(&mut self, formula: Formula, viewport: &Viewport, vals: &mut [u32], limit: u32) -> Option<Histogram> {
+        let level = level_for(viewport.pixel_size())?;
+        let step = BASE / 2f64.powi(level) / TILE as f64;
+
+        if self.computed_with != Some((formula, limit)) {
+            self.tiles.clear();
+            self.used.clear();
+            self.computed_with = Some((formula, limit));
+        }
+
+        // The tiles under the frame, from the samples nearest its corners.
+        let (width, height) = (viewport.width_px(), viewport.height_px());
+        let corners = [(0, 0), (width, 0), (0, height), (width, height)].map(|(a, b)| sample(viewport.pixel_to_complex(a as f64, b as f64), step));
+        let tile = |g: i64| g.div_euclid(TILE as i64);
+        let (x0, x1) = (tile(corners.iter().map(|c| c[0]).min()?), tile(corners.iter().map(|c| c[0]).max()?));
+        let (y0, y1) = (tile(corners.iter().map(|c| c[1]).min()?), tile(corners.iter().map(|c| c[1]).max()?));
+
+        let keys: Vec<TileKey> = (y0..=y1).flat_map(|y| (x0..=x1).map(move |x| (level, x, y))).collect();
+        let grid = self.tiles_for(formula, &keys, limit, step);
+
+        let across = (x1 - x0 + 1) as usize;
+        let mut histogram = Histogram::new(limit);
+        for (i, val) in vals.iter_mut().enumerate() {
+            let [gx, gy] = sample(viewport.pixel_to_complex((i % width) as f64, (i / width) as f64), step);
+            let counts = &grid[(tile(gy) - y0) as usize * across + (tile(gx) - x0) as usize];
+            *val = counts[gy.rem_euclid(TILE as i64) as usize * TILE + gx.rem_euclid(TILE as i64) as usize];
+            histogram.add(*val);
+        }
+
+        Some(histogram)
+    }
+
+    /// [Tiles For]
+    /// The counts of each of the tiles, in order, from memory, from the
+    /// directory, or computed, keeping the new ones.
+    fn tiles_for(&mut self, formula: Formula, keys: &[TileKey], limit: u32, step: f64) -> Vec<Arc<[u32]>> {
+        let mut found: Vec<Option<Arc<[u32]>>> = keys.iter().map(|key| self.take(key, formula, limit)).collect();
+        let missing: Vec<TileKey> = keys.iter().zip(&found).filter(|(_, tile)| tile.is_none()).map(|(key, _)| *key).collect();
+        self.hits += (keys.len() - missing.len()) as u64;
+        self.misses += missing.len() as u64;
+
+        // The missing tiles are stacked into one tall buffer, so that
+        // their rows are shared out across the pool together.
+        let mut computed = vec![0; missing.len() * TILE * TILE];
+        formula.compute_parallel(&mut computed, TILE, |a, b| {
+            let (_, x, y) = missing[b / TILE];
+            cmp::new((x * TILE as i64 + a as i64) as f64 * step, (y * TILE as i64 + (b % TILE) as i64) as f64 * step)
+        }, limit);
+
+        let mut computed = computed.chunks_exact(TILE * TILE).zip(&missing);
+        for slot in found.iter_mut().filter(|tile| tile.is_none()) {
+            let Some((counts, key)) = computed.next() else { break };
+            let counts: Arc<[u32]> = counts.into();
+            self.save(key, formula, limit, &counts);
+            self.insert(*key, counts.clone());
+            *slot = Some(counts);
+        }
+
+        self.evict();
+        found.into_iter().flatten().collect()
+    }
+
+    /// A tile from memory or the directory, marked as just used.
+    fn take(&mut self, key: &TileKey, formula: Formula, limit: u32) -> Option<Arc<[u32]>> {
+        if let Some((used, counts)) = self.tiles.get_mut(key) {
+            self.clock += 1;
+            self.used.remove(used);
+            self.used.insert(self.clock, *key);
+            *used = self.clock;
+            return Some(counts.clone());
+        }
+
+        let counts = self.load(key, formula, limit)?;
+        self.insert(*key, counts.clone());
+        Some(counts)
+    }
+
+    fn insert(&mut self, key: TileKey, counts: Arc<[u32]>) {
+        self.clock += 1;
+        self.used.insert(self.clock, key);
+        if let Some((used, _)) = self.tiles.insert(key, (self.clock, counts)) {
+            self.used.remove(&used);
+        }
+    }
+
+    /// Drops the tiles used longest ago until the rest fit the budget.
+    fn evict(&mut self) {
+        let capacity = self.settings.budget / (TILE * TILE * std::mem::size_of::<u32>());
+        while self.tiles.len() > capacity {
+            let Some((_, key)) = self.used.pop_first() else { break };
+            self.tiles.remove(&key);
+        }
+    }
+
+    /// Where a tile is kept in the directory, if there is one.
+    fn path(&self, (level, x, y): &TileKey, formula: Formula, limit: u32) -> Option<PathBuf> {
+        let dir = self.settings.dir.as_ref()?;
+        Some(dir.join(format!("{}-{limit}", formula.name())).join(level.to_string()).join(format!("{x}_{y}.tile")))
+    }
+
+    fn load(&self, key: &TileKey, formula: Formula, limit: u32) -> Option<Arc<[u32]>> {
+        let bytes = fs::read(self.path(key, formula, limit)?).ok()?;
+        (bytes.len() == TILE * TILE * 4).then(|| bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+    }
+
+    /// Writes a tile to the directory, reporting the first failure and
+    /// giving up on the directory after it.
+    fn save(&mut self, key: &TileKey, formula: Formula, limit: u32, counts: &[u32]) {
+        let Some(path) = self.path(key, formula, limit).filter(|_| !self.disk_failed) else { return };
+        if report(write_tile(&path, counts)).is_none() {
+            self.disk_failed = true;
+        }
+    }
+}
+
+/// [Level For]
+/// The shallowest level whose samples are at most `pixel_size` apart,
+/// or None past the deepest.
+pub fn level_for(pixel_size: f64) -> Option<i32> {
+    let level = (BASE / TILE as f64 / pixel_size).log2().ceil().max(0.0);
+    (level <= MAX_LEVEL as f64).then_some(level as i32)
+}
+
+/// The sample nearest a point, as whole samples from the origin.
+fn sample(c: cmp<f64>, step: f64) -> [i64; 2] {
+    [(c.re / step).round() as i64, (c.im / step).round() as i64]
+}
+
+fn write_tile(path: &Path, counts: &[u32]) -> Result<(), AppError> {
+    let error = |e: std::io::Error| AppError::Export { path: path.to_path_buf(), reason: e.to_string() };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(error)?;
+    }
+    fs::write(path, counts.iter().flat_map(|count| count.to_le_bytes()).collect::<Vec<u8>>()).map_err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(budget: usize) -> TileCache {
+        TileCache::new(CacheSettings { budget, dir: None })
+    }
+
+    #[test]
+    fn levels_are_at_least_as_fine_as_pixels() {
+        assert_eq!(level_for(1.0), Some(0));
+        assert_eq!(level_for(BASE / TILE as f64 / 4.0), Some(2));
+        assert_eq!(level_for(BASE / TILE as f64 / 5.0), Some(3));
+        assert_eq!(level_for(1e-300), None);
+    }
+
+    #[test]
+    fn revisited_views_are_all_hits() {
+        let viewport = Viewport::new(cmp::new(-0.75, 0.1), 3.0, 80, 40);
+        let mut cache = cache(DEFAULT_BUDGET);
+        let (mut first, mut second) = (vec![0; 80 * 40], vec![0; 80 * 40]);
+
+        let histogram = cache.fill(Formula::Mandelbrot, &viewport, &mut first, 200).unwrap();
+        assert_eq!(cache.hit_rate(), Some(0.0));
+        cache.fill(Formula::Mandelbrot, &viewport, &mut second, 200).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(cache.hit_rate(), Some(0.5));
+        assert_eq!(histogram.total(), 80 * 40);
+
+        // Each pixel is the count of a point within a sample of its own,
+        // which is mostly the same count, but for along the boundary.
+        let mut direct = vec![0; 80 * 40];
+        Formula::Mandelbrot.compute_parallel(&mut direct, 80, |a, b| viewport.pixel_to_complex(a as f64, b as f64), 200);
+        let agree = first.iter().zip(&direct).filter(|(a, b)| a == b).count();
+        assert!(agree > 80 * 40 * 3 / 4, "{agree}");
+    }
+
+    #[test]
+    fn changes_of_limit_or_formula_start_afresh() {
+        let viewport = Viewport::new(cmp::new(-0.75, 0.1), 3.0, 80, 40);
+        let mut cache = cache(DEFAULT_BUDGET);
+        let mut vals = vec![0; 80 * 40];
+
+        cache.fill(Formula::Mandelbrot, &viewport, &mut vals, 200);
+        cache.fill(Formula::Mandelbrot, &viewport, &mut vals, 300);
+        cache.fill(Formula::BurningShip, &viewport, &mut vals, 300);
+
+        assert_eq!(cache.hit_rate(), Some(0.0));
+    }
+
+    #[test]
+    fn the_least_recently_used_go_first() {
+        let tile = TILE * TILE * 4;
+        let mut cache = cache(3 * tile);
+        for x in 0..3 {
+            cache.insert((0, x, 0), vec![0; TILE * TILE].into());
+        }
+
+        cache.take(&(0, 0, 0), Formula::Mandelbrot, 1);
+        cache.insert((0, 3, 0), vec![0; TILE * TILE].into());
+        cache.evict();
+
+        assert_eq!(cache.len(), 3);
+        assert!(cache.tiles.contains_key(&(0, 0, 0)) && !cache.tiles.contains_key(&(0, 1, 0)));
+    }
+
+    #[test]
+    fn tiles_last_between_runs_in_the_directory() {
+        let dir = std::env::temp_dir().join(format!("mandelbrot-tilecache-{}", std::process::id()));
+        let viewport = Viewport::new(cmp::new(-0.5, 0.0), 0.5, 40, 20);
+        let settings = CacheSettings { budget: DEFAULT_BUDGET, dir: Some(dir.clone()) };
+        let (mut first, mut second) = (vec![0; 40 * 20], vec![0; 40 * 20]);
+
+        TileCache::new(settings.clone()).fill(Formula::Mandelbrot, &viewport, &mut first, 100);
+        let mut reopened = TileCache::new(settings);
+        reopened.fill(Formula::Mandelbrot, &viewport, &mut second, 100);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(first, second);
+        assert_eq!(reopened.hit_rate(), Some(1.0));
+    }
+}
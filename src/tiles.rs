@@ -0,0 +1,258 @@
+//! [Tiles]
+//!
+//! `render --view`: one image of a view, at sizes far beyond a window's
+//! (16384x8192 for a print, say), rendered a tile at a time. Every tile
+//! is computed and coloured on its own, with each pixel mapped to the
+//! plane exactly as in the whole image, so the tiles meet without
+//! seams. They are written to the PNG a row of tiles at a time, so that
+//! only one band of the image is ever held at once. Given a tile
+//! directory, each tile is also saved there as it is done, and tiles
+//! found there already are read back rather than computed again, so a
+//! render that was stopped carries on where it left off. Shaded, a tile
+//! is computed with a pixel of its neighbours' all round, so that its
+//! edge pixels are lit from the same differences as in the whole image.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::colour::{colourise_shaded, LegacyColorizer};
+use crate::error::AppError;
+use crate::export::save_png;
+use crate::settings::Settings;
+use crate::viewport::Viewport;
+
+/// The side of a tile when none is given.
+pub const TILE: usize = 1024;
+
+/// [Tile]
+///
+/// Fields:
+/// [column] Which tile it is across the image, from 0;
+/// [row] Which tile it is down the image, from 0;
+/// [rect] The pixels it covers, as [x, y, width, height].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub column: usize,
+    pub row: usize,
+    pub rect: [usize; 4],
+}
+
+impl Tile {
+    /// The name of its file in a tile directory.
+    pub fn file_name(&self) -> String {
+        format!("tile-{:04}-{:04}.png", self.row, self.column)
+    }
+}
+
+/// [Tiling]
+///
+/// Fields:
+/// [size] The whole image's width and height in pixels;
+/// [tile] The side of a tile, those along the right and bottom edges
+///        being cut short to fit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tiling {
+    pub size: [usize; 2],
+    pub tile: usize,
+}
+
+impl Tiling {
+    /// How many tiles there are across and down.
+    pub fn grid(&self) -> [usize; 2] {
+        self.size.map(|side| side.div_ceil(self.tile))
+    }
+
+    /// The tiles of one row, left to right.
+    pub fn row(&self, row: usize) -> Vec<Tile> {
+        let [width, height] = self.size;
+        let y = row * self.tile;
+
+        (0..self.grid()[0])
+            .map(|column| {
+                let x = column * self.tile;
+                Tile { column, row, rect: [x, y, self.tile.min(width - x), self.tile.min(height - y)] }
+            })
+            .collect()
+    }
+}
+
+/// [Render Tile]
+/// One tile of the image `viewport` covers, as packed RGBA bytes.
+pub fn render_tile(viewport: &Viewport, tile: &Tile, settings: &Settings) -> Vec<u8> {
+    let [x, y, width, height] = tile.rect;
+    let border = settings.light.enabled as usize;
+    let [left, top] = [x.saturating_sub(border), y.saturating_sub(border)];
+    let right = (x + width + border).min(viewport.width_px());
+    let bottom = (y + height + border).min(viewport.height_px());
+    let span = right - left;
+
+    let (mut vals, mut heights) = (vec![0; span * (bottom - top)], Vec::new());
+    let map = |a: usize, b: usize| viewport.pixel_to_complex((left + a) as f64, (top + b) as f64);
+    if settings.light.enabled {
+        settings.formula.compute_heights(&mut vals, &mut heights, span, map, settings.iterations);
+    } else {
+        settings.formula.compute_parallel(&mut vals, span, map, settings.iterations);
+    }
+    let shades = settings.light.shades(&heights, span, settings.iterations);
+
+    let rgba = colourise_shaded(&LegacyColorizer { scalar: settings.scalar, gradient: settings.gradient }, &vals, settings.iterations, settings.tone, shades.as_deref());
+    if border == 0 {
+        return rgba;
+    }
+    rgba.chunks(span * 4)
+        .skip(y - top)
+        .take(height)
+        .flat_map(|row| &row[(x - left) * 4..(x - left + width) * 4])
+        .copied()
+        .collect()
+}
+
+/// [Cached Tile]
+/// The tile as saved in the tile directory, if it is there whole.
+fn cached_tile(dir: &Path, tile: &Tile) -> Option<Vec<u8>> {
+    let image = image::open(dir.join(tile.file_name())).ok()?.into_rgba8();
+    let [_, _, width, height] = tile.rect;
+
+    ((image.width() as usize, image.height() as usize) == (width, height)).then(|| image.into_raw())
+}
+
+/// [Tiled Summary]
+///
+/// Fields:
+/// [computed] How many tiles were computed;
+/// [cached] How many were read back from the tile directory;
+/// [elapsed] How long the whole render took.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TiledSummary {
+    pub computed: usize,
+    pub cached: usize,
+    pub elapsed: Duration,
+}
+
+impl TiledSummary {
+    /// The summary as printed once the image is written.
+    pub fn line(&self) -> String {
+        format!("{} tiles computed and {} reused in {:.1} s", self.computed, self.cached, self.elapsed.as_secs_f64())
+    }
+}
+
+/// [Render]
+/// Renders the image `viewport` covers, at its size in pixels, to the
+/// PNG at `out`, printing a line per tile. With a tile directory, the
+/// tiles are saved there and reused from there.
+pub fn render(viewport: &Viewport, tile: usize, settings: &Settings, out: &Path, tile_dir: Option<&Path>) -> Result<TiledSummary, AppError> {
+    let start = Instant::now();
+    let error = |path: &Path, reason: String| AppError::Export { path: path.to_path_buf(), reason };
+    let tiling = Tiling { size: [viewport.width_px(), viewport.height_px()], tile: tile.max(1) };
+    let [width, height] = tiling.size;
+    let [across, down] = tiling.grid();
+
+    if let Some(dir) = tile_dir {
+        std::fs::create_dir_all(dir).map_err(|e| error(dir, e.to_string()))?;
+    }
+
+    let file = File::create(out).map_err(|e| error(out, e.to_string()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| error(out, e.to_string()))?;
+    let mut stream = writer.stream_writer().map_err(|e| error(out, e.to_string()))?;
+
+    let mut summary = TiledSummary { computed: 0, cached: 0, elapsed: Duration::ZERO };
+    for row in 0..down {
+        let tiles = tiling.row(row);
+        let band_height = tiles[0].rect[3];
+        let mut band = vec![0; width * band_height * 4];
+
+        for tile in &tiles {
+            let rendering = Instant::now();
+            let (rgba, how) = match tile_dir.and_then(|dir| cached_tile(dir, tile)) {
+                Some(rgba) => {
+                    summary.cached += 1;
+                    (rgba, "cached".to_string())
+                }
+                None => {
+                    let rgba = render_tile(viewport, tile, settings);
+                    if let Some(dir) = tile_dir {
+                        let [_, _, w, h] = tile.rect;
+                        save_png(&dir.join(tile.file_name()), w, h, &rgba)?;
+                    }
+                    summary.computed += 1;
+                    (rgba, format!("{:.2} s", rendering.elapsed().as_secs_f64()))
+                }
+            };
+
+            // Each row of the tile goes to its place in the band.
+            let [x, _, w, _] = tile.rect;
+            for (b, line) in rgba.chunks_exact(w * 4).enumerate() {
+                let at = (b * width + x) * 4;
+                band[at..at + w * 4].copy_from_slice(line);
+            }
+            println!("tile {}/{} at row {row}, column {} ({how})", row * across + tile.column + 1, across * down, tile.column);
+        }
+
+        stream.write_all(&band).map_err(|e| error(out, e.to_string()))?;
+    }
+
+    stream.finish().map_err(|e| error(out, e.to_string()))?;
+    summary.elapsed = start.elapsed();
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use num::complex::Complex as cmp;
+
+    use super::*;
+    use crate::lighting::Light;
+
+    fn temp(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mandelbrot-tiles-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn edge_tiles_are_cut_to_fit() {
+        let tiling = Tiling { size: [40, 24], tile: 16 };
+
+        assert_eq!(tiling.grid(), [3, 2]);
+        assert_eq!(tiling.row(1).iter().map(|tile| tile.rect).collect::<Vec<_>>(), [[0, 16, 16, 8], [16, 16, 16, 8], [32, 16, 8, 8]]);
+    }
+
+    #[test]
+    fn tiles_meet_without_seams() {
+        let settings = Settings { iterations: 300, ..Settings::default() };
+        let viewport = Viewport::new(cmp::new(-0.745, 0.1), 0.02, 40, 24);
+        let whole = render_tile(&viewport, &Tile { column: 0, row: 0, rect: [0, 0, 40, 24] }, &settings);
+
+        let (out, dir) = (temp("seams.png"), temp("seams"));
+        let first = render(&viewport, 16, &settings, &out, Some(&dir)).unwrap();
+        let tiled = image::open(&out).unwrap().into_rgba8().into_raw();
+        // A second run finds every tile already done.
+        let second = render(&viewport, 16, &settings, &out, Some(&dir)).unwrap();
+        let again = image::open(&out).unwrap().into_rgba8().into_raw();
+        let _ = (std::fs::remove_file(&out), std::fs::remove_dir_all(&dir));
+
+        assert!(tiled == whole);
+        assert!(again == whole);
+        assert_eq!((first.computed, first.cached), (6, 0));
+        assert_eq!((second.computed, second.cached), (0, 6));
+    }
+
+    #[test]
+    fn shaded_tiles_are_lit_as_the_whole_is() {
+        let settings = Settings { iterations: 300, light: Light { enabled: true, ..Light::default() }, ..Settings::default() };
+        let viewport = Viewport::new(cmp::new(-0.745, 0.1), 0.02, 40, 24);
+        let whole = render_tile(&viewport, &Tile { column: 0, row: 0, rect: [0, 0, 40, 24] }, &settings);
+
+        let out = temp("shaded.png");
+        render(&viewport, 16, &settings, &out, None).unwrap();
+        let tiled = image::open(&out).unwrap().into_rgba8().into_raw();
+        let _ = std::fs::remove_file(&out);
+
+        assert!(tiled == whole);
+    }
+}
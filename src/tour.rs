@@ -0,0 +1,214 @@
+//! [Tour]
+//!
+//! Visiting bookmarks in turn, started with U (Shift+U to loop). Each
+//! leg zooms out from where the view is until the next stop is in view
+//! too, pans across to it, and zooms in to its width. Zooming runs at a
+//! steady rate of magnification, the rate of the regular dive, so a leg
+//! lasts in proportion to how many times over it magnifies, and every
+//! part of it moves alike. A tour moves the viewport each update in
+//! place of the zoomer, so everything else carries on as usual.
+
+use num::complex::Complex as cmp;
+
+use crate::bookmark::Bookmark;
+use crate::viewport::Viewport;
+use crate::zoomer::DECAY;
+
+/// How many frames the pan between two stops takes.
+const PAN_FRAMES: u64 = 90;
+
+/// How much room to leave around the pair of points when both need to
+/// be in view.
+const MARGIN: f64 = 1.2;
+
+/// [Leg]
+///
+/// Fields:
+/// [from] Where the leg starts;
+/// [to] The stop it ends at;
+/// [wide] The width it zooms out to, with both in view;
+/// [frames] How long it zooms out, pans, and zooms in for;
+/// [frame] How far into the leg it is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Leg {
+    from: Bookmark,
+    to: Bookmark,
+    wide: f64,
+    frames: [u64; 3],
+    frame: u64,
+}
+
+impl Leg {
+    /// [New]
+    /// The leg from one view to another, for frames of the given aspect
+    /// (height over width) and no rotation.
+    pub fn new(from: Bookmark, to: Bookmark, aspect: f64) -> Leg {
+        let offset = to.centre - from.centre;
+        let both = 2.0 * MARGIN * offset.re.abs().max(offset.im.abs() / aspect);
+        let wide = from.width.max(to.width).max(both);
+
+        // The dive shrinks the width by DECAY a frame; this goes at the same rate.
+        let rate = -DECAY.ln();
+        let frames_for = |narrow: f64| ((wide / narrow).ln() / rate).ceil() as u64;
+        let pan = if offset == cmp::new(0.0, 0.0) { 0 } else { PAN_FRAMES };
+
+        Leg { from, to, wide, frames: [frames_for(from.width), pan, frames_for(to.width)], frame: 0 }
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames.iter().sum()
+    }
+
+    pub fn wide(&self) -> f64 {
+        self.wide
+    }
+
+    pub fn finished(&self) -> bool {
+        self.frame >= self.frames()
+    }
+
+    /// [View At]
+    /// The centre and width `frame` frames into the leg. Widths change
+    /// geometrically, and the pan eases in and out.
+    pub fn view_at(&self, frame: u64) -> (cmp<f64>, f64) {
+        let [out, pan, into] = self.frames;
+        let share = |f: u64, of: u64| if of == 0 { 1.0 } else { (f as f64 / of as f64).min(1.0) };
+
+        if frame < out {
+            (self.from.centre, self.from.width * (self.wide / self.from.width).powf(share(frame, out)))
+        } else if frame < out + pan {
+            let t = share(frame - out, pan);
+            let eased = t * t * (3.0 - 2.0 * t);
+            (self.from.centre + (self.to.centre - self.from.centre) * eased, self.wide)
+        } else {
+            let t = share(frame - out - pan, into);
+            (self.to.centre, self.wide * (self.to.width / self.wide).powf(t))
+        }
+    }
+
+    /// [Advance]
+    /// Moves the viewport on by one frame of the leg.
+    pub fn advance(&mut self, viewport: &mut Viewport) {
+        self.frame = (self.frame + 1).min(self.frames());
+
+        let (centre, width) = self.view_at(self.frame);
+        viewport.set_centre(centre);
+        viewport.set_width(width);
+    }
+}
+
+/// [Tour]
+///
+/// Fields:
+/// [stops] The bookmarks to visit, in order;
+/// [next] The stop the current leg is heading for;
+/// [looping] Whether to start over after the last stop;
+/// [leg] The leg under way, once the first update has begun it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tour {
+    stops: Vec<Bookmark>,
+    next: usize,
+    looping: bool,
+    leg: Option<Leg>,
+}
+
+impl Tour {
+    /// A tour of the stops, or None if there are none.
+    pub fn new(stops: Vec<Bookmark>, looping: bool) -> Option<Tour> {
+        (!stops.is_empty()).then_some(Tour { stops, next: 0, looping, leg: None })
+    }
+
+    /// [Advance]
+    /// Moves the viewport on by one frame, starting the next leg from
+    /// wherever the view is once the last one is done. Returns the stop
+    /// just reached, if one was.
+    pub fn advance(&mut self, viewport: &mut Viewport) -> Option<Bookmark> {
+        let aspect = viewport.height() / viewport.width();
+        let stop = self.stops[self.next];
+        let leg = self.leg.get_or_insert_with(|| Leg::new(Bookmark::of(viewport), stop, aspect));
+
+        leg.advance(viewport);
+        if !leg.finished() {
+            return None;
+        }
+
+        self.leg = None;
+        self.next = (self.next + 1) % self.stops.len();
+        Some(stop)
+    }
+
+    /// Whether the tour is over: a one-shot tour is once it has made its
+    /// way back round to the first stop.
+    pub fn over(&self) -> bool {
+        !self.looping && self.next == 0 && self.leg.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stop(re: f64, im: f64, width: f64) -> Bookmark {
+        Bookmark { centre: cmp::new(re, im), width }
+    }
+
+    #[test]
+    fn legs_end_on_their_stop() {
+        let to = stop(-0.7436, 0.1318, 1e-6);
+        let leg = Leg::new(stop(0.36, -0.64, 1e-4), to, 0.5);
+
+        let (centre, width) = leg.view_at(leg.frames());
+        assert_eq!(centre, to.centre);
+        assert!((width / to.width - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn both_ends_are_in_view_across_the_pan() {
+        let (from, to) = (stop(0.36, -0.64, 1e-4), stop(-0.7436, 0.1318, 1e-6));
+        let leg = Leg::new(from, to, 0.5);
+        let [out, pan, _] = leg.frames;
+
+        for frame in out..out + pan {
+            let (centre, width) = leg.view_at(frame);
+            let view = Viewport::new(centre, width, 400, 200);
+            for point in [from.centre, to.centre] {
+                let [x, y] = view.complex_to_pixel(point);
+                assert!((0.0..=400.0).contains(&x) && (0.0..=200.0).contains(&y), "frame {frame}: {point}");
+            }
+        }
+    }
+
+    #[test]
+    fn zooming_lasts_in_proportion_to_magnification() {
+        let leg = |width: f64| Leg::new(stop(0.0, 0.0, 1.0), stop(0.0, 0.0, width), 0.5);
+
+        // No pan, and nothing to zoom out by.
+        assert_eq!(leg(1e-3).frames, [0, 0, leg(1e-3).frames[2]]);
+        let (shallow, deep) = (leg(1e-3).frames(), leg(1e-6).frames());
+        assert!(deep.abs_diff(2 * shallow) <= 1, "{shallow} {deep}");
+    }
+
+    #[test]
+    fn one_shot_tours_end_looping_ones_do_not() {
+        let stops = vec![stop(-0.75, 0.1, 0.5), stop(-0.5, 0.0, 1.0)];
+        let mut viewport = Viewport::new(cmp::new(0.0, 0.0), 1.0, 40, 20);
+
+        for looping in [false, true] {
+            let mut tour = Tour::new(stops.clone(), looping).unwrap();
+            let mut reached = Vec::new();
+            for _ in 0..10_000 {
+                reached.extend(tour.advance(&mut viewport));
+                if tour.over() {
+                    break;
+                }
+            }
+
+            if looping {
+                assert!(reached.len() > 2);
+            } else {
+                assert_eq!(reached, stops);
+            }
+        }
+        assert_eq!(Tour::new(Vec::new(), false), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! [Transition]
+//!
+//! Animated retargeting: rather than the view snapping to a new one when
+//! a click recentres it or the zoom restarts, it travels there over a
+//! set number of frames, on the path van Wijk and Nuij found to be
+//! smoothest and most efficient for moving between two views of a plane.
+//! The width changes exponentially, and where the two centres are far
+//! apart for the widths the path first rises, zooming out until both are
+//! near enough in view, before coming down on the destination. Progress
+//! along the path eases in and out. A transition moves the viewport each
+//! update in place of the zoomer, as a tour does, so every frame of it is
+//! an ordinary one; Esc stops it where it is.
+
+use num::complex::Complex as cmp;
+
+use crate::bookmark::Bookmark;
+use crate::viewport::Viewport;
+
+/// How many frames a transition takes, if not told.
+pub const FRAMES: u64 = 60;
+
+/// The trade-off between zooming and panning, van Wijk and Nuij's rho:
+/// higher values zoom out further to pan less at the narrow widths.
+const RHO: f64 = std::f64::consts::SQRT_2;
+
+/// [Transition]
+///
+/// Fields:
+/// [from] Where the transition starts;
+/// [to] The view it ends at;
+/// [frames] How many frames it takes;
+/// [frame] How far into it it is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    from: Bookmark,
+    to: Bookmark,
+    frames: u64,
+    frame: u64,
+}
+
+impl Transition {
+    pub fn new(from: Bookmark, to: Bookmark, frames: u64) -> Transition {
+        Transition { from, to, frames, frame: 0 }
+    }
+
+    pub fn to(&self) -> Bookmark {
+        self.to
+    }
+
+    pub fn finished(&self) -> bool {
+        self.frame >= self.frames
+    }
+
+    /// How many frames the transition has left to go.
+    pub fn remaining(&self) -> u64 {
+        self.frames - self.frame
+    }
+
+    /// [View At]
+    /// The centre and width `frame` frames into the transition, ending
+    /// exactly on the destination.
+    pub fn view_at(&self, frame: u64) -> (cmp<f64>, f64) {
+        if frame >= self.frames {
+            return (self.to.centre, self.to.width);
+        }
+        let t = frame as f64 / self.frames as f64;
+        let eased = t * t * (3.0 - 2.0 * t);
+
+        let (w0, w1) = (self.from.width, self.to.width);
+        let offset = self.to.centre - self.from.centre;
+        let u1 = offset.norm();
+        let zoom = (w1 / w0).ln();
+
+        // Too near to pan by: the width alone changes, geometrically.
+        if u1 <= 1e-9 * w0.min(w1) {
+            return (self.from.centre + offset * eased, w0 * (zoom * eased).exp());
+        }
+
+        // The path's two ends, as in van Wijk and Nuij, with asinh for the
+        // logs so as to keep the precision at deep views.
+        let rho2 = RHO * RHO;
+        let b = |w: f64, sign: f64| (w1 * w1 - w0 * w0 + sign * rho2 * rho2 * u1 * u1) / (2.0 * w * rho2 * u1);
+        let (r0, r1) = (-b(w0, 1.0).asinh(), -b(w1, -1.0).asinh());
+        let s = eased * (r1 - r0) / RHO;
+
+        let u = w0 / rho2 * (r0.cosh() * (RHO * s + r0).tanh() - r0.sinh());
+        let width = w0 * r0.cosh() / (RHO * s + r0).cosh();
+        (self.from.centre + offset * (u / u1), width)
+    }
+
+    /// [Advance]
+    /// Moves the viewport on by one frame of the transition.
+    pub fn advance(&mut self, viewport: &mut Viewport) {
+        self.frame = (self.frame + 1).min(self.frames);
+
+        let (centre, width) = self.view_at(self.frame);
+        viewport.set_centre(centre);
+        viewport.set_width(width);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(re: f64, im: f64, width: f64) -> Bookmark {
+        Bookmark { centre: cmp::new(re, im), width }
+    }
+
+    #[test]
+    fn starts_and_ends_on_its_views() {
+        let (from, to) = (view(-0.7436, 0.1318, 1e-8), view(0.36, -0.64, 1e-4));
+        let transition = Transition::new(from, to, 40);
+
+        let (centre, width) = transition.view_at(0);
+        assert!((centre - from.centre).norm() < 1e-15 && (width / from.width - 1.0).abs() < 1e-9, "{centre} {width}");
+        assert_eq!(transition.view_at(40), (to.centre, to.width));
+
+        // Far apart for the widths, it zooms out past both on the way.
+        let widest = (0..40).map(|frame| transition.view_at(frame).1).fold(0.0, f64::max);
+        assert!(widest > 10.0 * to.width, "{widest}");
+        for frame in 1..40 {
+            let (centre, _) = transition.view_at(frame);
+            assert!((centre - from.centre).norm() <= (to.centre - from.centre).norm() * (1.0 + 1e-9), "frame {frame}");
+        }
+    }
+
+    #[test]
+    fn zooms_in_place_geometrically() {
+        let transition = Transition::new(view(-0.75, 0.1, 1.0), view(-0.75, 0.1, 1e-4), 10);
+        let widths: Vec<f64> = (0..=10).map(|frame| transition.view_at(frame).1).collect();
+        assert!(widths.windows(2).all(|pair| pair[1] < pair[0]), "{widths:?}");
+        assert_eq!(transition.view_at(5).1.log10().round(), -2.0);
+
+        let mut viewport = Viewport::new(cmp::new(-0.75, 0.1), 1.0, 40, 20);
+        let mut transition = transition;
+        while !transition.finished() {
+            transition.advance(&mut viewport);
+            assert_eq!(viewport.centre(), cmp::new(-0.75, 0.1));
+        }
+        assert_eq!(viewport.width(), 1e-4);
+    }
+}
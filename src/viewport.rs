@@ -0,0 +1,476 @@
+//! [Viewport]
+//!
+//! The mapping between window pixels and the complex plane. A
+//! viewport is described by the complex point at the centre of the
+//! window, the width of the window measured on the complex plane,
+//! a rotation about the centre, and the window's pixel dimensions.
+//!
+//! Pixel coordinates grow rightwards and downwards, and the pixel
+//! (a, b) samples the top-left corner of that cell, so pixel (0, 0)
+//! sits exactly on the top-left corner of the view. With no rotation,
+//! moving down the window increases the imaginary part.
+//!
+//! The plane a view is laid over need not be the one the points are
+//! iterated in: under the inverted projection each point w of it stands
+//! for c = 1/w, the point at infinity sitting at the origin, so that the
+//! unbounded outside of the set becomes a bounded figure. Everything
+//! geometric (zooming, targets, transitions) works in the view's plane,
+//! and only what is iterated is projected.
+//!
+//! The centre is kept to double-double precision as the view zooms and
+//! pans, for perturbation's reference orbits to start from; everything
+//! else takes it rounded to f64, and points as offsets from it.
+
+use num::complex::Complex as cmp;
+
+use crate::fractal::Formula;
+use crate::real::DoubleDouble;
+
+/// [Projection]
+/// How the points of the view's plane map to the points iterated.
+///
+/// Variants:
+/// [Plane] Each point is iterated as it is;
+/// [Inverted] Each point w is iterated as 1/w, the origin as a point
+///            far enough out to escape at once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Projection {
+    #[default]
+    Plane,
+    Inverted,
+}
+
+impl Projection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Plane => "plane",
+            Projection::Inverted => "inverted",
+        }
+    }
+
+    /// The projection the toggle switches to.
+    pub fn other(&self) -> Projection {
+        match self {
+            Projection::Plane => Projection::Inverted,
+            Projection::Inverted => Projection::Plane,
+        }
+    }
+
+    /// [Apply]
+    /// The point iterated for the point w of the view's plane. Inverting
+    /// is its own inverse, so this also takes an iterated point back to
+    /// the plane.
+    #[inline(always)]
+    pub fn apply(&self, w: cmp<f64>) -> cmp<f64> {
+        match self {
+            Projection::Plane => w,
+            Projection::Inverted if w.norm_sqr() == 0.0 => cmp::new(f64::INFINITY, 0.0),
+            Projection::Inverted => w.inv(),
+        }
+    }
+
+    /// [Overview]
+    /// The centre and width of a 2:1 view taking in the whole set under
+    /// the projection, with a margin around it. Inverted, the outside of
+    /// the set reaches out to 1/0.25 = 4, from the cusp of the cardioid.
+    pub fn overview(&self, formula: Formula) -> (cmp<f64>, f64) {
+        match (self, formula) {
+            (Projection::Plane, formula) => formula.overview(),
+            (Projection::Inverted, Formula::Mandelbrot) => (cmp::new(1.3, 0.0), 7.0),
+            (Projection::Inverted, Formula::BurningShip) => (cmp::new(0.5, -0.9), 12.0),
+        }
+    }
+}
+
+/// [Width Limits]
+/// The narrowest and widest a view is allowed to get.
+///
+/// Fields:
+/// [min] The smallest width on the complex plane;
+/// [max] The largest width on the complex plane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WidthLimits {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl WidthLimits {
+    /// No limits beyond a width staying positive and finite.
+    pub const NONE: WidthLimits = WidthLimits { min: f64::MIN_POSITIVE, max: f64::MAX };
+
+    /// [Clamp]
+    /// The factor to zoom a view `width` across by in place of `factor`,
+    /// so that its new width stays within the limits.
+    pub fn clamp(&self, width: f64, factor: f64) -> f64 {
+        width / (width / factor).clamp(self.min, self.max)
+    }
+}
+
+/// How far the cached turn may be from the rotation's before the view
+/// is taken to be inconsistent.
+const TURN_TOLERANCE: f64 = 1e-9;
+
+/// [Viewport]
+///
+/// Fields:
+/// [centre] The complex point at the centre of the window;
+/// [residue] What rounding the centre to f64 left off;
+/// [width] The distance across the window on the complex plane;
+/// [rotation] Rotation of the view about the centre, in radians;
+/// [turn] Unit complex number for the rotation (cached);
+/// [width_px] Window width in pixels;
+/// [height_px] Window height in pixels;
+/// [projection] How the points of the view map to the points iterated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    centre: cmp<f64>,
+    residue: cmp<f64>,
+    width: f64,
+    rotation: f64,
+    turn: cmp<f64>,
+    width_px: usize,
+    height_px: usize,
+    projection: Projection,
+}
+
+impl Viewport {
+    /// [New]
+    /// An unrotated viewport, on the plane.
+    pub fn new(centre: cmp<f64>, width: f64, width_px: usize, height_px: usize) -> Viewport {
+        Viewport {
+            centre,
+            residue: cmp::new(0.0, 0.0),
+            width,
+            rotation: 0.0,
+            turn: cmp::new(1.0, 0.0),
+            width_px,
+            height_px,
+            projection: Projection::Plane,
+        }
+    }
+
+    pub fn centre(&self) -> cmp<f64> {
+        self.centre
+    }
+
+    /// The centre at the precision it is kept to.
+    pub fn precise_centre(&self) -> cmp<DoubleDouble> {
+        cmp::new(DoubleDouble::new(self.centre.re, self.residue.re), DoubleDouble::new(self.centre.im, self.residue.im))
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// The distance down the window on the complex plane. Pixels are
+    /// square, so this follows from the width and the pixel aspect.
+    pub fn height(&self) -> f64 {
+        self.width * self.height_px as f64 / self.width_px as f64
+    }
+
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    pub fn width_px(&self) -> usize {
+        self.width_px
+    }
+
+    pub fn height_px(&self) -> usize {
+        self.height_px
+    }
+
+    /// The size of one pixel on the complex plane.
+    pub fn pixel_size(&self) -> f64 {
+        self.width / self.width_px as f64
+    }
+
+    /// Number of pixels per unit on the complex plane.
+    pub fn scale(&self) -> f64 {
+        self.width_px as f64 / self.width
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    pub fn set_centre(&mut self, centre: cmp<f64>) {
+        self.set_precise_centre(cmp::new(DoubleDouble::of(centre.re), DoubleDouble::of(centre.im)));
+    }
+
+    pub fn set_precise_centre(&mut self, centre: cmp<DoubleDouble>) {
+        self.centre = cmp::new(centre.re.hi(), centre.im.hi());
+        self.residue = cmp::new(centre.re.lo(), centre.im.lo());
+    }
+
+    pub fn set_width(&mut self, width: f64) {
+        self.width = width;
+    }
+
+    /// [Resize]
+    /// Changes the window dimensions, keeping the centre and the size of
+    /// a pixel, so the view covers more or less of the plane.
+    pub fn resize(&mut self, width_px: usize, height_px: usize) {
+        self.width *= width_px as f64 / self.width_px as f64;
+        self.width_px = width_px;
+        self.height_px = height_px;
+    }
+
+    /// [Rescaled]
+    /// The same view spread over a different number of pixels, as when
+    /// the window moves to a display with more of them to the inch.
+    pub fn rescaled(&self, width_px: usize, height_px: usize) -> Viewport {
+        Viewport { width_px, height_px, ..*self }
+    }
+
+    pub fn set_rotation(&mut self, rotation: f64) {
+        self.rotation = rotation;
+        self.turn = cmp::from_polar(1.0, rotation);
+    }
+
+    /// [Pixel to Complex]
+    /// The complex point sampled by the (possibly fractional) pixel
+    /// coordinate (x, y).
+    #[inline(always)]
+    pub fn pixel_to_complex(&self, x: f64, y: f64) -> cmp<f64> {
+        self.centre + self.pixel_offset(x, y)
+    }
+
+    /// [Sample]
+    /// The point iterated for the pixel coordinate (x, y): the point it
+    /// samples, under the view's projection.
+    #[inline(always)]
+    pub fn sample(&self, x: f64, y: f64) -> cmp<f64> {
+        self.projection.apply(self.pixel_to_complex(x, y))
+    }
+
+    /// [Pixel Offset]
+    /// How far the point sampled by the pixel coordinate (x, y) is from
+    /// the centre, which keeps its precision however deep the view, where
+    /// the point itself is rounded to the centre's.
+    #[inline(always)]
+    pub fn pixel_offset(&self, x: f64, y: f64) -> cmp<f64> {
+        let size = self.pixel_size();
+        let offset = cmp::new(
+            (x - self.width_px as f64 / 2.0) * size,
+            (y - self.height_px as f64 / 2.0) * size,
+        );
+
+        offset * self.turn
+    }
+
+    /// [Complex to Pixel]
+    /// The inverse of pixel_to_complex, as fractional pixel coordinates.
+    /// Points outside the view map outside the window dimensions.
+    pub fn complex_to_pixel(&self, c: cmp<f64>) -> [f64; 2] {
+        let scale = self.scale();
+        let offset = (c - self.centre) * self.turn.conj();
+
+        [
+            offset.re * scale + self.width_px as f64 / 2.0,
+            offset.im * scale + self.height_px as f64 / 2.0,
+        ]
+    }
+
+    /// [Zoom About]
+    /// Magnifies the view by factor, keeping point at the same pixel.
+    /// Factors above one zoom in, factors below one zoom out.
+    pub fn zoom_about(&mut self, point: cmp<f64>, factor: f64) {
+        let point = cmp::new(DoubleDouble::of(point.re), DoubleDouble::of(point.im));
+        self.set_precise_centre(point + (self.precise_centre() - point) / DoubleDouble::of(factor));
+        self.width /= factor;
+    }
+
+    /// [Pan]
+    /// Moves the view by delta on the complex plane.
+    pub fn pan(&mut self, delta: cmp<f64>) {
+        self.set_precise_centre(self.precise_centre() + cmp::new(DoubleDouble::of(delta.re), DoubleDouble::of(delta.im)));
+    }
+
+    /// [Fault]
+    /// What is wrong with the view, if anything, with the values at
+    /// fault: a centre, width or rotation that is not finite, a width
+    /// that is not positive, no pixels, a pixel too small for its scale
+    /// to be finite, or a cached turn that has drifted from the rotation.
+    pub fn fault(&self) -> Option<String> {
+        let Viewport { centre, width, rotation, turn, width_px, height_px, .. } = *self;
+        if !(centre.re.is_finite() && centre.im.is_finite()) {
+            return Some(format!("centre {centre} is not finite"));
+        }
+        if !(width.is_finite() && width >= f64::MIN_POSITIVE) {
+            return Some(format!("width {width} is not positive and finite"));
+        }
+        if width_px == 0 || height_px == 0 {
+            return Some(format!("the view is {width_px}x{height_px} pixels"));
+        }
+        if !self.scale().is_finite() {
+            return Some(format!("width {width} over {width_px} pixels leaves no finite scale"));
+        }
+        if !rotation.is_finite() {
+            return Some(format!("rotation {rotation} is not finite"));
+        }
+        let drift = (turn - cmp::from_polar(1.0, rotation)).norm();
+        if drift.is_nan() || drift > TURN_TOLERANCE {
+            return Some(format!("turn {turn} is {drift:e} off rotation {rotation}"));
+        }
+        None
+    }
+
+    /// Computes the cached turn again from the rotation.
+    pub fn rebuild(&mut self) {
+        self.turn = cmp::from_polar(1.0, self.rotation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: cmp<f64>, b: cmp<f64>, tol: f64) -> bool {
+        (a - b).norm() <= tol
+    }
+
+    fn view() -> Viewport {
+        Viewport::new(cmp::new(-0.75, 0.1), 3.0, 400, 200)
+    }
+
+    #[test]
+    fn corners_match_bounds() {
+        let v = view();
+
+        assert!(close(v.pixel_to_complex(0.0, 0.0), cmp::new(-2.25, -0.65), 1e-12));
+        assert!(close(v.pixel_to_complex(400.0, 200.0), cmp::new(0.75, 0.85), 1e-12));
+        assert!(close(v.pixel_to_complex(200.0, 100.0), v.centre(), 1e-12));
+        assert_eq!(v.height(), 1.5);
+    }
+
+    #[test]
+    fn pixel_round_trip() {
+        let mut v = view();
+
+        for rotation in [0.0, 0.3, -2.0] {
+            v.set_rotation(rotation);
+
+            for (x, y) in [(0.0, 0.0), (13.5, 170.25), (399.0, 199.0), (-20.0, 250.0)] {
+                let [x2, y2] = v.complex_to_pixel(v.pixel_to_complex(x, y));
+                assert!((x - x2).abs() < 1e-9 && (y - y2).abs() < 1e-9, "{rotation}: ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_about_keeps_point_fixed() {
+        let mut v = view();
+        v.set_rotation(0.7);
+
+        let point = v.pixel_to_complex(57.0, 31.0);
+        for factor in [1.05, 3.0, 0.5] {
+            v.zoom_about(point, factor);
+
+            let [x, y] = v.complex_to_pixel(point);
+            assert!((x - 57.0).abs() < 1e-9 && (y - 31.0).abs() < 1e-9, "{factor}");
+        }
+    }
+
+    #[test]
+    fn zoom_about_centre_keeps_centre() {
+        let mut v = view();
+        let centre = v.centre();
+
+        v.zoom_about(centre, 4.0);
+
+        assert_eq!(v.centre(), centre);
+        assert_eq!(v.width(), 0.75);
+    }
+
+    #[test]
+    fn inverted_views_sample_reciprocals() {
+        let mut v = Viewport::new(cmp::new(0.0, 0.0), 4.0, 4, 2);
+        assert_eq!(v.sample(3.0, 1.0), cmp::new(1.0, 0.0));
+
+        v.set_projection(Projection::Inverted);
+        assert_eq!(v.pixel_to_complex(3.0, 1.0), cmp::new(1.0, 0.0));
+        assert_eq!(v.sample(3.0, 1.0), cmp::new(1.0, 0.0));
+        assert_eq!(v.sample(2.0, 0.0), cmp::new(0.0, 1.0));
+        assert_eq!(v.sample(2.0, 0.0), Projection::Inverted.apply(v.pixel_to_complex(2.0, 0.0)));
+
+        // The origin is the point at infinity, which escapes straight away.
+        assert_eq!(v.sample(2.0, 1.0).norm_sqr(), f64::INFINITY);
+        assert_eq!(crate::kernel::escape_time(&crate::fractal::Mandelbrot, v.sample(2.0, 1.0), 100), 1);
+    }
+
+    #[test]
+    fn faults_name_the_values_at_fault() {
+        let mut v = view();
+        assert_eq!(v.fault(), None);
+
+        v.turn = cmp::new(0.0, 1.0);
+        assert!(v.fault().is_some_and(|fault| fault.contains("rotation 0")), "{:?}", v.fault());
+        v.rebuild();
+        assert_eq!(v.fault(), None);
+
+        v.zoom_about(v.centre(), -2.0);
+        assert_eq!(v.fault(), Some("width -1.5 is not positive and finite".to_string()));
+        v.set_width(1.5);
+        v.set_centre(cmp::new(f64::NAN, 0.0));
+        assert!(v.fault().is_some_and(|fault| fault.starts_with("centre")));
+    }
+
+    #[test]
+    fn width_limits_clamp_the_factor() {
+        let limits = WidthLimits { min: 0.5, max: 8.0 };
+
+        assert_eq!(limits.clamp(4.0, 2.0), 2.0);
+        assert_eq!(limits.clamp(4.0, 16.0), 8.0);
+        assert_eq!(limits.clamp(4.0, 0.25), 0.5);
+    }
+
+    #[test]
+    fn pan_moves_every_pixel() {
+        let mut v = view();
+        let before = v.pixel_to_complex(10.0, 20.0);
+
+        v.pan(cmp::new(0.5, -0.25));
+
+        assert!(close(v.pixel_to_complex(10.0, 20.0), before + cmp::new(0.5, -0.25), 1e-12));
+    }
+
+    #[test]
+    fn the_centre_keeps_what_f64_rounds_off() {
+        let mut v = Viewport::new(cmp::new(-0.75, 0.1), 1e-30, 400, 200);
+
+        v.pan(cmp::new(3e-30, 0.0));
+        assert_eq!(v.centre(), cmp::new(-0.75, 0.1));
+        assert_eq!((v.precise_centre().re + DoubleDouble::of(0.75)).hi(), 3e-30);
+
+        // Zooming in twofold about -0.75 halves the centre's way from it.
+        v.zoom_about(cmp::new(-0.75, 0.1), 2.0);
+        assert_eq!((v.precise_centre().re + DoubleDouble::of(0.75)).hi(), 1.5e-30);
+        v.set_centre(cmp::new(-0.5, 0.0));
+        assert_eq!(v.precise_centre().re, DoubleDouble::of(-0.5));
+    }
+
+    #[test]
+    fn resizing_keeps_the_pixels() {
+        let mut v = Viewport::new(cmp::new(-0.5, 0.25), 4.0, 400, 200);
+        let corner = v.pixel_to_complex(0.0, 0.0);
+
+        v.resize(800, 200);
+
+        assert_eq!((v.width(), v.height(), v.centre()), (8.0, 2.0, cmp::new(-0.5, 0.25)));
+        assert_eq!(v.pixel_to_complex(200.0, 0.0), corner);
+    }
+
+    #[test]
+    fn rescaling_keeps_the_view() {
+        let v = view();
+        let doubled = v.rescaled(800, 400);
+
+        assert_eq!((doubled.width(), doubled.height(), doubled.centre()), (v.width(), v.height(), v.centre()));
+        assert_eq!(doubled.pixel_to_complex(27.0, 13.0), v.pixel_to_complex(13.5, 6.5));
+    }
+}
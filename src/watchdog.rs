@@ -0,0 +1,105 @@
+//! [Watchdog]
+//!
+//! The check on the view after every step that moves it. The zoomer
+//! skips steps that would turn the view inside out, but a long enough
+//! run, or a control that moves the view some other way, could still
+//! leave it degenerate or inconsistent, and a frame of it would be
+//! garbage at best. The watchdog keeps the last view that passed, and
+//! on a fault first computes the cached turn again from the rotation,
+//! as that alone may have drifted, and failing that puts the last good
+//! view back. Either way the app pauses, so that what happened can be
+//! looked at rather than zoomed on past.
+
+use crate::viewport::Viewport;
+
+/// [Recovery]
+/// What the watchdog did about a faulty view.
+///
+/// Variants:
+/// [Rebuilt] The turn was computed again, which put the view right;
+/// [Restored] The last good view was put back.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Recovery {
+    Rebuilt { fault: String },
+    Restored { fault: String, view: Viewport },
+}
+
+impl Recovery {
+    /// The line logged for the recovery.
+    pub fn describe(&self) -> String {
+        match self {
+            Recovery::Rebuilt { fault } => format!("the view went wrong ({fault}); rebuilt it from its rotation"),
+            Recovery::Restored { fault, view } => format!(
+                "the view went wrong ({fault}); restored the last good one, {}+{}i {} wide",
+                view.centre().re, view.centre().im, view.width()
+            ),
+        }
+    }
+}
+
+/// [Watchdog]
+///
+/// Fields:
+/// [last_good] The last view that passed the check.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Watchdog {
+    last_good: Viewport,
+}
+
+impl Watchdog {
+    /// Watches views from a good one.
+    pub fn new(view: Viewport) -> Watchdog {
+        Watchdog { last_good: view }
+    }
+
+    /// [Check]
+    /// Checks the view, remembering it if it passes, and recovering it
+    /// if it does not, saying how.
+    pub fn check(&mut self, view: &mut Viewport) -> Option<Recovery> {
+        let Some(fault) = view.fault() else {
+            self.last_good = *view;
+            return None;
+        };
+
+        view.rebuild();
+        if view.fault().is_none() {
+            self.last_good = *view;
+            return Some(Recovery::Rebuilt { fault });
+        }
+        // The window may have been resized since the view was good.
+        *view = self.last_good.rescaled(view.width_px().max(1), view.height_px().max(1));
+        Some(Recovery::Restored { fault, view: *view })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::complex::Complex as cmp;
+
+    use super::*;
+
+    #[test]
+    fn a_zoom_step_past_the_width_is_recovered() {
+        let mut view = Viewport::new(cmp::new(-0.75, 0.1), 0.5, 400, 200);
+        let mut watchdog = Watchdog::new(view);
+        let target = view.pixel_to_complex(100.0, 50.0);
+        // A step trimming `zoom` off each side, as the zoomer's does.
+        let step = |view: &mut Viewport, zoom: f64| view.zoom_about(target, view.width() / (view.width() - 2.0 * zoom));
+
+        step(&mut view, 0.1);
+        assert_eq!(watchdog.check(&mut view), None);
+        let good = view;
+
+        // Trimming more than half the width turns the view inside out.
+        step(&mut view, 0.3);
+        let Some(Recovery::Restored { fault, view: restored }) = watchdog.check(&mut view) else { panic!("not restored") };
+        assert!(fault.starts_with("width -"), "{fault}");
+        assert_eq!((view, restored), (good, good));
+        assert_eq!(watchdog.check(&mut view), None);
+
+        // A zoom amount gone NaN takes the whole view with it.
+        step(&mut view, f64::NAN);
+        assert!(matches!(watchdog.check(&mut view), Some(Recovery::Restored { .. })));
+        assert_eq!(view, good);
+    }
+}
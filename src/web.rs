@@ -0,0 +1,113 @@
+//! [Web]
+//!
+//! The WebAssembly entry points. A page calls `init` once with the id
+//! of a canvas, then `tick` from its animation loop; each tick advances
+//! the zoom by one step and writes the coloured frame into the canvas
+//! as ImageData. See examples/web for a page that does this.
+
+use std::cell::RefCell;
+
+use js_sys::Reflect;
+use num::complex::Complex as cmp;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+use crate::app::App;
+use crate::fractal::Formula;
+use crate::settings::Settings;
+
+/// [Web]
+///
+/// Fields:
+/// [app] The zoom being shown;
+/// [context] The 2D context of the canvas it is shown on.
+struct Web {
+    app: App,
+    context: CanvasRenderingContext2d,
+}
+
+thread_local! {
+    static WEB: RefCell<Option<Web>> = const { RefCell::new(None) };
+}
+
+/// [Init]
+///
+/// Starts a zoom on the canvas with the given id, resizing the canvas
+/// to match. The options object may be undefined, or may set any of:
+/// [scale] Pixels per unit on the complex plane (100, giving 400x200);
+/// [iterations] The iteration limit (1200);
+/// [formula] "mandelbrot" or "burning-ship".
+#[wasm_bindgen]
+pub fn init(canvas_id: &str, options: JsValue) -> Result<(), JsValue> {
+    let mut settings = Settings::default();
+    if let Some(scale) = option(&options, "scale").and_then(|v| v.as_f64()) {
+        settings.graph_scale = scale;
+    }
+    if let Some(iterations) = option(&options, "iterations").and_then(|v| v.as_f64()) {
+        settings.iterations = iterations as u32;
+    }
+    if let Some(name) = option(&options, "formula").and_then(|v| v.as_string()) {
+        settings.formula = Formula::ALL.into_iter().find(|f| f.name() == name)
+            .ok_or_else(|| JsValue::from_str(&format!("unknown formula '{name}'")))?;
+    }
+
+    let app = App::new(&settings).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let canvas: HtmlCanvasElement = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(canvas_id))
+        .ok_or_else(|| JsValue::from_str(&format!("no element with id '{canvas_id}'")))?
+        .dyn_into()
+        .map_err(|_| JsValue::from_str(&format!("'{canvas_id}' is not a canvas")))?;
+    canvas.set_width(app.viewport().width_px() as u32);
+    canvas.set_height(app.viewport().height_px() as u32);
+
+    let context: CanvasRenderingContext2d = canvas.get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("the canvas has no 2d context"))?
+        .dyn_into()?;
+
+    WEB.with(|web| *web.borrow_mut() = Some(Web { app, context }));
+    Ok(())
+}
+
+/// [Tick]
+/// Advances the zoom by one step and draws the result.
+#[wasm_bindgen]
+pub fn tick() -> Result<(), JsValue> {
+    with_web(|web| {
+        web.app.update_sequential();
+
+        let (width, height) = (web.app.viewport().width_px() as u32, web.app.viewport().height_px() as u32);
+        let frame = ImageData::new_with_u8_clamped_array_and_sh(Clamped(web.app.frame()), width, height)?;
+        web.context.put_image_data(&frame, 0.0, 0.0)
+    })
+}
+
+/// [Set Center]
+/// Moves the zoom to a new point, keeping the current width.
+#[wasm_bindgen]
+pub fn set_center(re: f64, im: f64) -> Result<(), JsValue> {
+    with_web(|web| {
+        web.app.set_centre(cmp::new(re, im));
+        Ok(())
+    })
+}
+
+fn with_web(f: impl FnOnce(&mut Web) -> Result<(), JsValue>) -> Result<(), JsValue> {
+    WEB.with(|web| match web.borrow_mut().as_mut() {
+        Some(web) => f(web),
+        None => Err(JsValue::from_str("init must be called first")),
+    })
+}
+
+/// Reads a field of the options object, treating undefined and null
+/// (or no object at all) as absent.
+fn option(options: &JsValue, name: &str) -> Option<JsValue> {
+    if !options.is_object() {
+        return None;
+    }
+
+    Reflect::get(options, &JsValue::from_str(name)).ok()
+        .filter(|v| !v.is_undefined() && !v.is_null())
+}
@@ -0,0 +1,321 @@
+//! [Worker]
+//!
+//! Frames computed away from the event loop, so that the window keeps
+//! presenting, and answering keys, while a slow frame is under way. A
+//! job owns everything it computes from (a copy of the view, formula,
+//! limit and anti-aliasing, and a buffer of its own) and sends the
+//! finished frame back over a channel, the rows themselves still going
+//! to the rayon pool, which counts them off for the app to show how far
+//! the frame has got. There is one job at a time: the app polls for it
+//! on each update and starts the next once it is in. A job let go of
+//! is cancelled, the kernel giving up before its next row and the
+//! partial counts being thrown away, so that a view changed by the
+//! user is started on within a row's time rather than a frame's.
+//! Nothing calls this on wasm32, which has no threads to start.
+
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::antialias::{AntiAliasing, Supersamples};
+use crate::fractal::Formula;
+use crate::histogram::Histogram;
+use crate::perturb::{self, Perturbed};
+use crate::progress::{Phase, Progress, SHOW_AFTER};
+use crate::skip;
+use crate::tilecache::TileCache;
+use crate::viewport::{Projection, Viewport};
+
+/// [Work]
+/// What a frame is computed from.
+///
+/// Fields:
+/// [formula] The escape-time formula;
+/// [radius] The radius its orbits escape at;
+/// [viewport] The view;
+/// [limit] The iteration limit;
+/// [antialiasing] Whether and how edges get extra samples;
+/// [skip] Whether the counts far from the boundary are interpolated;
+/// [perturb] Whether the counts are iterated against reference orbits;
+/// [series] Whether they then skip iterations by series approximation;
+/// [validate] Whether the series is checked against a sample of pixels;
+/// [lyapunov] Whether each orbit's Lyapunov exponent is kept with its count;
+/// [light] Whether each orbit's smooth count is kept with it, for the light to shade from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Work {
+    pub formula: Formula,
+    pub radius: f64,
+    pub viewport: Viewport,
+    pub limit: u32,
+    pub antialiasing: AntiAliasing,
+    pub skip: bool,
+    pub perturb: bool,
+    pub series: bool,
+    pub validate: bool,
+    pub lyapunov: bool,
+    pub light: bool,
+}
+
+impl Work {
+    /// [Compute]
+    /// Fills vals with the counts of the view, from the tile cache where
+    /// it can be, and takes the extra samples along edges, returning the
+    /// histogram of the counts with the samples, and what perturbing them
+    /// took if they were perturbed. Rows computed are counted on
+    /// `progress` as they are done, and those from the cache all at once.
+    /// The edges are not sampled once the frame is cancelled. Rows are
+    /// computed outward from row `first`, if given, unless the counts far
+    /// from the boundary are being skipped, when they are computed top to
+    /// bottom after the coarse pass, or perturbed, when they are too.
+    /// Keeping the Lyapunov exponents, into `exponents`, or the smooth
+    /// counts, into `heights`, takes every orbit iterated in full, so
+    /// neither the cache, skipping nor perturbing is used for them;
+    /// otherwise they are left empty. Views under another projection than
+    /// the plane are iterated in full as well, as are orbits escaping at
+    /// another radius than the formulas' own, which the cache, the
+    /// distance estimate and the reference orbits all assume.
+    pub fn compute(&self, vals: &mut [u32], exponents: &mut Vec<f32>, heights: &mut Vec<f32>, tile_cache: Option<&Mutex<TileCache>>, progress: &Progress, first: Option<usize>) -> (Histogram, Supersamples, Option<Perturbed>) {
+        let Work { formula, radius, viewport, limit, antialiasing, skip, perturb, series, validate, lyapunov, light } = *self;
+        let mut perturbed = None;
+        exponents.clear();
+        heights.clear();
+        let escaping = formula.escaping(radius);
+        let plane = viewport.projection() == Projection::Plane && escaping.is_default();
+
+        let cached = tile_cache.filter(|_| plane && !lyapunov && !light).and_then(|cache| cache.lock().unwrap_or_else(PoisonError::into_inner).fill(formula, &viewport, vals, limit));
+        let histogram = match cached {
+            Some(histogram) => {
+                progress.rows.store(viewport.height_px(), Ordering::Relaxed);
+                histogram
+            }
+            None if lyapunov || light => {
+                let mut stats = vec![(0.0, 0.0); vals.len()];
+                let (width, map) = (viewport.width_px(), |a: usize, b: usize| viewport.sample(a as f64, b as f64));
+                let histogram = match (lyapunov, light) {
+                    (true, true) => escaping.compute_stats_counted::<_, _, true, true>(vals, &mut stats, width, map, limit, progress),
+                    (true, false) => escaping.compute_stats_counted::<_, _, true, false>(vals, &mut stats, width, map, limit, progress),
+                    _ => escaping.compute_stats_counted::<_, _, false, true>(vals, &mut stats, width, map, limit, progress),
+                };
+                if lyapunov {
+                    exponents.extend(stats.iter().map(|&(exponent, _)| exponent));
+                }
+                if light {
+                    heights.extend(stats.iter().map(|&(_, smooth)| smooth));
+                }
+                histogram
+            }
+            None if perturb && plane && formula == Formula::Mandelbrot => {
+                let (histogram, took) = perturb::compute(&viewport, vals, limit, series, validate, progress);
+                perturbed = Some(took);
+                histogram
+            }
+            None if skip && plane && formula == Formula::Mandelbrot => skip::compute(&viewport, vals, limit, progress).0,
+            None => escaping.compute_parallel_counted(vals, viewport.width_px(), |a, b| {
+                viewport.sample(a as f64, b as f64)
+            }, limit, progress, first),
+        };
+
+        if progress.is_cancelled() {
+            return (histogram, Supersamples::default(), perturbed);
+        }
+        progress.edges.store(antialiasing.enabled, Ordering::Relaxed);
+        (histogram, Supersamples::of(escaping, &viewport, vals, limit, &antialiasing), perturbed)
+    }
+}
+
+/// [Frame]
+/// A finished frame.
+///
+/// Fields:
+/// [work] What it was computed from;
+/// [vals] Its counts, row-major;
+/// [histogram] Their distribution;
+/// [supersamples] The extra samples along its edges;
+/// [perturbed] What perturbing its counts took, if they were;
+/// [exponents] The Lyapunov exponents of its pixels, if they were kept;
+/// [heights] Their smooth counts, if they were kept;
+/// [other] The frame computed alongside it for the other side of a split, if one was;
+/// [elapsed] How long it took.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub work: Work,
+    pub vals: Vec<u32>,
+    pub histogram: Histogram,
+    pub supersamples: Supersamples,
+    pub perturbed: Option<Perturbed>,
+    pub exponents: Vec<f32>,
+    pub heights: Vec<f32>,
+    pub other: Option<Box<Frame>>,
+    pub elapsed: Duration,
+}
+
+/// [Poll]
+/// How the frame under way is doing.
+///
+/// Variants:
+/// [Running] The frame is still under way;
+/// [Done] The frame is finished;
+/// [Lost] The worker died without finishing it.
+#[derive(Debug)]
+pub enum Poll {
+    Running,
+    Done(Box<Frame>),
+    Lost,
+}
+
+/// [Job]
+///
+/// Fields:
+/// [work] What the frame under way is computed from;
+/// [other] What the frame alongside it is, for the other side of a split;
+/// [started] When it was started;
+/// [progress] How far it has got;
+/// [receiver] Where the finished frame arrives.
+#[derive(Debug)]
+pub struct Job {
+    pub work: Work,
+    pub other: Option<Work>,
+    pub started: Instant,
+    progress: Arc<Progress>,
+    receiver: Receiver<Frame>,
+}
+
+impl Job {
+    /// [Start]
+    /// Starts computing a frame on a thread of its own, from row `first`
+    /// outward if given, and then the `other` frame, if there is one, for
+    /// the other side of a split, the progress starting over for it.
+    /// Where it starts is not part of the work, as the frame comes out
+    /// the same.
+    pub fn start(work: Work, other: Option<Work>, tile_cache: Option<Arc<Mutex<TileCache>>>, first: Option<usize>) -> Job {
+        let (sender, receiver) = mpsc::channel();
+        let (started, progress) = (Instant::now(), Arc::new(Progress::default()));
+
+        let counted = progress.clone();
+        thread::spawn(move || {
+            let compute = |work: Work| {
+                let (mut vals, mut exponents, mut heights) = (vec![0; work.viewport.width_px() * work.viewport.height_px()], Vec::new(), Vec::new());
+                let (histogram, supersamples, perturbed) = work.compute(&mut vals, &mut exponents, &mut heights, tile_cache.as_deref(), &counted, first);
+                Frame { work, vals, histogram, supersamples, perturbed, exponents, heights, other: None, elapsed: started.elapsed() }
+            };
+            let mut frame = compute(work);
+            if let Some(other) = other.filter(|_| !counted.is_cancelled()) {
+                counted.restart();
+                frame.other = Some(Box::new(compute(other)));
+                frame.elapsed = started.elapsed();
+            }
+
+            // The app may have let the job go in the meantime.
+            let _ = sender.send(frame);
+        });
+
+        Job { work, other, started, progress, receiver }
+    }
+
+    /// How far the frame has got, once it has been under way long enough
+    /// by `now` to be worth showing.
+    pub fn phase(&self, now: Instant) -> Option<Phase> {
+        (now.saturating_duration_since(self.started) >= SHOW_AFTER).then(|| self.progress.phase(self.work.viewport.height_px()))
+    }
+
+    /// Whether the frame is finished, without waiting for it.
+    pub fn poll(&self) -> Poll {
+        match self.receiver.try_recv() {
+            Ok(frame) => Poll::Done(Box::new(frame)),
+            Err(TryRecvError::Empty) => Poll::Running,
+            Err(TryRecvError::Disconnected) => Poll::Lost,
+        }
+    }
+
+    /// Waits for the frame, or None if the worker died without it.
+    pub fn wait(self) -> Option<Frame> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for Job {
+    /// Lets the worker give up on a frame no one will take.
+    fn drop(&mut self) {
+        self.progress.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::complex::Complex as cmp;
+
+    use super::*;
+    use crate::fractal::ESCAPE_RADIUS;
+
+    fn work(viewport: Viewport) -> Work {
+        Work {
+            formula: Formula::Mandelbrot,
+            radius: ESCAPE_RADIUS,
+            viewport,
+            limit: 300,
+            antialiasing: AntiAliasing::default(),
+            skip: false,
+            perturb: false,
+            series: true,
+            validate: false,
+            lyapunov: false,
+            light: false,
+        }
+    }
+
+    #[test]
+    fn jobs_compute_what_the_update_would() {
+        let work = work(Viewport::new(cmp::new(-0.745, 0.1), 0.02, 40, 20));
+        let mut vals = vec![0; 40 * 20];
+        let (histogram, _, _) = work.compute(&mut vals, &mut Vec::new(), &mut Vec::new(), None, &Progress::default(), None);
+
+        let job = Job::start(work, None, None, Some(15));
+        let frame = loop {
+            match job.poll() {
+                Poll::Running => thread::yield_now(),
+                Poll::Done(frame) => break frame,
+                Poll::Lost => panic!("the worker died"),
+            }
+        };
+
+        assert_eq!(job.progress.phase(20), Phase::Counts(1.0));
+        assert_eq!(job.phase(job.started), None);
+        assert_eq!(frame.work, work);
+        assert!(frame.vals == vals);
+        assert_eq!(frame.histogram, histogram);
+    }
+
+    #[test]
+    fn lit_frames_keep_smooth_counts_with_their_own() {
+        let work = Work { light: true, ..work(Viewport::new(cmp::new(-0.745, 0.1), 0.02, 40, 20)) };
+        let (mut vals, mut lit, mut heights) = (vec![0; 40 * 20], vec![0; 40 * 20], Vec::new());
+        work.compute(&mut vals, &mut Vec::new(), &mut Vec::new(), None, &Progress::default(), None);
+        work.compute(&mut lit, &mut Vec::new(), &mut heights, None, &Progress::default(), None);
+
+        assert!(lit == vals);
+        assert_eq!(heights.len(), vals.len());
+        // Each smooth count lies within two of the count itself.
+        assert!(heights.iter().zip(&vals).all(|(&height, &count)| (0.0..2.0).contains(&(height - count as f32))), "{heights:?}");
+        assert!(heights.iter().any(|height| height.fract() != 0.0));
+    }
+
+    #[test]
+    fn cancelled_frames_stop_short() {
+        let work = Work {
+            antialiasing: AntiAliasing { enabled: true, ..AntiAliasing::default() },
+            skip: true,
+            ..work(Viewport::new(cmp::new(-0.745, 0.1), 0.02, 40, 20))
+        };
+        let progress = Progress::default();
+        progress.cancel();
+
+        let mut vals = vec![0; 40 * 20];
+        let (_, supersamples, _) = work.compute(&mut vals, &mut Vec::new(), &mut Vec::new(), None, &progress, None);
+
+        assert!(vals.iter().all(|&count| count == 0));
+        assert_eq!(progress.phase(20), Phase::Counts(0.0));
+        assert_eq!(supersamples, Supersamples::default());
+    }
+}
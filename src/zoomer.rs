@@ -0,0 +1,209 @@
+//! [Zoomer]
+//!
+//! The zoom animation on its own: how far the view closes in on each
+//! update, the point it closes in on, and how that amount decays so the
+//! dive slows over time. The dive can also be turned around to zoom out,
+//! as far as the width limits allow.
+
+use num::complex::Complex as cmp;
+
+use crate::viewport::{Viewport, WidthLimits};
+
+/// How much of the zoom amount survives each step.
+pub const DECAY: f64 = 0.95;
+
+/// The factor each step zooms by while zooming out: the view grows by 2%.
+pub const OUTWARD: f64 = 1.0 / 1.02;
+
+/// [Zoomer]
+///
+/// Each step trims the zoom amount off both sides of the view, so the
+/// width shrinks by twice the zoom about the target, and then the zoom
+/// amount decays. From a width w and zoom z, the width after n steps is
+/// w - 4z(1 - 0.95^n) in exact arithmetic, which for the original
+/// settings (w = 4, z = 0.1) is simply 4 * 0.95^n.
+///
+/// The target starts at the centre of the view, where zooming about
+/// it leaves the centre exactly where it is.
+///
+/// Zooming out grows the view by a steady 2% a step instead, and grows
+/// the zoom amount with it, so that turning back in carries on diving at
+/// the rate it was before turning out. Neither decays while zooming out.
+///
+/// Fields:
+/// [zoom] current zoom amount (starts at 0.10);
+/// [target] The point the zoom converges on;
+/// [outward] Whether the zoom is running outwards;
+/// [limits] The narrowest and widest the view may get.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Zoomer {
+    zoom: f64,
+    target: cmp<f64>,
+    outward: bool,
+    limits: WidthLimits,
+}
+
+impl Zoomer {
+    pub fn new(zoom: f64, target: cmp<f64>) -> Zoomer {
+        Zoomer { zoom, target, outward: false, limits: WidthLimits::NONE }
+    }
+
+    /// Limits the width of the views this zoomer will produce.
+    pub fn with_limits(self, limits: WidthLimits) -> Zoomer {
+        Zoomer { limits, ..self }
+    }
+
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom;
+    }
+
+    pub fn outward(&self) -> bool {
+        self.outward
+    }
+
+    pub fn set_outward(&mut self, outward: bool) {
+        self.outward = outward;
+    }
+
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    pub fn target(&self) -> cmp<f64> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: cmp<f64>) {
+        self.target = target;
+    }
+
+    /// [Advance]
+    /// Applies one step of the zoom to the viewport about the target. The
+    /// height follows from the window's aspect ratio. A step which
+    /// would leave no width, or turn the view inside out, is skipped
+    /// rather than applied, and steps are cut short at the width limits.
+    pub fn advance(&mut self, viewport: &mut Viewport) {
+        let width = viewport.width();
+        let factor = if self.outward { OUTWARD } else { width / (width - 2.0 * self.zoom) };
+
+        if factor.is_finite() && factor > 0.0 {
+            let factor = self.limits.clamp(width, factor);
+            viewport.zoom_about(self.target, factor);
+
+            if self.outward {
+                self.zoom /= factor;
+            }
+        }
+
+        if !self.outward {
+            self.zoom *= DECAY;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{MAGIC_IM, MAGIC_RE};
+
+    fn view() -> Viewport {
+        Viewport::new(cmp::new(MAGIC_RE, MAGIC_IM), 4.0, 400, 200)
+    }
+
+    #[test]
+    fn width_follows_closed_form() {
+        let mut v = view();
+        let mut zoomer = Zoomer::new(0.1, v.centre());
+
+        for n in 1..=200 {
+            zoomer.advance(&mut v);
+
+            let expected = 4.0 * DECAY.powi(n);
+            assert!((v.width() - expected).abs() <= expected * 1e-9, "step {n}: {} != {expected}", v.width());
+            assert_eq!(v.centre(), cmp::new(MAGIC_RE, MAGIC_IM));
+        }
+    }
+
+    #[test]
+    fn target_keeps_its_pixel() {
+        let mut v = view();
+        let target = v.pixel_to_complex(100.0, 150.0);
+        let mut zoomer = Zoomer::new(0.1, target);
+
+        for n in 0..100 {
+            zoomer.advance(&mut v);
+
+            let [x, y] = v.complex_to_pixel(target);
+            assert!((x - 100.0).abs() < 1e-6 && (y - 150.0).abs() < 1e-6, "step {n}: ({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn aspect_ratio_holds() {
+        // The original 2:1 window, a square one and a 21:9 one.
+        for (width_px, height_px) in [(400, 200), (400, 400), (420, 180)] {
+            let mut v = Viewport::new(cmp::new(MAGIC_RE, MAGIC_IM), 4.0, width_px, height_px);
+            let mut zoomer = Zoomer::new(0.1, v.pixel_to_complex(100.0, 150.0));
+            let expected = height_px as f64 / width_px as f64;
+
+            for n in 0..10_000 {
+                zoomer.advance(&mut v);
+
+                let aspect = v.height() / v.width();
+                assert!((aspect - expected).abs() < 1e-12, "{width_px}x{height_px} step {n}: {aspect}");
+            }
+        }
+    }
+
+    #[test]
+    fn zooming_out_then_in_keeps_the_rate() {
+        let mut v = view();
+        let mut zoomer = Zoomer::new(0.1, v.centre());
+
+        zoomer.set_outward(true);
+        for _ in 0..100 {
+            zoomer.advance(&mut v);
+        }
+        assert!((v.width() - 4.0 * 1.02f64.powi(100)).abs() < 1e-9);
+
+        // The dive's width keeps shrinking by DECAY a step, from wherever it is.
+        zoomer.set_outward(false);
+        let before = v.width();
+        zoomer.advance(&mut v);
+        assert!((v.width() / before - DECAY).abs() < 1e-12);
+    }
+
+    #[test]
+    fn limits_hold_both_ways() {
+        let limits = WidthLimits { min: 0.5, max: 40.0 };
+        let mut v = view();
+        let mut zoomer = Zoomer::new(0.1, v.centre()).with_limits(limits);
+
+        for _ in 0..1000 {
+            zoomer.advance(&mut v);
+        }
+        assert!((v.width() - 0.5).abs() < 1e-12, "{}", v.width());
+
+        zoomer.set_outward(true);
+        for _ in 0..1000 {
+            zoomer.advance(&mut v);
+        }
+        assert!((v.width() - 40.0).abs() < 1e-9, "{}", v.width());
+    }
+
+    #[test]
+    fn bounds_never_invert() {
+        for zoom in [0.1, 1.0, 2.0, 5.0] {
+            let mut v = view();
+            let mut zoomer = Zoomer::new(zoom, v.centre());
+
+            for n in 0..10_000 {
+                zoomer.advance(&mut v);
+
+                let (min, max) = (v.pixel_to_complex(0.0, 0.0), v.pixel_to_complex(400.0, 200.0));
+                assert!(v.width() > 0.0 && v.width().is_finite(), "zoom {zoom}, step {n}: {}", v.width());
+                assert!(min.re <= max.re && min.im <= max.im, "zoom {zoom}, step {n}");
+            }
+        }
+    }
+}
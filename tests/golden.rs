@@ -0,0 +1,111 @@
+//! [Golden Images]
+//!
+//! Renders small fixed views through the default colouring and compares
+//! a hash of the RGBA bytes against the committed golden value. These
+//! are ignored by default; run them with
+//!
+//!     cargo test --test golden -- --ignored
+//!
+//! After an intentional change to the mapping or the colours, regenerate
+//! the goldens with `GOLDEN_UPDATE=1` set. On a mismatch the actual image
+//! and a difference image are written next to the golden PNG under
+//! `target/golden/` for inspection.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+use mandelbrot_piston::colour::{colourise, LegacyColorizer, Tone};
+use mandelbrot_piston::settings::Settings;
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+const OUTPUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/golden");
+
+/// 64-bit FNV-1a, which unlike the std hasher is stable across releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Writes a difference image, brightening every changed pixel so that
+/// single-count changes are still visible.
+fn write_diff(path: &Path, expected: &RgbaImage, actual: &RgbaImage) {
+    let diff = RgbaImage::from_fn(actual.width(), actual.height(), |x, y| {
+        let (e, a) = (expected.get_pixel(x, y).0, actual.get_pixel(x, y).0);
+        let d: [u8; 3] = std::array::from_fn(|i| e[i].abs_diff(a[i]));
+
+        if d == [0, 0, 0] {
+            image::Rgba([0, 0, 0, 255])
+        } else {
+            let [r, g, b] = d.map(|c| c.saturating_mul(4).max(64));
+            image::Rgba([r, g, b, 255])
+        }
+    });
+    diff.save(path).unwrap();
+}
+
+fn check(name: &str, width: u32, height: u32, rgba: Vec<u8>) {
+    let hash = format!("{:016x}", fnv1a(&rgba));
+    let actual = RgbaImage::from_raw(width, height, rgba).unwrap();
+
+    let hash_path = PathBuf::from(GOLDEN_DIR).join(format!("{name}.hash"));
+    let png_path = PathBuf::from(GOLDEN_DIR).join(format!("{name}.png"));
+
+    if std::env::var_os("GOLDEN_UPDATE").is_some() {
+        fs::write(&hash_path, format!("{hash}\n")).unwrap();
+        actual.save(&png_path).unwrap();
+        println!("updated golden {name}: {hash}");
+        return;
+    }
+
+    let expected_hash = fs::read_to_string(&hash_path)
+        .unwrap_or_else(|e| panic!("missing golden {}: {e} (run with GOLDEN_UPDATE=1)", hash_path.display()));
+    if expected_hash.trim() == hash {
+        return;
+    }
+
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    let actual_path = PathBuf::from(OUTPUT_DIR).join(format!("{name}.actual.png"));
+    actual.save(&actual_path).unwrap();
+
+    let mut message = format!("{name}: hash {hash} does not match golden {}; wrote {}", expected_hash.trim(), actual_path.display());
+    if let Ok(expected) = image::open(&png_path) {
+        let expected = expected.to_rgba8();
+        if expected.dimensions() == actual.dimensions() {
+            let diff_path = PathBuf::from(OUTPUT_DIR).join(format!("{name}.diff.png"));
+            write_diff(&diff_path, &expected, &actual);
+
+            let changed = expected.pixels().zip(actual.pixels()).filter(|(e, a)| e != a).count();
+            message += &format!(" and {} ({changed} pixels differ)", diff_path.display());
+        }
+    }
+
+    panic!("{message}");
+}
+
+/// The initial view at 200x100, coloured with the given tone.
+fn initial_view_in(tone: Tone) -> Vec<u8> {
+    let settings = Settings { dimensions: Some((200, 100)), iterations: 500, ..Settings::default() };
+    let viewport = settings.validate().unwrap();
+
+    let mut vals = vec![0; 200 * 100];
+    settings.formula.compute_parallel(&mut vals, 200, |a, b| {
+        viewport.pixel_to_complex(a as f64, b as f64)
+    }, settings.iterations);
+
+    let colorizer = LegacyColorizer { scalar: settings.scalar, gradient: settings.gradient };
+    colourise(&colorizer, &vals, settings.iterations, tone)
+}
+
+#[test]
+#[ignore]
+fn initial_view() {
+    check("initial_200x100", 200, 100, initial_view_in(Tone::Legacy));
+}
+
+#[test]
+#[ignore]
+fn initial_view_srgb() {
+    check("initial_200x100_srgb", 200, 100, initial_view_in(Tone::Srgb));
+}
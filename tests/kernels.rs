@@ -0,0 +1,73 @@
+//! The sequential and parallel kernels are separate loops, so these
+//! tests pin them to identical output over a handful of views.
+
+use mandelbrot_piston::fractal::Formula;
+use mandelbrot_piston::settings::{Settings, ITERATIONS};
+use mandelbrot_piston::viewport::Viewport;
+use num::complex::Complex as cmp;
+
+fn both(formula: Formula, viewport: Viewport, limit: u32) -> (Vec<u32>, Vec<u32>) {
+    let size = viewport.width_px() * viewport.height_px();
+    let map = |a: usize, b: usize| viewport.pixel_to_complex(a as f64, b as f64);
+
+    let mut parallel = vec![0; size];
+    formula.compute_parallel(&mut parallel, viewport.width_px(), map, limit);
+
+    let mut sequential = vec![u32::MAX; size];
+    formula.compute_sequential(&mut sequential, viewport.width_px(), map, limit);
+
+    (parallel, sequential)
+}
+
+fn assert_identical(formula: Formula, viewport: Viewport, limit: u32) {
+    let (parallel, sequential) = both(formula, viewport, limit);
+
+    let width = viewport.width_px();
+    for (i, (p, s)) in parallel.iter().zip(&sequential).enumerate() {
+        assert_eq!(p, s, "{} differs at pixel ({}, {})", formula.name(), i % width, i / width);
+    }
+}
+
+/// The view the zoom starts at.
+fn initial() -> Viewport {
+    Settings::default().validate().unwrap()
+}
+
+#[test]
+fn initial_view() {
+    assert_identical(Formula::Mandelbrot, initial(), ITERATIONS);
+}
+
+#[test]
+fn mid_zoom() {
+    let mut viewport = initial();
+    viewport.zoom_about(viewport.centre(), 300.0);
+
+    assert_identical(Formula::Mandelbrot, viewport, ITERATIONS);
+}
+
+#[test]
+fn minibrot() {
+    // The period-3 minibrot on the real axis.
+    let viewport = Viewport::new(cmp::new(-1.7548776662466927, 0.0), 0.05, 160, 80);
+    let (parallel, _) = both(Formula::Mandelbrot, viewport, 500);
+
+    // Make sure the view really does contain interior points.
+    assert!(parallel.contains(&500));
+    assert_identical(Formula::Mandelbrot, viewport, 500);
+}
+
+#[test]
+fn rotated_view() {
+    let mut viewport = Viewport::new(cmp::new(-0.745, 0.113), 0.02, 120, 90);
+    viewport.set_rotation(1.1);
+
+    assert_identical(Formula::Mandelbrot, viewport, 800);
+}
+
+#[test]
+fn every_formula() {
+    for formula in Formula::ALL {
+        assert_identical(formula, Viewport::new(cmp::new(-0.5, -0.5), 3.0, 120, 60), 300);
+    }
+}
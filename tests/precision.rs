@@ -0,0 +1,54 @@
+//! The kernel is generic over its scalar type; these tests compare the
+//! f32 instantiation against f64. Away from the boundary the two agree
+//! exactly, and near it f32 rounding only nudges counts, so the check
+//! is on the fraction of matching pixels rather than on every pixel.
+
+use mandelbrot_piston::fractal::Formula;
+use mandelbrot_piston::real::Real;
+use mandelbrot_piston::settings::Settings;
+use mandelbrot_piston::viewport::Viewport;
+use num::complex::Complex as cmp;
+
+fn compute<T: Real>(formula: Formula, viewport: &Viewport, limit: u32) -> Vec<u32> {
+    let mut vals = vec![0; viewport.width_px() * viewport.height_px()];
+    formula.compute_parallel(&mut vals, viewport.width_px(), |a, b| {
+        let c = viewport.pixel_to_complex(a as f64, b as f64);
+        cmp::new(T::from_f64(c.re), T::from_f64(c.im))
+    }, limit);
+    vals
+}
+
+fn agreement(formula: Formula, viewport: &Viewport, limit: u32) -> f64 {
+    let single = compute::<f32>(formula, viewport, limit);
+    let double = compute::<f64>(formula, viewport, limit);
+
+    let matching = single.iter().zip(&double).filter(|(s, d)| s == d).count();
+    matching as f64 / double.len() as f64
+}
+
+#[test]
+fn f32_agrees_with_f64_on_initial_view() {
+    let settings = Settings { dimensions: Some((200, 100)), ..Settings::default() };
+    let viewport = settings.validate().unwrap();
+
+    for formula in Formula::ALL {
+        let fraction = agreement(formula, &viewport, 300);
+        assert!(fraction > 0.95, "{}: only {fraction} of pixels agree", formula.name());
+    }
+}
+
+#[test]
+fn f32_agrees_with_f64_at_shallow_zoom() {
+    let viewport = Viewport::new(cmp::new(-0.745, 0.113), 0.05, 160, 80);
+
+    let fraction = agreement(Formula::Mandelbrot, &viewport, 500);
+    assert!(fraction > 0.9, "only {fraction} of pixels agree");
+}
+
+#[test]
+fn f32_escapes_like_f64_far_outside() {
+    // Every point with |c| > 2 escapes on the first iteration.
+    let viewport = Viewport::new(cmp::new(10.0, 10.0), 1.0, 20, 20);
+
+    assert!(compute::<f32>(Formula::Mandelbrot, &viewport, 100).iter().all(|&count| count == 1));
+}
@@ -0,0 +1,113 @@
+//! Property tests for the pixel/complex mapping. These pin down the
+//! invariants the zoom relies on frame after frame: the mapping inverts
+//! cleanly, zooming about a point leaves that point where it was, and
+//! zooming back out undoes a zoom in.
+
+use mandelbrot_piston::viewport::Viewport;
+use num::complex::Complex as cmp;
+use quickcheck::{quickcheck, Arbitrary, Gen, TestResult};
+
+/// A uniform value in [0, 1], built from integers so that no NaNs or
+/// infinities leak in from the float generators.
+fn unit(g: &mut Gen) -> f64 {
+    u32::arbitrary(g) as f64 / u32::MAX as f64
+}
+
+/// A random viewport, with a random pixel inside it.
+#[derive(Clone, Debug)]
+struct Case {
+    viewport: Viewport,
+    x: f64,
+    y: f64,
+}
+
+impl Arbitrary for Case {
+    fn arbitrary(g: &mut Gen) -> Case {
+        let centre = cmp::new(unit(g) * 4.0 - 2.0, unit(g) * 4.0 - 2.0);
+        // Widths from 10 down to 1e-9, spread evenly in magnitude. Any
+        // narrower and the pixels start to approach the f64 spacing of
+        // the centre, where no mapping can round-trip.
+        let width = 10f64.powf(1.0 - 10.0 * unit(g));
+        let width_px = 1 + u32::arbitrary(g) as usize % 2000;
+        let height_px = 1 + u32::arbitrary(g) as usize % 2000;
+
+        let mut viewport = Viewport::new(centre, width, width_px, height_px);
+        if bool::arbitrary(g) {
+            viewport.set_rotation(unit(g) * std::f64::consts::TAU);
+        }
+
+        Case { viewport, x: unit(g) * width_px as f64, y: unit(g) * height_px as f64 }
+    }
+}
+
+/// A zoom factor between 1/4 and 4, never exactly 1.
+fn factor(seed: u32) -> f64 {
+    let f = 1.0 + 3.0 * ((seed % 1000 + 1) as f64 / 1000.0);
+    if seed.is_multiple_of(2) { f } else { 1.0 / f }
+}
+
+quickcheck! {
+    fn pixel_round_trip_within_half_pixel(case: Case) -> bool {
+        let [x, y] = case.viewport.complex_to_pixel(case.viewport.pixel_to_complex(case.x, case.y));
+
+        (x - case.x).abs() < 0.5 && (y - case.y).abs() < 0.5
+    }
+
+    fn zoom_about_keeps_point_pixel_fixed(case: Case, seed: u32) -> bool {
+        let mut viewport = case.viewport;
+        let point = viewport.pixel_to_complex(case.x, case.y);
+
+        viewport.zoom_about(point, factor(seed));
+        let [x, y] = viewport.complex_to_pixel(point);
+
+        (x - case.x).abs() < 0.5 && (y - case.y).abs() < 0.5
+    }
+
+    fn symmetric_zoom_returns_to_original_bounds(case: Case, seed: u32, steps: u8) -> TestResult {
+        let mut viewport = case.viewport;
+        let point = viewport.pixel_to_complex(case.x, case.y);
+        let f = factor(seed);
+
+        // Views whose pixels shrink towards the f64 spacing at the point
+        // can't round-trip; that is the precision floor, not drift.
+        let deepest = viewport.pixel_size() / f.max(1.0 / f).powi(steps as i32);
+        if deepest < 1e6 * f64::EPSILON * (point.norm() + 1.0) {
+            return TestResult::discard();
+        }
+
+        for _ in 0..steps {
+            viewport.zoom_about(point, f);
+        }
+        for _ in 0..steps {
+            viewport.zoom_about(point, 1.0 / f);
+        }
+
+        // Compare the corners to within a hundredth of a pixel.
+        let (w, h) = (case.viewport.width_px() as f64, case.viewport.height_px() as f64);
+        let tolerance = case.viewport.pixel_size() * 0.01;
+        TestResult::from_bool([(0.0, 0.0), (w, h)].iter().all(|&(x, y)| {
+            (viewport.pixel_to_complex(x, y) - case.viewport.pixel_to_complex(x, y)).norm() < tolerance
+        }) && (viewport.width() / case.viewport.width() - 1.0).abs() < 1e-9)
+    }
+
+    fn zooming_about_centre_never_moves_it(case: Case, frames: u16) -> bool {
+        let mut viewport = case.viewport;
+        let centre = viewport.centre();
+
+        // The per-frame contraction the zoom applies, for as many frames as
+        // the pixels stay clear of the f64 spacing at the centre.
+        let floor = 1e3 * f64::EPSILON * (centre.norm() + 1.0);
+        let mut zoom = viewport.width() / 40.0;
+        for _ in 0..frames % 1000 {
+            if viewport.pixel_size() < floor {
+                break;
+            }
+
+            let width = viewport.width();
+            viewport.zoom_about(centre, width / (width - 2.0 * zoom));
+            zoom *= 0.95;
+        }
+
+        viewport.centre() == centre && viewport.width() > 0.0
+    }
+}